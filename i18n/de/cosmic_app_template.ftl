@@ -0,0 +1,17 @@
+# Display names of quickget editions. Configs keep the English identifiers.
+edition-desktop = Desktop
+edition-server = Server
+edition-minimal = Minimal
+edition-english-international = Englisch (international)
+edition-english-united-states = Englisch (USA)
+edition-german = Deutsch
+edition-french = Französisch
+edition-french-canadian = Französisch (Kanada)
+edition-spanish = Spanisch
+edition-italian = Italienisch
+edition-dutch = Niederländisch
+edition-polish = Polnisch
+edition-russian = Russisch
+edition-japanese = Japanisch
+edition-chinese-simplified = Chinesisch (vereinfacht)
+edition-chinese-traditional = Chinesisch (traditionell)
@@ -1,4 +1,8 @@
 app-title = COSMIC App Template
 about = About
 view = View
+file = File
+new-vm = New VM
+find = Find
+lock = Lock
 welcome = Welcome to COSMIC! ✨
@@ -0,0 +1,17 @@
+# Display names of quickget editions. Configs keep the English identifiers.
+edition-desktop = Bureau
+edition-server = Serveur
+edition-minimal = Minimale
+edition-english-international = Anglais (international)
+edition-english-united-states = Anglais (États-Unis)
+edition-german = Allemand
+edition-french = Français
+edition-french-canadian = Français canadien
+edition-spanish = Espagnol
+edition-italian = Italien
+edition-dutch = Néerlandais
+edition-polish = Polonais
+edition-russian = Russe
+edition-japanese = Japonais
+edition-chinese-simplified = Chinois simplifié
+edition-chinese-traditional = Chinois traditionnel
@@ -1,16 +1,36 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::config::{Config, StartPage};
+use crate::confirm::{self, Confirmation};
+use crate::core::activation::Activation;
+use crate::core::bus::{self, BusEvent};
+use crate::core::hooks;
+use crate::core::host_probe::{self, HostCapabilities};
+use crate::core::network;
+use crate::core::portal;
+use crate::core::recent::{self, RecentCreation};
+use crate::core::resume;
+use crate::core::vm;
 use crate::creation::{self, Creation};
 use crate::fl;
-use cosmic::app::{Command, Core};
+use crate::import::{self, Import};
+use crate::library::{self, Library};
+use crate::lock_screen::{self, LockScreen};
+use crate::onboarding::{self, Onboarding};
+use crate::settings::{self, Settings};
+use cosmic::app::{Command, Core, CosmicFlags, DbusActivationDetails, DbusActivationMessage};
+use cosmic::cosmic_config::{self, CosmicConfigEntry};
 use cosmic::iced::alignment::{Horizontal, Vertical};
-use cosmic::iced::{Alignment, Length};
+use cosmic::iced::keyboard::{self, key::Named, Key, Modifiers};
+use cosmic::iced::widget::{focus_next, focus_previous};
+use cosmic::iced::{event, window, Alignment, Length, Point, Size, Subscription};
 use cosmic::widget::{self, icon, menu, nav_bar};
 use cosmic::{cosmic_theme, theme, Application, ApplicationExt, Apply, Element};
 use quickget_core::data_structures::OS;
-use quickget_core::ConfigSearch;
 
 const REPOSITORY: &str = "https://github.com/edfloreshz/cosmic-app-template";
 
@@ -26,7 +46,39 @@ pub struct YourApp {
     /// A model that contains all of the pages assigned to the nav bar panel.
     nav: nav_bar::Model,
     page: Page,
-    creation: Creation,
+    /// Creation wizards by session id, each with its own nav bar entry.
+    creations: BTreeMap<u32, Creation>,
+    next_session: u32,
+    library: Library,
+    import: Import,
+    settings: Settings,
+    config: Config,
+    config_handler: Option<cosmic_config::Config>,
+    lock_screen: LockScreen,
+    /// Activations that arrived while locked, run once unlocked.
+    pending_activations: Vec<Activation>,
+    /// The first-run setup guide, until it is finished.
+    onboarding: Option<Onboarding>,
+    /// Asking whether to quit while downloads, imports or exports are still running.
+    close_dialog: bool,
+    /// A question some page asked before acting, waiting for an answer.
+    confirmation: Option<Confirmation>,
+    /// Last set, so the title is only changed (and announced) when it differs.
+    window_title: String,
+}
+
+/// Startup arguments, also forwarded to an already running instance.
+#[derive(Clone, Debug, Default)]
+pub struct Flags {
+    pub activation: Option<Activation>,
+}
+
+impl CosmicFlags for Flags {
+    type SubCommand = Activation;
+    type Args = Vec<String>;
+    fn action(&self) -> Option<&Activation> {
+        self.activation.as_ref()
+    }
 }
 
 /// This is the enum that contains all the possible variants that your application will need to transmit messages.
@@ -35,13 +87,60 @@ pub struct YourApp {
 #[derive(Debug, Clone)]
 pub enum Message {
     LaunchUrl(String),
+    /// Text put together in the background, e.g. an error report with its log.
+    CopyToClipboard(String),
     ToggleContextPage(ContextPage),
+    /// A creation message not tied to a session, e.g. the OS list loaded at startup, which goes
+    /// to every session; sessions' own messages are turned into [`Message::Session`].
     Creation(creation::Message),
+    Session(u32, creation::Message),
+    CloseSession(u32),
+    /// Close a session even though it is still creating a VM.
+    DiscardSession(u32),
+    /// A VM was created with these options.
+    RecordRecent(RecentCreation),
+    /// Star or unstar an OS by its quickget name.
+    ToggleFavoriteOS(String),
+    /// Start a VM like an outdated one, on the newer release.
+    CreateUpgraded(RecentCreation),
+    Library(library::Message),
+    Import(import::Message),
+    Settings(settings::Message),
+    Key(Modifiers, Key),
+    /// The main window was resized or moved, remembered for the next launch.
+    WindowResized(Size<u32>),
+    WindowMoved(Point<i32>),
+    NewVM,
+    FocusSearch,
+    Bus(BusEvent),
+    HostProbed(Arc<HostCapabilities>),
+    OpenChecklist(PathBuf),
+    /// Open a VM's overview in the library.
+    ShowVM(PathBuf),
+    Lock(lock_screen::Message),
+    Onboarding(onboarding::Message),
+    Close(CloseMessage),
+    Confirm(confirm::Message),
+}
+
+/// Handling of window close requests while operations are running.
+#[derive(Debug, Clone)]
+pub enum CloseMessage {
+    Requested,
+    Cancel,
+    Quit,
+    RunInBackground,
+    BackgroundGranted(bool),
 }
 
 /// Identifies a page in the application.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Page {
-    NewVM,
+    /// A creation session.
+    NewVM(u32),
+    Library,
+    Import,
+    Settings,
 }
 
 /// Identifies a context page to display in the context drawer.
@@ -62,6 +161,9 @@ impl ContextPage {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
     About,
+    NewVM,
+    Find,
+    Lock,
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -70,6 +172,9 @@ impl menu::action::MenuAction for MenuAction {
     fn message(&self) -> Self::Message {
         match self {
             MenuAction::About => Message::ToggleContextPage(ContextPage::About),
+            MenuAction::NewVM => Message::NewVM,
+            MenuAction::Find => Message::FocusSearch,
+            MenuAction::Lock => Message::Lock(lock_screen::Message::LockNow),
         }
     }
 }
@@ -85,7 +190,7 @@ impl menu::action::MenuAction for MenuAction {
 impl Application for YourApp {
     type Executor = cosmic::executor::Default;
 
-    type Flags = ();
+    type Flags = Flags;
 
     type Message = Message;
 
@@ -111,48 +216,121 @@ impl Application for YourApp {
     /// - `core` is used to passed on for you by libcosmic to use in the core of your own application.
     /// - `flags` is used to pass in any data that your application needs to use before it starts.
     /// - `Command` type is used to send messages to your application. `Command::none()` can be used to send no messages to your application.
-    fn init(core: Core, _flags: Self::Flags) -> (Self, Command<Self::Message>) {
+    fn init(core: Core, flags: Self::Flags) -> (Self, Command<Self::Message>) {
         let mut nav = nav_bar::Model::default();
 
         nav.insert()
             .text("Create new VM")
-            .data::<Page>(Page::NewVM)
+            .data::<Page>(Page::NewVM(0))
             .icon(icon::from_name("applications-science-symbolic"))
             .activate();
 
+        nav.insert()
+            .text("VM Library")
+            .data::<Page>(Page::Library)
+            .icon(icon::from_name("computer-symbolic"));
+
+        nav.insert()
+            .text("Import")
+            .data::<Page>(Page::Import)
+            .icon(icon::from_name("document-import-symbolic"));
+
+        nav.insert()
+            .text("Settings")
+            .data::<Page>(Page::Settings)
+            .icon(icon::from_name("preferences-system-symbolic"));
+
+        let config_handler = cosmic_config::Config::new(Self::APP_ID, Config::VERSION).ok();
+        let config = config_handler
+            .as_ref()
+            .map(|handler| Config::get_entry(handler).unwrap_or_else(|(_errors, config)| config))
+            .unwrap_or_default();
+
+        let vm_root = vm::default_root();
+
         let mut app = YourApp {
             core,
             context_page: ContextPage::default(),
-            key_binds: HashMap::new(),
+            key_binds: key_binds(),
             nav,
-            creation: Creation::default(),
-            page: Page::NewVM,
+            creations: BTreeMap::from([(0, Creation::new(0))]),
+            next_session: 1,
+            import: Import::new(vm_root.clone()),
+            library: Library::new(vec![vm_root], config.registered_vms.clone()),
+            settings: Settings::default(),
+            config_handler,
+            page: Page::NewVM(0),
+            lock_screen: LockScreen::new(&config.app_lock),
+            pending_activations: Vec::new(),
+            onboarding: (!config.onboarding_complete).then(Onboarding::new),
+            config,
+            close_dialog: false,
+            confirmation: None,
+            window_title: String::new(),
         };
+        // Setups interrupted in other sessions come back in sessions of their own.
+        for session in resume::sessions()
+            .into_iter()
+            .filter(|&session| session != 0)
+        {
+            app.open_session(session);
+        }
+        app.sync_lock();
+        app.settings.refresh_firmware();
+        network::configure(&app.config.network);
+        for creation in app.creations.values_mut() {
+            creation.restore_os_list_scroll(app.config.session.os_list_scroll as f32);
+            creation.set_last_created(app.config.last_created.clone());
+            creation.set_show_preview(app.config.show_os_preview);
+            creation.set_show_testing(app.config.show_testing_releases);
+            creation.set_recent(&app.config.recent_creations, &app.config.favorite_os);
+        }
+        app.library
+            .set_double_click_action(app.config.double_click_action);
+        app.library
+            .set_delete_installers_after_boot(app.config.delete_installers_after_boot);
 
         let update_titles = app.update_titles();
-        let fetch_os_list = Command::perform(
-            async {
-                ConfigSearch::new()
-                    .await
-                    .map_err(|e| e.to_string())
-                    .map(|x| x.into_os_list())
-            },
-            |x| Message::Creation(creation::Message::OSList(x)).into(),
-        );
-        let command = Command::batch([update_titles, fetch_os_list]);
+        let scan_library = app.library.refresh();
+        let probe_host = Command::perform(host_probe::probe(), |host| {
+            Message::HostProbed(Arc::new(host)).into()
+        });
+        // Restored before the activation, which picks its own page.
+        let restore = app.restore_session();
+        let activate = match flags.activation {
+            Some(activation) => app.activate(activation),
+            None => Command::none(),
+        };
+        let command = Command::batch([update_titles, scan_library, probe_host, restore, activate]);
 
         (app, command)
     }
 
     /// Elements to pack at the start of the header bar.
     fn header_start(&self) -> Vec<Element<Self::Message>> {
-        let menu_bar = menu::bar(vec![menu::Tree::with_children(
-            menu::root(fl!("view")),
-            menu::items(
-                &self.key_binds,
-                vec![menu::Item::Button(fl!("about"), MenuAction::About)],
+        if self.lock_screen.is_locked() {
+            return vec![];
+        }
+        let menu_bar = menu::bar(vec![
+            menu::Tree::with_children(
+                menu::root(fl!("file")),
+                menu::items(
+                    &self.key_binds,
+                    vec![
+                        menu::Item::Button(fl!("new-vm"), MenuAction::NewVM),
+                        menu::Item::Button(fl!("find"), MenuAction::Find),
+                        menu::Item::Button(fl!("lock"), MenuAction::Lock),
+                    ],
+                ),
             ),
-        )]);
+            menu::Tree::with_children(
+                menu::root(fl!("view")),
+                menu::items(
+                    &self.key_binds,
+                    vec![menu::Item::Button(fl!("about"), MenuAction::About)],
+                ),
+            ),
+        ]);
 
         vec![menu_bar.into()]
     }
@@ -164,8 +342,17 @@ impl Application for YourApp {
     ///
     /// To get a better sense of which widgets are available, check out the `widget` module.
     fn view(&self) -> Element<Self::Message> {
+        if self.lock_screen.is_locked() {
+            return self.lock_screen.view(&self.config.app_lock);
+        }
+        if let Some(onboarding) = &self.onboarding {
+            return onboarding.view();
+        }
         match self.page {
-            Page::NewVM => self.creation.view(),
+            Page::NewVM(session) => self.session_view(session),
+            Page::Library => self.library.view(),
+            Page::Import => self.import.view(),
+            Page::Settings => self.settings.view(&self.config),
         }
     }
 
@@ -178,6 +365,8 @@ impl Application for YourApp {
                 let _result = open::that_detached(url);
             }
 
+            Message::CopyToClipboard(text) => return cosmic::iced::clipboard::write(text),
+
             Message::ToggleContextPage(context_page) => {
                 if self.context_page == context_page {
                     // Close the context drawer if the toggled context page is the same.
@@ -192,11 +381,334 @@ impl Application for YourApp {
                 self.set_context_title(context_page.title());
             }
 
-            Message::Creation(msg) => return self.creation.update(msg),
+            Message::Creation(msg) => {
+                let commands = self
+                    .creations
+                    .iter_mut()
+                    .map(|(&session, creation)| in_session(session, creation.update(msg.clone())))
+                    .collect::<Vec<_>>();
+                return Command::batch(commands);
+            }
+            Message::Session(session, msg) => {
+                let Some(creation) = self.creations.get_mut(&session) else {
+                    return Command::none();
+                };
+                let command = in_session(session, creation.update(msg));
+                self.refuse_duplicate_job(session);
+                return Command::batch([command, self.sync_session_title(session)]);
+            }
+            Message::CloseSession(session) => {
+                let busy = self.creations.get(&session).filter(|c| c.is_busy());
+                if let Some(creation) = busy {
+                    return Confirmation::new(
+                        "Stop creating this VM?",
+                        format!("Closing the session stops {}.", creation.title()),
+                        "Stop and close",
+                        Message::DiscardSession(session),
+                    )
+                    .destructive()
+                    .ask();
+                }
+                return self.close_session(session);
+            }
+            Message::DiscardSession(session) => return self.close_session(session),
+            Message::RecordRecent(created) => {
+                recent::record(&mut self.config.recent_creations, created);
+                self.config.save(self.config_handler.as_ref());
+                self.sync_recent();
+            }
+            Message::ToggleFavoriteOS(os) => {
+                let favorites = &mut self.config.favorite_os;
+                match favorites.iter().position(|favorite| *favorite == os) {
+                    Some(index) => {
+                        favorites.remove(index);
+                    }
+                    None => favorites.push(os),
+                }
+                self.config.save(self.config_handler.as_ref());
+                self.sync_recent();
+            }
+            Message::CreateUpgraded(recipe) => {
+                let session = self.idle_session();
+                let request = match self.creations.get_mut(&session) {
+                    Some(creation) => in_session(session, creation.request_recipe(recipe)),
+                    None => Command::none(),
+                };
+                let title = self.sync_session_title(session);
+                let activate = self.activate_page(Page::NewVM(session));
+                return Command::batch([request, title, activate]);
+            }
+            Message::Library(msg) => {
+                let command = self.library.update(msg);
+                self.sync_commitments();
+                return command;
+            }
+            Message::Import(msg) => return self.import.update(msg),
+            Message::Settings(msg) => {
+                let network = self.config.network.clone();
+                let command =
+                    self.settings
+                        .update(msg, &mut self.config, self.config_handler.as_ref());
+                self.library
+                    .set_double_click_action(self.config.double_click_action);
+                self.library
+                    .set_delete_installers_after_boot(self.config.delete_installers_after_boot);
+                for creation in self.creations.values_mut() {
+                    creation.set_show_preview(self.config.show_os_preview);
+                    creation.set_show_testing(self.config.show_testing_releases);
+                }
+                if self.config.network != network {
+                    network::configure(&self.config.network);
+                }
+                return command;
+            }
+            Message::WindowResized(size) => {
+                self.config.session.size = Some((size.width, size.height));
+            }
+            Message::WindowMoved(position) => {
+                self.config.session.position = Some((position.x, position.y));
+            }
+            Message::Key(modifiers, key) => {
+                self.lock_screen.touch();
+                if self.lock_screen.is_locked() {
+                    return Command::none();
+                }
+                for (key_bind, action) in &self.key_binds {
+                    if key_bind.matches(modifiers, &key) {
+                        return self.update(action.message());
+                    }
+                }
+                if key == Key::Named(Named::Tab) {
+                    return if modifiers.shift() {
+                        focus_previous()
+                    } else {
+                        focus_next()
+                    };
+                }
+                match self.page {
+                    Page::NewVM(session) => {
+                        if let Some(creation) = self.creations.get_mut(&session) {
+                            let command = in_session(session, creation.on_key(&key));
+                            return Command::batch([command, self.sync_session_title(session)]);
+                        }
+                    }
+                    Page::Library if key == Key::Named(Named::F2) => {
+                        return self.library.update(library::Message::EditSelected)
+                    }
+                    _ => {}
+                }
+            }
+            Message::NewVM => {
+                let session = self.idle_session();
+                if let Some(creation) = self.creations.get_mut(&session) {
+                    creation.restart();
+                }
+                let title = self.sync_session_title(session);
+                return Command::batch([title, self.activate_page(Page::NewVM(session))]);
+            }
+            Message::FocusSearch => {
+                let session = self.idle_session();
+                let focus = match self.creations.get_mut(&session) {
+                    Some(creation) => in_session(session, creation.focus_search()),
+                    None => Command::none(),
+                };
+                let title = self.sync_session_title(session);
+                let activate = self.activate_page(Page::NewVM(session));
+                return Command::batch([title, activate, focus]);
+            }
+            Message::Bus(event) => {
+                if let BusEvent::VMAdded(config_path) | BusEvent::CreationComplete(config_path) =
+                    &event
+                {
+                    if !self.config.registered_vms.contains(config_path) {
+                        self.config.registered_vms.push(config_path.clone());
+                        self.config.save(self.config_handler.as_ref());
+                    }
+                }
+                if let BusEvent::VMRenamed { from, to } = &event {
+                    let renamed = self.config.last_created.as_ref() == Some(from);
+                    if renamed {
+                        self.config.last_created = Some(to.clone());
+                    }
+                    let registered = self
+                        .config
+                        .registered_vms
+                        .iter_mut()
+                        .find(|path| **path == *from);
+                    if let Some(registered) = registered {
+                        *registered = to.clone();
+                    }
+                    if renamed || self.config.registered_vms.contains(to) {
+                        self.config.save(self.config_handler.as_ref());
+                    }
+                }
+                if let BusEvent::CreationComplete(config_path) = &event {
+                    self.config.last_created = Some(config_path.clone());
+                    self.config.save(self.config_handler.as_ref());
+                    for creation in self.creations.values_mut() {
+                        creation.set_last_created(Some(config_path.clone()));
+                    }
+                }
+                let mut commands = vec![self.library.on_event(&event)];
+                self.sync_commitments();
+                if let Some(notification) = event.notification() {
+                    commands.push(bus::background(portal::notify(
+                        notification.id,
+                        notification.title,
+                        notification.body,
+                    )));
+                }
+                if let Some(hook_event) = event.hook_event() {
+                    let hooks = self.config.hooks.clone();
+                    commands.push(Command::perform(
+                        hooks::dispatch(hooks, hook_event),
+                        |errors| Message::Settings(settings::Message::HookErrors(errors)).into(),
+                    ));
+                }
+                return Command::batch(commands);
+            }
+            Message::OpenChecklist(config_path) => {
+                self.library.open_checklist(config_path);
+                return self.activate_page(Page::Library);
+            }
+            Message::ShowVM(config_path) => {
+                self.library.open_overview(config_path);
+                return self.activate_page(Page::Library);
+            }
+            Message::HostProbed(host) => {
+                for creation in self.creations.values_mut() {
+                    creation.set_host(host.clone());
+                }
+                if let Some(onboarding) = &mut self.onboarding {
+                    onboarding.set_host(&host);
+                }
+                self.library.set_host(host);
+            }
+            Message::Onboarding(
+                msg @ (onboarding::Message::Finish | onboarding::Message::OpenSettings),
+            ) => {
+                self.onboarding = None;
+                self.config.onboarding_complete = true;
+                self.config.save(self.config_handler.as_ref());
+                if let onboarding::Message::OpenSettings = msg {
+                    return self.activate_page(Page::Settings);
+                }
+            }
+            Message::Onboarding(msg) => {
+                if let Some(onboarding) = &mut self.onboarding {
+                    return onboarding.update(msg);
+                }
+            }
+            Message::Lock(msg) => {
+                let command = self.lock_screen.update(msg, &self.config.app_lock);
+                self.sync_lock();
+                if self.lock_screen.is_locked() || self.pending_activations.is_empty() {
+                    return command;
+                }
+                let activations = std::mem::take(&mut self.pending_activations)
+                    .into_iter()
+                    .map(|activation| self.activate(activation))
+                    .collect::<Vec<_>>();
+                return Command::batch(std::iter::once(command).chain(activations));
+            }
+            Message::Close(msg) => return self.close(msg),
+            Message::Confirm(confirm::Message::Ask(confirmation)) => {
+                self.confirmation = Some(*confirmation);
+            }
+            Message::Confirm(answer) => {
+                let message = self
+                    .confirmation
+                    .take()
+                    .and_then(|confirmation| confirmation.answer(answer));
+                if let Some(message) = message {
+                    return self.update(message);
+                }
+            }
         }
         Command::none()
     }
 
+    /// A second `qersui` invocation: focus this window and open what it asked for.
+    fn dbus_activation(&mut self, msg: DbusActivationMessage) -> Command<Self::Message> {
+        let focus = window::gain_focus(window::Id::MAIN);
+        match msg.msg {
+            DbusActivationDetails::ActivateAction { action, .. } => match action.parse() {
+                Ok(activation) => Command::batch([focus, self.activate(activation)]),
+                Err(e) => {
+                    tracing::warn!("Ignoring activation: {e}");
+                    focus
+                }
+            },
+            _ => focus,
+        }
+    }
+
+    /// Closing the window is intercepted so running operations aren't silently killed.
+    fn on_close_requested(&self, _id: window::Id) -> Option<Self::Message> {
+        Some(Message::Close(CloseMessage::Requested))
+    }
+
+    fn dialog(&self) -> Option<Element<Self::Message>> {
+        if !self.close_dialog {
+            // Nothing behind the lock screen can be answered.
+            if self.lock_screen.is_locked() {
+                return None;
+            }
+            return self.confirmation.as_ref().map(Confirmation::view);
+        }
+        let operations = self.active_operations().join(", ");
+        let dialog = widget::dialog("Operations in progress")
+            .body(format!(
+                "Quitting now will stop: {operations}. \
+                 They can keep running in the background instead."
+            ))
+            .primary_action(
+                widget::button::suggested("Continue in background")
+                    .on_press(Message::Close(CloseMessage::RunInBackground)),
+            )
+            .secondary_action(
+                widget::button::standard("Cancel").on_press(Message::Close(CloseMessage::Cancel)),
+            )
+            .tertiary_action(
+                widget::button::destructive("Quit anyway")
+                    .on_press(Message::Close(CloseMessage::Quit)),
+            );
+        Some(dialog.into())
+    }
+
+    /// Long-running background work, such as disk conversions and exports, reports progress through subscriptions.
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let sessions = self.creations.iter().map(|(&session, creation)| {
+            creation
+                .subscription(session)
+                .map(move |message| message.in_session(session))
+        });
+        Subscription::batch(
+            [
+                bus::subscription(),
+                self.import.subscription(),
+                self.library.subscription(),
+                keyboard::on_key_press(|key, modifiers| Some(Message::Key(modifiers, key))),
+                event::listen_with(|event, _status| match event {
+                    event::Event::Window(id, window::Event::Resized { width, height })
+                        if id == window::Id::MAIN =>
+                    {
+                        Some(Message::WindowResized(Size::new(width, height)))
+                    }
+                    event::Event::Window(id, window::Event::Moved { x, y })
+                        if id == window::Id::MAIN =>
+                    {
+                        Some(Message::WindowMoved(Point::new(x, y)))
+                    }
+                    _ => None,
+                }),
+                self.lock_screen.subscription(&self.config.app_lock),
+            ]
+            .into_iter()
+            .chain(sessions),
+        )
+    }
+
     /// Display a context drawer if the context page is requested.
     fn context_drawer(&self) -> Option<Element<Self::Message>> {
         if !self.core.window.show_context {
@@ -212,6 +724,9 @@ impl Application for YourApp {
     fn on_nav_select(&mut self, id: nav_bar::Id) -> Command<Self::Message> {
         // Activate the page in the model.
         self.nav.activate(id);
+        if let Some(page) = self.nav.data::<Page>(id) {
+            self.page = *page;
+        }
 
         self.update_titles()
     }
@@ -242,6 +757,261 @@ impl YourApp {
             .into()
     }
 
+    /// Human readable descriptions of work that would be lost by quitting.
+    fn active_operations(&self) -> Vec<&'static str> {
+        [
+            (self.creations.values().any(Creation::is_busy), "downloads"),
+            (self.import.is_busy(), "disk conversion"),
+            (self.library.is_busy(), "VM export or deletion"),
+        ]
+        .into_iter()
+        .filter_map(|(busy, operation)| busy.then_some(operation))
+        .collect()
+    }
+
+    /// Hide the nav bar and context drawer while locked, so nothing but the lock screen is reachable.
+    fn sync_lock(&mut self) {
+        let locked = self.lock_screen.is_locked();
+        if locked {
+            self.core.window.show_context = false;
+        }
+        self.core.nav_bar_set_toggled(!locked);
+    }
+
+    /// Reopen the page and window position left at the last close; the size is applied by
+    /// `main` when the window is created.
+    fn restore_session(&mut self) -> Command<Message> {
+        let session = self.config.session.clone();
+        let page = match session.page {
+            StartPage::NewVM => Command::none(),
+            StartPage::Library => self.activate_page(Page::Library),
+            StartPage::Import => self.activate_page(Page::Import),
+            StartPage::Settings => self.activate_page(Page::Settings),
+        };
+        let position = match session.position {
+            Some((x, y)) => window::move_to(window::Id::MAIN, Point::new(x as f32, y as f32)),
+            None => Command::none(),
+        };
+        Command::batch([page, position])
+    }
+
+    /// Remember the page and OS list scroll position for the next launch, along with the
+    /// window geometry tracked while it changed.
+    fn save_session(&mut self) {
+        self.config.session.page = match self.page {
+            Page::NewVM(_) => StartPage::NewVM,
+            Page::Library => StartPage::Library,
+            Page::Import => StartPage::Import,
+            Page::Settings => StartPage::Settings,
+        };
+        if let Some(creation) = self.creations.values().next() {
+            self.config.session.os_list_scroll = creation.os_list_scroll().max(0.0) as u32;
+        }
+        self.config.save(self.config_handler.as_ref());
+    }
+
+    fn close(&mut self, message: CloseMessage) -> Command<Message> {
+        match message {
+            CloseMessage::Requested => {
+                if self.active_operations().is_empty() {
+                    self.save_session();
+                    return window::close(window::Id::MAIN);
+                }
+                self.close_dialog = true;
+            }
+            CloseMessage::Cancel => self.close_dialog = false,
+            CloseMessage::Quit => {
+                self.save_session();
+                return window::close(window::Id::MAIN);
+            }
+            CloseMessage::RunInBackground => {
+                self.save_session();
+                self.close_dialog = false;
+                return Command::perform(
+                    portal::request_background("Finish downloads and exports in progress"),
+                    |granted| Message::Close(CloseMessage::BackgroundGranted(granted)).into(),
+                );
+            }
+            CloseMessage::BackgroundGranted(granted) => {
+                if !granted {
+                    tracing::info!(
+                        "Background portal denied; keeping the window minimized instead"
+                    );
+                }
+                // The app keeps all of its state, so restoring the window picks up where it left off.
+                return window::minimize(window::Id::MAIN, true);
+            }
+        }
+        Command::none()
+    }
+
+    /// Switch to a page as if its nav bar entry had been selected.
+    pub fn activate_page(&mut self, page: Page) -> Command<Message> {
+        let id = self
+            .nav
+            .iter()
+            .find(|&id| self.nav.data::<Page>(id) == Some(&page));
+        match id {
+            Some(id) => self.on_nav_select(id),
+            None => Command::none(),
+        }
+    }
+
+    /// Navigate to the flow requested on the command line or by a second instance.
+    fn activate(&mut self, activation: Activation) -> Command<Message> {
+        // Launching from the command line mustn't get around the app lock.
+        if self.lock_screen.is_locked() {
+            self.pending_activations.push(activation);
+            return Command::none();
+        }
+        match activation {
+            Activation::NewVM { os, release } => {
+                let session = self.idle_session();
+                let request = match self.creations.get_mut(&session) {
+                    Some(creation) => in_session(session, creation.request(os, release)),
+                    None => Command::none(),
+                };
+                let title = self.sync_session_title(session);
+                let activate = self.activate_page(Page::NewVM(session));
+                Command::batch([request, title, activate])
+            }
+            Activation::Launch(name) => {
+                let launch = self.library.launch_by_name(name);
+                Command::batch([self.activate_page(Page::Library), launch])
+            }
+        }
+    }
+
+    /// Share the library's resource accounting with the creation sessions.
+    fn sync_commitments(&mut self) {
+        let commitments = self.library.commitments();
+        for creation in self.creations.values_mut() {
+            creation.set_commitments(commitments.clone());
+        }
+    }
+
+    fn sync_recent(&mut self) {
+        for creation in self.creations.values_mut() {
+            creation.set_recent(&self.config.recent_creations, &self.config.favorite_os);
+        }
+    }
+
+    /// A creation session, with a button to close it while others are open.
+    fn session_view(&self, session: u32) -> Element<Message> {
+        let Some(creation) = self.creations.get(&session) else {
+            return widget::text("This session was closed").into();
+        };
+        let view = creation
+            .view(!self.core.is_condensed())
+            .map(move |message| message.in_session(session));
+        if self.creations.len() < 2 {
+            return view;
+        }
+        let close_button =
+            widget::button::standard("Close session").on_press(Message::CloseSession(session));
+        widget::column()
+            .push(
+                widget::row()
+                    .push(widget::horizontal_space(Length::Fill))
+                    .push(close_button),
+            )
+            .push(view)
+            .spacing(8)
+            .into()
+    }
+
+    /// A session to start a new VM in: the current one if nothing was entered in it yet, else
+    /// another idle one, else a new one.
+    fn idle_session(&mut self) -> u32 {
+        if let Page::NewVM(session) = self.page {
+            if self.creations.get(&session).is_some_and(Creation::is_idle) {
+                return session;
+            }
+        }
+        let idle = self
+            .creations
+            .iter()
+            .find(|(_, creation)| creation.is_idle())
+            .map(|(&session, _)| session);
+        if let Some(session) = idle {
+            return session;
+        }
+
+        let session = self.next_session;
+        self.open_session(session);
+        session
+    }
+
+    /// Add a creation session with its nav bar entry.
+    fn open_session(&mut self, session: u32) {
+        self.next_session = self.next_session.max(session + 1);
+        let creation = match self.creations.values().next() {
+            Some(creation) => creation.new_session(session),
+            None => Creation::new(session),
+        };
+        // Keep the sessions together at the top of the nav bar.
+        let position = self.creations.len() as u16;
+        let id = self
+            .nav
+            .insert()
+            .text(creation.title())
+            .data::<Page>(Page::NewVM(session))
+            .icon(icon::from_name("applications-science-symbolic"))
+            .id();
+        self.nav.position_set(id, position);
+        self.creations.insert(session, creation);
+    }
+
+    /// Fail `session`'s job if another session is already creating the same VM.
+    fn refuse_duplicate_job(&mut self, session: u32) {
+        let Some(target) = self.creations.get(&session).and_then(Creation::creating) else {
+            return;
+        };
+        let taken = self.creations.iter().any(|(&other, creation)| {
+            other != session && creation.creating() == Some(target.clone())
+        });
+        if let Some(creation) = self.creations.get_mut(&session).filter(|_| taken) {
+            creation.refuse_duplicate();
+        }
+    }
+
+    fn session_nav_id(&self, session: u32) -> Option<nav_bar::Id> {
+        self.nav
+            .iter()
+            .find(|&id| self.nav.data::<Page>(id) == Some(&Page::NewVM(session)))
+    }
+
+    /// Rename the nav bar entry of a session after the VM being created in it.
+    fn sync_session_title(&mut self, session: u32) -> Command<Message> {
+        let (Some(id), Some(creation)) =
+            (self.session_nav_id(session), self.creations.get(&session))
+        else {
+            return Command::none();
+        };
+        let title = creation.title();
+        if self.nav.text(id) != Some(title.as_str()) {
+            self.nav.text_set(id, title);
+        }
+        self.update_titles()
+    }
+
+    fn close_session(&mut self, session: u32) -> Command<Message> {
+        if self.creations.len() < 2 {
+            return Command::none();
+        }
+        self.creations.remove(&session);
+        if let Some(id) = self.session_nav_id(session) {
+            self.nav.remove(id);
+        }
+        let first = self.creations.keys().next().copied();
+        match first {
+            Some(first) if self.page == Page::NewVM(session) => {
+                self.activate_page(Page::NewVM(first))
+            }
+            _ => Command::none(),
+        }
+    }
+
     /// Updates the header and window titles.
     pub fn update_titles(&mut self) -> Command<Message> {
         let mut window_title = fl!("app-title");
@@ -252,8 +1022,71 @@ impl YourApp {
             window_title.push_str(page);
             header_title.push_str(page);
         }
+        // Screen readers announce the window title, so moving through the wizard is heard.
+        if let Page::NewVM(session) = self.page {
+            if let Some(creation) = self.creations.get(&session) {
+                window_title.push_str(" — ");
+                window_title.push_str(creation.step());
+            }
+        }
 
         self.set_header_title(header_title);
+        if window_title == self.window_title {
+            return Command::none();
+        }
+        self.window_title.clone_from(&window_title);
         self.set_window_title(window_title)
     }
 }
+
+impl Message {
+    /// Route a message a creation session sent itself back to that session.
+    fn in_session(self, session: u32) -> Self {
+        match self {
+            Self::Creation(msg) => Self::Session(session, msg),
+            // The dialog's answers are the session's too.
+            Self::Confirm(confirm::Message::Ask(confirmation)) => {
+                Self::Confirm(confirm::Message::Ask(Box::new(
+                    confirmation.map(|message| message.in_session(session)),
+                )))
+            }
+            message => message,
+        }
+    }
+}
+
+/// Tag the messages of a creation session's command with the session.
+fn in_session(session: u32, command: Command<Message>) -> Command<Message> {
+    command.map(move |message| match message {
+        cosmic::app::Message::App(message) => {
+            cosmic::app::Message::App(message.in_session(session))
+        }
+        message => message,
+    })
+}
+
+fn key_binds() -> HashMap<menu::KeyBind, MenuAction> {
+    let mut key_binds = HashMap::new();
+    key_binds.insert(
+        menu::KeyBind {
+            modifiers: vec![menu::key_bind::Modifier::Ctrl],
+            key: Key::Character("n".into()),
+        },
+        MenuAction::NewVM,
+    );
+    key_binds.insert(
+        menu::KeyBind {
+            modifiers: vec![menu::key_bind::Modifier::Ctrl],
+            key: Key::Character("f".into()),
+        },
+        MenuAction::Find,
+    );
+    key_binds.insert(
+        menu::KeyBind {
+            modifiers: vec![menu::key_bind::Modifier::Ctrl],
+            key: Key::Character("l".into()),
+        },
+        MenuAction::Lock,
+    );
+    key_binds
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use serde::{Deserialize, Serialize};
+
+use crate::core::autostart::LoginAutostart;
+use crate::core::hooks::Hook;
+use crate::core::lock::AppLock;
+use crate::core::logging::LogLevel;
+use crate::core::network::NetworkSettings;
+use crate::core::recent::RecentCreation;
+
+/// Persistent application settings, stored through cosmic-config.
+#[derive(Debug, Default, Clone, CosmicConfigEntry, Eq, PartialEq)]
+#[version = 1]
+pub struct Config {
+    /// Scripts and webhooks fired on VM lifecycle events.
+    pub hooks: Vec<Hook>,
+    /// Configs added through the Import page that live outside the VM directories.
+    pub registered_vms: Vec<PathBuf>,
+    /// Optional lock required to open the app.
+    pub app_lock: AppLock,
+    /// What double-clicking a row in the VM library does.
+    pub double_click_action: DoubleClickAction,
+    /// Show details of the highlighted OS beside the list on wide windows.
+    pub show_os_preview: bool,
+    /// List pre-release and end-of-life releases on the creation page.
+    pub show_testing_releases: bool,
+    /// Whether autostart VMs are also started at login, without opening QERSUI.
+    pub login_autostart: LoginAutostart,
+    /// Delete the ISOs quickget downloaded for a VM once its first session ends.
+    pub delete_installers_after_boot: bool,
+    /// Proxy and CA used for the OS catalog and all downloads.
+    pub network: NetworkSettings,
+    /// Config of the VM most recently finished on the creation page.
+    pub last_created: Option<PathBuf>,
+    /// What VMs were created with, newest first, offered at the top of the OS list.
+    pub recent_creations: Vec<RecentCreation>,
+    /// quickget names of the OSes starred in the OS list.
+    pub favorite_os: Vec<String>,
+    /// Least severe events written to the application log.
+    pub log_level: LogLevel,
+    /// Set once the first-run setup guide was finished or skipped.
+    pub onboarding_complete: bool,
+    /// Where the window and navigation were left, restored on the next launch.
+    pub session: SessionState,
+}
+
+/// The window and navigation as they were when QERSUI last closed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SessionState {
+    /// Width and height in logical pixels.
+    pub size: Option<(u32, u32)>,
+    /// Only reported, and only applied, where the window system lets apps place their windows.
+    pub position: Option<(i32, i32)>,
+    pub page: StartPage,
+    /// How far the OS list of the first creation session was scrolled, in pixels.
+    pub os_list_scroll: u32,
+}
+
+/// A nav bar page to reopen; creation sessions other than the first aren't kept.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StartPage {
+    #[default]
+    NewVM,
+    Library,
+    Import,
+    Settings,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DoubleClickAction {
+    /// Edit the name, RAM and CPU cores in place.
+    #[default]
+    Edit,
+    Launch,
+    OpenViewer,
+    ConnectSSH,
+}
+
+impl DoubleClickAction {
+    pub const ALL: [DoubleClickAction; 4] =
+        [Self::Edit, Self::Launch, Self::OpenViewer, Self::ConnectSSH];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Edit => "Edit details",
+            Self::Launch => "Launch",
+            Self::OpenViewer => "Open viewer",
+            Self::ConnectSSH => "Connect over SSH",
+        }
+    }
+}
+
+impl Config {
+    /// Write the whole config, ignoring the write when cosmic-config is unavailable.
+    pub fn save(&self, config_handler: Option<&cosmic_config::Config>) {
+        if let Some(handler) = config_handler {
+            if let Err(e) = self.write_entry(handler) {
+                tracing::error!("Failed to save settings: {e}");
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::app::Command;
+use cosmic::widget;
+use cosmic::Element;
+
+/// A question asked in a modal dialog before a destructive or long-running action. The
+/// asking module's own messages are sent back once the user answers.
+#[derive(Clone, Debug)]
+pub struct Confirmation {
+    title: String,
+    body: String,
+    confirm_label: String,
+    destructive: bool,
+    on_confirm: Box<crate::app::Message>,
+    on_cancel: Option<Box<crate::app::Message>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    Ask(Box<Confirmation>),
+    Confirm,
+    Cancel,
+}
+
+impl Confirmation {
+    pub fn new(
+        title: impl Into<String>,
+        body: impl Into<String>,
+        confirm_label: impl Into<String>,
+        on_confirm: impl Into<crate::app::Message>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+            confirm_label: confirm_label.into(),
+            destructive: false,
+            on_confirm: Box::new(on_confirm.into()),
+            on_cancel: None,
+        }
+    }
+    /// Show the confirm button as destructive, for actions that can't be undone.
+    pub fn destructive(mut self) -> Self {
+        self.destructive = true;
+        self
+    }
+    /// Also tell the asking module when the user backs out.
+    pub fn on_cancel(mut self, message: impl Into<crate::app::Message>) -> Self {
+        self.on_cancel = Some(Box::new(message.into()));
+        self
+    }
+    /// Rewrite the answers, e.g. to route a creation session's messages back to that session.
+    pub fn map(mut self, f: impl Fn(crate::app::Message) -> crate::app::Message) -> Self {
+        self.on_confirm = Box::new(f(*self.on_confirm));
+        self.on_cancel = self.on_cancel.map(|message| Box::new(f(*message)));
+        self
+    }
+    /// Ask the app shell to show this dialog.
+    pub fn ask(self) -> Command<crate::app::Message> {
+        Command::perform(async move { self }, |confirmation| {
+            crate::app::Message::Confirm(Message::Ask(Box::new(confirmation))).into()
+        })
+    }
+    /// The message answering the dialog sends.
+    pub fn answer(self, message: Message) -> Option<crate::app::Message> {
+        match message {
+            Message::Confirm => Some(*self.on_confirm),
+            Message::Cancel => self.on_cancel.map(|message| *message),
+            Message::Ask(_) => None,
+        }
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        let confirm = if self.destructive {
+            widget::button::destructive(self.confirm_label.clone())
+        } else {
+            widget::button::suggested(self.confirm_label.clone())
+        };
+        widget::dialog(self.title.clone())
+            .body(self.body.clone())
+            .primary_action(confirm.on_press(crate::app::Message::Confirm(Message::Confirm)))
+            .secondary_action(
+                widget::button::standard("Cancel")
+                    .on_press(crate::app::Message::Confirm(Message::Cancel)),
+            )
+            .into()
+    }
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+pub const USAGE: &str = "\
+Usage: qersui [OPTION]
+       qersui doctor
+       qersui autostart
+
+  --new OS [RELEASE]   start creating a VM, e.g. `--new ubuntu 24.04`
+  --launch NAME        launch the VM called NAME from the library
+  --help               show this help
+
+A running QERSUI is focused and navigated instead of opening a second window.
+";
+
+/// A flow the command line, or a second invocation over D-Bus, asks QERSUI to open.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    /// Open the creation options for `os`, a quickget OS name such as `ubuntu`.
+    NewVM { os: String, release: Option<String> },
+    /// Launch a library VM by name.
+    Launch(String),
+}
+
+/// Passed to the running instance as the D-Bus action name, so it has to round-trip.
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+impl FromStr for Activation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s).map_err(|e| format!("Invalid activation {s}: {e}"))
+    }
+}
+
+/// What the command line asked for, `None` for a plain start.
+///
+/// `Err` holds what to print before the usage, empty for `--help`.
+pub fn parse(args: &[String]) -> Result<Option<Activation>, String> {
+    let mut args = args.iter().map(String::as_str);
+    let activation = match args.next() {
+        None => return Ok(None),
+        Some("--help" | "-h") => return Err(String::new()),
+        Some("--new") => {
+            let os = args
+                .next()
+                .ok_or_else(|| String::from("--new needs an OS name"))?;
+            Activation::NewVM {
+                os: os.to_string(),
+                release: args.next().map(str::to_string),
+            }
+        }
+        Some("--launch") => {
+            let name = args
+                .next()
+                .ok_or_else(|| String::from("--launch needs a VM name"))?;
+            Activation::Launch(name.to_string())
+        }
+        Some(other) => return Err(format!("Unknown argument {other}")),
+    };
+    match args.next() {
+        Some(extra) => Err(format!("Unexpected argument {extra}")),
+        None => Ok(Some(activation)),
+    }
+}
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use serde::{Deserialize, Serialize};
+
+/// Colors a VM can be marked with, chosen to stay distinct in both light and dark themes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccentColor {
+    Blue,
+    Green,
+    Yellow,
+    Orange,
+    Red,
+    Purple,
+    Pink,
+    Gray,
+}
+
+/// Dropdown entries: no color, then [`AccentColor::ALL`] in order.
+pub const COLOR_CHOICES: [&str; 9] = [
+    "No color", "Blue", "Green", "Yellow", "Orange", "Red", "Purple", "Pink", "Gray",
+];
+
+impl AccentColor {
+    pub const ALL: [Self; 8] = [
+        Self::Blue,
+        Self::Green,
+        Self::Yellow,
+        Self::Orange,
+        Self::Red,
+        Self::Purple,
+        Self::Pink,
+        Self::Gray,
+    ];
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            Self::Blue => (0x35, 0x84, 0xe4),
+            Self::Green => (0x2e, 0xc2, 0x7e),
+            Self::Yellow => (0xf6, 0xd3, 0x2d),
+            Self::Orange => (0xff, 0x78, 0x00),
+            Self::Red => (0xe0, 0x1b, 0x24),
+            Self::Purple => (0x91, 0x41, 0xac),
+            Self::Pink => (0xdc, 0x5a, 0xa0),
+            Self::Gray => (0x77, 0x76, 0x7b),
+        }
+    }
+    /// Index into [`COLOR_CHOICES`], where 0 is no color.
+    pub fn choice(color: Option<Self>) -> usize {
+        color.map_or(0, |color| {
+            Self::ALL.iter().position(|c| *c == color).unwrap_or(0) + 1
+        })
+    }
+    pub fn from_choice(choice: usize) -> Option<Self> {
+        choice
+            .checked_sub(1)
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
+}
+
+/// How a VM is marked wherever it shows up, to tell similar guests apart.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Appearance {
+    pub color: Option<AccentColor>,
+    /// An emoji or other short glyph shown before the name.
+    pub glyph: String,
+}
+
+impl Appearance {
+    /// `name` prefixed with the glyph, for window titles and notifications.
+    pub fn title(&self, name: &str) -> String {
+        match self.glyph.trim() {
+            "" => name.to_string(),
+            glyph => format!("{glyph} {name}"),
+        }
+    }
+}
@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::core::units::parse_size;
+use crate::core::vm::VM;
+
+/// Only report progress after this many bytes to avoid flooding the UI.
+const PROGRESS_INTERVAL: u64 = 16 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// The config and VM directory in a zstd-compressed tarball, restorable by extracting it.
+    TarZst,
+    /// An OVF descriptor and streamOptimized VMDK, importable by other hypervisors.
+    Ova,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::TarZst => "tar.zst",
+            Self::Ova => "ova",
+        }
+    }
+}
+
+impl std::fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TarZst => write!(f, "Portable archive (.tar.zst)"),
+            Self::Ova => write!(f, "Open Virtual Appliance (.ova)"),
+        }
+    }
+}
+
+/// A shared flag checked between chunks of work; setting it aborts the export.
+#[derive(Clone, Debug, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ExportProgress {
+    Stage(String),
+    Bytes { done: u64, total: u64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct ExportRequest {
+    pub vm: VM,
+    pub format: ArchiveFormat,
+    /// zstd compression level, 1-19. Ignored for OVA, which is stored uncompressed.
+    pub level: i32,
+    pub destination: PathBuf,
+}
+
+impl ExportRequest {
+    pub fn output_path(&self) -> PathBuf {
+        self.destination
+            .join(format!("{}.{}", self.vm.name, self.format.extension()))
+    }
+}
+
+/// Write the archive, blocking the current thread. Partial output is removed on failure or cancellation.
+pub fn export(
+    request: &ExportRequest,
+    cancellation: &Cancellation,
+    progress: &UnboundedSender<ExportProgress>,
+) -> Result<PathBuf, String> {
+    let output = request.output_path();
+    if output.exists() {
+        return Err(format!("{} already exists", output.display()));
+    }
+    let result = match request.format {
+        ArchiveFormat::TarZst => export_tar_zst(request, &output, cancellation, progress),
+        ArchiveFormat::Ova => export_ova(request, &output, cancellation, progress),
+    };
+    if result.is_err() {
+        let _ = std::fs::remove_file(&output);
+    }
+    result.map(|_| output)
+}
+
+fn export_tar_zst(
+    request: &ExportRequest,
+    output: &Path,
+    cancellation: &Cancellation,
+    progress: &UnboundedSender<ExportProgress>,
+) -> Result<(), String> {
+    let vm = &request.vm;
+    let mut entries = vec![(
+        vm.config_path.clone(),
+        PathBuf::from(format!("{}.conf", vm.name)),
+    )];
+    for path in vm.disk_images().into_iter().chain(vm.firmware_vars()) {
+        if let Ok(relative) = path.strip_prefix(vm.root()) {
+            entries.push((path.clone(), relative.to_path_buf()));
+        }
+    }
+
+    let file =
+        File::create(output).map_err(|e| format!("Could not create {}: {e}", output.display()))?;
+    let encoder =
+        zstd::stream::write::Encoder::new(file, request.level).map_err(|e| e.to_string())?;
+    let mut builder = tar::Builder::new(encoder);
+    let mut counter = ProgressCounter::new(&entries, cancellation, progress);
+    for (source, name) in &entries {
+        let _ = progress.send(ExportProgress::Stage(format!(
+            "Compressing {}",
+            name.display()
+        )));
+        let file =
+            File::open(source).map_err(|e| format!("Could not open {}: {e}", source.display()))?;
+        let size = file.metadata().map_err(|e| e.to_string())?.len();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(size);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, counter.reader(file))
+            .map_err(|e| error_or_cancelled(e, cancellation))?;
+    }
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .and_then(|mut file| file.flush())
+        .map_err(|e| e.to_string())
+}
+
+fn export_ova(
+    request: &ExportRequest,
+    output: &Path,
+    cancellation: &Cancellation,
+    progress: &UnboundedSender<ExportProgress>,
+) -> Result<(), String> {
+    let vm = &request.vm;
+    let disk = vm
+        .disk_images()
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("{} has no disk image", vm.name))?;
+    let capacity = virtual_size(&disk)?;
+    let vmdk_name = format!("{}-disk1.vmdk", vm.name);
+    let vmdk = request.destination.join(format!(".{vmdk_name}.partial"));
+
+    let _ = progress.send(ExportProgress::Stage(String::from(
+        "Converting disk to VMDK",
+    )));
+    let mut child = std::process::Command::new("qemu-img")
+        .args(["convert", "-O", "vmdk", "-o", "subformat=streamOptimized"])
+        .arg(&disk)
+        .arg(&vmdk)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Could not run qemu-img: {e}"))?;
+    let status = loop {
+        if cancellation.is_cancelled() {
+            let _ = child.kill();
+            let _ = std::fs::remove_file(&vmdk);
+            return Err(String::from("Export cancelled"));
+        }
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None => std::thread::sleep(Duration::from_millis(250)),
+        }
+    };
+    if !status.success() {
+        let _ = std::fs::remove_file(&vmdk);
+        return Err(format!("qemu-img exited with {status}"));
+    }
+
+    let descriptor = ovf_descriptor(vm, &vmdk_name, capacity);
+    let result = package_ova(
+        output,
+        &format!("{}.ovf", vm.name),
+        &descriptor,
+        &vmdk,
+        &vmdk_name,
+        cancellation,
+        progress,
+    );
+    let _ = std::fs::remove_file(&vmdk);
+    result
+}
+
+fn package_ova(
+    output: &Path,
+    descriptor_name: &str,
+    descriptor: &str,
+    vmdk: &Path,
+    vmdk_name: &str,
+    cancellation: &Cancellation,
+    progress: &UnboundedSender<ExportProgress>,
+) -> Result<(), String> {
+    let entries = vec![(vmdk.to_path_buf(), PathBuf::from(vmdk_name))];
+    let file =
+        File::create(output).map_err(|e| format!("Could not create {}: {e}", output.display()))?;
+    let mut builder = tar::Builder::new(file);
+
+    // The OVF specification requires the descriptor to be the first file in the archive.
+    let mut header = tar::Header::new_ustar();
+    header.set_size(descriptor.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, descriptor_name, descriptor.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let _ = progress.send(ExportProgress::Stage(String::from("Packaging appliance")));
+    let mut counter = ProgressCounter::new(&entries, cancellation, progress);
+    let file = File::open(vmdk).map_err(|e| e.to_string())?;
+    let mut header = tar::Header::new_ustar();
+    header.set_size(file.metadata().map_err(|e| e.to_string())?.len());
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, vmdk_name, counter.reader(file))
+        .map_err(|e| error_or_cancelled(e, cancellation))?;
+    builder
+        .into_inner()
+        .and_then(|mut file| file.flush())
+        .map_err(|e| e.to_string())
+}
+
+fn virtual_size(disk: &Path) -> Result<u64, String> {
+    let output = std::process::Command::new("qemu-img")
+        .args(["info", "--output=json"])
+        .arg(disk)
+        .output()
+        .map_err(|e| format!("Could not run qemu-img: {e}"))?;
+    let info: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+    info["virtual-size"]
+        .as_u64()
+        .ok_or_else(|| format!("Could not determine the size of {}", disk.display()))
+}
+
+fn ovf_descriptor(vm: &VM, vmdk_name: &str, capacity: u64) -> String {
+    let memory_mib = vm.ram().and_then(parse_size).unwrap_or(4 << 30) >> 20;
+    let cpu_cores = vm
+        .cpu_cores()
+        .and_then(|cores| cores.parse::<u32>().ok())
+        .unwrap_or(2);
+    let name = &vm.name;
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Envelope xmlns="http://schemas.dmtf.org/ovf/envelope/1" xmlns:ovf="http://schemas.dmtf.org/ovf/envelope/1" xmlns:rasd="http://schemas.dmtf.org/wbem/wscim/1/cim-schema/2/CIM_ResourceAllocationSettingData" xmlns:vssd="http://schemas.dmtf.org/wbem/wscim/1/cim-schema/2/CIM_VirtualSystemSettingData">
+  <References>
+    <File ovf:id="file1" ovf:href="{vmdk_name}"/>
+  </References>
+  <DiskSection>
+    <Info>Virtual disk information</Info>
+    <Disk ovf:diskId="vmdisk1" ovf:fileRef="file1" ovf:capacity="{capacity}" ovf:capacityAllocationUnits="byte" ovf:format="http://www.vmware.com/interfaces/specifications/vmdk.html#streamOptimized"/>
+  </DiskSection>
+  <VirtualSystem ovf:id="{name}">
+    <Info>Exported from QERSUI</Info>
+    <Name>{name}</Name>
+    <VirtualHardwareSection>
+      <Info>Virtual hardware requirements</Info>
+      <Item>
+        <rasd:ElementName>{cpu_cores} virtual CPU(s)</rasd:ElementName>
+        <rasd:InstanceID>1</rasd:InstanceID>
+        <rasd:ResourceType>3</rasd:ResourceType>
+        <rasd:VirtualQuantity>{cpu_cores}</rasd:VirtualQuantity>
+      </Item>
+      <Item>
+        <rasd:AllocationUnits>byte * 2^20</rasd:AllocationUnits>
+        <rasd:ElementName>{memory_mib} MB of memory</rasd:ElementName>
+        <rasd:InstanceID>2</rasd:InstanceID>
+        <rasd:ResourceType>4</rasd:ResourceType>
+        <rasd:VirtualQuantity>{memory_mib}</rasd:VirtualQuantity>
+      </Item>
+      <Item>
+        <rasd:ElementName>SATA Controller</rasd:ElementName>
+        <rasd:InstanceID>3</rasd:InstanceID>
+        <rasd:ResourceSubType>AHCI</rasd:ResourceSubType>
+        <rasd:ResourceType>20</rasd:ResourceType>
+      </Item>
+      <Item>
+        <rasd:AddressOnParent>0</rasd:AddressOnParent>
+        <rasd:ElementName>Hard Disk 1</rasd:ElementName>
+        <rasd:HostResource>ovf:/disk/vmdisk1</rasd:HostResource>
+        <rasd:InstanceID>4</rasd:InstanceID>
+        <rasd:Parent>3</rasd:Parent>
+        <rasd:ResourceType>17</rasd:ResourceType>
+      </Item>
+    </VirtualHardwareSection>
+  </VirtualSystem>
+</Envelope>
+"#
+    )
+}
+
+fn error_or_cancelled(e: io::Error, cancellation: &Cancellation) -> String {
+    if cancellation.is_cancelled() {
+        String::from("Export cancelled")
+    } else {
+        e.to_string()
+    }
+}
+
+/// Tracks bytes read across all archived files and aborts reads once cancelled.
+struct ProgressCounter<'a> {
+    done: u64,
+    reported: u64,
+    total: u64,
+    cancellation: &'a Cancellation,
+    progress: &'a UnboundedSender<ExportProgress>,
+}
+
+impl<'a> ProgressCounter<'a> {
+    fn new(
+        entries: &[(PathBuf, PathBuf)],
+        cancellation: &'a Cancellation,
+        progress: &'a UnboundedSender<ExportProgress>,
+    ) -> Self {
+        let total = entries
+            .iter()
+            .filter_map(|(source, _)| std::fs::metadata(source).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        Self {
+            done: 0,
+            reported: 0,
+            total,
+            cancellation,
+            progress,
+        }
+    }
+    fn reader<R: Read>(&mut self, inner: R) -> CountingReader<'_, 'a, R> {
+        CountingReader {
+            inner,
+            counter: self,
+        }
+    }
+}
+
+struct CountingReader<'c, 'a, R> {
+    inner: R,
+    counter: &'c mut ProgressCounter<'a>,
+}
+
+impl<R: Read> Read for CountingReader<'_, '_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.counter.cancellation.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+        let read = self.inner.read(buf)?;
+        let counter = &mut *self.counter;
+        counter.done += read as u64;
+        if read == 0 || counter.done - counter.reported >= PROGRESS_INTERVAL {
+            counter.reported = counter.done;
+            let _ = counter.progress.send(ExportProgress::Bytes {
+                done: counter.done,
+                total: counter.total,
+            });
+        }
+        Ok(read)
+    }
+}
@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::encryption;
+use crate::core::launcher;
+use crate::core::vm::VM;
+
+/// Subcommand run at login, starting the autostart VMs headless without opening the window.
+pub const SUBCOMMAND: &str = "autostart";
+
+const DESKTOP_FILE: &str = "qersui-autostart.desktop";
+const SYSTEMD_UNIT: &str = "qersui-autostart.service";
+
+/// How VMs marked for autostart are started when the user logs in.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginAutostart {
+    /// Only when QERSUI is opened.
+    #[default]
+    Off,
+    /// An XDG autostart entry, run by the desktop session.
+    Desktop,
+    /// A systemd user unit, which also works without a graphical session.
+    Systemd,
+}
+
+impl LoginAutostart {
+    pub const ALL: [Self; 3] = [Self::Off, Self::Desktop, Self::Systemd];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Off => "Only when QERSUI opens",
+            Self::Desktop => "At login (desktop autostart)",
+            Self::Systemd => "At login (systemd user unit)",
+        }
+    }
+}
+
+/// The VMs among `vms` marked for autostart that aren't already running.
+///
+/// Templates with linked clones are left out, as writing to their disk breaks the clones.
+pub fn pending(vms: &[VM]) -> impl Iterator<Item = &VM> {
+    let templates = vms
+        .iter()
+        .filter_map(|vm| vm.metadata().cloned_from)
+        .collect::<HashSet<_>>();
+    vms.iter().filter(move |vm| {
+        vm.metadata().autostart
+            && vm.running_pid().is_none()
+            && !templates.contains(&vm.config_path)
+    })
+}
+
+/// Start an autostart VM, unlocking encrypted disks with the passphrase in the keyring.
+///
+/// Nobody is around to type a passphrase, so encrypted VMs without a stored one fail instead
+/// of prompting.
+pub async fn start(vm: VM, headless: bool) -> (VM, Result<(), String>) {
+    let passphrase = if vm.is_encrypted() {
+        match encryption::lookup_passphrase(&vm).await {
+            Some(passphrase) => Some(passphrase),
+            None => {
+                let error = String::from("Remember its disk passphrase to start it automatically");
+                return (vm, Err(error));
+            }
+        }
+    } else {
+        None
+    };
+    let result = if headless {
+        launcher::start_headless(&vm, passphrase).await
+    } else {
+        launcher::start(&vm, passphrase).await
+    };
+    (vm, result)
+}
+
+fn config_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+/// Replace whichever login entry is installed with `kind`.
+pub async fn install(kind: LoginAutostart) -> Result<(), String> {
+    let config_home =
+        config_home().ok_or_else(|| String::from("Could not find the config directory"))?;
+    let desktop_file = config_home.join("autostart").join(DESKTOP_FILE);
+    let unit_file = config_home.join("systemd/user").join(SYSTEMD_UNIT);
+    if unit_file.exists() {
+        systemctl(&["disable", SYSTEMD_UNIT]).await?;
+    }
+    for path in [&desktop_file, &unit_file] {
+        match tokio::fs::remove_file(path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Could not remove {}: {e}", path.display()))
+            }
+            _ => {}
+        }
+    }
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Could not find the QERSUI executable: {e}"))?;
+    let command = format!("{} {SUBCOMMAND}", exe.display());
+    let (path, contents) = match kind {
+        LoginAutostart::Off => return Ok(()),
+        LoginAutostart::Desktop => (
+            desktop_file,
+            format!(
+                "[Desktop Entry]\n\
+                 Type=Application\n\
+                 Name=QERSUI autostart VMs\n\
+                 Exec={command}\n\
+                 NoDisplay=true\n\
+                 X-GNOME-Autostart-enabled=true\n"
+            ),
+        ),
+        LoginAutostart::Systemd => (
+            unit_file,
+            format!(
+                "[Unit]\n\
+                 Description=Start QERSUI autostart VMs\n\
+                 \n\
+                 [Service]\n\
+                 Type=oneshot\n\
+                 RemainAfterExit=yes\n\
+                 ExecStart={command}\n\
+                 \n\
+                 [Install]\n\
+                 WantedBy=default.target\n"
+            ),
+        ),
+    };
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    }
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    if kind == LoginAutostart::Systemd {
+        systemctl(&["daemon-reload"]).await?;
+        systemctl(&["enable", SYSTEMD_UNIT]).await?;
+    }
+    Ok(())
+}
+
+async fn systemctl(args: &[&str]) -> Result<(), String> {
+    let status = tokio::process::Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .status()
+        .await
+        .map_err(|e| format!("Could not run systemctl: {e}"))?;
+    status
+        .success()
+        .then_some(())
+        .ok_or_else(|| format!("systemctl --user {} exited with {status}", args.join(" ")))
+}
@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::future::Future;
+use std::path::PathBuf;
+
+use cosmic::app::Command;
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{subscription, Subscription};
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::core::hooks::{Event, EventKind};
+use crate::core::vm::VM;
+
+/// App-wide events published by downloads, the VM supervisor and other background work.
+///
+/// Every page receives every event through [`crate::app::Message::Bus`], so publishers don't need
+/// to know who reacts to them.
+#[derive(Clone, Debug)]
+pub enum BusEvent {
+    VMStarted(VM),
+    VMStopped(VM),
+    /// A config was imported or otherwise added from outside the VM directories.
+    VMAdded(PathBuf),
+    /// A VM was renamed, moving its config from `from` to `to`.
+    VMRenamed {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    CreationComplete(PathBuf),
+    DownloadFailed {
+        name: String,
+        error: String,
+    },
+    /// A VM's release reached its end of life or has a successor.
+    ReleaseNotice {
+        title: String,
+        message: String,
+    },
+}
+
+static BUS: Lazy<broadcast::Sender<BusEvent>> = Lazy::new(|| broadcast::channel(256).0);
+
+impl BusEvent {
+    /// The user hook event corresponding to this bus event, if hooks may subscribe to it.
+    pub fn hook_event(&self) -> Option<Event> {
+        let event = match self {
+            Self::VMStarted(vm) => Event::new(EventKind::VMStarted, &vm.name),
+            Self::VMStopped(vm) => Event::new(EventKind::VMStopped, &vm.name),
+            Self::CreationComplete(config_path) => {
+                let name = config_path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                Event::new(EventKind::CreationComplete, name)
+            }
+            Self::DownloadFailed { name, error } => {
+                Event::new(EventKind::DownloadFailed, name).with_detail(error)
+            }
+            Self::VMAdded(_) | Self::VMRenamed { .. } | Self::ReleaseNotice { .. } => return None,
+        };
+        Some(event)
+    }
+}
+
+/// A desktop notification for events that happen without the user watching.
+pub struct BusNotification {
+    /// Notifications with the same ID replace each other.
+    pub id: String,
+    pub title: String,
+    pub body: String,
+}
+
+impl BusEvent {
+    pub fn notification(&self) -> Option<BusNotification> {
+        match self {
+            Self::VMStopped(vm) => Some(BusNotification {
+                id: format!("stopped-{}", vm.config_path.display()),
+                title: vm.metadata().appearance.title(&vm.name),
+                body: String::from("The VM has shut down"),
+            }),
+            Self::DownloadFailed { name, error } => Some(BusNotification {
+                id: format!("download-{name}"),
+                title: format!("Download of {name} failed"),
+                body: error.clone(),
+            }),
+            Self::ReleaseNotice { title, message } => Some(BusNotification {
+                id: format!("release-{title}"),
+                title: title.clone(),
+                body: message.clone(),
+            }),
+            Self::VMStarted(_)
+            | Self::VMAdded(_)
+            | Self::VMRenamed { .. }
+            | Self::CreationComplete(_) => None,
+        }
+    }
+}
+
+/// Publish an event. Events are dropped if nothing is subscribed yet.
+pub fn publish(event: BusEvent) {
+    let _ = BUS.send(event);
+}
+
+/// Run a future whose only output is what it publishes on the bus.
+pub fn background(
+    future: impl Future<Output = ()> + Send + 'static,
+) -> Command<crate::app::Message> {
+    Command::perform(future, |()| cosmic::app::Message::None)
+}
+
+pub fn subscription() -> Subscription<crate::app::Message> {
+    struct EventBus;
+    subscription::channel(
+        std::any::TypeId::of::<EventBus>(),
+        100,
+        |mut output| async move {
+            let mut receiver = BUS.subscribe();
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let _ = output.send(crate::app::Message::Bus(event)).await;
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Event bus lagged, {skipped} events dropped");
+                    }
+                    Err(RecvError::Closed) => std::future::pending().await,
+                }
+            }
+        },
+    )
+}
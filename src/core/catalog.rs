@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Instant, SystemTime};
+
+use quickget_core::data_structures::OS;
+use quickget_core::ConfigSearch;
+
+use crate::core::error::{AppError, ErrorCategory};
+
+/// Numbers catalog loads, so a retried load isn't mistaken for the cancelled one.
+static NEXT_LOAD: AtomicU64 = AtomicU64::new(0);
+
+/// What a catalog load is doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// quickget is downloading and unpacking its catalog.
+    Fetching,
+    /// Writing the catalog to the cache, for when loading it fails or takes too long.
+    Saving,
+}
+
+impl Stage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Fetching => "Downloading the OS catalog",
+            Self::Saving => "Saving a copy for offline use",
+        }
+    }
+}
+
+/// A catalog load shown on the loading page.
+#[derive(Clone, Debug)]
+pub struct CatalogLoad {
+    pub id: u64,
+    pub started: Instant,
+    pub stage: Stage,
+    /// Stopped from the loading page, which then offers to retry.
+    pub cancelled: bool,
+    /// When the cached catalog was saved, if there is one to fall back to.
+    pub cached_at: Option<SystemTime>,
+}
+
+impl CatalogLoad {
+    pub fn start() -> Self {
+        Self {
+            id: NEXT_LOAD.fetch_add(1, Ordering::Relaxed),
+            started: Instant::now(),
+            stage: Stage::Fetching,
+            cancelled: false,
+            cached_at: cached_at(),
+        }
+    }
+}
+
+/// `$XDG_CACHE_HOME/qersui/catalog.json`.
+fn cache_path() -> Option<PathBuf> {
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(cache_home.join("qersui").join("catalog.json"))
+}
+
+fn cache_error(e: impl std::fmt::Display) -> AppError {
+    AppError::new(
+        ErrorCategory::Quickget,
+        "Could not read the saved OS catalog",
+    )
+    .caused_by(e)
+}
+
+/// When the catalog was last saved.
+pub fn cached_at() -> Option<SystemTime> {
+    std::fs::metadata(cache_path()?).ok()?.modified().ok()
+}
+
+/// Fetch the quickget catalog.
+pub async fn fetch() -> Result<Vec<OS>, AppError> {
+    ConfigSearch::new()
+        .await
+        .map(|search| search.into_os_list())
+        .map_err(|e| {
+            AppError::from_error(ErrorCategory::Quickget, "Could not load the OS catalog", &e)
+        })
+}
+
+/// Keep `os_list` for [`load_cached`].
+pub async fn save(os_list: &[OS]) -> Result<(), String> {
+    let path = cache_path().ok_or_else(|| String::from("No cache directory"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let contents = serde_json::to_vec(os_list).map_err(|e| e.to_string())?;
+    // Written aside first, so a load racing the save never reads half a catalog.
+    let partial = path.with_extension("json.part");
+    tokio::fs::write(&partial, contents)
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::fs::rename(&partial, &path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The catalog as last saved, e.g. when the network is down.
+pub async fn load_cached() -> Result<Vec<OS>, AppError> {
+    let path = cache_path().ok_or_else(|| cache_error("No cache directory"))?;
+    let contents = tokio::fs::read(&path).await.map_err(cache_error)?;
+    serde_json::from_slice(&contents).map_err(cache_error)
+}
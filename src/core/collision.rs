@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::Path;
+
+use quickget_core::QGDownload;
+
+use crate::core::duplicate;
+use crate::core::vm::VMConfig;
+
+/// A VM of the name quickget would use that is already in the target directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Collision {
+    /// quickget's name for the VM, e.g. `ubuntu-24.04`.
+    pub name: String,
+    /// `<name>.conf` exists and would be overwritten.
+    pub config: bool,
+    /// The VM directory holds a disk image, which the new VM would boot instead of a fresh one.
+    pub disk: bool,
+    /// Installer images already downloaded into the VM directory.
+    pub downloads: usize,
+    /// The first name nothing in the directory uses yet.
+    pub free_name: String,
+}
+
+impl Collision {
+    /// Whether creating under quickget's name would overwrite or mix with another VM.
+    pub fn clobbers(&self) -> bool {
+        self.config || self.disk
+    }
+    pub fn message(&self) -> String {
+        let found = match (self.config, self.disk) {
+            (true, true) => "a config and disk",
+            (true, false) => "a config",
+            (false, true) => "a disk",
+            (false, false) => "downloaded installer images",
+        };
+        format!("This directory already has {found} for {}", self.name)
+    }
+}
+
+/// The VM directory quickget downloads into: `directory/<name>`.
+fn vm_dir<'a>(directory: &Path, downloads: &'a [QGDownload]) -> Option<&'a Path> {
+    downloads
+        .iter()
+        .filter_map(|download| download.path.parent())
+        .find(|parent| parent.parent() == Some(directory))
+}
+
+/// Look for an existing VM where quickget would put the new one.
+pub fn check(directory: &Path, downloads: &[QGDownload]) -> Option<Collision> {
+    let vm_dir = vm_dir(directory, downloads)?;
+    let name = vm_dir.file_name()?.to_string_lossy().into_owned();
+    let config = directory.join(format!("{name}.conf")).exists();
+    let disk = std::fs::read_dir(vm_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .any(|entry| entry.file_name().to_string_lossy().starts_with("disk."));
+    let downloads = downloads
+        .iter()
+        .filter(|download| download.path.starts_with(vm_dir) && download.path.exists())
+        .count();
+    if !config && !disk && downloads == 0 {
+        return None;
+    }
+    Some(Collision {
+        free_name: duplicate::numbered_name(directory, &name),
+        name,
+        config,
+        disk,
+        downloads,
+    })
+}
+
+/// Creating a VM under another name than quickget's, to stay clear of a [`Collision`].
+#[derive(Clone, Debug)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+    /// Keep using the installer images already downloaded for `from`.
+    pub reuse_downloads: bool,
+}
+
+impl Rename {
+    pub fn new(collision: &Collision, reuse_downloads: bool) -> Self {
+        Self {
+            from: collision.name.clone(),
+            to: collision.free_name.clone(),
+            reuse_downloads: reuse_downloads && collision.downloads > 0,
+        }
+    }
+    /// Download into the renamed VM's directory, unless the existing images are reused.
+    pub fn apply_downloads(&self, downloads: &mut [QGDownload], directory: &Path) {
+        if self.reuse_downloads {
+            return;
+        }
+        let old_dir = directory.join(&self.from);
+        for download in downloads {
+            if let Ok(rest) = download.path.strip_prefix(&old_dir) {
+                download.path = directory.join(&self.to).join(rest);
+            }
+        }
+    }
+    /// Point a config quickget wrote for `from` at the renamed VM's directory.
+    pub fn apply_config(&self, config: &mut VMConfig) {
+        duplicate::move_paths(config, &self.from, &self.to, self.reuse_downloads);
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fmt;
+use std::path::Path;
+
+use crate::core::vm::VM;
+
+/// A quickemu release, e.g. `4.9.6`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct QuickemuVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+const fn release(major: u32, minor: u32, patch: u32) -> QuickemuVersion {
+    QuickemuVersion {
+        major,
+        minor,
+        patch,
+    }
+}
+
+/// Config keys added after the oldest quickemu still packaged by distros, with the release that
+/// first read them. Keys not listed work with every version.
+const KEYS_SINCE: [(&str, QuickemuVersion); 9] = [
+    ("preallocation", release(3, 11, 0)),
+    ("macaddr", release(4, 0, 0)),
+    ("mouse", release(4, 0, 0)),
+    ("keyboard", release(4, 0, 0)),
+    ("keyboard_layout", release(4, 0, 0)),
+    ("braille", release(4, 0, 0)),
+    ("monitor", release(4, 0, 0)),
+    ("serial", release(4, 0, 0)),
+    ("sound_duplex", release(4, 9, 3)),
+];
+
+/// Values that need a newer quickemu than their key does, with what older releases understand
+/// closest to them.
+const VALUES_SINCE: [(&str, &str, QuickemuVersion, &str); 3] = [
+    ("sound_card", "usb-audio", release(4, 9, 3), "intel-hda"),
+    ("mouse", "virtio", release(4, 9, 3), "usb"),
+    ("keyboard", "virtio", release(4, 9, 3), "usb"),
+];
+
+impl QuickemuVersion {
+    /// Read the output of `quickemu --version`, e.g. `4.9.6` or `quickemu 4.9.6`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let version = text.split_whitespace().find_map(|word| {
+            let word = word.trim_start_matches('v');
+            word.starts_with(|c: char| c.is_ascii_digit())
+                .then_some(word)
+        })?;
+        let mut parts = version
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|part| part.parse::<u32>().ok());
+        Some(release(
+            parts.next()??,
+            parts.next().flatten().unwrap_or(0),
+            parts.next().flatten().unwrap_or(0),
+        ))
+    }
+}
+
+impl fmt::Display for QuickemuVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The version of the quickemu at `binary`, if it reports one.
+pub fn detect(binary: &Path) -> Option<QuickemuVersion> {
+    let output = std::process::Command::new(binary)
+        .arg("--version")
+        .output()
+        .ok()?;
+    let version = QuickemuVersion::parse(&String::from_utf8_lossy(&output.stdout));
+    if version.is_none() {
+        tracing::warn!("Could not read the quickemu version, assuming it supports everything");
+    }
+    version
+}
+
+/// Why the installed quickemu can't use `key`, for greying out the option that sets it.
+/// An unknown version is assumed to be recent.
+pub fn unsupported(installed: Option<QuickemuVersion>, key: &str) -> Option<String> {
+    let installed = installed?;
+    KEYS_SINCE
+        .iter()
+        .find(|(known, since)| *known == key && installed < *since)
+        .map(|(_, since)| format!("Needs quickemu {since} or newer, {installed} is installed"))
+}
+
+/// Rewrite `vm`'s config for the installed quickemu: keys it doesn't read are dropped and values
+/// it doesn't know are replaced with the closest older ones.
+pub fn adjust(installed: Option<QuickemuVersion>, vm: &mut VM) {
+    let Some(installed) = installed else {
+        return;
+    };
+    for (key, value, since, fallback) in VALUES_SINCE {
+        if installed < since && vm.config.get(key) == Some(value) {
+            tracing::info!("quickemu {installed} doesn't know {key}={value}, using {fallback}");
+            vm.config.set(key, fallback);
+        }
+    }
+    for (key, since) in KEYS_SINCE {
+        if installed < since && vm.config.get(key).is_some() {
+            tracing::info!("quickemu {installed} doesn't read {key}, leaving it out");
+            vm.config.remove(key);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::core::vm::VM;
+
+/// A quickemu config key, with the values it accepts if they are a fixed set.
+struct KnownKey {
+    key: &'static str,
+    values: Option<&'static [&'static str]>,
+}
+
+const fn key(key: &'static str) -> KnownKey {
+    KnownKey { key, values: None }
+}
+
+const fn choice(key: &'static str, values: &'static [&'static str]) -> KnownKey {
+    KnownKey {
+        key,
+        values: Some(values),
+    }
+}
+
+const ON_OFF: &[&str] = &["on", "off"];
+
+/// The keys quickemu reads from a VM config.
+const KNOWN_KEYS: [KnownKey; 28] = [
+    key("guest_os"),
+    key("disk_img"),
+    key("disk_size"),
+    key("iso"),
+    key("fixed_iso"),
+    key("img"),
+    key("floppy"),
+    key("macos_release"),
+    key("ram"),
+    key("cpu_cores"),
+    choice("boot", &["efi", "legacy"]),
+    choice("secureboot", ON_OFF),
+    choice("tpm", ON_OFF),
+    choice("preallocation", &["off", "metadata", "falloc", "full"]),
+    choice("display", &["gtk", "none", "sdl", "spice", "spice-app"]),
+    key("network"),
+    key("macaddr"),
+    key("port_forwards"),
+    key("usb_devices"),
+    choice(
+        "sound_card",
+        &["intel-hda", "ac97", "es1370", "sb16", "usb-audio", "none"],
+    ),
+    choice("sound_duplex", &["hda-micro", "hda-duplex", "hda-output"]),
+    choice("mouse", &["tablet", "ps2", "usb", "virtio"]),
+    choice("keyboard", &["usb", "ps2", "virtio"]),
+    key("keyboard_layout"),
+    choice("braille", ON_OFF),
+    choice("monitor", &["none", "socket", "telnet"]),
+    choice("serial", &["none", "socket", "telnet"]),
+    key("extra_args"),
+];
+
+/// Keys quickget writes when creating a VM, with the setting they come from.
+pub const CREATION_MANAGED: [(&str, &str); 10] = [
+    ("guest_os", "operating system"),
+    ("disk_img", "disk image"),
+    ("iso", "installer image"),
+    ("fixed_iso", "installer image"),
+    ("img", "installer image"),
+    ("ram", "RAM"),
+    ("cpu_cores", "CPU cores"),
+    ("sound_card", "sound"),
+    ("sound_duplex", "microphone"),
+    ("mouse", "tablet pointer"),
+];
+
+/// Keys the edit page manages, with the setting they come from.
+pub const EDIT_MANAGED: [(&str, &str); 12] = [
+    ("guest_os", "operating system"),
+    ("disk_img", "disk image"),
+    ("iso", "installer image"),
+    ("fixed_iso", "installer image"),
+    ("img", "installer image"),
+    ("ram", "RAM"),
+    ("cpu_cores", "CPU cores"),
+    ("network", "network"),
+    ("macaddr", "MAC address"),
+    ("sound_card", "sound"),
+    ("sound_duplex", "microphone"),
+    ("mouse", "tablet pointer"),
+];
+
+/// A config key set by hand in the advanced section, written after the managed settings.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtraKey {
+    pub key: String,
+    pub value: String,
+}
+
+impl ExtraKey {
+    /// Rows left completely empty are ignored rather than rejected.
+    fn is_blank(&self) -> bool {
+        self.key.trim().is_empty() && self.value.trim().is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+    /// Written anyway, but probably not what was meant.
+    Warning(String),
+    /// Keeps the config from being saved.
+    Error(String),
+}
+
+/// The keys of `vm` not covered by the `managed` settings, for editing by hand.
+pub fn from_vm(vm: &VM, managed: &[(&str, &str)]) -> Vec<ExtraKey> {
+    vm.config
+        .entries()
+        .filter(|(key, _)| !managed.iter().any(|(managed, _)| managed == key))
+        .map(|(key, value)| ExtraKey {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+        .collect()
+}
+
+/// The most serious problem with each row of `extras`.
+pub fn check(extras: &[ExtraKey], managed: &[(&str, &str)]) -> Vec<Option<Issue>> {
+    extras
+        .iter()
+        .enumerate()
+        .map(|(index, extra)| {
+            if extra.is_blank() {
+                return None;
+            }
+            let key = extra.key.trim();
+            let value = extra.value.trim();
+            if key.is_empty() {
+                return Some(Issue::Error(String::from("Enter a key")));
+            }
+            if !valid_key(key) {
+                return Some(Issue::Error(String::from(
+                    "Keys may only contain letters, digits and underscores",
+                )));
+            }
+            // quickemu sources the config with bash, so these would be expanded or break quoting.
+            if value.contains(['"', '`', '$', '\\', '\n']) {
+                return Some(Issue::Error(String::from(
+                    "Values can't contain quotes, backticks, backslashes or $",
+                )));
+            }
+            if extras[..index].iter().any(|other| other.key.trim() == key) {
+                return Some(Issue::Error(format!("{key} is already set above")));
+            }
+            let Some(known) = KNOWN_KEYS.iter().find(|known| known.key == key) else {
+                return Some(Issue::Warning(format!(
+                    "quickemu doesn't use {key}, so it will be ignored"
+                )));
+            };
+            if let Some(values) = known.values {
+                if !values.contains(&value) {
+                    return Some(Issue::Error(format!(
+                        "{key} must be one of {}",
+                        values.join(", ")
+                    )));
+                }
+            }
+            managed
+                .iter()
+                .find(|(managed, _)| *managed == key)
+                .map(|(_, setting)| Issue::Warning(format!("Replaces the {setting} setting")))
+        })
+        .collect()
+}
+
+/// The first error in `extras`, if any.
+pub fn validate(extras: &[ExtraKey], managed: &[(&str, &str)]) -> Result<(), String> {
+    let first_error = check(extras, managed)
+        .into_iter()
+        .zip(extras)
+        .find_map(|(issue, extra)| match issue {
+            Some(Issue::Error(e)) => Some((extra, e)),
+            _ => None,
+        });
+    match first_error {
+        Some((extra, e)) if extra.key.trim().is_empty() => Err(format!("Advanced: {e}")),
+        Some((extra, e)) => Err(format!("Advanced, {}: {e}", extra.key.trim())),
+        None => Ok(()),
+    }
+}
+
+fn valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Write `extras` into the config, after the managed settings so they take precedence.
+pub fn apply(extras: &[ExtraKey], vm: &mut VM) {
+    for extra in extras.iter().filter(|extra| !extra.is_blank()) {
+        vm.config.set(extra.key.trim(), extra.value.trim());
+    }
+}
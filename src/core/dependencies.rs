@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::Path;
+
+use itertools::Itertools;
+use quickemu::config::Arch;
+
+use crate::core::host_probe::{self, HostCapabilities};
+
+/// Distribution families, each with their own package manager and package names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distro {
+    /// Ubuntu and its derivatives, which get quickemu from its PPA.
+    Ubuntu,
+    Debian,
+    Fedora,
+    Arch,
+    OpenSuse,
+    NixOS,
+}
+
+impl Distro {
+    /// Read the host's distribution from the `ID` and `ID_LIKE` of `/etc/os-release`.
+    pub fn detect() -> Option<Self> {
+        let contents = std::fs::read_to_string("/etc/os-release").ok()?;
+        let field = |name: &str| {
+            contents.lines().find_map(|line| {
+                let value = line.strip_prefix(name)?.strip_prefix('=')?;
+                Some(value.trim_matches('"').to_string())
+            })
+        };
+        let ids = [field("ID"), field("ID_LIKE")]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Derivatives list their parents after their own ID, so the first known one wins.
+        ids.split_whitespace().find_map(|id| match id {
+            "ubuntu" => Some(Self::Ubuntu),
+            "debian" => Some(Self::Debian),
+            "fedora" | "rhel" | "centos" => Some(Self::Fedora),
+            "arch" => Some(Self::Arch),
+            "opensuse" | "suse" => Some(Self::OpenSuse),
+            "nixos" => Some(Self::NixOS),
+            _ => id.starts_with("opensuse").then_some(Self::OpenSuse),
+        })
+    }
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Ubuntu => "Ubuntu",
+            Self::Debian => "Debian",
+            Self::Fedora => "Fedora",
+            Self::Arch => "Arch Linux",
+            Self::OpenSuse => "openSUSE",
+            Self::NixOS => "NixOS",
+        }
+    }
+    fn install(&self, packages: &[&str]) -> String {
+        let packages = packages.join(" ");
+        match self {
+            Self::Ubuntu | Self::Debian => format!("sudo apt install {packages}"),
+            Self::Fedora => format!("sudo dnf install {packages}"),
+            Self::Arch => format!("sudo pacman -S --needed {packages}"),
+            Self::OpenSuse => format!("sudo zypper install {packages}"),
+            Self::NixOS => format!(
+                "nix-env -iA {}",
+                packages
+                    .split(' ')
+                    .map(|package| format!("nixpkgs.{package}"))
+                    .join(" ")
+            ),
+        }
+    }
+}
+
+/// Something outside QERSUI that creating or running VMs needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dependency {
+    Quickemu,
+    Qemu,
+    QemuImg,
+    Kvm,
+    Swtpm,
+    SpiceClient,
+}
+
+impl Dependency {
+    pub const ALL: [Self; 6] = [
+        Self::Quickemu,
+        Self::Qemu,
+        Self::QemuImg,
+        Self::Kvm,
+        Self::Swtpm,
+        Self::SpiceClient,
+    ];
+    pub fn label(&self) -> String {
+        match self {
+            Self::Quickemu => String::from("quickemu"),
+            Self::Qemu => format!(
+                "QEMU {} system emulator",
+                host_probe::arch_name(&host_probe::native_arch())
+            ),
+            Self::QemuImg => String::from("qemu-img"),
+            Self::Kvm => String::from("KVM access"),
+            Self::Swtpm => String::from("swtpm"),
+            Self::SpiceClient => String::from("SPICE client"),
+        }
+    }
+    /// What goes wrong without it.
+    fn purpose(&self) -> &'static str {
+        match self {
+            Self::Quickemu => "VMs can be created but not started",
+            Self::Qemu => "VMs for this computer's architecture can't run",
+            Self::QemuImg => "Disk images can't be created, resized or converted",
+            Self::Kvm => "VMs run under slow software emulation",
+            Self::Swtpm => "Windows 11 and other VMs needing a TPM won't start",
+            Self::SpiceClient => "VM displays can only be opened in QEMU's own window",
+        }
+    }
+    fn installed(&self, host: &HostCapabilities) -> bool {
+        match self {
+            Self::Quickemu => host.quickemu,
+            Self::Qemu => host.qemu_installed(&host_probe::native_arch()),
+            Self::QemuImg => host.qemu_img,
+            Self::Kvm => host.kvm,
+            Self::Swtpm => host.swtpm,
+            Self::SpiceClient => !host.spice_clients.is_empty(),
+        }
+    }
+    fn packages(&self, distro: Distro) -> Vec<&'static str> {
+        let arch = host_probe::native_arch();
+        match (self, distro) {
+            (Self::Quickemu, _) => vec!["quickemu"],
+            (Self::Qemu, Distro::NixOS) => vec!["qemu"],
+            (Self::Qemu, Distro::OpenSuse) => match arch {
+                Arch::aarch64 => vec!["qemu-arm"],
+                Arch::riscv64 => vec!["qemu-extra"],
+                _ => vec!["qemu-x86"],
+            },
+            (Self::Qemu, Distro::Ubuntu | Distro::Debian) => match arch {
+                Arch::aarch64 => vec!["qemu-system-arm"],
+                Arch::riscv64 => vec!["qemu-system-misc"],
+                _ => vec!["qemu-system-x86"],
+            },
+            (Self::Qemu, Distro::Fedora | Distro::Arch) => match arch {
+                Arch::aarch64 => vec!["qemu-system-aarch64"],
+                Arch::riscv64 => vec!["qemu-system-riscv"],
+                _ => vec!["qemu-system-x86"],
+            },
+            (Self::QemuImg, Distro::Ubuntu | Distro::Debian) => vec!["qemu-utils"],
+            (Self::QemuImg, Distro::OpenSuse) => vec!["qemu-tools"],
+            (Self::QemuImg, Distro::NixOS) => vec!["qemu"],
+            (Self::QemuImg, Distro::Fedora | Distro::Arch) => vec!["qemu-img"],
+            (Self::Swtpm, Distro::Ubuntu | Distro::Debian | Distro::Fedora) => {
+                vec!["swtpm", "swtpm-tools"]
+            }
+            (Self::Swtpm, _) => vec!["swtpm"],
+            (Self::SpiceClient, _) => vec!["virt-viewer"],
+            (Self::Kvm, _) => vec![],
+        }
+    }
+    /// The command that fixes this on `distro`, to copy into a terminal.
+    fn command(&self, distro: Option<Distro>) -> Option<String> {
+        if *self == Self::Kvm {
+            // The device is there, this user just isn't allowed to open it.
+            return Path::new("/dev/kvm")
+                .exists()
+                .then(|| String::from("sudo usermod -aG kvm $USER"));
+        }
+        let distro = distro?;
+        match (self, distro) {
+            (Self::Quickemu, Distro::Ubuntu) => Some(String::from(
+                "sudo apt-add-repository ppa:flexiondotorg/quickemu && sudo apt install quickemu",
+            )),
+            // Only packaged in the AUR.
+            (Self::Quickemu, Distro::Arch) => Some(String::from("yay -S quickemu")),
+            _ => Some(distro.install(&self.packages(distro))),
+        }
+    }
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Kvm if Path::new("/dev/kvm").exists() => {
+                Some("Log out and back in after joining the kvm group.")
+            }
+            Self::Kvm => Some(
+                "/dev/kvm is missing. Turn on virtualization (VT-x or AMD-V) in the firmware \
+                 setup, then make sure the kvm_intel or kvm_amd module is loaded.",
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// How the host fares for one dependency.
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub dependency: Dependency,
+    pub installed: bool,
+    pub details: String,
+    /// A command to copy that installs or fixes it.
+    pub command: Option<String>,
+}
+
+/// Check every dependency against the probed host.
+pub fn check(host: &HostCapabilities, distro: Option<Distro>) -> Vec<Finding> {
+    Dependency::ALL
+        .into_iter()
+        .map(|dependency| {
+            let installed = dependency.installed(host);
+            let details = match (installed, dependency.hint()) {
+                (true, _) => String::from("Ready"),
+                (false, Some(hint)) => format!("{}. {hint}", dependency.purpose()),
+                (false, None) => format!("{}.", dependency.purpose()),
+            };
+            Finding {
+                dependency,
+                installed,
+                details,
+                command: (!installed).then(|| dependency.command(distro)).flatten(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::core::vm::VM;
+
+/// quickemu `sound_card` values offered in the UI, with their labels.
+pub const SOUND_CARDS: [(&str, &str); 3] = [
+    ("intel-hda", "Intel HD Audio"),
+    ("ac97", "AC'97"),
+    ("none", "No sound"),
+];
+
+/// Dropdown entries, in the order of [`SOUND_CARDS`].
+pub const SOUND_CARD_CHOICES: [&str; 3] = [SOUND_CARDS[0].1, SOUND_CARDS[1].1, SOUND_CARDS[2].1];
+
+/// Guests too old for HD Audio or a USB tablet.
+const LEGACY_GUESTS: [&str; 3] = ["freedos", "kolibrios", "reactos"];
+
+/// Sound and pointer devices, stored in quickemu's `sound_card`, `sound_duplex` and `mouse` keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceOptions {
+    /// A `sound_card` value; others than [`SOUND_CARDS`] set by hand are kept as they are.
+    pub sound_card: String,
+    /// Expose the host microphone to HD Audio guests.
+    pub microphone: bool,
+    /// An absolute-pointing tablet, which follows the host cursor, rather than a relative mouse.
+    pub tablet: bool,
+}
+
+impl DeviceOptions {
+    /// Sensible devices for a quickget OS or quickemu `guest_os`, e.g. `ubuntu` or `macos`.
+    pub fn recommended(os: &str) -> Self {
+        let legacy = LEGACY_GUESTS.contains(&os);
+        Self {
+            sound_card: String::from(if legacy { "ac97" } else { "intel-hda" }),
+            microphone: !legacy,
+            // macOS only has drivers for relative USB mice.
+            tablet: !legacy && os != "macos",
+        }
+    }
+    pub fn from_vm(vm: &VM) -> Self {
+        let defaults = Self::recommended(vm.config.get("guest_os").unwrap_or_default());
+        Self {
+            sound_card: vm
+                .config
+                .get("sound_card")
+                .map_or(defaults.sound_card, str::to_string),
+            microphone: vm
+                .config
+                .get("sound_duplex")
+                .map_or(defaults.microphone, |duplex| duplex != "hda-output"),
+            tablet: vm
+                .config
+                .get("mouse")
+                .map_or(defaults.tablet, |mouse| mouse == "tablet"),
+        }
+    }
+    /// Index into [`SOUND_CARD_CHOICES`], `None` for a card set outside QERSUI.
+    pub fn sound_card_choice(&self) -> Option<usize> {
+        SOUND_CARDS
+            .iter()
+            .position(|(value, _)| *value == self.sound_card)
+    }
+    pub fn set_sound_card_choice(&mut self, choice: usize) {
+        if let Some((value, _)) = SOUND_CARDS.get(choice) {
+            self.sound_card = value.to_string();
+        }
+    }
+    /// The microphone is an HD Audio codec option.
+    pub fn has_microphone_option(&self) -> bool {
+        self.sound_card == "intel-hda"
+    }
+    pub fn apply(&self, vm: &mut VM) {
+        vm.config.set("sound_card", self.sound_card.as_str());
+        if self.has_microphone_option() {
+            let duplex = if self.microphone {
+                "hda-micro"
+            } else {
+                "hda-output"
+            };
+            vm.config.set("sound_duplex", duplex);
+        } else {
+            vm.config.remove("sound_duplex");
+        }
+        vm.config
+            .set("mouse", if self.tablet { "tablet" } else { "usb" });
+    }
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::core::units::format_size;
+use crate::core::vm::VM;
+
+/// Formats a system disk can be converted to; quickemu detects the format when booting.
+pub const FORMATS: [&str; 2] = ["qcow2", "raw"];
+
+/// What `qemu-img info` reports about a disk image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiskInfo {
+    pub path: PathBuf,
+    pub format: String,
+    /// The size the guest sees.
+    pub virtual_size: u64,
+    /// The space the image takes up on the host.
+    pub actual_size: u64,
+    /// Internal snapshots, which rewriting the image would lose.
+    pub snapshots: usize,
+}
+
+/// A maintenance operation on a VM's system disk. QEMU must not be running.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Maintenance {
+    /// Rewrite a qcow2 without the clusters the guest has zeroed or trimmed.
+    Compact,
+    /// Grow the virtual disk to this many bytes.
+    Grow(u64),
+    /// Convert to one of [`FORMATS`].
+    Convert(String),
+}
+
+impl Maintenance {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Compact => String::from("Compacting disk"),
+            Self::Grow(_) => String::from("Growing disk"),
+            Self::Convert(format) => format!("Converting disk to {format}"),
+        }
+    }
+    pub fn done_message(&self) -> String {
+        match self {
+            Self::Compact => String::from("Disk compacted"),
+            Self::Grow(size) => format!(
+                "Disk grown to {}; resize the partition inside the guest to use it",
+                format_size(*size)
+            ),
+            Self::Convert(format) => format!("Disk converted to {format}"),
+        }
+    }
+}
+
+pub async fn info(path: &Path) -> Result<DiskInfo, String> {
+    let output = tokio::process::Command::new("qemu-img")
+        .args(["info", "--output=json"])
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| format!("Could not run qemu-img: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Could not inspect {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let info: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+    Ok(DiskInfo {
+        path: path.to_path_buf(),
+        format: info["format"].as_str().unwrap_or("unknown").to_string(),
+        virtual_size: info["virtual-size"].as_u64().unwrap_or_default(),
+        actual_size: info["actual-size"].as_u64().unwrap_or_default(),
+        snapshots: info["snapshots"].as_array().map_or(0, Vec::len),
+    })
+}
+
+/// Run `action` on the VM's system disk, sending progress percentages while it rewrites it.
+pub async fn run(
+    vm: &VM,
+    action: &Maintenance,
+    progress: &UnboundedSender<f32>,
+) -> Result<(), String> {
+    if vm.running_pid().is_some() {
+        return Err(format!("Shut down {} before changing its disk", vm.name));
+    }
+    if vm.is_encrypted() {
+        return Err(String::from(
+            "Maintenance of encrypted disks isn't supported yet",
+        ));
+    }
+    let disk = vm.system_disk();
+    let info = info(&disk).await?;
+    match action {
+        Maintenance::Compact => {
+            if info.format != "qcow2" {
+                return Err(String::from("Only qcow2 disks can be compacted"));
+            }
+            let temp = partial_path(&disk);
+            convert(&disk, &temp, "qcow2", progress).await?;
+            tokio::fs::rename(&temp, &disk)
+                .await
+                .map_err(|e| format!("Could not replace {}: {e}", disk.display()))
+        }
+        Maintenance::Grow(size) => {
+            if *size <= info.virtual_size {
+                return Err(String::from("The new size must be larger than the disk"));
+            }
+            let output = tokio::process::Command::new("qemu-img")
+                .args(["resize", "-f", &info.format])
+                .arg(&disk)
+                .arg(size.to_string())
+                .output()
+                .await
+                .map_err(|e| format!("Could not run qemu-img: {e}"))?;
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Could not resize disk: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ))
+            }
+        }
+        Maintenance::Convert(format) => {
+            if *format == info.format {
+                return Err(format!("The disk is already {format}"));
+            }
+            let converted = disk.with_extension(format);
+            if converted.exists() {
+                return Err(format!("{} already exists", converted.display()));
+            }
+            let temp = partial_path(&converted);
+            convert(&disk, &temp, format, progress).await?;
+            tokio::fs::rename(&temp, &converted)
+                .await
+                .map_err(|e| format!("Could not rename {}: {e}", temp.display()))?;
+
+            let relative = converted.strip_prefix(vm.root()).unwrap_or(&converted);
+            let mut vm = vm.clone();
+            vm.config
+                .set("disk_img", relative.to_string_lossy().into_owned());
+            vm.save().await?;
+            tokio::fs::remove_file(&disk)
+                .await
+                .map_err(|e| format!("Could not remove {}: {e}", disk.display()))
+        }
+    }
+}
+
+/// Where a rewritten image is staged, next to the final one so it can be renamed into place.
+fn partial_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.partial"))
+}
+
+async fn convert(
+    source: &Path,
+    destination: &Path,
+    format: &str,
+    progress: &UnboundedSender<f32>,
+) -> Result<(), String> {
+    let mut child = tokio::process::Command::new("qemu-img")
+        .args(["convert", "-p", "-O", format])
+        .arg(source)
+        .arg(destination)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run qemu-img: {e}"))?;
+    if let Some(stdout) = child.stdout.take() {
+        let mut reader = BufReader::new(stdout);
+        let mut line = Vec::new();
+        while reader.read_until(b'\r', &mut line).await.unwrap_or(0) > 0 {
+            if let Some(percent) = parse_progress(&String::from_utf8_lossy(&line)) {
+                let _ = progress.send(percent);
+            }
+            line.clear();
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("qemu-img failed: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let _ = tokio::fs::remove_file(destination).await;
+        Err(format!(
+            "qemu-img failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// qemu-img redraws its progress line with carriage returns, e.g. "    (42.17/100%)\r".
+pub fn parse_progress(line: &str) -> Option<f32> {
+    let start = line.find('(')? + 1;
+    let end = line[start..].find('/')? + start;
+    line[start..end].trim().parse().ok()
+}
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use quickget_core::ConfigSearch;
+
+use crate::core::host_probe;
+
+/// The outcome of one self-test step, with details on success or the reason for failure.
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub result: Result<String, String>,
+}
+
+impl Check {
+    fn new(name: &'static str, result: Result<String, String>) -> Self {
+        Self { name, result }
+    }
+    pub fn passed(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Exercise everything QERSUI relies on outside its own process.
+pub async fn run(roots: Vec<PathBuf>) -> Vec<Check> {
+    let mut checks = vec![
+        Check::new("D-Bus session bus", session_bus().await),
+        Check::new("Desktop portal", portal().await),
+        Check::new(
+            "KVM access",
+            host_probe::open_kvm()
+                .map(|_| String::from("/dev/kvm is accessible"))
+                .map_err(|e| format!("/dev/kvm: {e}")),
+        ),
+        Check::new("qemu-img", version("qemu-img").await),
+        Check::new("quickemu", version("quickemu").await),
+    ];
+    for root in roots {
+        checks.push(Check::new("VM directory writable", writable(root).await));
+    }
+    checks.push(Check::new("OS index reachable", index().await));
+    checks
+}
+
+/// The directories VMs get written to: the working directory and those holding registered VMs.
+pub fn vm_roots(registered: &[PathBuf]) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    let parents = registered.iter().filter_map(|path| path.parent());
+    for root in std::env::current_dir()
+        .ok()
+        .into_iter()
+        .chain(parents.map(PathBuf::from))
+    {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
+/// Plain-text pass/fail report for bug reports.
+pub fn report(checks: &[Check]) -> String {
+    let mut report = format!(
+        "QERSUI {} self-test\nHost: {} {}\n\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    );
+    for check in checks {
+        let (status, details) = match &check.result {
+            Ok(details) => ("PASS", details),
+            Err(details) => ("FAIL", details),
+        };
+        report.push_str(&format!("[{status}] {}: {details}\n", check.name));
+    }
+    let failed = checks.iter().filter(|check| !check.passed()).count();
+    report.push_str(&format!(
+        "\n{} passed, {failed} failed\n",
+        checks.len() - failed
+    ));
+    report
+}
+
+async fn session_bus() -> Result<String, String> {
+    ashpd::zbus::Connection::session()
+        .await
+        .map(|_| String::from("connected"))
+        .map_err(|e| e.to_string())
+}
+
+async fn portal() -> Result<String, String> {
+    let settings = ashpd::desktop::settings::Settings::new()
+        .await
+        .map_err(|e| e.to_string())?;
+    settings
+        .color_scheme()
+        .await
+        .map(|_| String::from("org.freedesktop.portal.Desktop responded"))
+        .map_err(|e| e.to_string())
+}
+
+async fn version(program: &str) -> Result<String, String> {
+    let output = tokio::process::Command::new(program)
+        .arg("--version")
+        .output()
+        .await
+        .map_err(|e| format!("could not run {program}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("{program} --version exited with {}", output.status));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+async fn writable(root: PathBuf) -> Result<String, String> {
+    let probe = root.join(".qersui-doctor");
+    tokio::fs::write(&probe, b"")
+        .await
+        .map_err(|e| format!("{}: {e}", root.display()))?;
+    let _ = tokio::fs::remove_file(&probe).await;
+    Ok(root.display().to_string())
+}
+
+async fn index() -> Result<String, String> {
+    let os_list = ConfigSearch::new()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_os_list();
+    Ok(format!("{} operating systems listed", os_list.len()))
+}
@@ -0,0 +1,607 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use quickget_core::QGDownload;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::task::JoinSet;
+
+use crate::core::error::{AppError, ErrorCategory};
+use crate::core::network;
+
+/// Minimum number of bytes between two progress reports.
+const PROGRESS_INTERVAL: u64 = 4 * 1024 * 1024;
+
+/// Smaller files aren't worth splitting across connections.
+const CHUNKED_MIN_SIZE: u64 = 64 * 1024 * 1024;
+
+/// How often a chunked download reports its combined progress.
+const CHUNKED_PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Files downloaded at once across all creation sessions; the rest wait in the order they asked.
+const QUEUE_SLOTS: usize = 2;
+
+static QUEUE: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(QUEUE_SLOTS));
+
+/// Mirrors of hosts quickget often downloads from, as URL prefixes to swap, tried when the
+/// original keeps failing.
+const MIRRORS: [(&str, &str); 5] = [
+    (
+        "https://releases.ubuntu.com/",
+        "https://mirrors.kernel.org/ubuntu-releases/",
+    ),
+    (
+        "https://cdimage.debian.org/debian-cd/",
+        "https://mirrors.kernel.org/debian-cd/",
+    ),
+    (
+        "https://download.fedoraproject.org/pub/",
+        "https://dl.fedoraproject.org/pub/",
+    ),
+    (
+        "https://geo.mirror.pkgbuild.com/",
+        "https://mirrors.kernel.org/archlinux/",
+    ),
+    (
+        "https://mirrors.edge.kernel.org/",
+        "https://mirrors.kernel.org/",
+    ),
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub finished: bool,
+    /// Failed attempts so far, oldest first.
+    pub retries: Vec<Retry>,
+}
+
+/// A failed attempt at a download, followed by another one.
+#[derive(Clone, Debug)]
+pub struct Retry {
+    /// Counting from 1.
+    pub attempt: u32,
+    pub error: String,
+    /// How long it waited before trying again.
+    pub wait: Duration,
+    /// The mirror it switched to for the next attempt.
+    pub mirror: Option<String>,
+}
+
+impl Retry {
+    pub fn label(&self) -> String {
+        let mut label = format!(
+            "Attempt {} failed: {}. Retried after {} s",
+            self.attempt,
+            self.error,
+            self.wait.as_secs()
+        );
+        if let Some(mirror) = &self.mirror {
+            let host = reqwest::Url::parse(mirror)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_else(|| mirror.clone());
+            label.push_str(&format!(" from {host}"));
+        }
+        label
+    }
+}
+
+/// The same file on a mirror, for URLs on a host in [`MIRRORS`].
+fn mirror_for(url: &str) -> Option<String> {
+    MIRRORS.iter().find_map(|(original, mirror)| {
+        url.strip_prefix(original)
+            .map(|rest| format!("{mirror}{rest}"))
+    })
+}
+
+/// Stream a quickget download to disk, reporting progress along the way.
+///
+/// The file is written to a `.part` file next to the destination and only moved into place once
+/// complete. An existing `.part` file is resumed with a range request when the server supports it.
+///
+/// With more than one connection per download configured, large files from servers accepting
+/// range requests are fetched in parallel chunks instead; see [`download_chunked`].
+pub async fn download(
+    download: &QGDownload,
+    progress: impl FnMut(DownloadProgress),
+) -> Result<(), AppError> {
+    fetch(&download.url, &download.path, progress).await
+}
+
+/// A free slot in the download queue, if one is available right away.
+pub fn try_queue_slot() -> Option<SemaphorePermit<'static>> {
+    QUEUE.try_acquire().ok()
+}
+
+/// Wait for a slot in the download queue, held until the permit is dropped.
+pub async fn queue_slot() -> SemaphorePermit<'static> {
+    // The semaphore is never closed.
+    QUEUE.acquire().await.unwrap()
+}
+
+/// [`download`] `url` to `path`, e.g. to fetch an installer image again.
+///
+/// Network failures are retried as the [`network::RetryPolicy`] says, waiting longer each time
+/// and moving to a mirror once the original URL failed repeatedly.
+#[tracing::instrument(skip(path, progress), fields(path = %path.display()), err)]
+pub async fn fetch(
+    url: &str,
+    path: &Path,
+    mut progress: impl FnMut(DownloadProgress),
+) -> Result<(), AppError> {
+    let policy = network::retry_policy();
+    let mut mirror = mirror_for(url);
+    let mut current = url.to_string();
+    let mut failures_here = 0;
+    let mut retries = Vec::new();
+    let mut last = DownloadProgress::default();
+    loop {
+        let result = fetch_once(&current, path, |update| {
+            last = DownloadProgress {
+                retries: retries.clone(),
+                ..update
+            };
+            progress(last.clone());
+        })
+        .await;
+        let error = match result {
+            Ok(()) => return Ok(()),
+            // Disk errors won't go away by trying again.
+            Err(e) if e.category != ErrorCategory::Network => return Err(e),
+            Err(e) if retries.len() as u32 >= policy.attempts => return Err(e),
+            Err(e) => e,
+        };
+        failures_here += 1;
+        let switch = failures_here >= policy.failover_after.max(1) && mirror.is_some();
+        let retry = Retry {
+            attempt: retries.len() as u32 + 1,
+            error: error.summary().to_string(),
+            wait: policy.backoff(retries.len() as u32),
+            mirror: if switch { mirror.take() } else { None },
+        };
+        tracing::warn!(
+            attempt = retry.attempt,
+            "{error}, retrying in {:?}",
+            retry.wait
+        );
+        if let Some(mirror) = &retry.mirror {
+            tracing::info!("Switching to {mirror}");
+            current = mirror.clone();
+            failures_here = 0;
+        }
+        if !policy.resume {
+            let _ = tokio::fs::remove_file(partial_path(path)).await;
+        }
+        let wait = retry.wait;
+        retries.push(retry);
+        last.retries = retries.clone();
+        progress(last.clone());
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// A single attempt at [`fetch`].
+async fn fetch_once(
+    url: &str,
+    path: &Path,
+    mut progress: impl FnMut(DownloadProgress),
+) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| disk_error(format!("Could not create {}", parent.display()), &e))?;
+    }
+    // Finished by an earlier, interrupted creation.
+    if let Ok(metadata) = tokio::fs::metadata(path).await {
+        progress(DownloadProgress {
+            downloaded: metadata.len(),
+            total: Some(metadata.len()),
+            finished: true,
+            ..Default::default()
+        });
+        return Ok(());
+    }
+    let partial = partial_path(path);
+    let existing = tokio::fs::metadata(&partial)
+        .await
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    let connections = network::connections_per_download();
+    if existing == 0 && connections > 1 {
+        if let Some(size) = ranged_size(url).await {
+            match download_chunked(url, path, size, connections, &mut progress).await {
+                Err(ChunkedError::RangesIgnored) => {
+                    tracing::info!("{url} ignored a range request, downloading in a single stream");
+                    let _ = tokio::fs::remove_file(chunked_path(path)).await;
+                }
+                Err(ChunkedError::Failed(e)) => return Err(e),
+                Ok(()) => return Ok(()),
+            }
+        }
+    }
+
+    let mut request = network::client().get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={existing}-"));
+    }
+    let mut response = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| network_error(format!("Could not download {url}"), &e))?;
+    let resumed = existing > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { existing } else { 0 };
+    let total = response.content_length().map(|length| length + downloaded);
+    tracing::info!(resumed, total, "Downloading");
+
+    let file = if resumed {
+        tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(&partial)
+            .await
+    } else {
+        tokio::fs::File::create(&partial).await
+    };
+    let mut file =
+        file.map_err(|e| disk_error(format!("Could not open {}", partial.display()), &e))?;
+    progress(DownloadProgress {
+        downloaded,
+        total,
+        finished: false,
+        ..Default::default()
+    });
+    let mut reported = downloaded;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| network_error(format!("Download of {url} interrupted"), &e))?
+    {
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| disk_error(format!("Could not write {}", partial.display()), &e))?;
+        downloaded += chunk.len() as u64;
+        if downloaded - reported >= PROGRESS_INTERVAL {
+            reported = downloaded;
+            progress(DownloadProgress {
+                downloaded,
+                total,
+                finished: false,
+                ..Default::default()
+            });
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| disk_error(format!("Could not write {}", partial.display()), &e))?;
+    tokio::fs::rename(&partial, path)
+        .await
+        .map_err(|e| disk_error(format!("Could not move {} into place", path.display()), &e))?;
+    progress(DownloadProgress {
+        downloaded,
+        total: total.or(Some(downloaded)),
+        finished: true,
+        ..Default::default()
+    });
+    tracing::info!(downloaded, "Download finished");
+    Ok(())
+}
+
+/// Why [`download_chunked`] stopped.
+enum ChunkedError {
+    /// The server advertised ranges but sent the whole file, as some redirecting mirror pools
+    /// do; it has to be fetched in a single stream instead.
+    RangesIgnored,
+    Failed(AppError),
+}
+
+impl fmt::Display for ChunkedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RangesIgnored => f.write_str("The server ignored a range request"),
+            Self::Failed(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<AppError> for ChunkedError {
+    fn from(error: AppError) -> Self {
+        Self::Failed(error)
+    }
+}
+
+/// Fetch `url` as `connections` ranges at once, written in place into a preallocated file.
+///
+/// The chunks land at their offsets in a `.chunks` file, which can't be resumed like a `.part`
+/// file as it has holes until every chunk is done; an interrupted chunked download starts over.
+#[tracing::instrument(skip(url, path, progress))]
+async fn download_chunked(
+    url: &str,
+    path: &Path,
+    size: u64,
+    connections: usize,
+    mut progress: impl FnMut(DownloadProgress),
+) -> Result<(), ChunkedError> {
+    let chunked = chunked_path(path);
+    let file = tokio::fs::File::create(&chunked)
+        .await
+        .map_err(|e| disk_error(format!("Could not create {}", chunked.display()), &e))?;
+    file.set_len(size)
+        .await
+        .map_err(|e| disk_error(format!("Could not allocate {}", chunked.display()), &e))?;
+    drop(file);
+
+    let client = network::client();
+    let downloaded = Arc::new(AtomicU64::new(0));
+    let chunk_size = size.div_ceil(connections as u64);
+    tracing::info!("Downloading in {connections} ranges");
+    let mut chunks = JoinSet::new();
+    let mut start = 0;
+    while start < size {
+        let end = (start + chunk_size).min(size) - 1;
+        chunks.spawn(download_range(
+            client.clone(),
+            url.to_string(),
+            chunked.clone(),
+            (start, end),
+            downloaded.clone(),
+        ));
+        start = end + 1;
+    }
+    let report = |finished| DownloadProgress {
+        downloaded: downloaded.load(Ordering::Relaxed),
+        total: Some(size),
+        finished,
+        ..Default::default()
+    };
+    progress(report(false));
+    let mut ticker = tokio::time::interval(CHUNKED_PROGRESS_INTERVAL);
+    // Dropping `chunks` on an error aborts the remaining connections.
+    loop {
+        tokio::select! {
+            joined = chunks.join_next() => match joined {
+                Some(joined) => joined.map_err(|e| {
+                    let context = format!("Download of {url} stopped");
+                    AppError::new(ErrorCategory::Network, context).caused_by(e)
+                })??,
+                None => break,
+            },
+            _ = ticker.tick() => progress(report(false)),
+        }
+    }
+    tokio::fs::rename(&chunked, path)
+        .await
+        .map_err(|e| disk_error(format!("Could not move {} into place", path.display()), &e))?;
+    progress(report(true));
+    tracing::info!("Download finished");
+    Ok(())
+}
+
+/// Download bytes `start..=end` of `url` into the same range of `path`.
+#[tracing::instrument(skip(client, url, path, downloaded), err)]
+async fn download_range(
+    client: reqwest::Client,
+    url: String,
+    path: PathBuf,
+    (start, end): (u64, u64),
+    downloaded: Arc<AtomicU64>,
+) -> Result<(), ChunkedError> {
+    let mut response = client
+        .get(&url)
+        .header(reqwest::header::RANGE, format!("bytes={start}-{end}"))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| network_error(format!("Could not download {url}"), &e))?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(ChunkedError::RangesIgnored);
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .await
+        .map_err(|e| disk_error(format!("Could not open {}", path.display()), &e))?;
+    file.seek(SeekFrom::Start(start))
+        .await
+        .map_err(|e| disk_error(format!("Could not write {}", path.display()), &e))?;
+    let mut position = start;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| network_error(format!("Download of {url} interrupted"), &e))?
+    {
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| disk_error(format!("Could not write {}", path.display()), &e))?;
+        position += chunk.len() as u64;
+        downloaded.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+    }
+    file.flush()
+        .await
+        .map_err(|e| disk_error(format!("Could not write {}", path.display()), &e))?;
+    if position != end + 1 {
+        return Err(AppError::new(
+            ErrorCategory::Network,
+            format!("Download of {url} ended early"),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// The size of `url` if it is large enough to split and the server accepts range requests.
+async fn ranged_size(url: &str) -> Option<u64> {
+    let response = network::client()
+        .head(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .ok()?;
+    let headers = response.headers();
+    let accepts_ranges = headers
+        .get(reqwest::header::ACCEPT_RANGES)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("bytes"));
+    let size = content_length(headers)?;
+    (accepts_ranges && size >= CHUNKED_MIN_SIZE).then_some(size)
+}
+
+/// Sizes servers reported by URL, so changing one option only looks up the files it changed.
+static REMOTE_SIZES: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(Default::default);
+
+/// The connection speed, measured on the first estimate of the run.
+static MEASURED_SPEED: Mutex<Option<f64>> = Mutex::new(None);
+
+/// The size the server reports for `url`, without downloading it.
+pub async fn remote_size(url: &str) -> Option<u64> {
+    let cached = REMOTE_SIZES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(url)
+        .copied();
+    if cached.is_some() {
+        return cached;
+    }
+    let response = network::client()
+        .head(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .ok()?;
+    let size = content_length(response.headers())?;
+    REMOTE_SIZES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(url.to_string(), size);
+    Some(size)
+}
+
+/// The Content-Length header of a HEAD response.
+///
+/// `content_length()` describes the (empty) body of a HEAD response, not the resource.
+fn content_length(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// What a set of downloads will cost, looked up before committing to them.
+#[derive(Clone, Debug, Default)]
+pub struct DownloadEstimate {
+    /// Bytes left to download, `None` if a server did not report a size.
+    pub remaining: Option<u64>,
+    /// Measured by timing the start of the largest download of the run's first estimate.
+    pub bytes_per_second: Option<f64>,
+}
+
+impl DownloadEstimate {
+    /// Seconds the remaining downloads should take at the measured speed.
+    pub fn seconds(&self) -> Option<u64> {
+        let speed = self.bytes_per_second.filter(|speed| *speed > 0.0)?;
+        Some((self.remaining? as f64 / speed) as u64)
+    }
+}
+
+/// How much is sampled to measure the connection speed.
+const SPEED_SAMPLE: u64 = 4 * 1024 * 1024;
+
+/// Size up `downloads`, leaving out whatever earlier attempts already wrote to disk.
+pub async fn estimate(downloads: Vec<QGDownload>) -> DownloadEstimate {
+    let mut remaining = Some(0);
+    let mut largest: Option<(&str, u64)> = None;
+    for qg_download in &downloads {
+        if tokio::fs::try_exists(&qg_download.path)
+            .await
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let size = remote_size(&qg_download.url).await;
+        if let Some(size) = size {
+            if largest.map_or(true, |(_, largest)| size > largest) {
+                largest = Some((&qg_download.url, size));
+            }
+        }
+        let existing = tokio::fs::metadata(partial_path(&qg_download.path))
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        remaining = remaining
+            .zip(size)
+            .map(|(remaining, size)| remaining + size.saturating_sub(existing));
+    }
+    let measured = *MEASURED_SPEED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let bytes_per_second = match (measured, largest) {
+        (Some(speed), _) => Some(speed),
+        (None, Some((url, _))) => {
+            let speed = measure_speed(url).await;
+            if speed.is_some() {
+                *MEASURED_SPEED
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = speed;
+            }
+            speed
+        }
+        (None, None) => None,
+    };
+    DownloadEstimate {
+        remaining,
+        bytes_per_second,
+    }
+}
+
+async fn measure_speed(url: &str) -> Option<f64> {
+    let started = std::time::Instant::now();
+    let mut response = network::client()
+        .get(url)
+        .header(
+            reqwest::header::RANGE,
+            format!("bytes=0-{}", SPEED_SAMPLE - 1),
+        )
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .ok()?;
+    // Servers ignoring the range send the whole file, so stop reading after the sample.
+    let mut received = 0;
+    while received < SPEED_SAMPLE {
+        match response.chunk().await.ok()? {
+            Some(chunk) => received += chunk.len() as u64,
+            None => break,
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+    (received > 0 && elapsed > 0.0).then(|| received as f64 / elapsed)
+}
+
+/// Where an in-progress download is written before being moved into place.
+pub fn partial_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.part", path.display()))
+}
+
+/// Where a chunked download is assembled before being moved into place.
+fn chunked_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.chunks", path.display()))
+}
+
+fn disk_error(context: String, error: &std::io::Error) -> AppError {
+    AppError::from_error(ErrorCategory::Disk, context, error)
+}
+
+fn network_error(context: String, error: &reqwest::Error) -> AppError {
+    AppError::from_error(ErrorCategory::Network, context, error)
+}
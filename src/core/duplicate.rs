@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use crate::core::error::{AppError, ErrorCategory};
+use crate::core::vm::{VMConfig, INSTALLER_KEYS, VM};
+
+/// The first free name for a copy, e.g. `ubuntu-24.04` -> `ubuntu-24.04-copy`, then
+/// `ubuntu-24.04-copy-2`.
+pub fn numbered_name(root: &Path, name: &str) -> String {
+    // Copying a copy continues its numbering rather than producing `name-copy-copy`. Only the
+    // suffix added here is stripped, as release names like `windows-11` end in numbers too.
+    let base = name
+        .strip_suffix("-copy")
+        .or_else(|| {
+            let (rest, number) = name.rsplit_once('-')?;
+            number.parse::<u32>().ok()?;
+            rest.strip_suffix("-copy")
+        })
+        .filter(|base| !base.is_empty())
+        .unwrap_or(name);
+    std::iter::once(format!("{base}-copy"))
+        .chain((2..).map(|number| format!("{base}-copy-{number}")))
+        .find(|name| !root.join(format!("{name}.conf")).exists() && !root.join(name).exists())
+        .expect("an unused name")
+}
+
+/// Point the relative paths in `config` that lie in the VM directory `from` into `to`.
+/// Installer media stays where it is if `share_media` is set.
+pub fn move_paths(config: &mut VMConfig, from: &str, to: &str, share_media: bool) {
+    let old_prefix = format!("{from}/");
+    let moved = config
+        .entries()
+        .filter(|(key, _)| !(share_media && INSTALLER_KEYS.contains(key)))
+        .filter_map(|(key, value)| {
+            let rest = value.strip_prefix(&old_prefix)?;
+            Some((key.to_string(), format!("{to}/{rest}")))
+        })
+        .collect::<Vec<_>>();
+    for (key, value) in moved {
+        config.set(&key, value);
+    }
+}
+
+/// Write a new VM with the same recipe as `source` under a numbered name.
+///
+/// The already downloaded installer images are reused, and the copy gets its own empty VM
+/// directory; quickemu creates a fresh disk there on first boot.
+pub async fn duplicate(source: PathBuf) -> Result<PathBuf, AppError> {
+    let disk_error =
+        |context: &str, e: String| AppError::new(ErrorCategory::Disk, context).caused_by(e);
+    let vm = VM::load(source.clone())
+        .map_err(|e| disk_error("Could not read the previous VM", e.to_string()))?;
+    if vm.is_encrypted() {
+        return Err(AppError::new(
+            ErrorCategory::Disk,
+            "Encrypted VMs can't be duplicated, their disk needs a new passphrase",
+        ));
+    }
+
+    let name = numbered_name(vm.root(), &vm.name);
+    let mut copy = VM {
+        name: name.clone(),
+        config_path: vm.root().join(format!("{name}.conf")),
+        config: vm.config.clone(),
+    };
+    // Relative paths resolve from the shared root, so installer media stays valid as is.
+    move_paths(&mut copy.config, &vm.name, &name, true);
+
+    tokio::fs::create_dir_all(copy.vm_dir())
+        .await
+        .map_err(|e| disk_error("Could not create the VM directory", e.to_string()))?;
+    copy.save()
+        .await
+        .map_err(|e| disk_error("Could not write VM config", e))?;
+    Ok(copy.config_path)
+}
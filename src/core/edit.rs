@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use quickget_core::QuickgetInstance;
+
+use crate::core::compat::{self, QuickemuVersion};
+use crate::core::config_keys::{self, ExtraKey, EDIT_MANAGED};
+use crate::core::devices::DeviceOptions;
+use crate::core::guest_network::{self, NetworkMode};
+use crate::core::units::parse_size;
+use crate::core::vm::VM;
+
+/// The smallest amount of RAM quickemu will start a VM with.
+const MIN_RAM: u64 = 256 * 1024 * 1024;
+
+/// User-editable settings of an existing VM. All config writes go through [`apply`].
+#[derive(Clone, Debug)]
+pub struct VMEdit {
+    pub name: String,
+    pub ram: String,
+    pub cpu_cores: String,
+    pub network: NetworkMode,
+    /// Empty to let quickemu pick one.
+    pub mac: String,
+    pub devices: DeviceOptions,
+    /// Every other key of the config, edited by hand.
+    pub extra: Vec<ExtraKey>,
+}
+
+impl VMEdit {
+    pub fn from_vm(vm: &VM) -> Self {
+        Self {
+            name: vm.name.clone(),
+            ram: vm.ram().unwrap_or_default().to_string(),
+            cpu_cores: vm.cpu_cores().unwrap_or_default().to_string(),
+            network: NetworkMode::from_vm(vm),
+            mac: vm.config.get("macaddr").unwrap_or_default().to_string(),
+            devices: DeviceOptions::from_vm(vm),
+            extra: config_keys::from_vm(vm, &EDIT_MANAGED),
+        }
+    }
+    pub fn validate(&self, vm: &VM) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err(String::from("The name cannot be empty"));
+        }
+        if name.contains('/') || name.starts_with('.') {
+            return Err(String::from(
+                "The name cannot contain '/' or start with '.'",
+            ));
+        }
+        if name != vm.name && vm.root().join(format!("{name}.conf")).exists() {
+            return Err(format!("A VM named {name} already exists"));
+        }
+
+        if !self.ram.trim().is_empty() {
+            let ram =
+                parse_size(&self.ram).ok_or_else(|| format!("Invalid RAM size: {}", self.ram))?;
+            if ram < MIN_RAM {
+                return Err(String::from("At least 256M of RAM is required"));
+            }
+            if ram > QuickgetInstance::get_total_ram() {
+                return Err(String::from("RAM exceeds the host's physical memory"));
+            }
+        }
+
+        if !self.cpu_cores.trim().is_empty() {
+            let cores = self
+                .cpu_cores
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid CPU core count: {}", self.cpu_cores))?;
+            if cores == 0 || cores > QuickgetInstance::get_total_cpu_cores() {
+                return Err(format!(
+                    "CPU cores must be between 1 and {}",
+                    QuickgetInstance::get_total_cpu_cores()
+                ));
+            }
+        }
+
+        if let NetworkMode::Bridged(bridge) = &self.network {
+            if bridge.is_empty() {
+                return Err(String::from("Choose a bridge to attach the VM to"));
+            }
+            if !guest_network::bridges().contains(bridge) {
+                return Err(format!("There is no bridge called {bridge} on this host"));
+            }
+        }
+        let mac = self.mac.trim();
+        if !mac.is_empty() && !guest_network::valid_mac(mac) {
+            return Err(format!("Invalid MAC address: {mac}"));
+        }
+        config_keys::validate(&self.extra, &EDIT_MANAGED)
+    }
+}
+
+/// Validate the edit, rename the VM's files if needed and rewrite its config for the `quickemu`
+/// installed.
+pub async fn apply(
+    mut vm: VM,
+    edit: VMEdit,
+    quickemu: Option<QuickemuVersion>,
+) -> Result<VM, String> {
+    edit.validate(&vm)?;
+    let name = edit.name.trim().to_string();
+
+    if name != vm.name {
+        let old_dir = vm.vm_dir();
+        let new_dir = vm.root().join(&name);
+        if new_dir.exists() {
+            return Err(format!("{} already exists", new_dir.display()));
+        }
+        let moved = old_dir.exists();
+        if moved {
+            tokio::fs::rename(&old_dir, &new_dir)
+                .await
+                .map_err(|e| format!("Could not rename {}: {e}", old_dir.display()))?;
+        }
+        let new_config = vm.root().join(format!("{name}.conf"));
+        if let Err(e) = rename_config(&mut vm, name, new_config.clone()).await {
+            // Put everything back so the VM still starts under its old name.
+            let _ = tokio::fs::remove_file(&new_config).await;
+            if moved {
+                if let Err(undo) = tokio::fs::rename(&new_dir, &old_dir).await {
+                    tracing::error!("Could not move {} back: {undo}", new_dir.display());
+                }
+            }
+            return Err(e);
+        }
+    }
+
+    let network = edit.network.config_value().unwrap_or_default();
+    for (key, value) in [
+        ("ram", edit.ram.as_str()),
+        ("cpu_cores", edit.cpu_cores.as_str()),
+        ("network", network),
+        ("macaddr", edit.mac.as_str()),
+    ] {
+        match value.trim() {
+            "" => vm.config.remove(key),
+            value => vm.config.set(key, value),
+        }
+    }
+    edit.devices.apply(&mut vm);
+    compat::adjust(quickemu, &mut vm);
+    // Keys removed from the advanced section are removed from the config.
+    for removed in config_keys::from_vm(&vm, &EDIT_MANAGED) {
+        if !edit
+            .extra
+            .iter()
+            .any(|extra| extra.key.trim() == removed.key)
+        {
+            vm.config.remove(&removed.key);
+        }
+    }
+    config_keys::apply(&edit.extra, &mut vm);
+    vm.save().await?;
+    Ok(vm)
+}
+
+/// Point `vm`'s config at its renamed directory and move it to `new_config`.
+async fn rename_config(vm: &mut VM, name: String, new_config: PathBuf) -> Result<(), String> {
+    // Relative paths into the old VM directory have to follow it.
+    let old_prefix = format!("{}/", vm.name);
+    let updated = vm
+        .config
+        .entries()
+        .filter_map(|(key, value)| {
+            value
+                .strip_prefix(&old_prefix)
+                .map(|rest| (key.to_string(), format!("{name}/{rest}")))
+        })
+        .collect::<Vec<_>>();
+    for (key, value) in updated {
+        vm.config.set(&key, value);
+    }
+
+    let old_config = std::mem::replace(&mut vm.config_path, new_config);
+    vm.name = name;
+    vm.save().await?;
+    tokio::fs::remove_file(&old_config)
+        .await
+        .map_err(|e| format!("Could not remove {}: {e}", old_config.display()))
+}
+
+impl VM {
+    /// Atomically replace the config file so a failed write never leaves it truncated.
+    pub async fn save(&self) -> Result<(), String> {
+        let temp = PathBuf::from(format!("{}.tmp", self.config_path.display()));
+        tokio::fs::write(&temp, self.config.serialize())
+            .await
+            .map_err(|e| format!("Could not write {}: {e}", temp.display()))?;
+        tokio::fs::rename(&temp, &self.config_path)
+            .await
+            .map_err(|e| format!("Could not write {}: {e}", self.config_path.display()))
+    }
+}
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::core::vm::VM;
+
+/// Marks a VM whose system disk is a LUKS-encrypted qcow2. quickemu ignores unknown keys.
+pub const CONFIG_KEY: &str = "qersui_disk_encryption";
+const SECRET_ID: &str = "qersui-disk-secret";
+/// Attribute under which passphrases are stored in the Secret Service.
+const KEYRING_ATTRIBUTE: &str = "qersui-vm";
+
+impl VM {
+    pub fn is_encrypted(&self) -> bool {
+        self.config.get(CONFIG_KEY) == Some("luks")
+    }
+}
+
+/// A passphrase written to a private file for QEMU to read, removed when dropped.
+pub struct SecretFile(PathBuf);
+
+impl SecretFile {
+    pub fn new(passphrase: &str) -> Result<Self, String> {
+        let dir = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let path = dir.join(format!(
+            "qersui-secret-{}-{}",
+            std::process::id(),
+            rand_suffix()
+        ));
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .and_then(|mut file| file.write_all(passphrase.as_bytes()))
+            .map_err(|e| format!("Could not write passphrase file: {e}"))?;
+        Ok(Self(path))
+    }
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+    /// QEMU arguments unlocking quickemu's `SystemDisk` drive with this secret.
+    pub fn qemu_args(&self) -> String {
+        format!(
+            "-object secret,id={SECRET_ID},file={} -set drive.SystemDisk.encrypt.key-secret={SECRET_ID}",
+            self.0.display()
+        )
+    }
+}
+
+impl Drop for SecretFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn rand_suffix() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default()
+}
+
+/// Create an empty LUKS-encrypted qcow2 disk.
+pub async fn create_disk(path: &Path, size: &str, passphrase: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Could not create {}: {e}", parent.display()))?;
+    }
+    let secret = SecretFile::new(passphrase)?;
+    let output = tokio::process::Command::new("qemu-img")
+        .arg("create")
+        .arg("--object")
+        .arg(format!(
+            "secret,id={SECRET_ID},file={}",
+            secret.path().display()
+        ))
+        .args(["-f", "qcow2", "-o"])
+        .arg(format!(
+            "encrypt.format=luks,encrypt.key-secret={SECRET_ID}"
+        ))
+        .arg(path)
+        .arg(size)
+        .output()
+        .await
+        .map_err(|e| format!("Could not run qemu-img: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Could not create encrypted disk: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Pre-create the VM's system disk as encrypted qcow2 before quickemu's first boot, which would
+/// otherwise create a plain one.
+pub async fn encrypt_system_disk(vm: &mut VM, passphrase: &str) -> Result<(), String> {
+    let disk_img = match vm.config.get("disk_img") {
+        Some(disk_img) => disk_img.to_string(),
+        None => format!("{}/disk.qcow2", vm.name),
+    };
+    let disk = vm.resolve(&disk_img);
+    if disk.exists() {
+        return Err(format!("{} already exists", disk.display()));
+    }
+    // qcow2 is thin provisioned, so a generous default costs nothing up front.
+    let size = vm.config.get("disk_size").unwrap_or("64G").to_string();
+    create_disk(&disk, &size, passphrase).await?;
+    vm.config.set("disk_img", disk_img);
+    vm.config.set(CONFIG_KEY, "luks");
+    vm.save().await
+}
+
+/// Save the passphrase in the Secret Service through libsecret's `secret-tool`.
+pub async fn store_passphrase(vm: &VM, passphrase: &str) -> Result<(), String> {
+    let mut child = tokio::process::Command::new("secret-tool")
+        .arg("store")
+        .arg(format!("--label=QERSUI disk passphrase for {}", vm.name))
+        .arg(KEYRING_ATTRIBUTE)
+        .arg(&vm.config_path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run secret-tool: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(passphrase.as_bytes())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    status
+        .success()
+        .then_some(())
+        .ok_or_else(|| String::from("The keyring refused to store the passphrase"))
+}
+
+pub async fn lookup_passphrase(vm: &VM) -> Option<String> {
+    let output = tokio::process::Command::new("secret-tool")
+        .arg("lookup")
+        .arg(KEYRING_ATTRIBUTE)
+        .arg(&vm.config_path)
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    let passphrase = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !passphrase.is_empty()).then_some(passphrase)
+}
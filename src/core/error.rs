@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fmt;
+
+use crate::core::logging;
+
+/// Broad origin of a failure, used to give the user a hint about what to check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCategory {
+    Network,
+    Portal,
+    Disk,
+    Quickget,
+}
+
+impl ErrorCategory {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Network => "Network error",
+            Self::Portal => "Desktop portal error",
+            Self::Disk => "Disk error",
+            Self::Quickget => "quickget error",
+        }
+    }
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::Network => "Check your internet connection, or try again later.",
+            Self::Portal => "Make sure xdg-desktop-portal is running.",
+            Self::Disk => "Check that the VM directory is writable and has enough free space.",
+            Self::Quickget => "The OS catalog or VM configuration could not be processed.",
+        }
+    }
+    pub fn icon_name(&self) -> &'static str {
+        match self {
+            Self::Network => "network-error-symbolic",
+            Self::Portal => "dialog-error-symbolic",
+            Self::Disk => "drive-harddisk-symbolic",
+            Self::Quickget => "dialog-error-symbolic",
+        }
+    }
+}
+
+/// A failure with its category and the chain of underlying causes, outermost first.
+#[derive(Clone, Debug)]
+pub struct AppError {
+    pub category: ErrorCategory,
+    pub chain: Vec<String>,
+}
+
+impl AppError {
+    pub fn new(category: ErrorCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            chain: vec![message.into()],
+        }
+    }
+    /// Describe `error` with `context`, keeping each of its sources.
+    pub fn from_error(
+        category: ErrorCategory,
+        context: impl Into<String>,
+        error: &dyn std::error::Error,
+    ) -> Self {
+        let mut chain = vec![context.into(), error.to_string()];
+        let mut source = error.source();
+        while let Some(error) = source {
+            chain.push(error.to_string());
+            source = error.source();
+        }
+        chain.dedup();
+        Self { category, chain }
+    }
+    /// Append a cause that isn't available as a `std::error::Error`.
+    pub fn caused_by(mut self, cause: impl fmt::Display) -> Self {
+        self.chain.push(cause.to_string());
+        self
+    }
+    pub fn summary(&self) -> &str {
+        self.chain.first().map(String::as_str).unwrap_or_default()
+    }
+    /// Plain-text report for bug reports, including version and host details and the end of
+    /// the application log.
+    pub async fn report(&self) -> String {
+        let mut report = format!(
+            "QERSUI {}\nHost: {} {}\nCategory: {}\n\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            self.category.label(),
+        );
+        for (depth, cause) in self.chain.iter().enumerate() {
+            report.push_str(&format!("{}{cause}\n", "  ".repeat(depth)));
+        }
+        let log = logging::tail().await.unwrap_or_else(|e| e);
+        report.push_str(&format!("\nRecent log:\n{log}\n"));
+        report
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.chain.join(": "))
+    }
+}
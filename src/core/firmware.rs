@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::core::network;
+use crate::core::vm::VM;
+
+/// Prebuilt EDK2 firmware, BSD licensed and so fine to fetch on the user's behalf.
+///
+/// The files here are replaced by every build, so only ones matching [`PINNED_SHA256`] are kept.
+const EDK2_BUILDS: &str = "https://retrage.github.io/edk2-nightly/bin";
+
+/// SHA-256 of each checked build file by name; firmware without an entry can't be downloaded.
+const PINNED_SHA256: &[(&str, &str)] = &[];
+
+/// Per-VM copy of the variable store, named so exports and deletion pick it up with quickemu's.
+const VARS_FILE: &str = "OVMF_VARS_qersui.fd";
+
+/// UEFI firmware a VM needs, one per architecture plus Secure Boot for x86_64.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FirmwareKind {
+    Ovmf,
+    OvmfSecureBoot,
+    Aavmf,
+    RiscV,
+}
+
+/// A firmware image and the template its variable store is copied from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FirmwareFiles {
+    pub code: PathBuf,
+    pub vars: PathBuf,
+}
+
+impl FirmwareFiles {
+    fn new(code: impl Into<PathBuf>, vars: impl Into<PathBuf>) -> Self {
+        Self {
+            code: code.into(),
+            vars: vars.into(),
+        }
+    }
+    fn exist(&self) -> bool {
+        self.code.is_file() && self.vars.is_file()
+    }
+}
+
+/// Where a kind of firmware was found on this system.
+#[derive(Clone, Debug)]
+pub struct FirmwareStatus {
+    pub kind: FirmwareKind,
+    /// Installed by a distribution package, where quickemu looks for it.
+    pub system: Option<FirmwareFiles>,
+    /// Fetched by QERSUI.
+    pub managed: Option<FirmwareFiles>,
+}
+
+impl FirmwareKind {
+    pub const ALL: [Self; 4] = [Self::Ovmf, Self::OvmfSecureBoot, Self::Aavmf, Self::RiscV];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Ovmf => "OVMF (x86_64 UEFI)",
+            Self::OvmfSecureBoot => "OVMF with Secure Boot (x86_64)",
+            Self::Aavmf => "AAVMF (AArch64 UEFI)",
+            Self::RiscV => "EDK2 for RISC-V",
+        }
+    }
+    /// The firmware a VM's config asks quickemu for, if it boots with UEFI at all.
+    pub fn for_vm(vm: &VM) -> Option<Self> {
+        if vm.config.get("boot").unwrap_or("efi") != "efi" {
+            return None;
+        }
+        Some(match vm.config.get("arch").unwrap_or("x86_64") {
+            "aarch64" => Self::Aavmf,
+            "riscv64" => Self::RiscV,
+            _ if vm.config.get("secureboot") == Some("on") => Self::OvmfSecureBoot,
+            _ => Self::Ovmf,
+        })
+    }
+    /// Code and variable store locations used by distributions, in quickemu's search order.
+    fn system_paths(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Ovmf => &[
+                (
+                    "/usr/share/OVMF/OVMF_CODE_4M.fd",
+                    "/usr/share/OVMF/OVMF_VARS_4M.fd",
+                ),
+                (
+                    "/usr/share/edk2/ovmf/OVMF_CODE.fd",
+                    "/usr/share/edk2/ovmf/OVMF_VARS.fd",
+                ),
+                (
+                    "/usr/share/OVMF/OVMF_CODE.fd",
+                    "/usr/share/OVMF/OVMF_VARS.fd",
+                ),
+                (
+                    "/usr/share/OVMF/x64/OVMF_CODE.fd",
+                    "/usr/share/OVMF/x64/OVMF_VARS.fd",
+                ),
+                (
+                    "/usr/share/edk2-ovmf/OVMF_CODE.fd",
+                    "/usr/share/edk2-ovmf/OVMF_VARS.fd",
+                ),
+                (
+                    "/usr/share/qemu/ovmf-x86_64-4m-code.bin",
+                    "/usr/share/qemu/ovmf-x86_64-4m-vars.bin",
+                ),
+                (
+                    "/usr/share/qemu/edk2-x86_64-code.fd",
+                    "/usr/share/qemu/edk2-i386-vars.fd",
+                ),
+                (
+                    "/usr/share/edk2/x64/OVMF_CODE.4m.fd",
+                    "/usr/share/edk2/x64/OVMF_VARS.4m.fd",
+                ),
+            ],
+            Self::OvmfSecureBoot => &[
+                (
+                    "/usr/share/OVMF/OVMF_CODE_4M.secboot.fd",
+                    "/usr/share/OVMF/OVMF_VARS_4M.ms.fd",
+                ),
+                (
+                    "/usr/share/edk2/ovmf/OVMF_CODE.secboot.fd",
+                    "/usr/share/edk2/ovmf/OVMF_VARS.secboot.fd",
+                ),
+                (
+                    "/usr/share/OVMF/OVMF_CODE.secboot.fd",
+                    "/usr/share/OVMF/OVMF_VARS.ms.fd",
+                ),
+                (
+                    "/usr/share/edk2/x64/OVMF_CODE.secure.4m.fd",
+                    "/usr/share/edk2/x64/OVMF_VARS.4m.fd",
+                ),
+            ],
+            Self::Aavmf => &[
+                (
+                    "/usr/share/AAVMF/AAVMF_CODE.fd",
+                    "/usr/share/AAVMF/AAVMF_VARS.fd",
+                ),
+                (
+                    "/usr/share/edk2/aarch64/QEMU_EFI-pflash.raw",
+                    "/usr/share/edk2/aarch64/vars-template-pflash.raw",
+                ),
+                (
+                    "/usr/share/qemu/edk2-aarch64-code.fd",
+                    "/usr/share/qemu/edk2-arm-vars.fd",
+                ),
+            ],
+            Self::RiscV => &[
+                (
+                    "/usr/share/qemu-efi-riscv64/RISCV_VIRT_CODE.fd",
+                    "/usr/share/qemu-efi-riscv64/RISCV_VIRT_VARS.fd",
+                ),
+                (
+                    "/usr/share/edk2/riscv/RISCV_VIRT_CODE.fd",
+                    "/usr/share/edk2/riscv/RISCV_VIRT_VARS.fd",
+                ),
+                (
+                    "/usr/share/qemu/edk2-riscv-code.fd",
+                    "/usr/share/qemu/edk2-riscv-vars.fd",
+                ),
+            ],
+        }
+    }
+    /// Download names and the size QEMU's pflash devices expect them padded to.
+    ///
+    /// `None` for Secure Boot, whose Microsoft key enrollment can't be redistributed.
+    fn download(&self) -> Option<(&'static str, &'static str, Option<u64>)> {
+        match self {
+            Self::Ovmf => Some(("RELEASEX64_OVMF_CODE.fd", "RELEASEX64_OVMF_VARS.fd", None)),
+            Self::OvmfSecureBoot => None,
+            Self::Aavmf => Some((
+                "RELEASEAARCH64_QEMU_EFI.fd",
+                "RELEASEAARCH64_QEMU_VARS.fd",
+                Some(64 * 1024 * 1024),
+            )),
+            Self::RiscV => Some((
+                "RELEASERISCV64_VIRT_CODE.fd",
+                "RELEASERISCV64_VIRT_VARS.fd",
+                Some(32 * 1024 * 1024),
+            )),
+        }
+    }
+    pub fn fetchable(&self) -> bool {
+        self.download().is_some_and(|(code, vars, _)| {
+            pinned_sha256(code).is_some() && pinned_sha256(vars).is_some()
+        })
+    }
+    fn system_files(&self) -> Option<FirmwareFiles> {
+        self.system_paths()
+            .iter()
+            .map(|(code, vars)| FirmwareFiles::new(*code, *vars))
+            .find(FirmwareFiles::exist)
+    }
+    fn managed_files(&self) -> Option<FirmwareFiles> {
+        let (code, vars, _) = self.download()?;
+        let dir = managed_dir()?;
+        Some(FirmwareFiles::new(dir.join(code), dir.join(vars)))
+    }
+    pub fn status(&self) -> FirmwareStatus {
+        FirmwareStatus {
+            kind: *self,
+            system: self.system_files(),
+            managed: self.managed_files().filter(FirmwareFiles::exist),
+        }
+    }
+}
+
+fn pinned_sha256(name: &str) -> Option<&'static str> {
+    PINNED_SHA256
+        .iter()
+        .find(|(file, _)| *file == name)
+        .map(|(_, digest)| *digest)
+}
+
+/// `$XDG_DATA_HOME/qersui/firmware`, holding firmware fetched by QERSUI.
+fn managed_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(data_home.join("qersui").join("firmware"))
+}
+
+pub fn detect() -> Vec<FirmwareStatus> {
+    FirmwareKind::ALL.iter().map(FirmwareKind::status).collect()
+}
+
+/// Download a managed copy of `kind`.
+pub async fn fetch(kind: FirmwareKind) -> Result<FirmwareFiles, String> {
+    let (code_name, vars_name, pad_to) =
+        kind.download()
+            .filter(|_| kind.fetchable())
+            .ok_or_else(|| {
+                format!(
+                    "{} has to be installed from your distribution",
+                    kind.label()
+                )
+            })?;
+    let files = kind
+        .managed_files()
+        .ok_or_else(|| String::from("Could not find the data directory"))?;
+    if let Some(dir) = files.code.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    }
+    for (name, path) in [(code_name, &files.code), (vars_name, &files.vars)] {
+        fetch_file(name, path, pad_to).await?;
+    }
+    Ok(files)
+}
+
+async fn fetch_file(name: &str, path: &Path, pad_to: Option<u64>) -> Result<(), String> {
+    let expected =
+        pinned_sha256(name).ok_or_else(|| format!("No checksum is pinned for {name}"))?;
+    let url = format!("{EDK2_BUILDS}/{name}");
+    let mut contents = network::client()
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Could not download {url}: {e}"))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Could not download {url}: {e}"))?
+        .to_vec();
+    let actual: String = Sha256::digest(&contents)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "{name} does not match its pinned checksum (expected {expected}, got {actual})"
+        ));
+    }
+    if let Some(size) = pad_to {
+        if (contents.len() as u64) < size {
+            contents.resize(size as usize, 0);
+        }
+    }
+    let partial = crate::core::download::partial_path(path);
+    tokio::fs::write(&partial, contents)
+        .await
+        .map_err(|e| format!("Could not write {}: {e}", partial.display()))?;
+    tokio::fs::rename(&partial, path)
+        .await
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))
+}
+
+pub async fn remove(kind: FirmwareKind) -> Result<(), String> {
+    let Some(files) = kind.managed_files() else {
+        return Ok(());
+    };
+    for path in [files.code, files.vars] {
+        match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Could not remove {}: {e}", path.display()))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Point a new VM at a managed firmware copy when its distribution has none where quickemu looks.
+///
+/// quickemu refuses to start UEFI VMs without system firmware, so mapped VMs are switched to
+/// legacy boot for quickemu and the launcher attaches the UEFI pflash drives itself. Returns the
+/// mapped firmware so the switch can be shown to the user.
+pub async fn map_if_needed(vm: &mut VM) -> Result<Option<FirmwareKind>, String> {
+    let Some(kind) = FirmwareKind::for_vm(vm) else {
+        return Ok(None);
+    };
+    let status = kind.status();
+    // Without a copy to map, quickemu reports the missing firmware itself when the VM starts.
+    if status.system.is_some() || (status.managed.is_none() && !kind.fetchable()) {
+        return Ok(None);
+    }
+    if status.managed.is_none() {
+        fetch(kind).await?;
+    }
+    let mut metadata = vm.metadata();
+    metadata.firmware = Some(kind);
+    vm.save_metadata(&metadata).await?;
+    vm.config.set("boot", "legacy");
+    vm.save().await.map(|()| Some(kind))
+}
+
+/// QEMU arguments attaching a mapped VM's managed firmware, creating its variable store if needed.
+pub async fn qemu_args(vm: &VM, kind: FirmwareKind) -> Result<String, String> {
+    let files = kind
+        .status()
+        .managed
+        .ok_or_else(|| format!("{} is missing; download it again in Settings", kind.label()))?;
+    let vars = vm.vm_dir().join(VARS_FILE);
+    if !vars.exists() {
+        tokio::fs::copy(&files.vars, &vars)
+            .await
+            .map_err(|e| format!("Could not create {}: {e}", vars.display()))?;
+    }
+    Ok(format!(
+        "-drive if=pflash,format=raw,unit=0,readonly=on,file={} \
+         -drive if=pflash,format=raw,unit=1,file={}",
+        files.code.display(),
+        vars.display()
+    ))
+}
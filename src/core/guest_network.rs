@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use crate::core::vm::VM;
+
+/// Where distributions install QEMU's setuid helper for attaching taps to a bridge.
+const BRIDGE_HELPER_PATHS: [&str; 4] = [
+    "/usr/lib/qemu/qemu-bridge-helper",
+    "/usr/libexec/qemu-bridge-helper",
+    "/usr/lib/qemu-bridge-helper",
+    "/usr/libexec/qemu/qemu-bridge-helper",
+];
+
+/// Lists the bridges unprivileged users may attach to, one `allow <bridge>` per line.
+const BRIDGE_CONF: &str = "/etc/qemu/bridge.conf";
+
+/// How a guest is networked, stored in quickemu's `network` key.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum NetworkMode {
+    /// QEMU's user-mode NAT, quickemu's default.
+    #[default]
+    User,
+    /// Attached to this host bridge.
+    Bridged(String),
+    /// User-mode networking isolated from the host and internet, keeping port forwards.
+    Restricted,
+    None,
+}
+
+/// Dropdown entries, in the order of [`NetworkMode::choice`].
+pub const MODE_CHOICES: [&str; 4] = [
+    "NAT (default)",
+    "Bridged",
+    "Restricted (no internet)",
+    "No network",
+];
+
+impl NetworkMode {
+    pub fn from_vm(vm: &VM) -> Self {
+        match vm.config.get("network").unwrap_or_default() {
+            "" | "user" => Self::User,
+            "restrict" => Self::Restricted,
+            "none" => Self::None,
+            bridge => Self::Bridged(bridge.to_string()),
+        }
+    }
+    /// The `network` value, `None` for quickemu's default.
+    pub fn config_value(&self) -> Option<&str> {
+        match self {
+            Self::User => None,
+            Self::Bridged(bridge) => Some(bridge),
+            Self::Restricted => Some("restrict"),
+            Self::None => Some("none"),
+        }
+    }
+    /// Index into [`MODE_CHOICES`].
+    pub fn choice(&self) -> usize {
+        match self {
+            Self::User => 0,
+            Self::Bridged(_) => 1,
+            Self::Restricted => 2,
+            Self::None => 3,
+        }
+    }
+    /// The mode for a [`MODE_CHOICES`] index, bridging to `bridge` if that is chosen.
+    pub fn from_choice(choice: usize, bridge: Option<&str>) -> Self {
+        match choice {
+            1 => Self::Bridged(bridge.unwrap_or_default().to_string()),
+            2 => Self::Restricted,
+            3 => Self::None,
+            _ => Self::User,
+        }
+    }
+}
+
+/// Bridge interfaces on this host, sorted by name.
+pub fn bridges() -> Vec<String> {
+    let mut bridges = std::fs::read_dir("/sys/class/net")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join("bridge").is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    bridges.sort();
+    bridges
+}
+
+/// What keeps QEMU from attaching an unprivileged guest to `bridge`, if anything.
+pub fn bridge_warning(bridge: &str) -> Option<String> {
+    let helper = BRIDGE_HELPER_PATHS
+        .iter()
+        .map(Path::new)
+        .find(|path| path.is_file());
+    let Some(helper) = helper else {
+        return Some(String::from(
+            "qemu-bridge-helper is not installed; install your distribution's QEMU networking package",
+        ));
+    };
+    let setuid = std::fs::metadata(helper)
+        .map(|metadata| metadata.permissions().mode() & 0o4000 != 0)
+        .unwrap_or(false);
+    if !setuid {
+        return Some(format!(
+            "{} needs the setuid bit (chmod u+s) to attach guests to bridges",
+            helper.display()
+        ));
+    }
+    let allowed = std::fs::read_to_string(BRIDGE_CONF)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("allow"))
+        .map(str::trim)
+        .any(|allowed| allowed == "all" || allowed == bridge);
+    (!allowed).then(|| format!("Add \"allow {bridge}\" to {BRIDGE_CONF} to use this bridge"))
+}
+
+/// Whether `mac` is a unicast address written as six colon-separated hex pairs.
+pub fn valid_mac(mac: &str) -> bool {
+    let octets = mac.split(':').collect::<Vec<_>>();
+    octets.len() == 6
+        && octets
+            .iter()
+            .all(|octet| octet.len() == 2 && octet.chars().all(|c| c.is_ascii_hexdigit()))
+        && u8::from_str_radix(octets[0], 16).is_ok_and(|first| first & 1 == 0)
+}
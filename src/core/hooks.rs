@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use crate::core::network;
+
+/// A user-registered script or webhook, fired for the selected events.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Hook {
+    pub target: HookTarget,
+    pub events: Vec<EventKind>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HookTarget {
+    /// Executed with the event JSON on stdin and in `QERSUI_EVENT`.
+    Script(PathBuf),
+    /// Receives the event JSON as a POST body.
+    Webhook(String),
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    CreationComplete,
+    VMStarted,
+    VMStopped,
+    DownloadFailed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Event {
+    pub event: EventKind,
+    pub vm_name: String,
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 4] = [
+        Self::CreationComplete,
+        Self::VMStarted,
+        Self::VMStopped,
+        Self::DownloadFailed,
+    ];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::CreationComplete => "Creation complete",
+            Self::VMStarted => "VM started",
+            Self::VMStopped => "VM stopped",
+            Self::DownloadFailed => "Download failed",
+        }
+    }
+}
+
+impl HookTarget {
+    /// URLs are treated as webhooks, anything else as a path to an executable.
+    pub fn parse(input: &str) -> Self {
+        let input = input.trim();
+        if input.starts_with("http://") || input.starts_with("https://") {
+            Self::Webhook(input.to_string())
+        } else {
+            Self::Script(PathBuf::from(input))
+        }
+    }
+}
+
+impl std::fmt::Display for HookTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Script(path) => write!(f, "{}", path.display()),
+            Self::Webhook(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+impl Event {
+    pub fn new(event: EventKind, vm_name: impl Into<String>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        Self {
+            event,
+            vm_name: vm_name.into(),
+            timestamp,
+            detail: None,
+        }
+    }
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Run every hook registered for the event, returning a description of each failure.
+pub async fn dispatch(hooks: Vec<Hook>, event: Event) -> Vec<String> {
+    let payload = match serde_json::to_string(&event) {
+        Ok(payload) => payload,
+        Err(e) => return vec![e.to_string()],
+    };
+    let mut errors = Vec::new();
+    for hook in hooks
+        .iter()
+        .filter(|hook| hook.events.contains(&event.event))
+    {
+        let result = match &hook.target {
+            HookTarget::Script(path) => run_script(path, &payload).await,
+            HookTarget::Webhook(url) => post_webhook(url, &payload).await,
+        };
+        if let Err(e) = result {
+            errors.push(format!("{}: {e}", hook.target));
+        }
+    }
+    errors
+}
+
+#[tracing::instrument(skip(payload), err)]
+async fn run_script(path: &PathBuf, payload: &str) -> Result<(), String> {
+    let mut child = tokio::process::Command::new(path)
+        .env("QERSUI_EVENT", payload)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(payload.as_bytes())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    status
+        .success()
+        .then_some(())
+        .ok_or_else(|| format!("exited with {status}"))
+}
+
+async fn post_webhook(url: &str, payload: &str) -> Result<(), String> {
+    network::client()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(payload.to_string())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
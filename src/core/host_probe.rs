@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use quickemu::config::Arch;
+
+use crate::core::compat::{self, QuickemuVersion};
+
+pub const QUICKEMU_URL: &str = "https://github.com/quickemu-project/quickemu";
+pub const QUICKEMU_HINT: &str = "Install quickemu to launch VMs";
+
+/// The guest architecture QEMU can run with hardware acceleration on this host.
+pub fn native_arch() -> Arch {
+    match std::env::consts::ARCH {
+        "aarch64" => Arch::aarch64,
+        "riscv64" => Arch::riscv64,
+        _ => Arch::x86_64,
+    }
+}
+
+pub fn arch_name(arch: &Arch) -> &'static str {
+    match arch {
+        Arch::x86_64 => "x86_64",
+        Arch::aarch64 => "aarch64",
+        Arch::riscv64 => "riscv64",
+    }
+}
+
+/// Guests of a foreign architecture run under TCG, QEMU's much slower software emulation.
+pub fn needs_emulation(arch: &Arch) -> bool {
+    arch != &native_arch()
+}
+
+/// Locate an executable in `PATH`.
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH")
+        .into_iter()
+        .flat_map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+pub fn qemu_system_binary(arch: &Arch) -> String {
+    format!("qemu-system-{}", arch_name(arch))
+}
+
+pub fn qemu_system_installed(arch: &Arch) -> bool {
+    find_program(&qemu_system_binary(arch)).is_some()
+}
+
+/// What this host can run, probed once at startup so pages can explain missing
+/// features up front instead of failing when they're used.
+#[derive(Clone, Debug, Default)]
+pub struct HostCapabilities {
+    /// `/dev/kvm` exists and this user may open it.
+    pub kvm: bool,
+    /// Without quickemu VMs can still be created, listed and edited, but not launched.
+    pub quickemu: bool,
+    /// `None` when quickemu is missing or didn't say; options are then offered as if it's recent.
+    pub quickemu_version: Option<QuickemuVersion>,
+    pub qemu: Vec<QemuBinary>,
+    pub qemu_img: bool,
+    pub swtpm: bool,
+    /// Installed SPICE clients, in order of preference.
+    pub spice_clients: Vec<&'static str>,
+    pub virtiofsd: bool,
+    pub free_space: Vec<FreeSpace>,
+}
+
+#[derive(Clone, Debug)]
+pub struct QemuBinary {
+    pub arch: Arch,
+    /// Reported by `--version`, e.g. `8.2.2`.
+    pub version: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FreeSpace {
+    pub path: PathBuf,
+    pub available: u64,
+}
+
+const SPICE_CLIENTS: [&str; 2] = ["remote-viewer", "spicy"];
+const VIRTIOFSD_PATHS: [&str; 3] = [
+    "/usr/libexec/virtiofsd",
+    "/usr/lib/qemu/virtiofsd",
+    "/usr/lib/virtiofsd",
+];
+
+pub async fn probe() -> HostCapabilities {
+    tokio::task::spawn_blocking(probe_blocking)
+        .await
+        .unwrap_or_default()
+}
+
+fn probe_blocking() -> HostCapabilities {
+    let kvm = open_kvm().is_ok();
+    let quickemu = find_program("quickemu");
+    let quickemu_version = quickemu.as_deref().and_then(compat::detect);
+    let qemu = [Arch::x86_64, Arch::aarch64, Arch::riscv64]
+        .into_iter()
+        .filter_map(|arch| {
+            let binary = find_program(&qemu_system_binary(&arch))?;
+            let version = qemu_version(&binary);
+            Some(QemuBinary { arch, version })
+        })
+        .collect();
+    let spice_clients = SPICE_CLIENTS
+        .into_iter()
+        .filter(|client| find_program(client).is_some())
+        .collect();
+    let virtiofsd = find_program("virtiofsd").is_some()
+        || VIRTIOFSD_PATHS
+            .iter()
+            .any(|path| std::path::Path::new(path).is_file());
+    let free_space = common_locations()
+        .into_iter()
+        .filter_map(|path| {
+            let available = available_space(&path)?;
+            Some(FreeSpace { path, available })
+        })
+        .collect();
+    HostCapabilities {
+        kvm,
+        quickemu: quickemu.is_some(),
+        quickemu_version,
+        qemu,
+        qemu_img: find_program("qemu-img").is_some(),
+        swtpm: find_program("swtpm").is_some(),
+        spice_clients,
+        virtiofsd,
+        free_space,
+    }
+}
+
+/// Open `/dev/kvm` the way QEMU does, failing if it's missing or this user lacks access.
+pub fn open_kvm() -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/kvm")
+}
+
+fn qemu_version(binary: &std::path::Path) -> Option<String> {
+    let output = std::process::Command::new(binary)
+        .arg("--version")
+        .output()
+        .ok()?;
+    // "QEMU emulator version 8.2.2 (Debian 1:8.2.2+ds-0ubuntu1)"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (_, version) = stdout.lines().next()?.split_once("version ")?;
+    version.split_whitespace().next().map(String::from)
+}
+
+/// Where VMs and downloads usually end up.
+fn common_locations() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+    let mut locations = Vec::new();
+    for path in [std::env::current_dir().ok(), home, data_dir]
+        .into_iter()
+        .flatten()
+    {
+        if !locations.contains(&path) {
+            locations.push(path);
+        }
+    }
+    locations
+}
+
+/// Bytes available to this user on the filesystem holding `path`.
+pub fn available_space(path: &std::path::Path) -> Option<u64> {
+    let output = std::process::Command::new("df")
+        .args(["--output=avail", "-B1"])
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .nth(1)?
+        .trim()
+        .parse()
+        .ok()
+}
+
+impl HostCapabilities {
+    pub fn qemu_installed(&self, arch: &Arch) -> bool {
+        self.qemu.iter().any(|qemu| &qemu.arch == arch)
+    }
+    pub fn installed_arches(&self) -> Vec<Arch> {
+        self.qemu.iter().map(|qemu| qemu.arch.clone()).collect()
+    }
+    pub fn qemu_version(&self, arch: &Arch) -> Option<&str> {
+        self.qemu
+            .iter()
+            .find(|qemu| &qemu.arch == arch)
+            .and_then(|qemu| qemu.version.as_deref())
+    }
+    /// Why the installed quickemu can't use the config `key`, see [`compat::unsupported`].
+    pub fn unsupported(&self, key: &str) -> Option<String> {
+        compat::unsupported(self.quickemu_version, key)
+    }
+    /// Free space of the probed location that contains `path`, if any does.
+    pub fn free_space_at(&self, path: &std::path::Path) -> Option<u64> {
+        self.free_space
+            .iter()
+            .filter(|location| path.starts_with(&location.path))
+            .max_by_key(|location| location.path.components().count())
+            .map(|location| location.available)
+    }
+    /// Why a VM for `arch` would run slowly or not at all, with what to do about it.
+    pub fn acceleration_hint(&self, arch: &Arch) -> Option<String> {
+        if !self.qemu_installed(arch) {
+            return Some(format!(
+                "{} is not installed. Install QEMU's {} system emulator.",
+                qemu_system_binary(arch),
+                arch_name(arch)
+            ));
+        }
+        (!needs_emulation(arch) && !self.kvm).then(|| {
+            String::from(
+                "KVM is unavailable, so VMs will be emulated slowly. Enable virtualization in \
+                 your firmware settings and add your user to the kvm group.",
+            )
+        })
+    }
+    /// Why a VM can't be launched on this host.
+    pub fn launch_blocker(&self, arch: &Arch, tpm: bool) -> Option<String> {
+        if !self.quickemu {
+            return Some(String::from(QUICKEMU_HINT));
+        }
+        if !self.qemu_installed(arch) {
+            return Some(format!("Install {} to launch", qemu_system_binary(arch)));
+        }
+        (tpm && !self.swtpm).then(|| String::from("Install swtpm to launch VMs with a TPM"))
+    }
+}
+
+/// Parse an architecture name as written in quickemu configs.
+pub fn parse_arch(name: &str) -> Option<Arch> {
+    [Arch::x86_64, Arch::aarch64, Arch::riscv64]
+        .into_iter()
+        .find(|arch| arch_name(arch) == name)
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use crate::core::vm::{INSTALLER_KEYS, VM};
+
+/// An installer image quickget downloaded into a VM directory.
+#[derive(Clone, Debug)]
+pub struct Installer {
+    pub path: PathBuf,
+    pub size: u64,
+    /// VMs whose config attaches the image, several for duplicated VMs.
+    pub vms: Vec<VM>,
+    /// When one of those VMs was last started, in seconds since the Unix epoch.
+    pub last_used: Option<u64>,
+}
+
+/// Installer images inside the VM directory of `vm`, i.e. ones QERSUI may delete.
+fn downloaded_by(vm: &VM) -> Vec<PathBuf> {
+    let vm_dir = vm.vm_dir();
+    vm.installer_images()
+        .into_iter()
+        .filter(|path| path.starts_with(&vm_dir))
+        .collect()
+}
+
+/// The downloaded installer images of `vms` that still exist, largest first.
+pub async fn scan(vms: Vec<VM>) -> Vec<Installer> {
+    let mut paths = vms.iter().flat_map(downloaded_by).collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+    let mut installers = Vec::new();
+    for path in paths {
+        let Ok(metadata) = tokio::fs::metadata(&path).await else {
+            continue;
+        };
+        let vms = vms
+            .iter()
+            .filter(|vm| vm.installer_images().contains(&path))
+            .cloned()
+            .collect::<Vec<_>>();
+        let last_used = vms
+            .iter()
+            .filter_map(|vm| vm.metadata().last_started())
+            .max();
+        installers.push(Installer {
+            path,
+            size: metadata.len(),
+            vms,
+            last_used,
+        });
+    }
+    installers.sort_by(|a, b| b.size.cmp(&a.size));
+    installers
+}
+
+/// Delete an installer image and drop it from the configs attaching it, returning its size.
+///
+/// quickemu refuses to start a VM whose ISO is missing, so the configs are updated first.
+pub async fn delete(installer: &Installer) -> Result<u64, String> {
+    for vm in &installer.vms {
+        detach(vm, &installer.path).await?;
+    }
+    match tokio::fs::remove_file(&installer.path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!(
+            "Could not delete {}: {e}",
+            installer.path.display()
+        )),
+        _ => Ok(installer.size),
+    }
+}
+
+/// Delete the installer images `vm` was installed from, keeping ones other `vms` still attach.
+pub async fn delete_for(vm: &VM, vms: Vec<VM>) -> Result<u64, String> {
+    let mut reclaimed = 0;
+    for installer in scan(vms).await {
+        if !installer
+            .vms
+            .iter()
+            .any(|other| other.config_path == vm.config_path)
+        {
+            continue;
+        }
+        if installer.vms.len() == 1 {
+            reclaimed += delete(&installer).await?;
+        } else {
+            detach(vm, &installer.path).await?;
+        }
+    }
+    Ok(reclaimed)
+}
+
+/// Remove the keys attaching `image` from the VM's config, re-read in case it was edited since.
+async fn detach(vm: &VM, image: &Path) -> Result<(), String> {
+    let mut vm = VM::load(vm.config_path.clone())
+        .map_err(|e| format!("Could not read {}: {e}", vm.config_path.display()))?;
+    let keys = INSTALLER_KEYS
+        .into_iter()
+        .filter(|key| {
+            vm.config
+                .get(key)
+                .is_some_and(|value| vm.resolve(value) == image)
+        })
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        return Ok(());
+    }
+    for key in keys {
+        vm.config.remove(key);
+    }
+    vm.save().await
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::core::encryption::SecretFile;
+use crate::core::firmware;
+use crate::core::qmp;
+use crate::core::vm::VM;
+
+impl VM {
+    /// quickemu records the QEMU process ID here while the VM is running.
+    pub fn pid_file(&self) -> PathBuf {
+        self.vm_dir().join(format!("{}.pid", self.name))
+    }
+    pub fn running_pid(&self) -> Option<u32> {
+        let pid = std::fs::read_to_string(self.pid_file())
+            .ok()?
+            .trim()
+            .parse::<u32>()
+            .ok()?;
+        PathBuf::from(format!("/proc/{pid}"))
+            .exists()
+            .then_some(pid)
+    }
+    /// Host ports quickemu forwarded for the running VM, e.g. `ssh` and `spice`.
+    pub fn ports(&self) -> HashMap<String, u16> {
+        std::fs::read_to_string(self.vm_dir().join(format!("{}.ports", self.name)))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once(','))
+            .filter_map(|(service, port)| {
+                Some((service.trim().to_string(), port.trim().parse().ok()?))
+            })
+            .collect()
+    }
+}
+
+/// Start the VM through quickemu, which spawns QEMU and exits.
+///
+/// QEMU is asked to open a QMP socket for accurate state and control. Encrypted disks are
+/// unlocked with `passphrase`; quickemu returns before QEMU has read the secret file, so it is
+/// kept until QEMU answers on QMP, which it only does once its disks are open.
+pub async fn start(vm: &VM, passphrase: Option<String>) -> Result<(), String> {
+    run_quickemu(vm, passphrase, &[]).await
+}
+
+/// Start the VM without opening a display window, e.g. for a test boot.
+pub async fn start_headless(vm: &VM, passphrase: Option<String>) -> Result<(), String> {
+    run_quickemu(vm, passphrase, &["--display", "none"]).await
+}
+
+#[tracing::instrument(skip(vm, passphrase), fields(vm = %vm.name), err)]
+async fn run_quickemu(vm: &VM, passphrase: Option<String>, args: &[&str]) -> Result<(), String> {
+    let secret = passphrase.as_deref().map(SecretFile::new).transpose()?;
+    let mut extra_args = qmp::qemu_args(vm);
+    if let Some(secret) = &secret {
+        extra_args.push(' ');
+        extra_args.push_str(&secret.qemu_args());
+    }
+    if let Some(kind) = vm.metadata().firmware {
+        extra_args.push(' ');
+        extra_args.push_str(&firmware::qemu_args(vm, kind).await?);
+    }
+    let mut command = tokio::process::Command::new("quickemu");
+    command
+        .arg("--vm")
+        .arg(&vm.config_path)
+        .args(args)
+        .arg("--extra_args")
+        .arg(extra_args);
+    tracing::info!(?command, "Starting quickemu");
+    let status = command
+        .current_dir(vm.root())
+        .status()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => String::from("quickemu is not installed"),
+            _ => format!("Could not run quickemu: {e}"),
+        })?;
+    if !status.success() {
+        return Err(format!("quickemu exited with {status}"));
+    }
+    if let Some(secret) = secret {
+        wait_for_qmp(vm).await;
+        drop(secret);
+    }
+    Ok(())
+}
+
+/// How long a starting QEMU may take to answer on QMP before its secret file is removed anyway.
+const QMP_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Wait until the VM's QEMU answers on QMP, or has given up starting.
+async fn wait_for_qmp(vm: &VM) {
+    let started = std::time::Instant::now();
+    while started.elapsed() < QMP_STARTUP_TIMEOUT {
+        if qmp::status(vm).await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    tracing::warn!("{} didn't answer on QMP, removing its disk secret", vm.name);
+}
+
+/// Open a SPICE client on a running VM's display.
+pub async fn open_viewer(vm: &VM) -> Result<(), String> {
+    let port = *vm
+        .ports()
+        .get("spice")
+        .ok_or_else(|| format!("{} is not running with a SPICE display", vm.name))?;
+    let uri = format!("spice://localhost:{port}");
+    spawn_first(&[
+        (
+            "remote-viewer",
+            vec![
+                String::from("--title"),
+                vm.metadata().appearance.title(&vm.name),
+                uri.clone(),
+            ],
+        ),
+        ("spicy", vec![String::from("--uri"), uri]),
+    ])
+}
+
+/// Open a terminal with an SSH session to a running VM's forwarded port.
+pub async fn connect_ssh(vm: &VM) -> Result<(), String> {
+    let port = *vm
+        .ports()
+        .get("ssh")
+        .ok_or_else(|| format!("{} has no forwarded SSH port", vm.name))?;
+    let ssh = ["ssh", "-p", &port.to_string(), "localhost"].map(String::from);
+    let with_args = |args: &[&str]| {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .chain(ssh.iter().cloned())
+            .collect::<Vec<String>>()
+    };
+    spawn_first(&[
+        ("cosmic-term", with_args(&["-e"])),
+        ("x-terminal-emulator", with_args(&["-e"])),
+        ("gnome-terminal", with_args(&["--"])),
+        ("konsole", with_args(&["-e"])),
+    ])
+}
+
+/// Spawn the first of `candidates` that is installed, without waiting for it to exit.
+///
+/// The child is waited on in the background, so it's reaped rather than left a zombie.
+#[tracing::instrument(err)]
+fn spawn_first(candidates: &[(&str, Vec<String>)]) -> Result<(), String> {
+    for (program, args) in candidates {
+        match tokio::process::Command::new(program).args(args).spawn() {
+            Ok(mut child) => {
+                tracing::info!(program, pid = child.id(), "Spawned");
+                tokio::spawn(async move {
+                    let _ = child.wait().await;
+                });
+                return Ok(());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Could not run {program}: {e}")),
+        }
+    }
+    let names = candidates
+        .iter()
+        .map(|(program, _)| *program)
+        .collect::<Vec<_>>();
+    Err(format!("None of {} are installed", names.join(", ")))
+}
+
+/// Resolve once the VM's QEMU process is no longer running.
+pub async fn wait_for_exit(vm: &VM) {
+    while vm.running_pid().is_some() {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+}
@@ -1,8 +1,10 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use std::fmt;
+
 use i18n_embed::{
     fluent::{fluent_language_loader, FluentLanguageLoader},
-    LanguageLoader,
+    DesktopLanguageRequester, LanguageLoader,
 };
 use once_cell::sync::Lazy;
 use rust_embed::RustEmbed;
@@ -21,6 +23,42 @@ pub static LANGUAGE_LOADER: Lazy<FluentLanguageLoader> = Lazy::new(|| {
     loader
 });
 
+/// Switch to the user's desktop languages, keeping English for anything untranslated.
+pub fn init() {
+    let requested = DesktopLanguageRequester::requested_languages();
+    if let Err(e) = i18n_embed::select(&*LANGUAGE_LOADER, &Localizations, &requested) {
+        tracing::warn!("Failed to load translations: {e}");
+    }
+}
+
+/// The translation of a quickget identifier under `<prefix>-<identifier>`, e.g. `edition-server`.
+///
+/// Only display text goes through here; configs and saved state keep the canonical identifiers.
+pub fn display_name(prefix: &str, identifier: &str) -> Option<String> {
+    // "English (United States)" becomes "english-united-states".
+    let slug = identifier
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let id = format!("{prefix}-{slug}");
+    LANGUAGE_LOADER.has(&id).then(|| LANGUAGE_LOADER.get(&id))
+}
+
+/// A canonical edition (or Windows language) that displays as its localized form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edition(pub String);
+
+impl fmt::Display for Edition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match display_name("edition", &self.0) {
+            Some(name) => f.write_str(&name),
+            None => f.write_str(&self.0),
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! fl {
     ($message_id:literal) => {{
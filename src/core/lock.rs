@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::io::Read;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Hash rounds, to make brute-forcing a copied config slow.
+const ROUNDS: u32 = 100_000;
+
+/// Installed from `res/`; asks the user for their own credentials rather than an admin's.
+const UNLOCK_ACTION: &str = "com.example.CosmicAppTemplate.unlock";
+
+/// How the app is unlocked on start and after inactivity.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LockMethod {
+    #[default]
+    Disabled,
+    Passphrase,
+    /// Authenticate through the polkit agent, which may offer a password or fingerprint.
+    /// Needs the unlock action from `res/` to be installed.
+    System,
+}
+
+impl LockMethod {
+    pub const ALL: [LockMethod; 3] = [Self::Disabled, Self::Passphrase, Self::System];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Disabled => "Disabled",
+            Self::Passphrase => "Passphrase",
+            Self::System => "System authentication",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AppLock {
+    pub method: LockMethod,
+    /// `salt$hash`, both hex encoded.
+    pub passphrase_hash: String,
+    /// Lock again after this many minutes without input. Zero never locks automatically.
+    pub auto_lock_minutes: u32,
+}
+
+impl AppLock {
+    pub fn is_enabled(&self) -> bool {
+        self.method != LockMethod::Disabled
+    }
+    pub fn auto_lock(&self) -> Option<Duration> {
+        (self.is_enabled() && self.auto_lock_minutes > 0)
+            .then(|| Duration::from_secs(self.auto_lock_minutes as u64 * 60))
+    }
+    pub fn set_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
+        let mut salt = [0; 16];
+        std::fs::File::open("/dev/urandom")
+            .and_then(|mut random| random.read_exact(&mut salt))
+            .map_err(|e| format!("Could not generate salt: {e}"))?;
+        let salt = hex(&salt);
+        self.passphrase_hash = format!("{salt}${}", hash(&salt, passphrase));
+        Ok(())
+    }
+    pub fn verify(&self, passphrase: &str) -> bool {
+        self.passphrase_hash
+            .split_once('$')
+            .is_some_and(|(salt, expected)| constant_time_eq(&hash(salt, passphrase), expected))
+    }
+}
+
+/// Compare without stopping at the first difference, so timing reveals nothing about the hash.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn hash(salt: &str, passphrase: &str) -> String {
+    let mut digest = Sha256::new()
+        .chain_update(salt)
+        .chain_update(passphrase)
+        .finalize();
+    for _ in 1..ROUNDS {
+        digest = Sha256::new()
+            .chain_update(digest)
+            .chain_update(passphrase)
+            .finalize();
+    }
+    hex(&digest)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Ask polkit to authenticate the user for this process.
+pub async fn authenticate_system() -> Result<(), String> {
+    let status = tokio::process::Command::new("pkcheck")
+        .args(["--action-id", UNLOCK_ACTION, "--process"])
+        .arg(std::process::id().to_string())
+        .arg("--allow-user-interaction")
+        .status()
+        .await
+        .map_err(|e| format!("Could not run pkcheck: {e}"))?;
+    status
+        .success()
+        .then_some(())
+        .ok_or_else(|| String::from("Authentication failed"))
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+/// A log past this size is moved to `qersui.log.old` at startup and a new one begun.
+const MAX_LOG_SIZE: u64 = 8 * 1024 * 1024;
+
+/// How many of the most recent lines the log page shows.
+const VIEW_LINES: usize = 1000;
+
+/// Changes the level of the running subscriber, set once [`init`] installed it.
+static LEVEL: OnceCell<reload::Handle<LevelFilter, Registry>> = OnceCell::new();
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Error => "Errors",
+            Self::Warn => "Warnings",
+            Self::Info => "Information",
+            Self::Debug => "Debugging",
+            Self::Trace => "Everything",
+        }
+    }
+    fn filter(self) -> LevelFilter {
+        match self {
+            Self::Error => LevelFilter::ERROR,
+            Self::Warn => LevelFilter::WARN,
+            Self::Info => LevelFilter::INFO,
+            Self::Debug => LevelFilter::DEBUG,
+            Self::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// `$XDG_STATE_HOME/qersui/qersui.log`.
+pub fn log_path() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+    Some(state_home.join("qersui").join("qersui.log"))
+}
+
+fn open_log() -> Option<File> {
+    let path = log_path()?;
+    let opened = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| {
+            let too_large = std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_SIZE);
+            if too_large {
+                std::fs::rename(&path, path.with_extension("log.old"))?;
+            }
+            OpenOptions::new().create(true).append(true).open(&path)
+        });
+    match opened {
+        Ok(file) => Some(file),
+        Err(e) => {
+            // Nothing is listening for events yet.
+            eprintln!("Could not open the log {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Write events at `level` and above to stderr and the log file.
+pub fn init(level: LogLevel) {
+    let (filter, handle) = reload::Layer::new(level.filter());
+    let file = open_log().map(|file| fmt::layer().with_ansi(false).with_writer(Mutex::new(file)));
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(file)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .try_init();
+    if installed.is_ok() {
+        let _ = LEVEL.set(handle);
+    }
+}
+
+/// Apply a level chosen in Settings without restarting.
+pub fn set_level(level: LogLevel) {
+    if let Some(handle) = LEVEL.get() {
+        if let Err(e) = handle.reload(level.filter()) {
+            tracing::warn!("Could not change the log level: {e}");
+        }
+    }
+}
+
+/// The most recent lines of the log file.
+pub async fn tail() -> Result<String, String> {
+    let path = log_path().ok_or_else(|| String::from("No state directory to keep a log in"))?;
+    let contents = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    let contents = String::from_utf8_lossy(&contents);
+    let lines = contents.lines().collect::<Vec<_>>();
+    Ok(lines[lines.len().saturating_sub(VIEW_LINES)..].join("\n"))
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use quickget_core::QGDownload;
+
+use crate::core::vm::VM;
+
+/// Where quickget fetches OpenCore from; the ref after `raw/` selects the release.
+const OPENCORE_REPO: &str = "https://github.com/kholia/OSX-KVM/raw/";
+pub const DEFAULT_OPENCORE_REF: &str = "master";
+pub const RESOLUTIONS: [&str; 5] = [
+    "1280x800",
+    "1440x900",
+    "1920x1080",
+    "2560x1440",
+    "2880x1800",
+];
+
+/// macOS specific choices the generic options don't cover.
+#[derive(Clone, Debug)]
+pub struct MacOSOptions {
+    /// Branch, tag or commit of the OpenCore assets.
+    pub opencore_ref: String,
+    pub installer: MacInstaller,
+    /// Index into [`RESOLUTIONS`].
+    pub resolution: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MacInstaller {
+    /// Download Apple's recovery image and install over the network, as quickget does.
+    Recovery,
+    /// Boot a full installer image the user already has, e.g. made with `createinstallmedia`.
+    Full(Option<PathBuf>),
+}
+
+impl Default for MacOSOptions {
+    fn default() -> Self {
+        Self {
+            opencore_ref: String::from(DEFAULT_OPENCORE_REF),
+            installer: MacInstaller::Recovery,
+            resolution: 2,
+        }
+    }
+}
+
+impl MacOSOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        let opencore_ref = self.opencore_ref.trim();
+        if opencore_ref.is_empty() || opencore_ref.contains(char::is_whitespace) {
+            return Err(String::from("Enter an OpenCore branch, tag or commit"));
+        }
+        match &self.installer {
+            MacInstaller::Full(Some(path)) if path.is_file() => Ok(()),
+            MacInstaller::Full(Some(path)) => Err(format!("{} does not exist", path.display())),
+            MacInstaller::Full(None) => Err(String::from("Select a macOS installer image")),
+            MacInstaller::Recovery => Ok(()),
+        }
+    }
+    /// Point the OpenCore download at the chosen ref and skip the recovery image if unused.
+    pub fn apply_downloads(&self, downloads: &mut Vec<QGDownload>) {
+        let opencore_ref = self.opencore_ref.trim();
+        for download in downloads.iter_mut() {
+            if let Some(rest) = download.url.strip_prefix(OPENCORE_REPO) {
+                if let Some((_, path)) = rest.split_once('/') {
+                    download.url = format!("{OPENCORE_REPO}{opencore_ref}/{path}");
+                }
+            }
+        }
+        if let MacInstaller::Full(_) = self.installer {
+            downloads.retain(|download| {
+                !download.path.file_name().is_some_and(|name| {
+                    let name = name.to_string_lossy();
+                    name.starts_with("RecoveryImage") || name.starts_with("BaseSystem")
+                })
+            });
+        }
+    }
+    /// Write the quickemu keys for the installer and display.
+    pub async fn apply_config(&self, vm: &mut VM) -> Result<(), String> {
+        if let MacInstaller::Full(Some(path)) = &self.installer {
+            vm.config.set("img", path.to_string_lossy());
+        }
+        if let Some((width, height)) = RESOLUTIONS[self.resolution].split_once('x') {
+            vm.config.set("width", width);
+            vm.config.set("height", height);
+        }
+        vm.save().await
+    }
+}
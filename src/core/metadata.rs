@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::appearance::Appearance;
+use crate::core::firmware::FirmwareKind;
+use crate::core::recent::RecentCreation;
+use crate::core::vm::VM;
+
+/// Stored in the VM directory so it follows the VM through renames, exports and imports.
+const FILE_NAME: &str = "qersui.json";
+
+/// Oldest activity entries are dropped beyond this many.
+const ACTIVITY_LIMIT: usize = 500;
+
+/// Split comma-separated tags as typed, dropping blanks and repeats.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// QERSUI's own data about a VM, which quickemu's config has no place for.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Metadata {
+    /// Present for VMs created in QERSUI until the checklist is finished or dismissed.
+    pub first_boot: Option<Checklist>,
+    /// Free-form notes, e.g. the guest's login.
+    pub notes: String,
+    /// Set once a test boot after creation reached the guest's bootloader or installer.
+    pub verified: bool,
+    /// Managed UEFI firmware the launcher attaches, for hosts without a distribution copy.
+    pub firmware: Option<FirmwareKind>,
+    pub appearance: Appearance,
+    /// Started when QERSUI opens, and at login if enabled in Settings.
+    pub autostart: bool,
+    /// Labels for grouping and filtering the library, e.g. `work` or `testing`.
+    pub tags: Vec<String>,
+    /// Lifecycle events, oldest first.
+    pub activity: Vec<Activity>,
+    /// What quickget downloaded for the VM, so the images can be re-checked later.
+    pub sources: Vec<SourceImage>,
+    /// The OS, release, edition and architecture it was created from, for release checks.
+    pub recipe: Option<RecentCreation>,
+    /// The release notice last shown as a desktop notification, so it isn't repeated.
+    pub release_notice: Option<String>,
+    /// Kept as the base for linked clones rather than used directly.
+    pub template: bool,
+    /// Config of the template whose disk backs this VM's disk, for linked clones.
+    pub cloned_from: Option<PathBuf>,
+}
+
+/// A file quickget downloaded into the VM directory, with its published checksum.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct SourceImage {
+    /// Kept as a bare name so it survives the VM being renamed or moved.
+    pub file_name: String,
+    pub url: String,
+    pub checksum: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Activity {
+    pub kind: ActivityKind,
+    /// Seconds since the Unix epoch.
+    pub at: u64,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Created,
+    Started,
+    Stopped,
+    Snapshotted,
+    ConfigEdited,
+    BackedUp,
+    DiskMaintained,
+}
+
+impl ActivityKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Created => "Created",
+            Self::Started => "Started",
+            Self::Stopped => "Stopped",
+            Self::Snapshotted => "Snapshot taken",
+            Self::ConfigEdited => "Settings edited",
+            Self::BackedUp => "Backed up",
+            Self::DiskMaintained => "Disk maintenance",
+        }
+    }
+    pub fn icon_name(&self) -> &'static str {
+        match self {
+            Self::Created => "list-add-symbolic",
+            Self::Started => "media-playback-start-symbolic",
+            Self::Stopped => "media-playback-stop-symbolic",
+            Self::Snapshotted => "camera-photo-symbolic",
+            Self::ConfigEdited => "document-edit-symbolic",
+            Self::BackedUp => "document-export-symbolic",
+            Self::DiskMaintained => "drive-harddisk-symbolic",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Checklist {
+    pub done: Vec<ChecklistItem>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecklistItem {
+    BootOnce,
+    InstallGuestTools,
+    FreshSnapshot,
+    CredentialsNote,
+}
+
+impl ChecklistItem {
+    pub const ALL: [ChecklistItem; 4] = [
+        Self::BootOnce,
+        Self::InstallGuestTools,
+        Self::FreshSnapshot,
+        Self::CredentialsNote,
+    ];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::BootOnce => "Boot the VM and finish the OS installer",
+            Self::InstallGuestTools => "Install guest tools",
+            Self::FreshSnapshot => "Take a \"fresh install\" snapshot",
+            Self::CredentialsNote => "Note down the login",
+        }
+    }
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::BootOnce => "Checked automatically the first time the VM is launched.",
+            Self::InstallGuestTools => {
+                "spice-vdagent and qemu-guest-agent on Linux, or the SPICE guest tools on \
+                 Windows, enable clipboard sharing and display resizing."
+            }
+            Self::FreshSnapshot => "Lets you roll back to a clean install. The VM must be off.",
+            Self::CredentialsNote => "Kept in the VM's notes on this computer only.",
+        }
+    }
+}
+
+impl Checklist {
+    pub fn is_done(&self, item: ChecklistItem) -> bool {
+        self.done.contains(&item)
+    }
+    pub fn set(&mut self, item: ChecklistItem, done: bool) {
+        self.done.retain(|i| i != &item);
+        if done {
+            self.done.push(item);
+        }
+    }
+    pub fn is_complete(&self) -> bool {
+        ChecklistItem::ALL.iter().all(|item| self.is_done(*item))
+    }
+}
+
+impl Metadata {
+    /// A fresh VM starts with every checklist item open.
+    pub fn new_vm() -> Self {
+        let mut metadata = Self {
+            first_boot: Some(Checklist::default()),
+            ..Default::default()
+        };
+        metadata.record(ActivityKind::Created, None);
+        metadata
+    }
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+    /// Add an entry to the activity timeline, timestamped now.
+    pub fn record(&mut self, kind: ActivityKind, detail: Option<String>) {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        self.activity.push(Activity { kind, at, detail });
+        if self.activity.len() > ACTIVITY_LIMIT {
+            self.activity.drain(..self.activity.len() - ACTIVITY_LIMIT);
+        }
+    }
+    /// When the VM was last started, in seconds since the Unix epoch.
+    pub fn last_started(&self) -> Option<u64> {
+        self.activity
+            .iter()
+            .rev()
+            .find(|activity| activity.kind == ActivityKind::Started)
+            .map(|activity| activity.at)
+    }
+}
+
+impl VM {
+    pub fn metadata_path(&self) -> PathBuf {
+        self.vm_dir().join(FILE_NAME)
+    }
+    /// The VM's metadata, or the defaults if it has none yet or it can't be read.
+    pub fn metadata(&self) -> Metadata {
+        std::fs::read(self.metadata_path())
+            .ok()
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default()
+    }
+    pub async fn save_metadata(&self, metadata: &Metadata) -> Result<(), String> {
+        let path = self.metadata_path();
+        tokio::fs::create_dir_all(self.vm_dir())
+            .await
+            .map_err(|e| format!("Could not create {}: {e}", self.vm_dir().display()))?;
+        let contents = serde_json::to_vec_pretty(metadata).map_err(|e| e.to_string())?;
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, contents)
+            .await
+            .map_err(|e| format!("Could not write {}: {e}", temp.display()))?;
+        tokio::fs::rename(&temp, &path)
+            .await
+            .map_err(|e| format!("Could not write {}: {e}", path.display()))
+    }
+}
@@ -1,3 +1,52 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+pub mod activation;
+pub mod appearance;
+pub mod archive;
+pub mod autostart;
+pub mod bus;
+pub mod catalog;
+pub mod collision;
+pub mod compat;
+pub mod config_keys;
+pub mod dependencies;
+pub mod devices;
+pub mod disk;
+pub mod doctor;
+pub mod download;
+pub mod duplicate;
+pub mod edit;
+pub mod encryption;
+pub mod error;
+pub mod firmware;
+pub mod guest_network;
+pub mod hooks;
+pub mod host_probe;
+pub mod installers;
+pub mod launcher;
 pub mod localization;
+pub mod lock;
+pub mod logging;
+pub mod macos;
+pub mod metadata;
+pub mod network;
+pub mod overcommit;
+pub mod pipeline;
+pub mod portal;
+pub mod probe;
+pub mod qmp;
+pub mod recent;
+pub mod release_watch;
+pub mod releases;
+pub mod requirements;
+pub mod resume;
+pub mod reverify;
+pub mod snapshot;
+pub mod spec;
+pub mod template;
+pub mod test_boot;
+pub mod unattended;
+pub mod units;
+pub mod vfio;
+pub mod vm;
+pub mod watch;
@@ -0,0 +1,289 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+/// Fetched by the connection test; it hosts the OS catalog and most quickget downloads.
+const TEST_URL: &str = "https://github.com";
+
+const PROXY_VARIABLES: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+const NO_PROXY_VARIABLES: [&str; 2] = ["NO_PROXY", "no_proxy"];
+const CA_VARIABLE: &str = "SSL_CERT_FILE";
+
+/// Where distributions keep the system certificate authorities, for OpenSSL-based clients that
+/// would otherwise lose them once [`CA_VARIABLE`] points elsewhere.
+const SYSTEM_CA_BUNDLES: [&str; 4] = [
+    "/etc/ssl/certs/ca-certificates.crt",
+    "/etc/pki/tls/certs/ca-bundle.crt",
+    "/etc/ssl/ca-bundle.pem",
+    "/etc/ssl/cert.pem",
+];
+
+/// Connection counts offered in Settings.
+pub const CONNECTION_CHOICES: [usize; 4] = [1, 2, 4, 8];
+
+/// Retry counts offered in Settings.
+pub const RETRY_CHOICES: [u32; 4] = [0, 3, 5, 10];
+
+/// Waits before the first retry offered in Settings, in seconds.
+pub const BACKOFF_CHOICES: [u64; 4] = [1, 2, 5, 10];
+
+/// Failures before switching to a mirror offered in Settings.
+pub const FAILOVER_CHOICES: [u32; 3] = [1, 2, 3];
+
+/// Retries never wait longer than this, however many came before.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Use the proxy from the environment, if any.
+    #[default]
+    Auto,
+    Direct,
+    Manual,
+}
+
+impl ProxyMode {
+    pub const ALL: [Self; 3] = [Self::Auto, Self::Direct, Self::Manual];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Auto => "From environment",
+            Self::Direct => "No proxy",
+            Self::Manual => "Manual",
+        }
+    }
+}
+
+/// How QERSUI reaches the network, for networks behind a proxy or TLS inspection.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub proxy_mode: ProxyMode,
+    /// e.g. `http://proxy.example.com:3128`, used for both HTTP and HTTPS.
+    pub proxy_url: String,
+    /// Comma-separated hosts reached directly in manual mode.
+    pub no_proxy: String,
+    /// PEM bundle trusted by QERSUI's requests in addition to their built-in webpki roots, and by
+    /// OpenSSL-based clients such as the OS catalog's through a bundle combined with the system's
+    /// certificate authorities.
+    pub ca_bundle: Option<PathBuf>,
+    /// Parallel range requests per large download; 0 and 1 both download in a single stream.
+    pub connections_per_download: usize,
+    pub retry: RetryPolicy,
+}
+
+/// How downloads recover from network failures.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts after the first one failed; 0 gives up right away.
+    pub attempts: u32,
+    /// Wait before the first retry, doubled for each one after it.
+    pub backoff_secs: u64,
+    /// Continue from what was already downloaded instead of starting over.
+    pub resume: bool,
+    /// Failed attempts on one URL before switching to a mirror, where one is known.
+    pub failover_after: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            backoff_secs: 2,
+            resume: true,
+            failover_after: 2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retry number `retry`, counting from 0.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        Duration::from_secs(self.backoff_secs.saturating_mul(1 << retry.min(16))).min(MAX_BACKOFF)
+    }
+}
+
+static SETTINGS: Lazy<RwLock<NetworkSettings>> = Lazy::new(Default::default);
+
+/// The proxy environment QERSUI was started with.
+static ORIGINAL_ENV: Lazy<Vec<(&'static str, Option<OsString>)>> = Lazy::new(|| {
+    PROXY_VARIABLES
+        .iter()
+        .chain(&NO_PROXY_VARIABLES)
+        .chain(&[CA_VARIABLE])
+        .map(|name| (*name, std::env::var_os(name)))
+        .collect()
+});
+
+/// Apply `settings` at startup, before the async runtime or any other thread is running.
+///
+/// quickget's catalog fetch builds its own client, so the settings are also mirrored into the
+/// standard proxy and CA environment variables it reads. Changing the environment isn't safe
+/// once other threads may read it, so later changes only reach it after a restart.
+pub fn init(settings: &NetworkSettings) {
+    Lazy::force(&ORIGINAL_ENV);
+    match settings.proxy_mode {
+        ProxyMode::Auto => {}
+        ProxyMode::Direct => {
+            for name in PROXY_VARIABLES {
+                std::env::remove_var(name);
+            }
+        }
+        ProxyMode::Manual => {
+            for name in PROXY_VARIABLES {
+                std::env::set_var(name, settings.proxy_url.trim());
+            }
+            for name in NO_PROXY_VARIABLES {
+                std::env::set_var(name, settings.no_proxy.trim());
+            }
+        }
+    }
+    if let Some(ca_bundle) = &settings.ca_bundle {
+        match combined_ca_bundle(ca_bundle) {
+            Ok(combined) => std::env::set_var(CA_VARIABLE, combined),
+            Err(e) => tracing::warn!("The OS catalog won't trust {}: {e}", ca_bundle.display()),
+        }
+    }
+    configure(settings);
+}
+
+/// Apply new settings to every client QERSUI creates from now on.
+pub fn configure(settings: &NetworkSettings) {
+    if let Ok(mut current) = SETTINGS.write() {
+        *current = settings.clone();
+    }
+}
+
+/// Write the system certificate authorities followed by `ca_bundle` to
+/// `$XDG_CACHE_HOME/qersui/ca-bundle.pem`, as [`CA_VARIABLE`] replaces the trusted roots
+/// rather than adding to them.
+fn combined_ca_bundle(ca_bundle: &Path) -> Result<PathBuf, String> {
+    let custom = std::fs::read(ca_bundle).map_err(|e| format!("Could not read it: {e}"))?;
+    let original = ORIGINAL_ENV
+        .iter()
+        .find(|(name, _)| *name == CA_VARIABLE)
+        .and_then(|(_, value)| value.clone())
+        .map(PathBuf::from);
+    let system = original
+        .into_iter()
+        .chain(SYSTEM_CA_BUNDLES.iter().map(PathBuf::from))
+        .find_map(|path| std::fs::read(path).ok())
+        .unwrap_or_else(|| {
+            tracing::warn!("No system certificate authorities found to combine with the CA bundle");
+            Vec::new()
+        });
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .ok_or("No cache directory to keep the combined bundle in")?;
+    let directory = cache_home.join("qersui");
+    std::fs::create_dir_all(&directory)
+        .map_err(|e| format!("Could not create {}: {e}", directory.display()))?;
+    let path = directory.join("ca-bundle.pem");
+    let mut combined = system;
+    if !combined.ends_with(b"\n") && !combined.is_empty() {
+        combined.push(b'\n');
+    }
+    combined.extend_from_slice(&custom);
+    std::fs::write(&path, combined)
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+/// The proxy found in the environment QERSUI was started with.
+pub fn detected_proxy() -> Option<String> {
+    ORIGINAL_ENV
+        .iter()
+        .filter(|(name, _)| PROXY_VARIABLES.contains(name))
+        .find_map(|(_, value)| value.as_ref())
+        .map(|value| value.to_string_lossy().into_owned())
+}
+
+fn build_client(settings: &NetworkSettings) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder();
+    match settings.proxy_mode {
+        // reqwest reads the proxy environment variables by itself.
+        ProxyMode::Auto => {}
+        ProxyMode::Direct => builder = builder.no_proxy(),
+        ProxyMode::Manual => {
+            let proxy = reqwest::Proxy::all(settings.proxy_url.trim())
+                .map_err(|e| format!("Invalid proxy {}: {e}", settings.proxy_url))?
+                .no_proxy(reqwest::NoProxy::from_string(&settings.no_proxy));
+            builder = builder.proxy(proxy);
+        }
+    }
+    if let Some(ca_bundle) = &settings.ca_bundle {
+        let pem = std::fs::read(ca_bundle)
+            .map_err(|e| format!("Could not read {}: {e}", ca_bundle.display()))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| format!("Invalid CA bundle {}: {e}", ca_bundle.display()))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Could not set up HTTP: {e}"))
+}
+
+/// An HTTP client honoring the proxy and CA settings.
+///
+/// Invalid settings are reported and fall back to a default client, so a typo in the proxy
+/// does not stop QERSUI from working on networks that don't need it.
+pub fn client() -> reqwest::Client {
+    let settings = SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default();
+    build_client(&settings).unwrap_or_else(|e| {
+        tracing::warn!("{e}");
+        reqwest::Client::new()
+    })
+}
+
+/// How many connections a large download may be split across.
+pub fn connections_per_download() -> usize {
+    SETTINGS
+        .read()
+        .map(|settings| settings.connections_per_download)
+        .unwrap_or_default()
+        .max(1)
+}
+
+pub fn retry_policy() -> RetryPolicy {
+    SETTINGS
+        .read()
+        .map(|settings| settings.retry.clone())
+        .unwrap_or_default()
+}
+
+/// Check that `settings` reach the internet, reporting the round trip time.
+pub async fn test_connection(settings: NetworkSettings) -> Result<String, String> {
+    let client = build_client(&settings)?;
+    let started = Instant::now();
+    client
+        .head(TEST_URL)
+        .timeout(Duration::from_secs(15))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Could not reach {TEST_URL}: {e}"))?;
+    Ok(format!(
+        "Reached {TEST_URL} in {} ms",
+        started.elapsed().as_millis()
+    ))
+}
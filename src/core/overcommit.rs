@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use quickget_core::QuickgetInstance;
+
+use crate::core::units::{format_size, parse_size};
+use crate::core::vm::VM;
+
+/// Share of the host's RAM that VMs may ask for together before it is reported as over-committed;
+/// the rest is left to the host itself.
+const RAM_LIMIT: f64 = 0.9;
+
+/// A VM counted against the host: running now, or started automatically.
+#[derive(Clone, Debug)]
+pub struct Commitment {
+    pub name: String,
+    pub config_path: PathBuf,
+    pub ram: u64,
+    pub cpu_cores: usize,
+}
+
+impl Commitment {
+    /// What `vm` asks for, with quickemu's defaults for anything its config leaves out.
+    pub fn of(vm: &VM) -> Self {
+        Self {
+            name: vm.name.clone(),
+            config_path: vm.config_path.clone(),
+            ram: vm
+                .ram()
+                .and_then(parse_size)
+                .unwrap_or_else(QuickgetInstance::get_recommended_ram),
+            cpu_cores: vm
+                .cpu_cores()
+                .and_then(|cores| cores.trim().parse().ok())
+                .unwrap_or_else(QuickgetInstance::get_recommended_cpu_cores),
+        }
+    }
+}
+
+/// The resources the library's running and autostart VMs take, shared with creation sessions.
+#[derive(Clone, Debug, Default)]
+pub struct Commitments {
+    vms: Vec<Commitment>,
+}
+
+/// A VM's allocation that takes the host past what it has.
+#[derive(Clone, Debug)]
+pub struct Overcommit {
+    /// Asked for in total, including the VM being created or edited.
+    pub ram: u64,
+    pub cpu_cores: usize,
+    pub host_ram: u64,
+    pub host_cores: usize,
+    /// The other VMs counted towards the totals.
+    pub vms: Vec<Commitment>,
+}
+
+impl Commitments {
+    pub fn new<'a>(vms: impl IntoIterator<Item = &'a VM>) -> Self {
+        Self {
+            vms: vms.into_iter().map(Commitment::of).collect(),
+        }
+    }
+    /// Check a VM asking for `ram` and `cpu_cores` against the host; `vm` is left out of the
+    /// counted VMs, as its old allocation is what's being changed.
+    pub fn check(&self, vm: Option<&Path>, ram: u64, cpu_cores: usize) -> Option<Overcommit> {
+        let vms = self
+            .vms
+            .iter()
+            .filter(|commitment| Some(commitment.config_path.as_path()) != vm)
+            .cloned()
+            .collect::<Vec<_>>();
+        let overcommit = Overcommit {
+            ram: ram + vms.iter().map(|commitment| commitment.ram).sum::<u64>(),
+            cpu_cores: cpu_cores
+                + vms
+                    .iter()
+                    .map(|commitment| commitment.cpu_cores)
+                    .sum::<usize>(),
+            host_ram: QuickgetInstance::get_total_ram(),
+            host_cores: QuickgetInstance::get_total_cpu_cores(),
+            vms,
+        };
+        (overcommit.ram_exceeded() || overcommit.cpu_exceeded()).then_some(overcommit)
+    }
+}
+
+impl Overcommit {
+    pub fn ram_exceeded(&self) -> bool {
+        self.ram as f64 > self.host_ram as f64 * RAM_LIMIT
+    }
+    /// Every core taken at once leaves the host to compete with its guests.
+    pub fn cpu_exceeded(&self) -> bool {
+        self.cpu_cores > self.host_cores
+    }
+    pub fn message(&self) -> String {
+        let mut parts = Vec::new();
+        if self.ram_exceeded() {
+            parts.push(format!(
+                "{} of the host's {} of RAM",
+                format_size(self.ram),
+                format_size(self.host_ram)
+            ));
+        }
+        if self.cpu_exceeded() {
+            parts.push(format!(
+                "{} of its {} CPU cores",
+                self.cpu_cores, self.host_cores
+            ));
+        }
+        let parts = parts.join(" and ");
+        match self.vms.len() {
+            0 => format!("This VM would take {parts}"),
+            1 => {
+                format!("With the VM below running too, this one would bring the total to {parts}")
+            }
+            count => format!(
+                "With the {count} VMs below running too, this one would bring the total to {parts}"
+            ),
+        }
+    }
+}
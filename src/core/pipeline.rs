@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use quickget_core::QGDownload;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::core::error::{AppError, ErrorCategory};
+
+/// The steps of creating a VM, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    ResolveConfig,
+    Download,
+    Verify,
+    Decompress,
+    WriteConfig,
+    CreateDisk,
+    Finalize,
+    /// Optional, see [`crate::core::test_boot`].
+    TestBoot,
+}
+
+impl Stage {
+    pub const ALL: [Self; 8] = [
+        Self::ResolveConfig,
+        Self::Download,
+        Self::Verify,
+        Self::Decompress,
+        Self::WriteConfig,
+        Self::CreateDisk,
+        Self::Finalize,
+        Self::TestBoot,
+    ];
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ResolveConfig => "Resolve configuration",
+            Self::Download => "Download",
+            Self::Verify => "Verify checksums",
+            Self::Decompress => "Decompress",
+            Self::WriteConfig => "Write VM config",
+            Self::CreateDisk => "Prepare disk",
+            Self::Finalize => "Finalize",
+            Self::TestBoot => "Test boot",
+        }
+    }
+}
+
+/// The last stage that completed, saved so a failed or interrupted creation resumes after it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub stage: Stage,
+    /// Set once the config has been written, since later stages work on the VM it describes.
+    pub config_path: Option<PathBuf>,
+}
+
+impl Checkpoint {
+    pub fn is_done(checkpoint: Option<&Self>, stage: Stage) -> bool {
+        checkpoint.is_some_and(|checkpoint| checkpoint.stage >= stage)
+    }
+}
+
+/// Check every download that has a published checksum against it.
+pub async fn verify(downloads: &[QGDownload]) -> Result<(), AppError> {
+    for qg_download in downloads {
+        let Some(expected) = &qg_download.checksum else {
+            continue;
+        };
+        let path = &qg_download.path;
+        if checksum_matches(path, expected).await? == Some(false) {
+            // A corrupt file is no use to resume from, so the next attempt downloads it again.
+            let _ = tokio::fs::remove_file(path).await;
+            return Err(AppError::new(
+                ErrorCategory::Network,
+                format!("{} does not match its published checksum", path.display()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` matches a published checksum, `None` if its algorithm isn't supported.
+///
+/// quickget publishes MD5 and SHA-1 sums for some OSes, which are not checked.
+pub async fn checksum_matches(path: &Path, expected: &str) -> Result<Option<bool>, AppError> {
+    let expected = expected.trim().to_string();
+    let actual = tokio::task::spawn_blocking({
+        let path = path.to_path_buf();
+        let len = expected.len();
+        move || file_digest(&path, len)
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCategory::Disk, "Verification was interrupted").caused_by(e))?
+    .map_err(|e| {
+        AppError::new(
+            ErrorCategory::Disk,
+            format!("Could not verify {}", path.display()),
+        )
+        .caused_by(e)
+    })?;
+    Ok(actual.map(|actual| actual.eq_ignore_ascii_case(&expected)))
+}
+
+/// Hex digest of `path` using the SHA-2 variant matching a checksum of `len` characters.
+fn file_digest(path: &Path, len: usize) -> Result<Option<String>, std::io::Error> {
+    fn digest<D: Digest>(mut file: std::fs::File) -> Result<String, std::io::Error> {
+        let mut hasher = D::new();
+        let mut buffer = vec![0; 1024 * 1024];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect())
+    }
+    let file = std::fs::File::open(path)?;
+    match len {
+        64 => digest::<Sha256>(file).map(Some),
+        128 => digest::<Sha512>(file).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Unpack compressed downloads next to the archive, skipping any already unpacked.
+pub async fn decompress(downloads: &[QGDownload]) -> Result<(), AppError> {
+    for qg_download in downloads {
+        let path = &qg_download.path;
+        let Some((target, program)) = decompressed_path(path) else {
+            continue;
+        };
+        if tokio::fs::try_exists(&target).await.unwrap_or(false) {
+            continue;
+        }
+        let decompress_error = |e: String| {
+            AppError::new(
+                ErrorCategory::Disk,
+                format!("Could not decompress {}", path.display()),
+            )
+            .caused_by(e)
+        };
+        // Written to a partial file first, so an interrupted run does not leave a truncated image.
+        let partial = crate::core::download::partial_path(&target);
+        let output =
+            std::fs::File::create(&partial).map_err(|e| decompress_error(e.to_string()))?;
+        tracing::info!(program, path = %path.display(), "Decompressing");
+        let status = tokio::process::Command::new(program)
+            .args(["-d", "-c"])
+            .arg(path)
+            .stdout(output)
+            .status()
+            .await
+            .map_err(|e| decompress_error(format!("Could not run {program}: {e}")))?;
+        if !status.success() {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(decompress_error(format!("{program} exited with {status}")));
+        }
+        tokio::fs::rename(&partial, &target)
+            .await
+            .map_err(|e| decompress_error(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Where a compressed download unpacks to, and the program that unpacks it.
+fn decompressed_path(path: &Path) -> Option<(PathBuf, &'static str)> {
+    let program = match path.extension()?.to_str()? {
+        "xz" => "xz",
+        "gz" => "gzip",
+        "bz2" => "bzip2",
+        "zst" => "zstd",
+        _ => return None,
+    };
+    Some((path.with_extension(""), program))
+}
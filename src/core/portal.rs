@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use ashpd::desktop::background::Background;
+use ashpd::desktop::file_chooser::{FileFilter, SelectedFiles};
+use ashpd::desktop::notification::{Notification, NotificationProxy};
+use ashpd::desktop::ResponseError;
+
+use crate::core::error::{AppError, ErrorCategory};
+
+/// Ask the file chooser portal for a single file or directory; `None` if the user cancelled.
+///
+/// Fails when no portal backend is running, as under many minimal window managers.
+pub async fn pick(
+    title: &'static str,
+    directory: bool,
+    filter: Option<FileFilter>,
+) -> Result<Option<PathBuf>, AppError> {
+    let portal_error = |e: ashpd::Error| {
+        AppError::new(ErrorCategory::Portal, "Could not open a file chooser").caused_by(e)
+    };
+    let mut request = SelectedFiles::open_file()
+        .title(title)
+        .accept_label("Select")
+        .modal(true)
+        .multiple(false)
+        .directory(directory);
+    if let Some(filter) = filter {
+        request = request.filter(filter);
+    }
+    let files = match request.send().await.map_err(portal_error)?.response() {
+        Ok(files) => files,
+        Err(ashpd::Error::Response(ResponseError::Cancelled)) => return Ok(None),
+        Err(e) => return Err(portal_error(e)),
+    };
+    Ok(files
+        .uris()
+        .iter()
+        .next()
+        .and_then(|file| file.to_file_path().ok())
+        .filter(|path| path.exists()))
+}
+
+/// Ask the background portal to let the app keep running without a visible window.
+pub async fn request_background(reason: &str) -> bool {
+    let Ok(request) = Background::request().reason(reason).send().await else {
+        return false;
+    };
+    request
+        .response()
+        .is_ok_and(|background| background.run_in_background())
+}
+
+/// Show a desktop notification, replacing any earlier one with the same `id`.
+pub async fn notify(id: String, title: String, body: String) {
+    let result = async {
+        let proxy = NotificationProxy::new().await?;
+        proxy
+            .add_notification(&id, Notification::new(&title).body(body.as_str()))
+            .await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!("Failed to show notification: {e}");
+    }
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How much of the start of the disk is read for partition table heuristics.
+const PROBE_BYTES: usize = 1024 * 1024;
+
+/// Numbers probe scratch files, so images probed at the same time don't share one.
+static NEXT_PROBE: AtomicU64 = AtomicU64::new(0);
+
+/// A best-effort guess at the operating system installed on a disk image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuestGuess {
+    /// The quickemu `guest_os` value, e.g. `linux` or `windows`.
+    pub guest_os: String,
+    pub distro: Option<String>,
+    pub version: Option<String>,
+    /// Whether the guess came from full filesystem inspection rather than partition types.
+    pub inspected: bool,
+}
+
+impl GuestGuess {
+    fn heuristic(guest_os: &str) -> Self {
+        Self {
+            guest_os: guest_os.to_string(),
+            distro: None,
+            version: None,
+            inspected: false,
+        }
+    }
+    /// A human readable label such as `ubuntu 22.04` or `windows`.
+    pub fn label(&self) -> String {
+        let mut label = self.distro.clone().unwrap_or_else(|| self.guest_os.clone());
+        if let Some(version) = &self.version {
+            label.push(' ');
+            label.push_str(version);
+        }
+        label
+    }
+    /// Themed icon following the freedesktop `distributor-logo-*` naming.
+    pub fn icon_name(&self) -> String {
+        let name = self.distro.as_deref().unwrap_or(&self.guest_os);
+        format!("distributor-logo-{name}")
+    }
+    /// Suggested RAM and CPU cores for the detected guest.
+    pub fn recommended_resources(&self) -> (&'static str, usize) {
+        match self.guest_os.as_str() {
+            "windows" => ("4G", 2),
+            "macos" => ("8G", 4),
+            "linux" => ("2G", 2),
+            _ => ("1G", 1),
+        }
+    }
+}
+
+/// Inspect a disk image of any format qemu-img understands.
+pub async fn inspect(image: PathBuf) -> Option<GuestGuess> {
+    if let Some(guess) = virt_inspector(&image).await {
+        return Some(guess);
+    }
+    let head = read_head(&image).await?;
+    guess_from_partitions(&head)
+}
+
+/// libguestfs inspection, when installed, identifies the exact distribution and version.
+async fn virt_inspector(image: &Path) -> Option<GuestGuess> {
+    let output = tokio::process::Command::new("virt-inspector")
+        .arg("--no-applications")
+        .arg("--no-icon")
+        .arg("-a")
+        .arg(image)
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let xml = String::from_utf8_lossy(&output.stdout);
+    let tag = |name: &str| {
+        let start = xml.find(&format!("<{name}>"))? + name.len() + 2;
+        let end = xml[start..].find(&format!("</{name}>"))? + start;
+        Some(xml[start..end].to_string())
+    };
+    let guest_os = tag("name")?;
+    if matches!(guest_os.as_str(), "unknown" | "hurd" | "dos") {
+        return None;
+    }
+    let version = tag("major_version").map(|major| match tag("minor_version") {
+        Some(minor) if minor != "0" => format!("{major}.{minor}"),
+        _ => major,
+    });
+    Some(GuestGuess {
+        guest_os,
+        distro: tag("distro").filter(|distro| distro != "unknown"),
+        version,
+        inspected: true,
+    })
+}
+
+/// Extract the first MiB as raw bytes, letting qemu-img handle qcow2/vdi/vmdk.
+async fn read_head(image: &Path) -> Option<Vec<u8>> {
+    let temp = std::env::temp_dir().join(format!(
+        "qersui-probe-{}-{}.raw",
+        std::process::id(),
+        NEXT_PROBE.fetch_add(1, Ordering::Relaxed)
+    ));
+    let status = tokio::process::Command::new("qemu-img")
+        .arg("dd")
+        .args(["-O", "raw", "bs=512"])
+        .arg(format!("count={}", PROBE_BYTES / 512))
+        .arg(format!("if={}", image.display()))
+        .arg(format!("of={}", temp.display()))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .ok()?;
+    let head = if status.success() {
+        tokio::fs::read(&temp).await.ok()
+    } else {
+        None
+    };
+    let _ = tokio::fs::remove_file(&temp).await;
+    head.filter(|head| head.len() >= 1024)
+}
+
+fn guess_from_partitions(head: &[u8]) -> Option<GuestGuess> {
+    if head.get(512..520) == Some(&b"EFI PART"[..]) {
+        return guess_from_gpt(head);
+    }
+    if head.get(510..512) != Some(&[0x55, 0xAA][..]) {
+        return None;
+    }
+    (0..4)
+        .map(|i| head[0x1BE + 16 * i + 4])
+        .find_map(|partition_type| {
+            let guest_os = match partition_type {
+                0x07 | 0x27 => "windows",
+                0x83 | 0x8E => "linux",
+                0xA5 => "freebsd",
+                0xA6 => "openbsd",
+                0xA9 => "netbsd",
+                0xAF => "macos",
+                0xBF => "solaris",
+                0xEB => "haiku",
+                _ => return None,
+            };
+            Some(GuestGuess::heuristic(guest_os))
+        })
+}
+
+fn guess_from_gpt(head: &[u8]) -> Option<GuestGuess> {
+    let read_u32 = |offset: usize| {
+        head.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let entries_lba = read_u32(512 + 72)? as usize;
+    let entry_count = read_u32(512 + 80)? as usize;
+    let entry_size = read_u32(512 + 84)? as usize;
+    if entry_size < 16 {
+        return None;
+    }
+
+    let mut windows = false;
+    for index in 0..entry_count.min(128) {
+        let offset = entries_lba * 512 + index * entry_size;
+        let Some(guid) = head.get(offset..offset + 16) else {
+            break;
+        };
+        let guest_os = match format_guid(guid).as_str() {
+            // Basic data partitions are also used for shared FAT volumes, so keep looking for
+            // something more specific before settling on Windows.
+            "EBD0A0A2-B9E5-4433-87C0-68B6B72699C7" | "E3C9E316-0B5C-4DB8-817D-F92DF00215AE" => {
+                windows = true;
+                continue;
+            }
+            "0FC63DAF-8483-4772-8E79-3D69D8477DE4"
+            | "4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709"
+            | "B921B045-1DF0-41C3-AF44-4C6F280D3FAE"
+            | "E6D6D379-F507-44C2-A23C-238F2A3DF928" => "linux",
+            "48465300-0000-11AA-AA11-00306543ECAC" | "7C3457EF-0000-11AA-AA11-00306543ECAC" => {
+                "macos"
+            }
+            "516E7CB6-6ECF-11D6-8FF8-00022D09712B" | "516E7CBA-6ECF-11D6-8FF8-00022D09712B" => {
+                "freebsd"
+            }
+            "824CC7A0-36A8-11E3-890A-952519AD3F61" => "openbsd",
+            "49F48D5A-B10E-11DC-B99B-0019D1879648" => "netbsd",
+            "42465331-3BA3-10F1-802A-4861696B7521" => "haiku",
+            _ => continue,
+        };
+        return Some(GuestGuess::heuristic(guest_os));
+    }
+    windows.then(|| GuestGuess::heuristic("windows"))
+}
+
+/// GPT GUIDs store their first three fields little-endian.
+fn format_guid(bytes: &[u8]) -> String {
+    format!(
+        "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        bytes[3], bytes[2], bytes[1], bytes[0], bytes[5], bytes[4], bytes[7], bytes[6],
+        bytes[8], bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15]
+    )
+}
@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixStream;
+
+use crate::core::vm::VM;
+
+/// Thumbnails are scaled down to this width, about twice the library's card on HiDPI screens.
+const THUMBNAIL_WIDTH: u32 = 480;
+
+/// What QEMU itself reports through `query-status`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunState {
+    Running,
+    Paused,
+    /// Shutting down, saving state, or any other transitional state.
+    Other,
+}
+
+impl VM {
+    /// The most recent screendump, kept so stopped VMs still show their last frame.
+    pub fn thumbnail_path(&self) -> PathBuf {
+        self.vm_dir().join("qersui-thumbnail.png")
+    }
+    /// Where QEMU writes the full-size frame a thumbnail is scaled from.
+    fn screendump_path(&self) -> PathBuf {
+        self.vm_dir().join("qersui-screendump.png")
+    }
+    /// The QMP socket QERSUI asks QEMU to open when it launches a VM.
+    pub fn qmp_socket(&self) -> PathBuf {
+        self.vm_dir().join(format!("{}-qmp.socket", self.name))
+    }
+}
+
+/// QEMU arguments opening the VM's QMP socket, passed through quickemu's `--extra_args`.
+pub fn qemu_args(vm: &VM) -> String {
+    format!("-qmp unix:{},server=on,wait=off", vm.qmp_socket().display())
+}
+
+/// A connection to a QEMU Machine Protocol socket, past capability negotiation.
+pub struct Qmp {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl Qmp {
+    #[tracing::instrument(fields(socket = %socket.display()), skip(socket))]
+    pub async fn connect(socket: &Path) -> Result<Self, String> {
+        let stream = UnixStream::connect(socket)
+            .await
+            .map_err(|e| format!("Could not connect to {}: {e}", socket.display()))?;
+        let (reader, writer) = stream.into_split();
+        let mut qmp = Self {
+            reader: BufReader::new(reader),
+            writer,
+        };
+        // QEMU greets with its version, then waits for capabilities before taking commands.
+        qmp.read_message().await?;
+        qmp.execute("qmp_capabilities", None).await?;
+        Ok(qmp)
+    }
+    async fn read_message(&mut self) -> Result<Value, String> {
+        let mut line = String::new();
+        let read = self
+            .reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| format!("QMP read failed: {e}"))?;
+        if read == 0 {
+            return Err(String::from("QEMU closed the QMP connection"));
+        }
+        serde_json::from_str(&line).map_err(|e| format!("Invalid QMP message: {e}"))
+    }
+    /// Run a command and return its `return` value, skipping asynchronous events.
+    #[tracing::instrument(skip(self, arguments))]
+    pub async fn execute(
+        &mut self,
+        command: &str,
+        arguments: Option<Value>,
+    ) -> Result<Value, String> {
+        let mut request = json!({ "execute": command });
+        if let Some(arguments) = arguments {
+            request["arguments"] = arguments;
+        }
+        let mut line = request.to_string();
+        tracing::trace!(%line, "Sending");
+        line.push('\n');
+        self.writer
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("QMP write failed: {e}"))?;
+        loop {
+            let mut message = self.read_message().await?;
+            if let Some(value) = message.get_mut("return") {
+                return Ok(value.take());
+            }
+            if let Some(error) = message.get("error") {
+                let description = error["desc"].as_str().unwrap_or("unknown error");
+                tracing::debug!(description, "Command failed");
+                return Err(format!("{command} failed: {description}"));
+            }
+        }
+    }
+    pub async fn status(&mut self) -> Result<RunState, String> {
+        let status = self.execute("query-status", None).await?;
+        Ok(match status["status"].as_str() {
+            Some("running") => RunState::Running,
+            Some("paused" | "suspended") => RunState::Paused,
+            _ => RunState::Other,
+        })
+    }
+}
+
+async fn connect(vm: &VM) -> Result<Qmp, String> {
+    Qmp::connect(&vm.qmp_socket()).await
+}
+
+pub async fn status(vm: &VM) -> Result<RunState, String> {
+    connect(vm).await?.status().await
+}
+
+/// Press the virtual power button, letting the guest shut down cleanly.
+pub async fn powerdown(vm: &VM) -> Result<(), String> {
+    connect(vm)
+        .await?
+        .execute("system_powerdown", None)
+        .await
+        .map(drop)
+}
+
+/// Stop QEMU immediately, as if the power cord were pulled.
+pub async fn quit(vm: &VM) -> Result<(), String> {
+    connect(vm).await?.execute("quit", None).await.map(drop)
+}
+
+pub async fn pause(vm: &VM) -> Result<(), String> {
+    connect(vm).await?.execute("stop", None).await.map(drop)
+}
+
+pub async fn resume(vm: &VM) -> Result<(), String> {
+    connect(vm).await?.execute("cont", None).await.map(drop)
+}
+
+/// Write the guest's display to `path` as a PNG image (QEMU 7.1 or newer).
+pub async fn screendump(vm: &VM, path: &Path) -> Result<(), String> {
+    let arguments = json!({ "filename": path.to_string_lossy(), "format": "png" });
+    connect(vm)
+        .await?
+        .execute("screendump", Some(arguments))
+        .await
+        .map(drop)
+}
+
+/// Capture the VM's display into its thumbnail, returning the PNG.
+///
+/// Frames are scaled down once here rather than kept and decoded at the guest's resolution.
+pub async fn capture_thumbnail(vm: &VM) -> Result<Vec<u8>, String> {
+    let screendump = vm.screendump_path();
+    screendump(vm, &screendump).await?;
+    let png = tokio::task::spawn_blocking(move || {
+        let scaled = scale_down(&screendump);
+        let _ = std::fs::remove_file(&screendump);
+        scaled
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let path = vm.thumbnail_path();
+    tokio::fs::write(&path, &png)
+        .await
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    Ok(png)
+}
+
+fn scale_down(path: &Path) -> Result<Vec<u8>, String> {
+    let frame = image::open(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    let frame = if frame.width() > THUMBNAIL_WIDTH {
+        frame.thumbnail(THUMBNAIL_WIDTH, u32::MAX)
+    } else {
+        frame
+    };
+    let mut png = Vec::new();
+    frame
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Could not encode the thumbnail: {e}"))?;
+    Ok(png)
+}
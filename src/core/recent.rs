@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use serde::{Deserialize, Serialize};
+
+/// How many combinations the Recent section of the OS list remembers.
+const MAX_RECENT: usize = 5;
+
+/// An OS, release, edition and architecture a VM was created with.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecentCreation {
+    /// The quickget name, e.g. `ubuntu`.
+    pub os: String,
+    pub release: Option<String>,
+    pub edition: Option<String>,
+    pub arch: Option<String>,
+}
+
+impl RecentCreation {
+    /// E.g. "Ubuntu 24.04 desktop (x86_64)", given the OS's display name.
+    pub fn label(&self, os_name: &str) -> String {
+        let mut label = [
+            Some(os_name),
+            self.release.as_deref(),
+            self.edition.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+        if let Some(arch) = &self.arch {
+            label.push_str(&format!(" ({arch})"));
+        }
+        label
+    }
+}
+
+/// Put `recent` first, dropping an earlier copy of it and the oldest beyond the limit.
+pub fn record(list: &mut Vec<RecentCreation>, recent: RecentCreation) {
+    list.retain(|entry| *entry != recent);
+    list.insert(0, recent);
+    list.truncate(MAX_RECENT);
+}
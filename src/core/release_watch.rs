@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use quickget_core::data_structures::OS;
+
+use crate::core::catalog;
+use crate::core::error::AppError;
+use crate::core::recent::RecentCreation;
+use crate::core::releases::{self, Release, ReleaseKind};
+use crate::core::vm::VM;
+
+/// How often the library compares its VMs against a freshly loaded catalog.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReleaseStatus {
+    /// quickget marks the release end of life, or no longer offers it at all.
+    EndOfLife,
+    /// A newer stable release is available.
+    Outdated,
+}
+
+/// Why a VM's release deserves attention, with the release to upgrade to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReleaseNotice {
+    pub status: ReleaseStatus,
+    pub release: String,
+    /// The VM's recipe with the newest stable release, for creating an upgraded VM.
+    pub upgrade: Option<RecentCreation>,
+}
+
+impl ReleaseNotice {
+    pub fn label(&self) -> &'static str {
+        match self.status {
+            ReleaseStatus::EndOfLife => "End of life",
+            ReleaseStatus::Outdated => "New release",
+        }
+    }
+    pub fn message(&self) -> String {
+        let release = &self.release;
+        match (
+            self.status,
+            self.upgrade
+                .as_ref()
+                .and_then(|upgrade| upgrade.release.as_ref()),
+        ) {
+            (ReleaseStatus::EndOfLife, Some(newer)) => {
+                format!("{release} is no longer supported; {newer} is the current release")
+            }
+            (ReleaseStatus::EndOfLife, None) => format!("{release} is no longer supported"),
+            (ReleaseStatus::Outdated, Some(newer)) => {
+                format!("{newer} is available, this VM runs {release}")
+            }
+            (ReleaseStatus::Outdated, None) => format!("A release newer than {release} is out"),
+        }
+    }
+}
+
+/// What `vm` was created from: recorded at creation, or else read from the
+/// `<os>-<release>[-<edition>]` names quickget gives its VMs.
+pub fn recipe(vm: &VM, os_list: &[OS]) -> Option<RecentCreation> {
+    if let Some(recipe) = vm.metadata().recipe {
+        return Some(recipe);
+    }
+    let (os, rest) = os_list
+        .iter()
+        .filter_map(|os| {
+            let rest = vm.name.strip_prefix(os.name.as_str())?.strip_prefix('-')?;
+            Some((os, rest))
+        })
+        // `ubuntu-server` rather than `ubuntu`.
+        .max_by_key(|(os, _)| os.name.len())?;
+    let edition = os
+        .releases
+        .iter()
+        .filter_map(|config| config.edition.as_deref())
+        .find(|edition| {
+            rest.strip_suffix(edition)
+                .is_some_and(|release| release.ends_with('-'))
+        });
+    let release = match edition {
+        Some(edition) => &rest[..rest.len() - edition.len() - 1],
+        None => rest,
+    };
+    Some(RecentCreation {
+        os: os.name.clone(),
+        release: Some(release.to_string()),
+        edition: edition.map(str::to_string),
+        arch: None,
+    })
+}
+
+fn has_version(release: &str) -> bool {
+    release.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Compare a VM's recipe against the catalog; `None` if its release is current or can't be told.
+pub fn check(recipe: &RecentCreation, os_list: &[OS]) -> Option<ReleaseNotice> {
+    let os = os_list.iter().find(|os| os.name == recipe.os)?;
+    let current = recipe.release.as_deref()?;
+    // Only releases of the same edition and architecture are upgrades.
+    let mut candidates = os
+        .releases
+        .iter()
+        .filter(|config| {
+            recipe
+                .arch
+                .as_deref()
+                .map_or(true, |arch| config.arch.to_string() == arch)
+                && (recipe.edition.is_none() || config.edition == recipe.edition)
+        })
+        .filter_map(|config| config.release.clone())
+        .map(Release::new)
+        .collect::<Vec<_>>();
+    if candidates.is_empty() {
+        return None;
+    }
+    candidates.sort_by(releases::compare);
+    candidates.dedup();
+
+    let newest = candidates
+        .iter()
+        .find(|release| release.kind == ReleaseKind::Stable);
+    let upgrade = newest
+        .filter(|newest| newest.name != current)
+        .map(|newest| RecentCreation {
+            release: Some(newest.name.clone()),
+            ..recipe.clone()
+        });
+    let status = match candidates.iter().find(|release| release.name == current) {
+        None => ReleaseStatus::EndOfLife,
+        Some(release) if release.kind == ReleaseKind::EndOfLife => ReleaseStatus::EndOfLife,
+        Some(release) if release.kind == ReleaseKind::Stable => {
+            // Codenames and rolling releases can't be ordered against version numbers.
+            let newer = newest.is_some_and(|newest| {
+                newest.name != current
+                    && has_version(&newest.name)
+                    && has_version(current)
+                    && releases::compare(newest, release).is_lt()
+            });
+            if !newer {
+                return None;
+            }
+            ReleaseStatus::Outdated
+        }
+        // Pre-releases were picked on purpose.
+        Some(_) => return None,
+    };
+    Some(ReleaseNotice {
+        status,
+        release: current.to_string(),
+        upgrade,
+    })
+}
+
+/// Check every VM against a freshly loaded catalog.
+pub async fn check_all(vms: Vec<VM>) -> Result<HashMap<PathBuf, ReleaseNotice>, AppError> {
+    let os_list = catalog::fetch().await?;
+    Ok(vms
+        .into_iter()
+        .filter_map(|vm| {
+            let notice = check(&recipe(&vm, &os_list)?, &os_list)?;
+            Some((vm.config_path, notice))
+        })
+        .collect())
+}
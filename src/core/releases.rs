@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Words in a release name marking a build that is not meant for general use yet.
+const PRE_RELEASE_WORDS: [&str; 14] = [
+    "alpha", "beta", "rc", "daily", "dev", "devel", "nightly", "preview", "insider", "testing",
+    "unstable", "rawhide", "sid", "canary",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReleaseKind {
+    Stable,
+    PreRelease,
+    /// quickget prefixes releases that are only served from archive mirrors with `eol`.
+    EndOfLife,
+}
+
+/// A release as listed in the creation page, marked with what kind of release it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Release {
+    pub name: String,
+    pub kind: ReleaseKind,
+}
+
+impl Release {
+    pub fn new(name: String) -> Self {
+        let kind = kind(&name);
+        Self { name, kind }
+    }
+    pub fn is_testing(&self) -> bool {
+        self.kind != ReleaseKind::Stable
+    }
+}
+
+impl fmt::Display for Release {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ReleaseKind::Stable => f.write_str(&self.name),
+            ReleaseKind::PreRelease => write!(f, "{} (pre-release)", self.name),
+            ReleaseKind::EndOfLife => write!(f, "{} (end of life)", self.name),
+        }
+    }
+}
+
+fn words(name: &str) -> impl Iterator<Item = String> + '_ {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            // `rc2`, `beta1` and the like.
+            word.trim_end_matches(|c: char| c.is_ascii_digit())
+                .to_ascii_lowercase()
+        })
+}
+
+pub fn kind(name: &str) -> ReleaseKind {
+    let mut kind = ReleaseKind::Stable;
+    for word in words(name) {
+        if word == "eol" {
+            return ReleaseKind::EndOfLife;
+        }
+        if PRE_RELEASE_WORDS.contains(&word.as_str()) {
+            kind = ReleaseKind::PreRelease;
+        }
+    }
+    kind
+}
+
+fn numbers(name: &str) -> Vec<u64> {
+    name.split(|c: char| !c.is_ascii_digit())
+        .filter_map(|number| number.parse().ok())
+        .collect()
+}
+
+/// Newest first: by version numbers, then stable before testing builds of the same version.
+///
+/// Releases without a version number, such as codenames, come after numbered ones.
+pub fn compare(a: &Release, b: &Release) -> Ordering {
+    let (a_numbers, b_numbers) = (numbers(&a.name), numbers(&b.name));
+    match (a_numbers.is_empty(), b_numbers.is_empty()) {
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        _ => {}
+    }
+    b_numbers
+        .cmp(&a_numbers)
+        .then(a.kind.cmp(&b.kind))
+        .then_with(|| a.name.cmp(&b.name))
+}
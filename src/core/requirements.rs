@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::core::units::format_size;
+
+const GIB: u64 = 1024 * 1024 * 1024;
+const MIB: u64 = 1024 * 1024;
+
+/// quickget OSes built to run on old or small machines.
+const LIGHTWEIGHT: [&str; 10] = [
+    "alpine",
+    "antix",
+    "bodhi",
+    "crunchbang++",
+    "easyos",
+    "porteus",
+    "puppy",
+    "slax",
+    "slitaz",
+    "tinycore",
+];
+
+/// quickget OSes with a full desktop that is sluggish in less than a few GiB.
+const DESKTOPS: [&str; 17] = [
+    "deepin",
+    "elementary",
+    "fedora",
+    "garuda",
+    "kali",
+    "kubuntu",
+    "opensuse",
+    "popos",
+    "ubuntu",
+    "ubuntu-budgie",
+    "ubuntu-unity",
+    "ubuntucinnamon",
+    "ubuntukylin",
+    "ubuntustudio",
+    "vanillaos",
+    "xubuntu",
+    "zorin",
+];
+
+/// How much RAM and how many CPU cores an OS needs to install, and to run comfortably.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Requirements {
+    /// Below this the installer refuses to run or the guest doesn't boot.
+    pub min_ram: u64,
+    pub ram: u64,
+    pub min_cpu_cores: usize,
+    pub cpu_cores: usize,
+}
+
+impl Requirements {
+    /// Requirements of a quickget OS and release, e.g. `windows` and `11`.
+    pub fn for_os(os: &str, release: Option<&str>) -> Self {
+        let (min_ram, ram, min_cpu_cores, cpu_cores) = match os {
+            // Windows 11 checks for 4 GiB and 2 cores before it installs.
+            "windows" if release.map_or(true, |release| release.starts_with("11")) => {
+                (4 * GIB, 8 * GIB, 2, 4)
+            }
+            "windows" | "windows-server" => (2 * GIB, 4 * GIB, 1, 2),
+            "macos" => (4 * GIB, 8 * GIB, 2, 4),
+            _ if LIGHTWEIGHT.contains(&os) => (256 * MIB, GIB, 1, 1),
+            _ if DESKTOPS.contains(&os) => (2 * GIB, 4 * GIB, 1, 2),
+            _ => (GIB, 2 * GIB, 1, 2),
+        };
+        Self {
+            min_ram,
+            ram,
+            min_cpu_cores,
+            cpu_cores,
+        }
+    }
+    /// The OS's recommended RAM, lowered to what the host can spare by `host_ram` (quickget's
+    /// recommendation for this host) but never below the minimum.
+    pub fn default_ram(&self, host_ram: u64) -> u64 {
+        self.ram.min(host_ram.max(self.min_ram))
+    }
+    pub fn default_cpu_cores(&self, host_cores: usize) -> usize {
+        self.cpu_cores.min(host_cores.max(self.min_cpu_cores))
+    }
+    /// What `ram` and `cpu_cores` fall short of for the OS, if anything.
+    pub fn warning(&self, ram: u64, cpu_cores: usize) -> Option<String> {
+        let mut parts = Vec::new();
+        let below_minimum = ram < self.min_ram || cpu_cores < self.min_cpu_cores;
+        let (wanted_ram, wanted_cores) = if below_minimum {
+            (self.min_ram, self.min_cpu_cores)
+        } else {
+            (self.ram, self.cpu_cores)
+        };
+        if ram < wanted_ram {
+            parts.push(format!("{} of RAM", format_size(wanted_ram)));
+        }
+        if cpu_cores < wanted_cores {
+            parts.push(match wanted_cores {
+                1 => String::from("1 CPU core"),
+                cores => format!("{cores} CPU cores"),
+            });
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        let parts = parts.join(" and ");
+        Some(if below_minimum {
+            format!("Below the minimum of {parts} for this OS, it may not install or boot")
+        } else {
+            format!("Below the recommended {parts} for this OS, it may run slowly")
+        })
+    }
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::download;
+use crate::core::pipeline::Checkpoint;
+
+/// Creation wizard state saved while a VM is being set up, so it survives crashes and restarts.
+///
+/// The disk passphrase is deliberately not saved and has to be entered again when resuming.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SavedCreation {
+    pub os: String,
+    pub release: Option<String>,
+    pub edition: Option<String>,
+    pub arch: Option<String>,
+    pub cpu_cores: usize,
+    pub ram: f64,
+    pub directory: PathBuf,
+    pub encrypt: bool,
+    pub remember_passphrase: bool,
+    pub downloads: Vec<SavedDownload>,
+    /// The last creation stage that completed, if creation had started.
+    #[serde(default)]
+    pub checkpoint: Option<Checkpoint>,
+    /// Bytes of the downloads found on disk when the setup was loaded.
+    #[serde(skip)]
+    pub downloaded: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SavedDownload {
+    pub url: String,
+    pub path: PathBuf,
+}
+
+impl SavedDownload {
+    /// Bytes already written, to the finished file or the partial one.
+    fn on_disk(&self) -> u64 {
+        [self.path.clone(), download::partial_path(&self.path)]
+            .iter()
+            .find_map(|path| std::fs::metadata(path).ok())
+            .map_or(0, |metadata| metadata.len())
+    }
+}
+
+impl SavedCreation {
+    /// A short description such as `Ubuntu 24.04 desktop`.
+    pub fn label(&self) -> String {
+        [Some(&self.os), self.release.as_ref(), self.edition.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// `$XDG_STATE_HOME/qersui/creation`, holding one `<session>.json` per creation session.
+fn state_dir() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+    Some(state_home.join("qersui").join("creation"))
+}
+
+fn state_path(session: u32) -> Option<PathBuf> {
+    Some(state_dir()?.join(format!("{session}.json")))
+}
+
+/// Where releases before sessions kept the one saved setup, read as session 0's.
+fn legacy_path() -> Option<PathBuf> {
+    Some(state_dir()?.with_file_name("creation.json"))
+}
+
+/// The sessions with a saved setup, so they can be reopened.
+pub fn sessions() -> Vec<u32> {
+    let mut sessions = state_dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            name.to_str()?.strip_suffix(".json")?.parse().ok()
+        })
+        .collect::<Vec<u32>>();
+    sessions.sort_unstable();
+    sessions
+}
+
+pub fn load(session: u32) -> Option<SavedCreation> {
+    let path = state_path(session)?;
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(_) if session == 0 => std::fs::read(legacy_path()?).ok()?,
+        Err(_) => return None,
+    };
+    let mut saved: SavedCreation = serde_json::from_slice(&contents).ok()?;
+    // Measured rather than saved, so progress doesn't have to be written as it happens.
+    saved.downloaded = saved.downloads.iter().map(SavedDownload::on_disk).sum();
+    Some(saved)
+}
+
+pub fn save(session: u32, saved: &SavedCreation) {
+    let Some(path) = state_path(session) else {
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| {
+            let contents = serde_json::to_vec(saved).map_err(std::io::Error::other)?;
+            std::fs::write(&path, contents)
+        });
+    if let Err(e) = result {
+        tracing::warn!("Failed to save creation state: {e}");
+    }
+}
+
+pub fn clear(session: u32) {
+    if let Some(path) = state_path(session) {
+        let _ = std::fs::remove_file(path);
+    }
+    if session == 0 {
+        if let Some(path) = legacy_path() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use quickget_core::QuickgetInstance;
+
+use crate::core::catalog;
+use crate::core::download::{self, DownloadProgress};
+use crate::core::error::{AppError, ErrorCategory};
+use crate::core::metadata::SourceImage;
+use crate::core::pipeline;
+use crate::core::release_watch;
+use crate::core::vm::VM;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageStatus {
+    Verified,
+    /// The file differs from what quickget published, e.g. after a bad copy between disks.
+    Mismatch,
+    Missing,
+    /// No checksum was published, or only an MD5 or SHA-1 one.
+    Unchecked,
+    Failed(String),
+}
+
+impl ImageStatus {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Verified => String::from("Matches its published checksum"),
+            Self::Mismatch => String::from("Does not match its published checksum"),
+            Self::Missing => String::from("Missing"),
+            Self::Unchecked => String::from("No supported checksum was published"),
+            Self::Failed(e) => format!("Could not be checked: {e}"),
+        }
+    }
+    /// Whether downloading the image again would fix it.
+    pub fn repairable(&self) -> bool {
+        matches!(self, Self::Mismatch | Self::Missing)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageCheck {
+    pub source: SourceImage,
+    pub path: PathBuf,
+    pub status: ImageStatus,
+}
+
+/// Where a source image of `vm` is now: the installer attached under that name, or else the
+/// VM directory quickget downloaded it into.
+fn locate(vm: &VM, source: &SourceImage) -> PathBuf {
+    vm.installer_images()
+        .into_iter()
+        .find(|path| {
+            path.file_name()
+                .is_some_and(|name| *name == *source.file_name)
+        })
+        .unwrap_or_else(|| vm.vm_dir().join(&source.file_name))
+}
+
+async fn status(source: &SourceImage, path: &Path) -> ImageStatus {
+    if !tokio::fs::try_exists(path).await.unwrap_or(false) {
+        return ImageStatus::Missing;
+    }
+    let Some(checksum) = &source.checksum else {
+        return ImageStatus::Unchecked;
+    };
+    match pipeline::checksum_matches(path, checksum).await {
+        Ok(Some(true)) => ImageStatus::Verified,
+        Ok(Some(false)) => ImageStatus::Mismatch,
+        Ok(None) => ImageStatus::Unchecked,
+        Err(e) => ImageStatus::Failed(e.to_string()),
+    }
+}
+
+/// What quickget publishes for the OS, release and edition `vm` was created from, for VMs with
+/// no record of their downloads, e.g. imported ones or ones created by quickget itself.
+async fn published_sources(vm: &VM) -> Vec<SourceImage> {
+    let os_list = match catalog::load_cached().await {
+        Ok(os_list) => os_list,
+        Err(_) => match catalog::fetch().await {
+            Ok(os_list) => os_list,
+            Err(e) => {
+                tracing::warn!("Could not look up the images of {}: {e}", vm.name);
+                return Vec::new();
+            }
+        },
+    };
+    let Some(recipe) = release_watch::recipe(vm, &os_list) else {
+        return Vec::new();
+    };
+    let config = os_list
+        .iter()
+        .find(|os| os.name == recipe.os)
+        .and_then(|os| {
+            os.releases.iter().find(|config| {
+                config.release == recipe.release
+                    && config.edition == recipe.edition
+                    && recipe
+                        .arch
+                        .as_deref()
+                        .map_or(true, |arch| config.arch.to_string() == arch)
+            })
+        });
+    let Some(config) = config.cloned() else {
+        return Vec::new();
+    };
+    // Only the file names are used, so the directory the instance is set up for doesn't matter.
+    QuickgetInstance::new(config, std::env::temp_dir())
+        .map(|instance| instance.get_downloads())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|qg_download| {
+            Some(SourceImage {
+                file_name: qg_download.path.file_name()?.to_string_lossy().into_owned(),
+                url: qg_download.url,
+                checksum: qg_download.checksum,
+            })
+        })
+        .collect()
+}
+
+/// Check the images quickget downloaded for `vm` against the checksums recorded at creation,
+/// or, without a record, against the ones quickget publishes for its release.
+///
+/// Empty when neither is known.
+pub async fn check(vm: &VM) -> Vec<ImageCheck> {
+    let mut sources = vm.metadata().sources;
+    if sources.is_empty() {
+        sources = published_sources(vm).await;
+    }
+    let mut checks = Vec::new();
+    for source in sources {
+        let path = locate(vm, &source);
+        let status = status(&source, &path).await;
+        checks.push(ImageCheck {
+            source,
+            path,
+            status,
+        });
+    }
+    checks
+}
+
+/// Download a damaged or missing image again and check the new copy.
+///
+/// The new copy is fetched next to the old one and only replaces it once it matches, so a
+/// failed download leaves the VM with the image it had.
+pub async fn repair(
+    check: &ImageCheck,
+    progress: impl FnMut(DownloadProgress),
+) -> Result<ImageStatus, AppError> {
+    let disk_error = |context: String, e: std::io::Error| {
+        AppError::new(ErrorCategory::Disk, context).caused_by(e)
+    };
+    let staging = PathBuf::from(format!("{}.repair", check.path.display()));
+    // Left by an earlier repair that was interrupted after downloading.
+    match tokio::fs::remove_file(&staging).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(disk_error(
+                format!("Could not remove {}", staging.display()),
+                e,
+            ));
+        }
+        _ => {}
+    }
+    let _slot = download::queue_slot().await;
+    download::fetch(&check.source.url, &staging, progress).await?;
+    let status = status(&check.source, &staging).await;
+    if matches!(status, ImageStatus::Verified | ImageStatus::Unchecked) {
+        tokio::fs::rename(&staging, &check.path)
+            .await
+            .map_err(|e| disk_error(format!("Could not replace {}", check.path.display()), e))?;
+    } else {
+        let _ = tokio::fs::remove_file(&staging).await;
+    }
+    Ok(status)
+}
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::core::vm::VM;
+
+/// Create an internal qcow2 snapshot of the VM's system disk. QEMU must not be running.
+pub async fn create(vm: &VM, name: &str) -> Result<(), String> {
+    if vm.running_pid().is_some() {
+        return Err(format!("Shut down {} before taking a snapshot", vm.name));
+    }
+    if vm.is_encrypted() {
+        return Err(String::from(
+            "Snapshots of encrypted disks aren't supported yet",
+        ));
+    }
+    let disk = vm.system_disk();
+    if !disk.exists() {
+        return Err(format!("{} has no disk yet, boot it once first", vm.name));
+    }
+    let output = tokio::process::Command::new("qemu-img")
+        .args(["snapshot", "-c", name])
+        .arg(&disk)
+        .output()
+        .await
+        .map_err(|e| format!("Could not run qemu-img: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Could not create snapshot: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
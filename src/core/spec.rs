@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::recent::RecentCreation;
+use crate::core::units::parse_size;
+
+/// A VM definition to share as text, e.g. in chat or an issue, and create again from a paste.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VMSpec {
+    #[serde(flatten)]
+    pub recipe: RecentCreation,
+    /// In quickemu's notation, e.g. `4G`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ram: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_cores: Option<usize>,
+}
+
+impl VMSpec {
+    /// A spec for `ram` GiB of RAM, as the creation page's slider has it.
+    pub fn new(recipe: RecentCreation, ram: f64, cpu_cores: usize) -> Self {
+        let ram = if ram.fract() == 0.0 {
+            format!("{ram}G")
+        } else {
+            format!("{}M", (ram * 1024.0).round())
+        };
+        Self {
+            recipe,
+            ram: Some(ram),
+            cpu_cores: Some(cpu_cores),
+        }
+    }
+    pub fn to_text(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+    /// Read a pasted spec, ignoring whatever surrounds the JSON, such as code fences.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let json = match (text.find('{'), text.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &text[start..=end],
+            _ => return Err(String::from("This doesn't look like a VM spec")),
+        };
+        let spec: Self =
+            serde_json::from_str(json).map_err(|e| format!("This VM spec can't be read: {e}"))?;
+        if spec.recipe.os.trim().is_empty() {
+            return Err(String::from("This VM spec doesn't name an OS"));
+        }
+        if spec.ram.is_some() && spec.ram_bytes().is_none() {
+            return Err(format!(
+                "This VM spec has an unreadable RAM size: {}",
+                spec.ram.unwrap_or_default()
+            ));
+        }
+        Ok(spec)
+    }
+    pub fn ram_bytes(&self) -> Option<u64> {
+        self.ram.as_deref().and_then(parse_size)
+    }
+}
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::duplicate;
+use crate::core::error::{AppError, ErrorCategory};
+use crate::core::metadata::Metadata;
+use crate::core::vm::{INSTALLER_KEYS, VM};
+
+/// Configs of the VMs whose disks are backed by the disk of the template `template`.
+pub fn clones_of<'a>(
+    template: &'a Path,
+    metadata: &'a HashMap<PathBuf, Metadata>,
+) -> impl Iterator<Item = &'a PathBuf> + 'a {
+    metadata
+        .iter()
+        .filter(move |(_, metadata)| metadata.cloned_from.as_deref() == Some(template))
+        .map(|(config_path, _)| config_path)
+}
+
+/// Create a VM from the template `template` whose disk only stores what it changes, reading the
+/// rest from the template's disk. This takes seconds, as nothing is downloaded or installed.
+pub async fn clone(template: PathBuf) -> Result<PathBuf, AppError> {
+    let disk_error =
+        |context: &str, e: String| AppError::new(ErrorCategory::Disk, context).caused_by(e);
+    let vm = VM::load(template.clone())
+        .map_err(|e| disk_error("Could not read the template", e.to_string()))?;
+    if vm.is_encrypted() {
+        return Err(AppError::new(
+            ErrorCategory::Disk,
+            "Encrypted templates can't be cloned, a clone would need the template's passphrase",
+        ));
+    }
+    let backing = vm.system_disk();
+    let format = crate::core::disk::info(&backing)
+        .await
+        .map_err(|e| disk_error("The template has no disk to clone yet", e))?
+        .format;
+
+    let name = duplicate::numbered_name(vm.root(), &vm.name);
+    let mut clone = VM {
+        name: name.clone(),
+        config_path: vm.root().join(format!("{name}.conf")),
+        config: vm.config.clone(),
+    };
+    duplicate::move_paths(&mut clone.config, &vm.name, &name, false);
+    // The guest is already installed, so there is nothing to boot the installer for.
+    for key in INSTALLER_KEYS {
+        clone.config.remove(key);
+    }
+    let vm_dir = clone.vm_dir();
+    tokio::fs::create_dir_all(&vm_dir)
+        .await
+        .map_err(|e| disk_error("Could not create the VM directory", e.to_string()))?;
+
+    // The EFI variables hold the boot entries the installer added.
+    for vars in vm.firmware_vars() {
+        if let Some(file_name) = vars.file_name() {
+            tokio::fs::copy(&vars, vm_dir.join(file_name))
+                .await
+                .map_err(|e| disk_error("Could not copy the EFI variables", e.to_string()))?;
+        }
+    }
+    let disk = clone.system_disk();
+    let output = tokio::process::Command::new("qemu-img")
+        .args(["create", "-f", "qcow2", "-F", &format, "-b"])
+        // Absolute, so the clone keeps working if only it is moved.
+        .arg(&backing)
+        .arg(&disk)
+        .output()
+        .await
+        .map_err(|e| disk_error("Could not run qemu-img", e.to_string()))?;
+    if !output.status.success() {
+        return Err(disk_error(
+            "Could not create the clone's disk",
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    // Looks and grouping carry over; the history and template mark are the template's own.
+    let template_metadata = vm.metadata();
+    let metadata = Metadata {
+        appearance: template_metadata.appearance,
+        tags: template_metadata.tags,
+        recipe: template_metadata.recipe,
+        cloned_from: Some(template),
+        ..Metadata::new_vm()
+    };
+    clone
+        .save_metadata(&metadata)
+        .await
+        .map_err(|e| disk_error("Could not write VM metadata", e))?;
+    clone
+        .save()
+        .await
+        .map_err(|e| disk_error("Could not write VM config", e))?;
+    Ok(clone.config_path)
+}
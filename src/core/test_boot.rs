@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::time::{Duration, Instant};
+
+use crate::core::launcher;
+use crate::core::qmp::{self, RunState};
+use crate::core::vm::VM;
+
+/// How long a test boot waits for the guest to show something before giving up.
+pub const TIMEOUT: Duration = Duration::from_secs(60);
+
+const POLL: Duration = Duration::from_secs(2);
+
+/// Screendumps of an empty display compress to a few hundred bytes; anything drawn is far larger.
+const BLANK_SCREENDUMP_SIZE: u64 = 4 * 1024;
+
+/// Boot the VM headless and check that it gets as far as drawing a bootloader or installer.
+///
+/// The VM is stopped again afterwards either way, and marked as verified in its metadata when
+/// the boot succeeded.
+pub async fn run(vm: &VM, passphrase: Option<String>) -> Result<(), String> {
+    launcher::start_headless(vm, passphrase).await?;
+    let result = wait_for_display(vm).await;
+    if vm.running_pid().is_some() {
+        if let Err(e) = qmp::quit(vm).await {
+            tracing::warn!("Failed to stop {} after its test boot: {e}", vm.name);
+        }
+        launcher::wait_for_exit(vm).await;
+    }
+    let _ = tokio::fs::remove_file(screendump_path(vm)).await;
+    result?;
+    let mut metadata = vm.metadata();
+    metadata.verified = true;
+    vm.save_metadata(&metadata).await
+}
+
+async fn wait_for_display(vm: &VM) -> Result<(), String> {
+    let started = Instant::now();
+    let path = screendump_path(vm);
+    while started.elapsed() < TIMEOUT {
+        tokio::time::sleep(POLL).await;
+        if vm.running_pid().is_none() {
+            return Err(match log_tail(vm) {
+                Some(tail) => format!("QEMU exited during the test boot:\n{tail}"),
+                None => String::from("QEMU exited during the test boot"),
+            });
+        }
+        // The socket may not be up yet right after launch.
+        if qmp::status(vm).await != Ok(RunState::Running) {
+            continue;
+        }
+        if qmp::screendump(vm, &path).await.is_err() {
+            continue;
+        }
+        let size = tokio::fs::metadata(&path)
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        if size > BLANK_SCREENDUMP_SIZE {
+            return Ok(());
+        }
+    }
+    Err(format!(
+        "Nothing appeared on the display within {} seconds",
+        TIMEOUT.as_secs()
+    ))
+}
+
+fn screendump_path(vm: &VM) -> std::path::PathBuf {
+    vm.vm_dir().join("qersui-test-boot.png")
+}
+
+/// The last lines quickemu logged, which usually explain why QEMU gave up.
+fn log_tail(vm: &VM) -> Option<String> {
+    let log = std::fs::read_to_string(vm.vm_dir().join(format!("{}.log", vm.name))).ok()?;
+    let lines = log.lines().collect::<Vec<_>>();
+    let tail = lines[lines.len().saturating_sub(5)..].join("\n");
+    (!tail.trim().is_empty()).then_some(tail)
+}
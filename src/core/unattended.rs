@@ -0,0 +1,271 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::Path;
+
+use crate::core::vm::VM;
+
+/// Tools able to master a Joliet ISO, tried in order. xorriso needs its mkisofs emulation mode.
+const ISO_TOOLS: [(&str, &[&str]); 3] = [
+    ("genisoimage", &[]),
+    ("mkisofs", &[]),
+    ("xorriso", &["-as", "mkisofs"]),
+];
+
+/// Answers for a hands-free Windows installation.
+#[derive(Clone, Debug)]
+pub struct Unattended {
+    pub username: String,
+    pub password: String,
+    /// A Windows locale name such as `en-US`.
+    pub locale: String,
+}
+
+impl Unattended {
+    pub fn validate(&self) -> Result<(), String> {
+        let username = self.username.trim();
+        if username.is_empty() {
+            return Err(String::from("Enter a user name for the Windows account"));
+        }
+        // Characters Windows rejects in local account names.
+        if username.contains(|c| "\"/\\[]:;|=,+*?<>@".contains(c)) {
+            return Err(format!("{username} is not a valid Windows user name"));
+        }
+        if self.locale.trim().is_empty() {
+            return Err(String::from("Enter a locale, e.g. en-US"));
+        }
+        Ok(())
+    }
+    /// The `autounattend.xml` Windows Setup picks up from any attached drive.
+    pub fn xml(&self) -> String {
+        let username = escape(self.username.trim());
+        let password = escape(&self.password);
+        let locale = escape(self.locale.trim());
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<unattend xmlns="urn:schemas-microsoft-com:unattend" xmlns:wcm="http://schemas.microsoft.com/WMIConfig/2002/State">
+  <settings pass="windowsPE">
+    <component name="Microsoft-Windows-International-Core-WinPE" processorArchitecture="amd64" publicKeyToken="31bf3856ad364e35" language="neutral" versionScope="nonSxS">
+      <SetupUILanguage>
+        <UILanguage>{locale}</UILanguage>
+      </SetupUILanguage>
+      <InputLocale>{locale}</InputLocale>
+      <SystemLocale>{locale}</SystemLocale>
+      <UILanguage>{locale}</UILanguage>
+      <UserLocale>{locale}</UserLocale>
+    </component>
+    <component name="Microsoft-Windows-Setup" processorArchitecture="amd64" publicKeyToken="31bf3856ad364e35" language="neutral" versionScope="nonSxS">
+      <DiskConfiguration>
+        <Disk wcm:action="add">
+          <DiskID>0</DiskID>
+          <WillWipeDisk>true</WillWipeDisk>
+          <CreatePartitions>
+            <CreatePartition wcm:action="add">
+              <Order>1</Order>
+              <Type>EFI</Type>
+              <Size>260</Size>
+            </CreatePartition>
+            <CreatePartition wcm:action="add">
+              <Order>2</Order>
+              <Type>MSR</Type>
+              <Size>16</Size>
+            </CreatePartition>
+            <CreatePartition wcm:action="add">
+              <Order>3</Order>
+              <Type>Primary</Type>
+              <Extend>true</Extend>
+            </CreatePartition>
+          </CreatePartitions>
+          <ModifyPartitions>
+            <ModifyPartition wcm:action="add">
+              <Order>1</Order>
+              <PartitionID>1</PartitionID>
+              <Format>FAT32</Format>
+            </ModifyPartition>
+            <ModifyPartition wcm:action="add">
+              <Order>2</Order>
+              <PartitionID>3</PartitionID>
+              <Format>NTFS</Format>
+              <Label>Windows</Label>
+            </ModifyPartition>
+          </ModifyPartitions>
+        </Disk>
+      </DiskConfiguration>
+      <ImageInstall>
+        <OSImage>
+          <InstallTo>
+            <DiskID>0</DiskID>
+            <PartitionID>3</PartitionID>
+          </InstallTo>
+        </OSImage>
+      </ImageInstall>
+      <UserData>
+        <AcceptEula>true</AcceptEula>
+      </UserData>
+      <RunSynchronous>
+        <RunSynchronousCommand wcm:action="add">
+          <Order>1</Order>
+          <Path>reg add HKLM\SYSTEM\Setup\LabConfig /v BypassTPMCheck /t REG_DWORD /d 1 /f</Path>
+        </RunSynchronousCommand>
+        <RunSynchronousCommand wcm:action="add">
+          <Order>2</Order>
+          <Path>reg add HKLM\SYSTEM\Setup\LabConfig /v BypassSecureBootCheck /t REG_DWORD /d 1 /f</Path>
+        </RunSynchronousCommand>
+      </RunSynchronous>
+    </component>
+  </settings>
+  <settings pass="oobeSystem">
+    <component name="Microsoft-Windows-International-Core" processorArchitecture="amd64" publicKeyToken="31bf3856ad364e35" language="neutral" versionScope="nonSxS">
+      <InputLocale>{locale}</InputLocale>
+      <SystemLocale>{locale}</SystemLocale>
+      <UILanguage>{locale}</UILanguage>
+      <UserLocale>{locale}</UserLocale>
+    </component>
+    <component name="Microsoft-Windows-Shell-Setup" processorArchitecture="amd64" publicKeyToken="31bf3856ad364e35" language="neutral" versionScope="nonSxS">
+      <OOBE>
+        <HideEULAPage>true</HideEULAPage>
+        <HideOnlineAccountScreens>true</HideOnlineAccountScreens>
+        <HideWirelessSetupInOOBE>true</HideWirelessSetupInOOBE>
+        <ProtectYourPC>3</ProtectYourPC>
+      </OOBE>
+      <UserAccounts>
+        <LocalAccounts>
+          <LocalAccount wcm:action="add">
+            <Name>{username}</Name>
+            <Group>Administrators</Group>
+            <Password>
+              <Value>{password}</Value>
+              <PlainText>true</PlainText>
+            </Password>
+          </LocalAccount>
+        </LocalAccounts>
+      </UserAccounts>
+    </component>
+  </settings>
+</unattend>
+"#
+        )
+    }
+}
+
+/// The Windows locale matching one of quickget's Windows language names, where it's unambiguous.
+pub fn locale_for_language(language: &str) -> Option<&'static str> {
+    let locale = match language {
+        "English (United States)" => "en-US",
+        "English International" => "en-GB",
+        "Arabic" => "ar-SA",
+        "Chinese (Simplified)" => "zh-CN",
+        "Chinese (Traditional)" => "zh-TW",
+        "Czech" => "cs-CZ",
+        "Danish" => "da-DK",
+        "Dutch" => "nl-NL",
+        "Finnish" => "fi-FI",
+        "French" => "fr-FR",
+        "French Canadian" => "fr-CA",
+        "German" => "de-DE",
+        "Greek" => "el-GR",
+        "Hungarian" => "hu-HU",
+        "Italian" => "it-IT",
+        "Japanese" => "ja-JP",
+        "Korean" => "ko-KR",
+        "Norwegian" => "nb-NO",
+        "Polish" => "pl-PL",
+        "Brazilian Portuguese" => "pt-BR",
+        "Portuguese" => "pt-PT",
+        "Russian" => "ru-RU",
+        "Spanish" => "es-ES",
+        "Spanish (Mexico)" => "es-MX",
+        "Swedish" => "sv-SE",
+        "Turkish" => "tr-TR",
+        "Ukrainian" => "uk-UA",
+        _ => return None,
+    };
+    Some(locale)
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Master `unattended.iso` in the VM directory and attach it as quickemu's second drive.
+///
+/// quickget already uses that drive for the virtio driver ISO on Windows, in which case the answer
+/// file is added to a copy of that ISO so both stay available to Setup.
+pub async fn attach(vm: &mut VM, unattended: &Unattended) -> Result<(), String> {
+    let staging = vm.vm_dir().join("unattended");
+    tokio::fs::create_dir_all(&staging)
+        .await
+        .map_err(|e| format!("Could not create {}: {e}", staging.display()))?;
+    let answer_file = staging.join("autounattend.xml");
+    tokio::fs::write(&answer_file, unattended.xml())
+        .await
+        .map_err(|e| format!("Could not write autounattend.xml: {e}"))?;
+
+    let iso = vm.vm_dir().join("unattended.iso");
+    let drivers = vm
+        .config
+        .get("fixed_iso")
+        .map(|fixed_iso| vm.resolve(fixed_iso));
+    let result = match drivers {
+        Some(drivers) => {
+            let mut command = tokio::process::Command::new("xorriso");
+            command
+                .arg("-indev")
+                .arg(drivers)
+                .arg("-outdev")
+                .arg(&iso)
+                .arg("-map")
+                .arg(&answer_file)
+                .args(["/autounattend.xml", "-commit"]);
+            match run(command, "xorriso").await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(String::from(
+                    "Install xorriso to add the answer file to the driver ISO",
+                )),
+                Err(e) => Err(e),
+            }
+        }
+        None => master(&staging, &iso).await,
+    };
+    // The answer file holds the password in plain text, so only the ISO is kept.
+    let _ = tokio::fs::remove_dir_all(&staging).await;
+    result?;
+
+    vm.config
+        .set("fixed_iso", format!("{}/unattended.iso", vm.name));
+    vm.save().await
+}
+
+async fn master(staging: &Path, iso: &Path) -> Result<(), String> {
+    for (program, prefix) in ISO_TOOLS {
+        let mut command = tokio::process::Command::new(program);
+        command
+            .args(prefix)
+            .args(["-quiet", "-J", "-l", "-V", "UNATTENDED", "-o"])
+            .arg(iso)
+            .arg(staging);
+        if run(command, program).await? {
+            return Ok(());
+        }
+    }
+    Err(String::from(
+        "Install genisoimage or xorriso to create the answer file drive",
+    ))
+}
+
+/// Run an ISO tool, returning `Ok(false)` if it isn't installed.
+async fn run(mut command: tokio::process::Command, program: &str) -> Result<bool, String> {
+    match command.output().await {
+        Ok(output) if output.status.success() => Ok(true),
+        Ok(output) => Err(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(format!("Could not run {program}: {e}")),
+    }
+}
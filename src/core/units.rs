@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+/// Format a byte count using binary prefixes, e.g. `4.21 GiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.2} {}", UNITS[unit])
+    }
+}
+
+/// Format a rough duration, e.g. `about 1 h 20 min`.
+pub fn format_duration(seconds: u64) -> String {
+    let minutes = seconds.div_ceil(60);
+    match minutes {
+        0..=1 => String::from("under a minute"),
+        2..=59 => format!("about {minutes} min"),
+        _ => format!("about {} h {} min", minutes / 60, minutes % 60),
+    }
+}
+
+/// Format a Unix timestamp relative to `now`, e.g. `5 min ago`, or as a date once it is a week old.
+pub fn format_age(timestamp: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(timestamp);
+    match seconds {
+        0..=59 => String::from("just now"),
+        60..=3599 => format!("{} min ago", seconds / 60),
+        3600..=86_399 => format!("{} h ago", seconds / 3600),
+        86_400..=604_799 => match seconds / 86_400 {
+            1 => String::from("yesterday"),
+            days => format!("{days} days ago"),
+        },
+        _ => {
+            // Civil date from days since the epoch, after Howard Hinnant's `civil_from_days`.
+            let days = (timestamp / 86_400) as i64 + 719_468;
+            let era = days.div_euclid(146_097);
+            let day_of_era = days.rem_euclid(146_097);
+            let year_of_era =
+                (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+            let day_of_year =
+                day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+            let month_index = (5 * day_of_year + 2) / 153;
+            let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+            let month = if month_index < 10 {
+                month_index + 3
+            } else {
+                month_index - 9
+            };
+            let year = year_of_era + era * 400 + i64::from(month <= 2);
+            format!("{year}-{month:02}-{day:02}")
+        }
+    }
+}
+
+/// Parse a quickemu size such as `4G`, `512M` or `64G` into bytes.
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, multiplier) = match size.chars().last()?.to_ascii_uppercase() {
+        'K' => (&size[..size.len() - 1], 1u64 << 10),
+        'M' => (&size[..size.len() - 1], 1u64 << 20),
+        'G' => (&size[..size.len() - 1], 1u64 << 30),
+        'T' => (&size[..size.len() - 1], 1u64 << 40),
+        _ => (size, 1u64),
+    };
+    let number = number.trim().parse::<f64>().ok()?;
+    Some((number * multiplier as f64) as u64)
+}
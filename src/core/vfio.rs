@@ -0,0 +1,396 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::vm::VM;
+
+const PCI_DEVICES: &str = "/sys/bus/pci/devices";
+const IOMMU_GROUPS: &str = "/sys/kernel/iommu_groups";
+
+/// A PCI function on the host, read from sysfs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PciDevice {
+    /// E.g. `0000:01:00.0`.
+    pub address: String,
+    /// The class code, e.g. `0x030000` for a VGA controller.
+    pub class: u32,
+    pub vendor: u16,
+    pub device: u16,
+    /// The kernel driver bound to the device, e.g. `amdgpu` or `vfio-pci`.
+    pub driver: Option<String>,
+    /// From `lspci` when installed, else the vendor and device IDs.
+    pub name: String,
+}
+
+impl PciDevice {
+    /// `vendor:device`, as `vfio-pci.ids` takes them.
+    pub fn ids(&self) -> String {
+        format!("{:04x}:{:04x}", self.vendor, self.device)
+    }
+    pub fn is_gpu(&self) -> bool {
+        self.class >> 16 == 0x03
+    }
+    /// Bridges stay with the host; only the devices behind them are passed.
+    fn is_bridge(&self) -> bool {
+        self.class >> 16 == 0x06
+    }
+    pub fn bound_to_vfio(&self) -> bool {
+        self.driver.as_deref() == Some("vfio-pci")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Gpu {
+    pub device: PciDevice,
+    /// `None` without an IOMMU.
+    pub group: Option<u32>,
+    /// The other devices in its IOMMU group, which can only be passed along with it.
+    pub group_devices: Vec<PciDevice>,
+    /// The firmware showed its boot screen on this GPU, so it likely drives the host's display.
+    pub boot_vga: bool,
+}
+
+impl Gpu {
+    /// The GPU and every non-bridge device sharing its group, in address order.
+    pub fn passed_devices(&self) -> Vec<&PciDevice> {
+        let mut devices = std::iter::once(&self.device)
+            .chain(
+                self.group_devices
+                    .iter()
+                    .filter(|device| !device.is_bridge()),
+            )
+            .collect::<Vec<_>>();
+        devices.sort_by(|a, b| a.address.cmp(&b.address));
+        devices
+    }
+    /// Group members that aren't functions of the GPU's own card, such as a USB controller.
+    fn unrelated_devices(&self) -> Vec<&PciDevice> {
+        let slot = slot(&self.device.address);
+        self.group_devices
+            .iter()
+            .filter(|device| !device.is_bridge() && slot(&device.address) != slot)
+            .collect()
+    }
+}
+
+/// The address without its function number, shared by the functions of one card.
+fn slot(address: &str) -> &str {
+    address.rsplit_once('.').map_or(address, |(slot, _)| slot)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CpuVendor {
+    Intel,
+    Amd,
+}
+
+/// What the host offers for VFIO passthrough.
+#[derive(Clone, Debug, Default)]
+pub struct HostReport {
+    /// The kernel created IOMMU groups, so the IOMMU is on.
+    pub iommu: bool,
+    pub vfio_module: bool,
+    pub cpu_vendor: Option<CpuVendor>,
+    pub gpus: Vec<Gpu>,
+}
+
+/// One thing passthrough of a GPU needs, and how to provide it if it's missing.
+#[derive(Clone, Debug)]
+pub struct Requirement {
+    pub label: &'static str,
+    pub met: bool,
+    pub details: String,
+}
+
+impl Requirement {
+    fn new(label: &'static str, met: bool, details: impl Into<String>) -> Self {
+        Self {
+            label,
+            met,
+            details: details.into(),
+        }
+    }
+}
+
+pub async fn scan() -> HostReport {
+    tokio::task::spawn_blocking(scan_blocking)
+        .await
+        .unwrap_or_default()
+}
+
+fn scan_blocking() -> HostReport {
+    let names = lspci_names();
+    let devices = std::fs::read_dir(PCI_DEVICES)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| read_device(&entry.file_name().to_string_lossy(), &names))
+        .collect::<Vec<_>>();
+    let mut gpus = devices
+        .iter()
+        .filter(|device| device.is_gpu())
+        .map(|device| {
+            let dir = Path::new(PCI_DEVICES).join(&device.address);
+            let group = std::fs::read_link(dir.join("iommu_group"))
+                .ok()
+                .and_then(|link| link.file_name()?.to_str()?.parse().ok());
+            let group_devices = group
+                .map(group_members)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|address| *address != device.address)
+                .filter_map(|address| devices.iter().find(|d| d.address == address).cloned())
+                .collect();
+            Gpu {
+                device: device.clone(),
+                group,
+                group_devices,
+                boot_vga: read(&dir.join("boot_vga")).as_deref() == Some("1"),
+            }
+        })
+        .collect::<Vec<_>>();
+    gpus.sort_by(|a, b| a.device.address.cmp(&b.device.address));
+    HostReport {
+        iommu: std::fs::read_dir(IOMMU_GROUPS).is_ok_and(|mut groups| groups.next().is_some()),
+        vfio_module: Path::new("/sys/module/vfio_pci").exists(),
+        cpu_vendor: cpu_vendor(),
+        gpus,
+    }
+}
+
+fn read(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_string())
+}
+
+fn read_hex(path: &Path) -> Option<u32> {
+    u32::from_str_radix(read(path)?.trim_start_matches("0x"), 16).ok()
+}
+
+fn read_device(address: &str, names: &HashMap<String, String>) -> Option<PciDevice> {
+    let dir = Path::new(PCI_DEVICES).join(address);
+    let vendor = read_hex(&dir.join("vendor"))? as u16;
+    let device = read_hex(&dir.join("device"))? as u16;
+    let name = names.get(address).cloned().unwrap_or_else(|| {
+        let vendor_name = match vendor {
+            0x1002 => "AMD",
+            0x10de => "NVIDIA",
+            0x8086 => "Intel",
+            _ => "PCI device",
+        };
+        format!("{vendor_name} {vendor:04x}:{device:04x}")
+    });
+    Some(PciDevice {
+        address: address.to_string(),
+        class: read_hex(&dir.join("class"))?,
+        vendor,
+        device,
+        driver: std::fs::read_link(dir.join("driver"))
+            .ok()
+            .and_then(|link| Some(link.file_name()?.to_string_lossy().into_owned())),
+        name,
+    })
+}
+
+fn group_members(group: u32) -> Vec<String> {
+    let mut members = std::fs::read_dir(
+        Path::new(IOMMU_GROUPS)
+            .join(group.to_string())
+            .join("devices"),
+    )
+    .into_iter()
+    .flatten()
+    .flatten()
+    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+    .collect::<Vec<_>>();
+    members.sort();
+    members
+}
+
+/// Device names by address, e.g. "NVIDIA Corporation GA104 [GeForce RTX 3070]".
+fn lspci_names() -> HashMap<String, String> {
+    let Ok(output) = std::process::Command::new("lspci").arg("-D").output() else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (address, description) = line.split_once(' ')?;
+            let name = description
+                .split_once(": ")
+                .map_or(description, |(_, name)| name);
+            Some((address.to_string(), name.to_string()))
+        })
+        .collect()
+}
+
+fn cpu_vendor() -> Option<CpuVendor> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    let vendor = cpuinfo
+        .lines()
+        .find_map(|line| line.strip_prefix("vendor_id"))?;
+    if vendor.contains("GenuineIntel") {
+        Some(CpuVendor::Intel)
+    } else if vendor.contains("AuthenticAMD") {
+        Some(CpuVendor::Amd)
+    } else {
+        None
+    }
+}
+
+/// Whether this user may open the VFIO group device QEMU needs.
+fn group_accessible(group: u32) -> Result<(), String> {
+    let path = PathBuf::from("/dev/vfio").join(group.to_string());
+    match std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+    {
+        Ok(_) => Ok(()),
+        // EBUSY: a running VM holds the group, so it can be opened.
+        Err(e) if e.raw_os_error() == Some(16) => Ok(()),
+        Err(e) => Err(format!("{}: {e}", path.display())),
+    }
+}
+
+/// What passing `gpu` through still needs, in the order to take care of it.
+pub fn requirements(report: &HostReport, gpu: &Gpu) -> Vec<Requirement> {
+    let kernel_option = match report.cpu_vendor {
+        Some(CpuVendor::Amd) => "amd_iommu=on iommu=pt",
+        _ => "intel_iommu=on iommu=pt",
+    };
+    let mut requirements = vec![Requirement::new(
+        "IOMMU enabled",
+        report.iommu,
+        if report.iommu {
+            String::from("The kernel has set up IOMMU groups")
+        } else {
+            format!(
+                "Enable VT-d or AMD-Vi in the firmware settings and add `{kernel_option}` to the \
+                 kernel command line"
+            )
+        },
+    )];
+
+    let unrelated = gpu.unrelated_devices();
+    requirements.push(Requirement::new(
+        "GPU isolated in its IOMMU group",
+        gpu.group.is_some() && unrelated.is_empty(),
+        match (gpu.group, unrelated.as_slice()) {
+            (None, _) => String::from("No IOMMU group until the IOMMU is enabled"),
+            (Some(group), []) => format!("Group {group} holds only this card"),
+            (Some(group), others) => format!(
+                "Group {group} also holds {}, which would have to be passed too; try another \
+                 PCIe slot",
+                others
+                    .iter()
+                    .map(|device| device.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        },
+    ));
+
+    requirements.push(Requirement::new(
+        "Not the host's primary display",
+        !gpu.boot_vga,
+        if gpu.boot_vga {
+            "The firmware uses this GPU for the boot screen. Select another one as primary in the \
+             firmware settings, or keep this one for the host"
+        } else {
+            "Another GPU shows the boot screen"
+        },
+    ));
+
+    requirements.push(Requirement::new(
+        "vfio-pci available",
+        report.vfio_module,
+        if report.vfio_module {
+            "The vfio-pci module is loaded"
+        } else {
+            "Load it at boot by adding `vfio-pci` to /etc/modules-load.d/vfio.conf"
+        },
+    ));
+
+    let passed = gpu.passed_devices();
+    let unbound = passed
+        .iter()
+        .filter(|device| !device.bound_to_vfio())
+        .collect::<Vec<_>>();
+    let ids = passed
+        .iter()
+        .map(|device| device.ids())
+        .collect::<Vec<_>>()
+        .join(",");
+    requirements.push(Requirement::new(
+        "Bound to vfio-pci",
+        unbound.is_empty(),
+        match unbound.first().and_then(|device| device.driver.as_deref()) {
+            _ if unbound.is_empty() => String::from("The host no longer uses these devices"),
+            Some(driver) => format!(
+                "Add `options vfio-pci ids={ids}` and `softdep {driver} pre: vfio-pci` to \
+                 /etc/modprobe.d/vfio.conf, rebuild the initramfs and reboot"
+            ),
+            None => format!(
+                "Add `options vfio-pci ids={ids}` to /etc/modprobe.d/vfio.conf, rebuild the \
+                 initramfs and reboot"
+            ),
+        },
+    ));
+
+    if let Some(group) = gpu.group {
+        let access = group_accessible(group);
+        requirements.push(Requirement::new(
+            "VFIO group accessible",
+            access.is_ok(),
+            match access {
+                Ok(()) => format!("/dev/vfio/{group} can be opened"),
+                Err(e) => format!(
+                    "{e}. Give your user access with a udev rule, e.g. \
+                     `SUBSYSTEM==\"vfio\", OWNER=\"{}\"`",
+                    std::env::var("USER").unwrap_or_else(|_| String::from("you"))
+                ),
+            },
+        ));
+    }
+    requirements
+}
+
+/// QEMU arguments passing `gpu` and the rest of its group to the guest.
+pub fn qemu_args(gpu: &Gpu) -> String {
+    gpu.passed_devices()
+        .iter()
+        .map(|device| format!("-device vfio-pci,host={}", device.address))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The quickemu config lines for passing `gpu` through.
+pub fn config_snippet(gpu: &Gpu) -> String {
+    format!("# {}\nextra_args=\"{}\"\n", gpu.device.name, qemu_args(gpu))
+}
+
+/// Add `gpu` to the extra QEMU arguments of `vm`'s config, keeping arguments already there.
+pub async fn apply(mut vm: VM, gpu: Gpu) -> Result<VM, String> {
+    let mut args = vm
+        .config
+        .get("extra_args")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    for device in gpu.passed_devices() {
+        let value = format!("vfio-pci,host={}", device.address);
+        let present = args
+            .windows(2)
+            .any(|pair| pair[0] == "-device" && pair[1] == value);
+        if !present {
+            args.extend([String::from("-device"), value]);
+        }
+    }
+    vm.config.set("extra_args", args.join(" "));
+    vm.save().await?;
+    Ok(vm)
+}
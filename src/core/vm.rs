@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+
+/// Config keys pointing at installer media.
+pub const INSTALLER_KEYS: [&str; 3] = ["iso", "fixed_iso", "img"];
+
+/// A quickemu VM discovered on disk.
+#[derive(Clone, Debug)]
+pub struct VM {
+    pub name: String,
+    pub config_path: PathBuf,
+    pub config: VMConfig,
+}
+
+/// The key/value pairs of a quickemu configuration file, kept in file order.
+#[derive(Clone, Debug, Default)]
+pub struct VMConfig {
+    entries: Vec<(String, String)>,
+}
+
+impl VMConfig {
+    pub fn parse(contents: &str) -> Self {
+        let entries = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| {
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+                (key.trim().to_string(), value.to_string())
+            })
+            .collect();
+        Self { entries }
+    }
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((key.to_string(), value)),
+        }
+    }
+    pub fn remove(&mut self, key: &str) {
+        self.entries.retain(|(k, _)| k != key);
+    }
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+    pub fn serialize(&self) -> String {
+        let mut output = String::from("#!/usr/bin/quickemu --vm\n");
+        for (key, value) in &self.entries {
+            output.push_str(&format!("{key}=\"{value}\"\n"));
+        }
+        output
+    }
+}
+
+impl VM {
+    pub fn load(config_path: PathBuf) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(&config_path)?;
+        let name = config_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Self {
+            name,
+            config_path,
+            config: VMConfig::parse(&contents),
+        })
+    }
+    /// Directory containing the config file. Relative paths in the config are resolved from here.
+    pub fn root(&self) -> &Path {
+        self.config_path.parent().unwrap_or(Path::new("."))
+    }
+    /// quickemu stores a VM's disks and firmware in a directory named after the config.
+    pub fn vm_dir(&self) -> PathBuf {
+        self.resolve(&self.name)
+    }
+    pub fn resolve(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root().join(path)
+        }
+    }
+    pub fn disk_images(&self) -> Vec<PathBuf> {
+        self.config
+            .get("disk_img")
+            .map(|disk| self.resolve(disk))
+            .into_iter()
+            .collect()
+    }
+    /// The disk quickemu boots from, which defaults to `disk.qcow2` in the VM directory.
+    pub fn system_disk(&self) -> PathBuf {
+        match self.config.get("disk_img") {
+            Some(disk) => self.resolve(disk),
+            None => self.vm_dir().join("disk.qcow2"),
+        }
+    }
+    pub fn installer_images(&self) -> Vec<PathBuf> {
+        INSTALLER_KEYS
+            .into_iter()
+            .filter_map(|key| self.config.get(key))
+            .map(|image| self.resolve(image))
+            .collect()
+    }
+    pub fn firmware_vars(&self) -> Vec<PathBuf> {
+        std::fs::read_dir(self.vm_dir())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().starts_with("OVMF_VARS"))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+    pub fn ram(&self) -> Option<&str> {
+        self.config.get("ram")
+    }
+    pub fn cpu_cores(&self) -> Option<&str> {
+        self.config.get("cpu_cores")
+    }
+}
+
+/// Where new VMs go by default: the working directory, or the home directory when that is gone,
+/// e.g. after being launched from a deleted directory.
+pub fn default_root() -> PathBuf {
+    std::env::current_dir()
+        .ok()
+        .or_else(|| std::env::var_os("HOME").map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("/"))
+}
+
+/// Find all quickemu configs directly inside the given directories, plus individually registered configs.
+pub async fn discover(roots: Vec<PathBuf>, registered: Vec<PathBuf>) -> Vec<VM> {
+    tokio::task::spawn_blocking(move || {
+        let mut vms = roots
+            .iter()
+            .flat_map(std::fs::read_dir)
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "conf"))
+            .chain(registered)
+            .unique()
+            .filter_map(|path| VM::load(path).ok())
+            .filter(|vm| vm.config.get("guest_os").is_some())
+            .collect::<Vec<VM>>();
+        vms.sort_by(|a, b| a.name.cmp(&b.name));
+        vms
+    })
+    .await
+    .unwrap_or_default()
+}
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{subscription, Subscription};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+/// quickget writes a config in several steps; they are reported as one change once this long
+/// has passed without another.
+const SETTLE: Duration = Duration::from_millis(500);
+
+/// Whether `event` may have added, removed, renamed or edited a VM config.
+fn touches_config(event: &Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event.paths.iter().any(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "conf")
+        })
+}
+
+/// Report changes to VM configs made outside QERSUI, e.g. by quickget or quickemu in a terminal,
+/// in the directories holding them.
+pub fn subscription<M: Clone + Send + 'static>(
+    directories: Vec<PathBuf>,
+    changed: M,
+) -> Subscription<M> {
+    struct ConfigWatcher;
+    // A new set of directories starts a new watcher.
+    let id = (std::any::TypeId::of::<ConfigWatcher>(), directories.clone());
+    subscription::channel(id, 1, move |mut output| async move {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) if touches_config(&event) => {
+                    let _ = sender.send(());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Watching VM directories failed: {e}"),
+            });
+        let mut watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                tracing::warn!("Could not watch VM directories, external changes won't show: {e}");
+                return std::future::pending().await;
+            }
+        };
+        for directory in directories
+            .iter()
+            .map(PathBuf::as_path)
+            .filter(|d| d.is_dir())
+        {
+            watch(&mut watcher, directory);
+        }
+        while receiver.recv().await.is_some() {
+            while tokio::time::timeout(SETTLE, receiver.recv())
+                .await
+                .is_ok_and(|event| event.is_some())
+            {}
+            let _ = output.send(changed.clone()).await;
+        }
+        std::future::pending().await
+    })
+}
+
+fn watch(watcher: &mut impl Watcher, directory: &Path) {
+    // Configs sit directly in the directory; what happens inside VM directories doesn't matter.
+    if let Err(e) = watcher.watch(directory, RecursiveMode::NonRecursive) {
+        tracing::warn!("Could not watch {}: {e}", directory.display());
+    }
+}
@@ -1,38 +1,280 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use ashpd::desktop::file_chooser::{FileFilter, SelectedFiles};
 use cosmic::app::{Command, Core};
 use cosmic::iced::alignment::{Horizontal, Vertical};
-use cosmic::iced::{Alignment, Length, Padding, Pixels};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::keyboard::{key, Key};
+use cosmic::iced::{subscription, Alignment, Length, Padding, Pixels, Subscription};
 use cosmic::iced_widget::combo_box::State;
+use cosmic::iced_widget::scrollable::{self, AbsoluteOffset};
 use cosmic::widget::icon::Named;
 use cosmic::widget::{self, icon, list_column, menu, nav_bar};
 use cosmic::{cosmic_theme, theme, Application, ApplicationExt, Apply, Element};
 use itertools::Itertools;
+use once_cell::sync::Lazy;
 use quickemu::config::Arch;
 use quickget_core::data_structures::Config;
 use quickget_core::QuickgetInstance;
-use quickget_core::{data_structures::OS, ConfigSearch, ConfigSearchError, QGDownload};
+use quickget_core::{data_structures::OS, ConfigSearchError, QGDownload};
+
+use crate::confirm::Confirmation;
+use crate::core::bus::{self, BusEvent};
+use crate::core::catalog::{self, CatalogLoad};
+use crate::core::collision::{self, Collision, Rename};
+use crate::core::compat::{self, QuickemuVersion};
+use crate::core::config_keys::{self, ExtraKey, CREATION_MANAGED};
+use crate::core::devices::{self, DeviceOptions};
+use crate::core::download::{self, DownloadEstimate, DownloadProgress};
+use crate::core::duplicate;
+use crate::core::encryption;
+use crate::core::error::{AppError, ErrorCategory};
+use crate::core::firmware::{self, FirmwareKind};
+use crate::core::host_probe::{self, HostCapabilities};
+use crate::core::localization::Edition;
+use crate::core::macos::{self, MacInstaller, MacOSOptions};
+use crate::core::metadata::{Metadata, SourceImage};
+use crate::core::overcommit::Commitments;
+use crate::core::pipeline::{self, Checkpoint, Stage};
+use crate::core::portal;
+use crate::core::recent::RecentCreation;
+use crate::core::releases::{self, Release};
+use crate::core::requirements::Requirements;
+use crate::core::resume::{self, SavedCreation, SavedDownload};
+use crate::core::spec::VMSpec;
+use crate::core::test_boot;
+use crate::core::unattended::{self, Unattended};
+use crate::core::units::{format_age, format_duration, format_size};
+use crate::core::vm::{self, VMConfig, VM};
+use crate::widgets::config_view::config_view;
+use crate::widgets::directory_picker::{DirectoryPicker, DirectoryPickerActions};
+use crate::widgets::error_view::{error_view, ErrorActions};
+use crate::widgets::extra_keys::{extra_keys_view, ExtraKeyActions};
+use crate::widgets::gated::gated;
+use crate::widgets::os_icon::os_icon;
+use crate::widgets::overcommit::overcommit_view;
+
+static SEARCH_ID: Lazy<widget::Id> = Lazy::new(|| widget::Id::new("os-search"));
+static OS_LIST_ID: Lazy<widget::Id> = Lazy::new(|| widget::Id::new("os-list"));
+
+/// Numbers scratch directories, so renders for several sessions at once don't share one.
+static NEXT_SCRATCH: AtomicU64 = AtomicU64::new(0);
+
+/// How many of an OS's releases the preview pane looks up download sizes for.
+const PREVIEW_RELEASES: usize = 3;
+
+/// How often the loading page redraws its spinner and elapsed time.
+const CATALOG_TICK: Duration = Duration::from_millis(250);
+const SPINNER: [&str; 4] = ["◐", "◓", "◑", "◒"];
 
 #[derive(Default, Clone, Debug)]
 pub struct Creation {
-    os_list: Vec<OS>,
+    /// The app's id for this wizard, keying its saved setup.
+    session: u32,
+    /// The quickget catalog, shared so views and messages can refer to entries by index.
+    os_list: Arc<[OS]>,
+    /// The catalog being loaded while on the loading page.
+    catalog: Option<CatalogLoad>,
     page: Page,
     options: Option<OptionSelection>,
+    search: String,
+    highlighted: usize,
+    download_progress: Vec<DownloadProgress>,
+    /// A download waiting for a slot in the queue shared with the other sessions.
+    queued_download: Option<usize>,
+    error_details: bool,
+    /// Setup interrupted in an earlier run, offered for resuming once the OS list is loaded.
+    saved: Option<SavedCreation>,
+    /// Startup probe results; `None` until the probe finishes.
+    host: Option<Arc<HostCapabilities>>,
+    /// The most recently created VM, offered as a recipe for quick copies.
+    last_created: Option<VM>,
+    show_preview: bool,
+    /// Download sizes of an OS's newest releases by OS index; `None` while being looked up.
+    preview_sizes: HashMap<usize, Option<Vec<ReleaseSize>>>,
+    show_testing: bool,
+    /// Download size and time for the current options; `None` while being looked up.
+    estimate: Option<DownloadEstimate>,
+    /// Bumped whenever the downloads change, so estimates for earlier options are dropped.
+    estimate_generation: u64,
+    /// Progress restored from an interrupted setup, applied to the next job if the options are unchanged.
+    resume_checkpoint: Option<Checkpoint>,
+    /// OS and release asked for on the command line, opened once the OS list is loaded.
+    requested: Option<(String, Option<String>)>,
+    /// Combinations VMs were created with before, newest first.
+    recent: Vec<RecentCreation>,
+    /// quickget names of starred OSes.
+    favorites: Vec<String>,
+    /// What the library's running and autostart VMs already take from the host.
+    commitments: Commitments,
+    /// A VM spec pasted on the OS list, to create from.
+    spec: String,
+    spec_error: Option<String>,
+    /// How far the OS list is scrolled, in pixels, kept for the next launch.
+    os_list_scroll: f32,
+    /// Scrolled to once the OS list is first shown.
+    restore_scroll: Option<f32>,
+    /// Downloaded firmware the last job's VM boots with, shown on the complete page.
+    mapped_firmware: Option<FirmwareKind>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReleaseSize {
+    label: String,
+    size: Option<u64>,
 }
 
 #[derive(Clone, Debug)]
 pub enum Message {
     None,
-    OSList(Result<Vec<OS>, String>),
-    SelectedOS(OS),
+    OSList(Result<Vec<OS>, AppError>),
+    CatalogStage(catalog::Stage),
+    /// Redraws the loading page.
+    CatalogTick,
+    CancelCatalog,
+    RetryCatalog,
+    UseCachedCatalog,
+    /// Index into the OS list.
+    SelectedOS(usize),
+    /// Index into the recent combinations.
+    SelectedRecent(usize),
+    Search(String),
+    OSListScrolled(f32),
+    /// Scroll back to where the OS list was left in the last run.
+    RestoreScroll,
+    SelectHighlighted,
+    Back,
     SelectedRelease(String),
     SelectedEdition(String),
     SelectedArch(Arch),
     SetRAM(f64),
     SetCPUCores(usize),
     SelectVMDir,
+    DirectoryPicked(Result<Option<PathBuf>, AppError>),
     SelectedDir(PathBuf),
+    /// Index into the built-in directory picker's listing.
+    PickerOpen(usize),
+    PickerUp,
+    PickerChoose,
+    PickerCancel,
+    SetAllowEmulation(bool),
+    SetEncrypt(bool),
+    SetPassphrase(String),
+    SetPassphraseConfirm(String),
+    SetRememberPassphrase(bool),
+    SetUnattended(bool),
+    SetUnattendedUser(String),
+    SetUnattendedPassword(String),
+    SetUnattendedLocale(String),
+    SetOpenCoreRef(String),
+    SetMacFullInstaller(bool),
+    SelectMacInstaller,
+    MacInstallerPicked(Result<Option<PathBuf>, AppError>),
+    SelectedMacInstaller(PathBuf),
+    SetMacResolution(usize),
+    /// Index into [`devices::SOUND_CARD_CHOICES`].
+    SetSoundCard(usize),
+    SetMicrophone(bool),
+    SetTablet(bool),
+    SetTestBoot(bool),
+    SetRenameVM(bool),
+    SetReuseDownloads(bool),
+    ToggleAdvanced,
+    SetExtraKey(usize, String),
+    SetExtraValue(usize, String),
+    RemoveExtra(usize),
+    AddExtra,
+    /// Check the options and show the config they produce.
+    Review,
+    Previewed(Result<ConfigPreview, AppError>),
+    SetRawConfig(bool),
+    SetConfigLine(usize, String),
+    RemoveConfigLine(usize),
+    AddConfigLine,
+    Create,
+    /// Create even though it replaces a config already in the directory.
+    Overwrite,
+    DownloadProgress(usize, DownloadProgress),
+    /// Whether a download is waiting for downloads of other sessions to finish.
+    DownloadQueued(usize, bool),
+    StageStarted(Stage),
+    StageCompleted(Checkpoint),
+    StageFailed(Stage, AppError),
+    /// The new VM was switched to downloaded UEFI firmware, as its distribution has none.
+    FirmwareMapped(FirmwareKind),
+    Created(Result<PathBuf, AppError>),
+    ToggleErrorDetails,
+    CopyErrorReport,
+    Retry,
+    ResumeSaved,
+    DiscardSaved,
+    CreateLikeLast,
+    SetSpec(String),
+    CreateFromSpec,
+    /// Copy the current options as a VM spec.
+    CopySpec,
+    PreviewSizes(usize, Vec<ReleaseSize>),
+    Estimated(u64, DownloadEstimate),
+    /// Look again for a VM already where the current options would create one.
+    CheckCollision,
+}
+
+impl Message {
+    /// Messages that change what would be saved for resuming an interrupted setup.
+    fn changes_saved_state(&self) -> bool {
+        matches!(
+            self,
+            Self::SelectedOS(_)
+                | Self::SelectedRecent(_)
+                | Self::CreateFromSpec
+                | Self::SelectedRelease(_)
+                | Self::SelectedEdition(_)
+                | Self::SelectedArch(_)
+                | Self::SetRAM(_)
+                | Self::SetCPUCores(_)
+                | Self::SelectedDir(_)
+                | Self::SetEncrypt(_)
+                | Self::SetRememberPassphrase(_)
+                | Self::Create
+                | Self::Overwrite
+                | Self::StageCompleted(_)
+        )
+    }
+    /// Messages that change which files the current options would download.
+    fn changes_downloads(&self) -> bool {
+        matches!(
+            self,
+            Self::SelectedOS(_)
+                | Self::SelectedRecent(_)
+                | Self::CreateFromSpec
+                | Self::SelectedRelease(_)
+                | Self::SelectedEdition(_)
+                | Self::SelectedArch(_)
+                | Self::SelectedDir(_)
+                | Self::SetMacFullInstaller(_)
+                | Self::SelectedMacInstaller(_)
+                | Self::ResumeSaved
+        )
+    }
+    /// Messages that make a restored checkpoint describe a different VM.
+    fn changes_recipe(&self) -> bool {
+        matches!(
+            self,
+            Self::SelectedRelease(_)
+                | Self::SelectedEdition(_)
+                | Self::SelectedArch(_)
+                | Self::SetRAM(_)
+                | Self::SetCPUCores(_)
+                | Self::SelectedDir(_)
+                | Self::SetEncrypt(_)
+                | Self::SetUnattended(_)
+                | Self::SetMacFullInstaller(_)
+                | Self::SelectedMacInstaller(_)
+        )
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -41,53 +283,177 @@ enum Page {
     Loading,
     SelectOS,
     Options,
-    Downloading(Vec<QGDownload>),
+    Review(ConfigReview),
+    Downloading(CreationJob),
     Docker,
-    Complete,
-    Error(String),
+    Complete(PathBuf),
+    Error(AppError, FailedStep),
+}
+
+/// The step that produced an error page, re-run by its Retry button.
+#[derive(Clone, Debug)]
+enum FailedStep {
+    LoadOSList,
+    /// Creation goes back to the job, which resumes after its last checkpoint.
+    Create(Box<CreationJob>),
+    /// Creation failed before it started, so Retry goes through the options again.
+    Options,
+    /// A file chooser didn't open; Retry opens it again from the options.
+    Pick,
+}
+
+/// Everything needed to download and write out a VM, independent of the options page widgets.
+#[derive(Clone, Debug)]
+struct CreationJob {
+    name: String,
+    /// The quickget name of the OS.
+    os: String,
+    config: Config,
+    directory: PathBuf,
+    cpu_cores: usize,
+    ram: u64,
+    downloads: Vec<QGDownload>,
+    encryption: Option<DiskEncryption>,
+    unattended: Option<Unattended>,
+    macos: Option<MacOSOptions>,
+    devices: DeviceOptions,
+    extra: Vec<ExtraKey>,
+    /// The config written is adjusted to what this quickemu reads.
+    quickemu_version: Option<QuickemuVersion>,
+    test_boot: bool,
+    checkpoint: Option<Checkpoint>,
+    /// The stage currently being worked on.
+    running: Option<Stage>,
+    /// Written instead of quickget's config, as edited on the review page.
+    config_override: Option<String>,
+    /// Set when quickget's name for the VM is taken in the directory.
+    rename: Option<Rename>,
+}
+
+/// A job about to start, shown with the config it will write.
+#[derive(Clone, Debug)]
+struct ConfigReview {
+    job: CreationJob,
+    /// `None` while it is being rendered.
+    preview: Option<Result<ConfigPreview, AppError>>,
+    /// Edit the config by hand rather than writing the generated one.
+    raw: bool,
+    lines: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigPreview {
+    file_name: String,
+    contents: String,
+}
+
+impl ConfigReview {
+    /// The hand-edited config, if it differs from the generated one.
+    fn config_override(&self) -> Option<String> {
+        let Some(Ok(preview)) = &self.preview else {
+            return None;
+        };
+        let mut contents = self.lines.join("\n");
+        contents.push('\n');
+        (self.raw && contents != preview.contents).then_some(contents)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct DiskEncryption {
+    passphrase: String,
+    remember: bool,
 }
 
 #[derive(Clone, Debug)]
 struct OptionSelection {
-    config_list: Vec<Config>,
-    release_list: State<String>,
+    /// Shared with the OS it came from; the widgets below only ever need borrowed views of it.
+    config_list: Arc<[Config]>,
+    release_list: State<Release>,
     release: Option<String>,
-    edition_list: Option<State<String>>,
+    /// Pre-release and end-of-life releases are left out of `release_list` unless this is set.
+    show_testing: bool,
+    /// How many releases `show_testing` is currently hiding.
+    hidden_releases: usize,
+    edition_list: Option<State<Edition>>,
     edition: Option<String>,
     arch_list: State<Arch>,
     arch: Option<Arch>,
     cpu_cores: usize,
     ram: f64,
     directory: PathBuf,
+    /// quickget's identifier, e.g. `ubuntu`, alongside the display name.
+    os_id: String,
+    os_name: String,
+    encrypt: bool,
+    passphrase: String,
+    passphrase_confirm: String,
+    remember_passphrase: bool,
+    /// Architectures with a `qemu-system-*` binary installed.
+    installed_arches: Vec<Arch>,
+    quickemu_version: Option<QuickemuVersion>,
+    allow_emulation: bool,
+    /// Windows editions are languages, and get the unattended install panel.
+    windows: bool,
+    unattended: Option<Unattended>,
+    /// Set for macOS entries, which need OpenCore and an installer choice.
+    macos: Option<MacOSOptions>,
+    /// Sound and pointer devices, defaulting to what suits the OS.
+    devices: DeviceOptions,
+    /// Config keys set by hand, written after the ones quickget generates.
+    extra: Vec<ExtraKey>,
+    show_advanced: bool,
+    /// Boot the VM headless once it is created to check that it starts.
+    test_boot: bool,
+    /// A VM already in the directory under the name quickget would give this one.
+    collision: Option<Collision>,
+    /// Create under [`Collision::free_name`] instead.
+    rename: bool,
+    /// Point a renamed VM at the installer images already downloaded for the other one.
+    reuse_downloads: bool,
+    /// Shown in place of the portal's file chooser after it failed to open.
+    directory_picker: Option<DirectoryPicker>,
+    error: Option<String>,
 }
 
 impl OptionSelection {
     fn refresh(&mut self) {
         let releases = self
             .config_list
-            .clone()
-            .into_iter()
+            .iter()
             .filter(|config| self.arch.as_ref().map_or(true, |arch| &config.arch == arch))
-            .filter(|config| self.edition.is_none() || &config.edition == &self.edition)
-            .filter_map(|config| config.release)
+            .filter(|config| self.edition.is_none() || config.edition == self.edition)
+            .filter_map(|config| config.release.clone())
             .unique()
-            .collect::<Vec<String>>();
+            .map(Release::new)
+            .sorted_by(releases::compare)
+            .collect::<Vec<Release>>();
 
         if let Some(ref release) = self.release {
-            if !releases.contains(release) {
+            if !releases.iter().any(|entry| &entry.name == release) {
                 self.release = None;
             }
         }
+        // Hiding every release would leave nothing to pick for OSes that only have testing builds.
+        let total = releases.len();
+        let releases = if self.show_testing || releases.iter().all(Release::is_testing) {
+            releases
+        } else {
+            releases
+                .into_iter()
+                .filter(|entry| !entry.is_testing() || self.release.as_ref() == Some(&entry.name))
+                .collect()
+        };
+        self.hidden_releases = total - releases.len();
         self.release_list = State::new(releases);
 
         let editions = self.release.as_ref().and({
             let editions = self
                 .config_list
-                .clone()
-                .into_iter()
+                .iter()
                 .filter(|config| self.arch.as_ref().map_or(true, |arch| &config.arch == arch))
                 .filter(|config| self.release == config.release)
-                .filter_map(|config| config.edition)
+                .filter_map(|config| config.edition.clone())
                 .unique()
                 .collect::<Vec<String>>();
             (!editions.is_empty()).then_some(editions)
@@ -101,19 +467,19 @@ impl OptionSelection {
                 self.edition = None;
             }
         }
-        self.edition_list = editions.map(State::new);
+        self.edition_list =
+            editions.map(|editions| State::new(editions.into_iter().map(Edition).collect()));
 
         let full_arch_list = self
             .config_list
-            .clone()
-            .into_iter()
+            .iter()
             .filter(|config| self.release.is_none() || config.release == self.release)
             .filter(|config| self.edition.is_none() || config.edition == self.edition)
-            .map(|config| config.arch)
-            .collect::<Vec<Arch>>();
+            .map(|config| &config.arch)
+            .collect::<Vec<&Arch>>();
         let arch_list = [Arch::x86_64, Arch::aarch64, Arch::riscv64]
             .into_iter()
-            .filter(|arch| full_arch_list.contains(arch))
+            .filter(|arch| full_arch_list.contains(&arch))
             .collect::<Vec<Arch>>();
         if let Some(ref arch) = self.arch {
             if !arch_list.contains(arch) {
@@ -125,8 +491,28 @@ impl OptionSelection {
     fn set_release(&mut self, release: String) {
         self.release = Some(release);
         self.refresh();
+        self.raise_to_minimum();
+    }
+    fn requirements(&self) -> Requirements {
+        Requirements::for_os(&self.os_id, self.release.as_deref())
+    }
+    /// Keep the allocation at what the OS needs to install, as far as the host has it.
+    fn raise_to_minimum(&mut self) {
+        let requirements = self.requirements();
+        let min_ram = requirements.min_ram.min(QuickgetInstance::get_total_ram());
+        self.ram = self.ram.max(min_ram as f64 / (1024 * 1024 * 1024) as f64);
+        self.cpu_cores = self.cpu_cores.max(
+            requirements
+                .min_cpu_cores
+                .min(QuickgetInstance::get_total_cpu_cores()),
+        );
     }
     fn set_edition(&mut self, edition: String) {
+        if let (true, Some(unattended)) = (self.windows, &mut self.unattended) {
+            if let Some(locale) = unattended::locale_for_language(&edition) {
+                unattended.locale = locale.to_string();
+            }
+        }
         self.edition = Some(edition);
         self.refresh();
     }
@@ -134,65 +520,983 @@ impl OptionSelection {
         self.arch = Some(arch);
         self.refresh();
     }
+    /// Select a release, edition and architecture remembered by name.
+    fn select(&mut self, release: Option<String>, edition: Option<String>, arch: Option<&str>) {
+        self.arch = [Arch::x86_64, Arch::aarch64, Arch::riscv64]
+            .into_iter()
+            .find(|candidate| arch == Some(candidate.to_string().as_str()));
+        self.release = release;
+        self.edition = edition;
+        self.refresh();
+    }
+    fn restore(&mut self, saved: &SavedCreation) {
+        self.select(
+            saved.release.clone(),
+            saved.edition.clone(),
+            saved.arch.as_deref(),
+        );
+        self.cpu_cores = saved.cpu_cores;
+        self.ram = saved.ram;
+        self.directory = saved.directory.clone();
+        self.encrypt = saved.encrypt;
+        self.remember_passphrase = saved.remember_passphrase;
+        if saved.encrypt {
+            self.error = Some(String::from("Enter the disk passphrase again to continue"));
+        }
+    }
+    fn spec(&self) -> VMSpec {
+        VMSpec::new(self.recent(), self.ram, self.cpu_cores)
+    }
+    /// Take the resources from a pasted spec, within what the host has.
+    fn apply_spec(&mut self, spec: &VMSpec) {
+        if let Some(ram) = spec.ram_bytes() {
+            let ram = ram.min(QuickgetInstance::get_total_ram());
+            self.ram = ram as f64 / (1024 * 1024 * 1024) as f64;
+        }
+        if let Some(cpu_cores) = spec.cpu_cores {
+            self.cpu_cores = cpu_cores.clamp(1, QuickgetInstance::get_total_cpu_cores());
+        }
+    }
+    fn recent(&self) -> RecentCreation {
+        RecentCreation {
+            os: self.os_id.clone(),
+            release: self.release.clone(),
+            edition: self.edition.clone(),
+            arch: self.arch.as_ref().map(ToString::to_string),
+        }
+    }
+    fn selected_config(&self) -> Option<Config> {
+        self.config_list
+            .iter()
+            .find(|config| {
+                config.release == self.release
+                    && config.edition == self.edition
+                    && Some(&config.arch) == self.arch.as_ref()
+            })
+            .cloned()
+    }
+    /// What the current selection would download, if it is complete.
+    fn downloads(&self) -> Option<Vec<QGDownload>> {
+        let instance =
+            QuickgetInstance::new(self.selected_config()?, self.directory.clone()).ok()?;
+        let mut downloads = instance.get_downloads();
+        if let Some(macos) = &self.macos {
+            macos.apply_downloads(&mut downloads);
+        }
+        Some(downloads)
+    }
+    /// Look for a VM already where this one would be created.
+    fn check_collision(&mut self, downloads: &[QGDownload]) {
+        let collision = collision::check(&self.directory, downloads);
+        if collision != self.collision {
+            // Staying clear of the other VM is the safe default.
+            self.rename = collision.as_ref().is_some_and(Collision::clobbers);
+            self.collision = collision;
+        }
+    }
+    fn job(&self) -> Result<CreationJob, String> {
+        let config = self
+            .selected_config()
+            .ok_or_else(|| String::from("Select a release, edition and architecture"))?;
+        if !self.installed_arches.contains(&config.arch) {
+            return Err(format!(
+                "{} is not installed",
+                host_probe::qemu_system_binary(&config.arch)
+            ));
+        }
+        if host_probe::needs_emulation(&config.arch) && !self.allow_emulation {
+            return Err(String::from(
+                "Allow emulation to create a VM for a different architecture",
+            ));
+        }
+        let encryption = if self.encrypt {
+            if self.passphrase.is_empty() {
+                return Err(String::from("Enter a disk passphrase"));
+            }
+            if self.passphrase != self.passphrase_confirm {
+                return Err(String::from("The passphrases do not match"));
+            }
+            Some(DiskEncryption {
+                passphrase: self.passphrase.clone(),
+                remember: self.remember_passphrase,
+            })
+        } else {
+            None
+        };
+        if let Some(unattended) = &self.unattended {
+            unattended.validate()?;
+        }
+        if let Some(macos) = &self.macos {
+            macos.validate()?;
+        }
+        config_keys::validate(&self.extra, &CREATION_MANAGED)?;
+        let name = [
+            Some(&self.os_name),
+            self.release.as_ref(),
+            self.edition.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .join(" ");
+        let mut job = CreationJob {
+            name,
+            os: self.os_id.clone(),
+            config,
+            directory: self.directory.clone(),
+            cpu_cores: self.cpu_cores,
+            ram: (self.ram * (1024 * 1024 * 1024) as f64) as u64,
+            downloads: vec![],
+            encryption,
+            unattended: self.unattended.clone(),
+            macos: self.macos.clone(),
+            devices: self.devices.clone(),
+            extra: self.extra.clone(),
+            quickemu_version: self.quickemu_version,
+            test_boot: self.test_boot,
+            checkpoint: Some(Checkpoint {
+                stage: Stage::ResolveConfig,
+                config_path: None,
+            }),
+            running: None,
+            config_override: None,
+            rename: None,
+        };
+        job.downloads = job.instance().map_err(|e| e.to_string())?.get_downloads();
+        if let Some(macos) = &job.macos {
+            macos.apply_downloads(&mut job.downloads);
+        }
+        if let Some(collision) = self.collision.as_ref().filter(|_| self.rename) {
+            let rename = Rename::new(collision, self.reuse_downloads);
+            rename.apply_downloads(&mut job.downloads, &job.directory);
+            job.rename = Some(rename);
+        }
+        Ok(job)
+    }
+}
+
+impl CreationJob {
+    fn instance(&self) -> Result<QuickgetInstance, AppError> {
+        let mut instance = QuickgetInstance::new(self.config.clone(), self.directory.clone())
+            .map_err(|e| {
+                AppError::new(ErrorCategory::Quickget, "Could not prepare the VM").caused_by(e)
+            })?;
+        instance.set_cpu_cores(self.cpu_cores);
+        instance.set_ram(self.ram);
+        Ok(instance)
+    }
+    fn is_done(&self, stage: Stage) -> bool {
+        Checkpoint::is_done(self.checkpoint.as_ref(), stage)
+    }
+    /// Whether `stage` is part of this job; the test boot only runs when asked for.
+    fn includes(&self, stage: Stage) -> bool {
+        stage != Stage::TestBoot || self.test_boot
+    }
+    /// Run one stage of the pipeline, returning the config path once it is known.
+    #[tracing::instrument(skip(self, output), fields(os = %self.os), err)]
+    async fn run_stage(
+        &self,
+        stage: Stage,
+        output: &mut cosmic::iced::futures::channel::mpsc::Sender<crate::app::Message>,
+    ) -> Result<Option<PathBuf>, AppError> {
+        let config_path = self
+            .checkpoint
+            .as_ref()
+            .and_then(|checkpoint| checkpoint.config_path.clone());
+        let written = || {
+            config_path.clone().ok_or_else(|| {
+                AppError::new(
+                    ErrorCategory::Quickget,
+                    "The VM config has not been written",
+                )
+            })
+        };
+        match stage {
+            // Done by `OptionSelection::job` before the pipeline starts.
+            Stage::ResolveConfig => {}
+            Stage::Download => self.download(output).await?,
+            Stage::Verify => pipeline::verify(&self.downloads).await?,
+            Stage::Decompress => pipeline::decompress(&self.downloads).await?,
+            Stage::WriteConfig => return self.write_config().map(Some),
+            Stage::CreateDisk => self.create_disk(&written()?).await?,
+            Stage::Finalize => {
+                if let Some(kind) = self.finalize(&written()?).await? {
+                    let _ = output.send(Message::FirmwareMapped(kind).into()).await;
+                }
+            }
+            Stage::TestBoot => {
+                let vm = VM::load(written()?).map_err(|e| {
+                    AppError::new(ErrorCategory::Quickget, "Could not load the new VM").caused_by(e)
+                })?;
+                let passphrase = self
+                    .encryption
+                    .as_ref()
+                    .map(|encryption| encryption.passphrase.clone());
+                test_boot::run(&vm, passphrase).await.map_err(|e| {
+                    AppError::new(ErrorCategory::Quickget, "The VM failed its test boot")
+                        .caused_by(e)
+                })?;
+            }
+        }
+        Ok(config_path)
+    }
+    /// Downloads run one after another, each taking a slot in the queue shared by all creation
+    /// sessions; completed ones are skipped on later runs.
+    async fn download(
+        &self,
+        output: &mut cosmic::iced::futures::channel::mpsc::Sender<crate::app::Message>,
+    ) -> Result<(), AppError> {
+        for (index, qg_download) in self.downloads.iter().enumerate() {
+            let _slot = match download::try_queue_slot() {
+                Some(slot) => slot,
+                None => {
+                    let _ = output
+                        .send(Message::DownloadQueued(index, true).into())
+                        .await;
+                    let slot = download::queue_slot().await;
+                    let _ = output
+                        .send(Message::DownloadQueued(index, false).into())
+                        .await;
+                    slot
+                }
+            };
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let task = download::download(qg_download, move |progress| {
+                let _ = tx.send(progress);
+            });
+            tokio::pin!(task);
+            let result = loop {
+                tokio::select! {
+                    result = &mut task => break result,
+                    Some(progress) = rx.recv() => {
+                        let _ = output.send(Message::DownloadProgress(index, progress).into()).await;
+                    }
+                }
+            };
+            while let Ok(progress) = rx.try_recv() {
+                let _ = output
+                    .send(Message::DownloadProgress(index, progress).into())
+                    .await;
+            }
+            result?;
+        }
+        Ok(())
+    }
+    fn write_config(&self) -> Result<PathBuf, AppError> {
+        let write_error = |e: std::io::Error| {
+            AppError::new(ErrorCategory::Disk, "Could not write VM config").caused_by(e)
+        };
+        if let Some(rename) = &self.rename {
+            // quickget would write over the VM that has its name, so the config is rendered aside.
+            let (_, config) = self.render()?;
+            let config_path = self.directory.join(format!("{}.conf", rename.to));
+            let contents = self
+                .config_override
+                .clone()
+                .unwrap_or_else(|| config.serialize());
+            std::fs::create_dir_all(self.directory.join(&rename.to)).map_err(write_error)?;
+            std::fs::write(&config_path, contents).map_err(write_error)?;
+            return Ok(config_path);
+        }
+        let started = SystemTime::now();
+        self.instance()?.create_config().map_err(|e| {
+            AppError::new(ErrorCategory::Quickget, "Could not write VM config").caused_by(e)
+        })?;
+        let config_path = newest_config(&self.directory, started).ok_or_else(|| {
+            AppError::new(
+                ErrorCategory::Quickget,
+                "quickget did not write a VM config",
+            )
+        })?;
+        if let Some(contents) = &self.config_override {
+            std::fs::write(&config_path, contents).map_err(write_error)?;
+        }
+        Ok(config_path)
+    }
+    /// Let quickget write the config into a scratch directory, returning quickget's name for
+    /// the VM and the config with its paths as they will be in the real directory.
+    fn render(&self) -> Result<(String, VMConfig), AppError> {
+        let preview_error =
+            || AppError::new(ErrorCategory::Quickget, "Could not render the VM config");
+        let scratch = std::env::temp_dir().join(format!(
+            "qersui-preview-{}-{}",
+            std::process::id(),
+            NEXT_SCRATCH.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&scratch).map_err(|e| preview_error().caused_by(e))?;
+        let started = SystemTime::now();
+        let written = QuickgetInstance::new(self.config.clone(), scratch.clone())
+            .map_err(|e| preview_error().caused_by(e))
+            .and_then(|mut instance| {
+                instance.set_cpu_cores(self.cpu_cores);
+                instance.set_ram(self.ram);
+                instance
+                    .create_config()
+                    .map_err(|e| preview_error().caused_by(e))
+            })
+            .and_then(|_| {
+                let path = newest_config(&scratch, started).ok_or_else(|| {
+                    preview_error().caused_by("quickget did not write a VM config")
+                })?;
+                let contents =
+                    std::fs::read_to_string(&path).map_err(|e| preview_error().caused_by(e))?;
+                Ok((path, contents))
+            });
+        let _ = std::fs::remove_dir_all(&scratch);
+        let (path, contents) = written?;
+
+        // Any absolute paths quickget wrote point into the scratch directory.
+        let contents = contents.replace(
+            &*scratch.to_string_lossy(),
+            &self.directory.to_string_lossy(),
+        );
+        let name = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut config = VMConfig::parse(&contents);
+        if let Some(rename) = &self.rename {
+            rename.apply_config(&mut config);
+        }
+        Ok((name, config))
+    }
+    /// Render the config this job will write, with the device options applied as
+    /// [`Self::finalize`] does.
+    fn preview(&self) -> Result<ConfigPreview, AppError> {
+        let (name, config) = self.render()?;
+        let name = self
+            .rename
+            .as_ref()
+            .map_or(name, |rename| rename.to.clone());
+        let file_name = format!("{name}.conf");
+        let mut vm = VM {
+            name,
+            config_path: self.directory.join(&file_name),
+            config,
+        };
+        self.devices.apply(&mut vm);
+        compat::adjust(self.quickemu_version, &mut vm);
+        config_keys::apply(&self.extra, &mut vm);
+        Ok(ConfigPreview {
+            file_name,
+            contents: vm.config.serialize(),
+        })
+    }
+    /// Set up disk encryption, the only disk preparation quickemu does not do itself.
+    async fn create_disk(&self, config_path: &Path) -> Result<(), AppError> {
+        let Some(encryption) = &self.encryption else {
+            return Ok(());
+        };
+        let disk_error = |e: String| {
+            AppError::new(ErrorCategory::Disk, "Could not set up disk encryption").caused_by(e)
+        };
+        let mut vm = VM::load(config_path.to_path_buf()).map_err(|e| disk_error(e.to_string()))?;
+        encryption::encrypt_system_disk(&mut vm, &encryption.passphrase)
+            .await
+            .map_err(disk_error)?;
+        if encryption.remember {
+            // The VM is usable without a stored passphrase, it will just be asked for at launch.
+            if let Err(e) = encryption::store_passphrase(&vm, &encryption.passphrase).await {
+                tracing::warn!("Failed to store disk passphrase: {e}");
+            }
+        }
+        Ok(())
+    }
+    /// Apply OS-specific settings and write the VM's metadata, returning the downloaded
+    /// firmware the VM was mapped to, if any.
+    async fn finalize(&self, config_path: &Path) -> Result<Option<FirmwareKind>, AppError> {
+        if let Some(macos) = &self.macos {
+            let mut vm = VM::load(config_path.to_path_buf()).map_err(|e| {
+                AppError::new(ErrorCategory::Disk, "Could not configure macOS").caused_by(e)
+            })?;
+            macos.apply_config(&mut vm).await.map_err(|e| {
+                AppError::new(ErrorCategory::Disk, "Could not configure macOS").caused_by(e)
+            })?;
+        }
+        if let Some(unattended) = &self.unattended {
+            let unattended_error = |e: String| {
+                AppError::new(
+                    ErrorCategory::Disk,
+                    "Could not create the unattended install drive",
+                )
+                .caused_by(e)
+            };
+            let mut vm =
+                VM::load(config_path.to_path_buf()).map_err(|e| unattended_error(e.to_string()))?;
+            unattended::attach(&mut vm, unattended)
+                .await
+                .map_err(unattended_error)?;
+        }
+        // A config edited on the review page already has the keys the user wants.
+        if self.config_override.is_none() {
+            let device_error = |e: String| {
+                AppError::new(ErrorCategory::Disk, "Could not configure sound and input")
+                    .caused_by(e)
+            };
+            let mut vm =
+                VM::load(config_path.to_path_buf()).map_err(|e| device_error(e.to_string()))?;
+            self.devices.apply(&mut vm);
+            // Keys set by hand are written as typed, even ones this quickemu won't read.
+            compat::adjust(self.quickemu_version, &mut vm);
+            config_keys::apply(&self.extra, &mut vm);
+            vm.save().await.map_err(device_error)?;
+        }
+        // The checklist is a convenience, the VM is complete without it.
+        if let Ok(vm) = VM::load(config_path.to_path_buf()) {
+            let mut metadata = Metadata::new_vm();
+            metadata.sources = self
+                .downloads
+                .iter()
+                .filter_map(|qg_download| {
+                    Some(SourceImage {
+                        file_name: qg_download.path.file_name()?.to_string_lossy().into_owned(),
+                        url: qg_download.url.clone(),
+                        checksum: qg_download.checksum.clone(),
+                    })
+                })
+                .collect();
+            metadata.recipe = Some(RecentCreation {
+                os: self.os.clone(),
+                release: self.config.release.clone(),
+                edition: self.config.edition.clone(),
+                arch: Some(self.config.arch.to_string()),
+            });
+            if let Err(e) = vm.save_metadata(&metadata).await {
+                tracing::warn!("Failed to save VM metadata: {e}");
+            }
+        }
+        let firmware_error = |e: String| {
+            AppError::new(ErrorCategory::Disk, "Could not set up UEFI firmware").caused_by(e)
+        };
+        let mut vm =
+            VM::load(config_path.to_path_buf()).map_err(|e| firmware_error(e.to_string()))?;
+        firmware::map_if_needed(&mut vm)
+            .await
+            .map_err(firmware_error)
+    }
+}
+
+/// Name a combo box above it, since its placeholder is gone once something is picked.
+fn labelled<'a>(
+    label: &'a str,
+    control: impl Into<Element<'a, crate::app::Message>>,
+) -> Element<'a, crate::app::Message> {
+    widget::column()
+        .push(widget::text::caption(label))
+        .push(control)
+        .spacing(4)
+        .into()
+}
+
+/// quickget names configs after the OS, so find the one written since `since`.
+fn newest_config(directory: &Path, since: SystemTime) -> Option<PathBuf> {
+    std::fs::read_dir(directory)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "conf"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .filter(|(modified, _)| *modified >= since)
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
 }
 
 impl Creation {
-    pub fn new() -> Self {
+    pub fn new(session: u32) -> Self {
         Self {
-            os_list: vec![],
+            session,
+            os_list: Arc::new([]),
+            catalog: Some(CatalogLoad::start()),
             page: Page::Loading,
+            saved: resume::load(session),
             ..Default::default()
         }
     }
+    /// Save the wizard so an interrupted setup can be resumed on the next launch.
+    fn persist(&self) {
+        let Some(options) = &self.options else {
+            return;
+        };
+        let job = match &self.page {
+            Page::Downloading(job) => Some(job),
+            Page::Error(_, FailedStep::Create(job)) => Some(job.as_ref()),
+            _ => None,
+        };
+        let downloads = match job {
+            Some(job) => job
+                .downloads
+                .iter()
+                .map(|qg_download| SavedDownload {
+                    url: qg_download.url.clone(),
+                    path: qg_download.path.clone(),
+                })
+                .collect(),
+            None => vec![],
+        };
+        resume::save(
+            self.session,
+            &SavedCreation {
+                os: options.os_name.clone(),
+                release: options.release.clone(),
+                edition: options.edition.clone(),
+                arch: options.arch.as_ref().map(ToString::to_string),
+                cpu_cores: options.cpu_cores,
+                ram: options.ram,
+                directory: options.directory.clone(),
+                encrypt: options.encrypt,
+                remember_passphrase: options.remember_passphrase,
+                downloads,
+                checkpoint: job
+                    .and_then(|job| job.checkpoint.clone())
+                    .or_else(|| self.resume_checkpoint.clone()),
+                ..Default::default()
+            },
+        );
+    }
+    /// OS entries matching the search query, in catalog order.
+    fn filtered_os_list(&self) -> impl Iterator<Item = (usize, &OS)> {
+        let search = self.search.to_lowercase();
+        self.os_list
+            .iter()
+            .enumerate()
+            .filter(move |(_, os)| os.pretty_name.to_lowercase().contains(&search))
+    }
+    /// Return to the OS list, keeping the search query.
+    pub fn restart(&mut self) {
+        if matches!(
+            self.page,
+            Page::Options | Page::Review(_) | Page::Complete(_) | Page::Error(..)
+        ) && !self.os_list.is_empty()
+        {
+            self.page = Page::SelectOS;
+            self.options = None;
+        }
+    }
+    /// Where the VM being created is written, while its pipeline runs.
+    pub fn creating(&self) -> Option<PathBuf> {
+        match &self.page {
+            Page::Downloading(job) => Some(job.directory.join(&job.name)),
+            _ => None,
+        }
+    }
+    /// Stop a job another session is already running for the same VM, as both would write
+    /// the same files. Retry starts it again once the other session is done.
+    pub fn refuse_duplicate(&mut self) {
+        match std::mem::take(&mut self.page) {
+            Page::Downloading(job) => {
+                let error = AppError::new(
+                    ErrorCategory::Disk,
+                    format!("Another session is already creating {}", job.name),
+                );
+                self.show_error(error, FailedStep::Create(Box::new(job)));
+            }
+            page => self.page = page,
+        }
+    }
+    fn show_error(&mut self, error: AppError, step: FailedStep) {
+        self.error_details = false;
+        self.page = Page::Error(error, step);
+    }
+    pub fn set_show_preview(&mut self, show_preview: bool) {
+        self.show_preview = show_preview;
+    }
+    pub fn os_list_scroll(&self) -> f32 {
+        self.os_list_scroll
+    }
+    /// Scroll the OS list to `offset` once it is loaded, as it was left in the last run.
+    pub fn restore_os_list_scroll(&mut self, offset: f32) {
+        self.os_list_scroll = offset;
+        self.restore_scroll = (offset > 0.0).then_some(offset);
+    }
+    /// Open the options for `os`, matched by quickget or display name, with `release` selected.
+    ///
+    /// Unknown names end up in the search field so the closest matches are listed instead.
+    pub fn request(&mut self, os: String, release: Option<String>) -> Command<crate::app::Message> {
+        match self.page {
+            Page::Loading => {
+                self.requested = Some((os, release));
+                return Command::none();
+            }
+            Page::SelectOS | Page::Options | Page::Review(_) => {}
+            // Don't throw away a creation in progress or its result.
+            _ => return Command::none(),
+        }
+        let index = self.os_list.iter().position(|entry| {
+            entry.name.eq_ignore_ascii_case(&os) || entry.pretty_name.eq_ignore_ascii_case(&os)
+        });
+        let Some(index) = index else {
+            self.page = Page::SelectOS;
+            self.options = None;
+            return self.update(Message::Search(os));
+        };
+        let command = self.update(Message::SelectedOS(index));
+        if let (Some(release), Some(options)) = (release, &mut self.options) {
+            let known = options
+                .config_list
+                .iter()
+                .any(|config| config.release.as_deref() == Some(release.as_str()));
+            if known {
+                options.set_release(release);
+            } else {
+                options.error = Some(format!("{} has no release {release}", options.os_name));
+            }
+        }
+        command
+    }
+    pub fn set_show_testing(&mut self, show_testing: bool) {
+        self.show_testing = show_testing;
+        if let Some(options) = &mut self.options {
+            if options.show_testing != show_testing {
+                options.show_testing = show_testing;
+                options.refresh();
+            }
+        }
+    }
+    /// Look up download sizes for the preview of the highlighted OS, unless already known.
+    fn load_preview(&mut self) -> Command<crate::app::Message> {
+        if !self.show_preview {
+            return Command::none();
+        }
+        let Some((index, os)) = self.filtered_os_list().nth(self.highlighted) else {
+            return Command::none();
+        };
+        if self.preview_sizes.contains_key(&index) {
+            return Command::none();
+        }
+        let native_arch = host_probe::native_arch();
+        let configs = os
+            .releases
+            .iter()
+            .filter(|config| config.arch == native_arch)
+            .take(PREVIEW_RELEASES)
+            .cloned()
+            .collect::<Vec<_>>();
+        self.preview_sizes.insert(index, None);
+        Command::perform(
+            async move {
+                let mut sizes = Vec::new();
+                for config in configs {
+                    let label = [config.release.as_deref(), config.edition.as_deref()]
+                        .into_iter()
+                        .flatten()
+                        .join(" ");
+                    let downloads = QuickgetInstance::new(config, std::env::temp_dir())
+                        .map(|instance| instance.get_downloads())
+                        .unwrap_or_default();
+                    let mut size = Some(0);
+                    for qg_download in &downloads {
+                        let download_size = download::remote_size(&qg_download.url).await;
+                        size = size.zip(download_size).map(|(a, b)| a + b);
+                    }
+                    sizes.push(ReleaseSize {
+                        label,
+                        size: size.filter(|_| !downloads.is_empty()),
+                    });
+                }
+                sizes
+            },
+            move |sizes| crate::app::Message::Creation(Message::PreviewSizes(index, sizes)).into(),
+        )
+    }
+    /// Another wizard next to this one, sharing its OS list and settings.
+    pub fn new_session(&self, session: u32) -> Self {
+        Self {
+            session,
+            saved: resume::load(session),
+            page: if self.os_list.is_empty() {
+                Page::Loading
+            } else {
+                Page::SelectOS
+            },
+            os_list: self.os_list.clone(),
+            catalog: self.os_list.is_empty().then(CatalogLoad::start),
+            host: self.host.clone(),
+            last_created: self.last_created.clone(),
+            show_preview: self.show_preview,
+            show_testing: self.show_testing,
+            recent: self.recent.clone(),
+            favorites: self.favorites.clone(),
+            commitments: self.commitments.clone(),
+            ..Default::default()
+        }
+    }
+    /// Nothing has been entered yet, or the VM is created, so the session can be reused.
+    pub fn is_idle(&self) -> bool {
+        // A setup waiting to be resumed isn't taken over by a different one.
+        if self.saved.is_some() {
+            return false;
+        }
+        matches!(
+            self.page,
+            Page::Loading | Page::SelectOS | Page::Complete(_)
+        )
+    }
+    /// The step the wizard is on, added to the window title so screen readers announce
+    /// moving between pages.
+    pub fn step(&self) -> &'static str {
+        match self.page {
+            Page::Loading => "Loading the OS catalog",
+            Page::SelectOS => "Choose an operating system",
+            Page::Options => "Choose a release and resources",
+            Page::Review(_) => "Review the config",
+            Page::Downloading(_) => "Downloading",
+            Page::Docker => "Building the Docker image",
+            Page::Complete(_) => "VM created",
+            Page::Error(..) => "Something went wrong",
+        }
+    }
+    /// The nav bar label, naming the OS once one is selected so sessions can be told apart.
+    pub fn title(&self) -> String {
+        match (&self.page, &self.options) {
+            (Page::Downloading(job), _) => format!("Creating {}", job.name),
+            (Page::Loading | Page::SelectOS | Page::Complete(_), _) | (_, None) => {
+                String::from("Create new VM")
+            }
+            (_, Some(options)) => format!("New {}", options.os_name),
+        }
+    }
+    /// Open the options for a recipe, e.g. a VM's with a newer release.
+    pub fn request_recipe(&mut self, recipe: RecentCreation) -> Command<crate::app::Message> {
+        let command = self.request(recipe.os.clone(), recipe.release.clone());
+        if let Some(options) = self.options.as_mut().filter(|o| o.os_id == recipe.os) {
+            options.select(recipe.release, recipe.edition, recipe.arch.as_deref());
+        }
+        Command::batch([
+            command,
+            self.handle(Message::CheckCollision),
+            self.load_estimate(),
+        ])
+    }
+    pub fn set_recent(&mut self, recent: &[RecentCreation], favorites: &[String]) {
+        self.recent = recent.to_vec();
+        self.favorites = favorites.to_vec();
+    }
+    pub fn set_last_created(&mut self, config_path: Option<PathBuf>) {
+        self.last_created = config_path.and_then(|config_path| VM::load(config_path).ok());
+    }
+    pub fn set_commitments(&mut self, commitments: Commitments) {
+        self.commitments = commitments;
+    }
+    pub fn set_host(&mut self, host: Arc<HostCapabilities>) {
+        if let Some(options) = &mut self.options {
+            options.installed_arches = host.installed_arches();
+            options.quickemu_version = host.quickemu_version;
+        }
+        self.host = Some(host);
+    }
+    /// Whether downloads are in flight and would be lost by quitting.
+    pub fn is_busy(&self) -> bool {
+        matches!(self.page, Page::Downloading(_))
+    }
+    pub fn focus_search(&mut self) -> Command<crate::app::Message> {
+        self.restart();
+        widget::text_input::focus(SEARCH_ID.clone())
+    }
+    pub fn on_key(&mut self, key: &Key) -> Command<crate::app::Message> {
+        match (&self.page, key) {
+            (Page::SelectOS, Key::Named(key::Named::ArrowDown)) => {
+                let count = self.filtered_os_list().count();
+                self.highlighted = (self.highlighted + 1).min(count.saturating_sub(1));
+                return self.load_preview();
+            }
+            (Page::SelectOS, Key::Named(key::Named::ArrowUp)) => {
+                self.highlighted = self.highlighted.saturating_sub(1);
+                return self.load_preview();
+            }
+            (Page::SelectOS, Key::Named(key::Named::Enter)) => {
+                return self.update(Message::SelectHighlighted)
+            }
+            (Page::Options | Page::Review(_), Key::Named(key::Named::Escape)) => {
+                return self.update(Message::Back)
+            }
+            _ => {}
+        }
+        Command::none()
+    }
     pub fn update(&mut self, message: Message) -> Command<crate::app::Message> {
+        let persist = message.changes_saved_state();
+        if message.changes_recipe() {
+            self.resume_checkpoint = None;
+        }
+        let estimate = message.changes_downloads();
+        let command = self.handle(message);
+        if persist {
+            self.persist();
+        }
+        if estimate {
+            return Command::batch([
+                command,
+                self.handle(Message::CheckCollision),
+                self.load_estimate(),
+            ]);
+        }
+        command
+    }
+    /// Look up the size of the current selection's downloads and how long they would take.
+    fn load_estimate(&mut self) -> Command<crate::app::Message> {
+        self.estimate_generation += 1;
+        self.estimate = None;
+        let Some(downloads) = self.options.as_ref().and_then(OptionSelection::downloads) else {
+            return Command::none();
+        };
+        let generation = self.estimate_generation;
+        Command::perform(download::estimate(downloads), move |estimate| {
+            crate::app::Message::Creation(Message::Estimated(generation, estimate)).into()
+        })
+    }
+    fn handle(&mut self, message: Message) -> Command<crate::app::Message> {
         match message {
+            Message::Search(search) => {
+                self.search = search;
+                self.highlighted = 0;
+                return self.load_preview();
+            }
+            Message::OSListScrolled(offset) => self.os_list_scroll = offset,
+            Message::RestoreScroll => {
+                if let (Some(offset), Page::SelectOS) = (self.restore_scroll.take(), &self.page) {
+                    return scrollable::scroll_to(
+                        OS_LIST_ID.clone(),
+                        AbsoluteOffset { x: 0.0, y: offset },
+                    );
+                }
+            }
+            Message::SelectHighlighted => {
+                if let Some((index, _)) = self.filtered_os_list().nth(self.highlighted) {
+                    return self.update(Message::SelectedOS(index));
+                }
+            }
+            Message::Back => match self.page {
+                Page::Review(_) => self.page = Page::Options,
+                Page::Options => {
+                    self.page = Page::SelectOS;
+                    self.options = None;
+                    resume::clear(self.session);
+                }
+                // The options are still there behind a chooser that didn't open.
+                Page::Error(_, FailedStep::Pick) => self.page = Page::Options,
+                Page::Complete(_) | Page::Error(..) => self.restart(),
+                _ => {}
+            },
             Message::OSList(list) => match list {
                 Ok(os_list) => {
-                    self.os_list = os_list;
+                    self.catalog = None;
+                    // The saved catalog was picked just as the download finished.
+                    if !self.os_list.is_empty() {
+                        return Command::none();
+                    }
+                    self.os_list = os_list.into();
                     self.page = Page::SelectOS;
+                    if let Some((os, release)) = self.requested.take() {
+                        return Command::batch([self.load_preview(), self.request(os, release)]);
+                    }
+                    // The list only exists once this page has been drawn.
+                    let restore = match self.restore_scroll {
+                        Some(_) => Command::perform(async {}, |()| {
+                            crate::app::Message::Creation(Message::RestoreScroll).into()
+                        }),
+                        None => Command::none(),
+                    };
+                    return Command::batch([self.load_preview(), restore]);
                 }
                 Err(e) => {
-                    self.page = Page::Error(e);
+                    self.catalog = None;
+                    self.show_error(e, FailedStep::LoadOSList);
                 }
             },
-            Message::SelectedOS(os) => {
-                let release_list = State::new(
-                    os.releases
-                        .clone()
-                        .into_iter()
-                        .filter_map(|config| config.release)
-                        .unique()
-                        .collect(),
-                );
+            Message::CatalogStage(stage) => {
+                if let Some(load) = &mut self.catalog {
+                    load.stage = stage;
+                }
+            }
+            Message::CatalogTick => {}
+            Message::CancelCatalog => {
+                if let Some(load) = &mut self.catalog {
+                    load.cancelled = true;
+                }
+            }
+            Message::RetryCatalog => self.catalog = Some(CatalogLoad::start()),
+            Message::UseCachedCatalog => {
+                self.catalog = None;
+                return Command::perform(catalog::load_cached(), |list| {
+                    crate::app::Message::Creation(Message::OSList(list)).into()
+                });
+            }
+            Message::SelectedOS(index) => {
+                let Some(os) = self.os_list.get(index) else {
+                    return Command::none();
+                };
                 let arch_list = [Arch::x86_64, Arch::aarch64, Arch::riscv64]
                     .into_iter()
                     .filter(|arch| os.releases.iter().any(|config| &config.arch == arch))
                     .collect::<Vec<Arch>>();
 
-                let preferred_arch = match std::env::consts::ARCH {
-                    "aarch64" => Arch::aarch64,
-                    "riscv64" => Arch::riscv64,
-                    _ => Arch::x86_64,
+                let installed_arches = match &self.host {
+                    Some(host) => host.installed_arches(),
+                    None => [Arch::x86_64, Arch::aarch64, Arch::riscv64]
+                        .into_iter()
+                        .filter(host_probe::qemu_system_installed)
+                        .collect(),
+                };
+                // Prefer the native architecture among those QEMU can run, then any of those. With
+                // none installed, the native one is picked so the missing binary is the one named.
+                let native_arch = host_probe::native_arch();
+                let runnable = arch_list
+                    .iter()
+                    .filter(|arch| installed_arches.contains(arch))
+                    .cloned()
+                    .collect::<Vec<Arch>>();
+                let arch = if runnable.contains(&native_arch) {
+                    Some(native_arch)
+                } else {
+                    runnable
+                        .into_iter()
+                        .next()
+                        .or_else(|| arch_list.contains(&native_arch).then_some(native_arch))
                 };
-                let arch = (arch_list.contains(&preferred_arch)).then_some(preferred_arch);
                 let arch_list = State::new(arch_list);
 
-                let ram =
-                    QuickgetInstance::get_recommended_ram() as f64 / (1024 * 1024 * 1024) as f64;
-                let cpu_cores = QuickgetInstance::get_recommended_cpu_cores();
+                // Windows without a release yet counts as Windows 11, the more demanding one.
+                let requirements = Requirements::for_os(&os.name, None);
+                let ram = requirements.default_ram(QuickgetInstance::get_recommended_ram()) as f64
+                    / (1024 * 1024 * 1024) as f64;
+                let cpu_cores =
+                    requirements.default_cpu_cores(QuickgetInstance::get_recommended_cpu_cores());
 
-                self.options = Some(OptionSelection {
-                    config_list: os.releases,
+                let mut options = OptionSelection {
+                    config_list: os.releases.as_slice().into(),
                     release: None,
-                    release_list,
+                    release_list: State::new(vec![]),
+                    show_testing: self.show_testing,
+                    hidden_releases: 0,
                     edition: None,
                     edition_list: None,
                     arch,
                     arch_list,
                     ram,
                     cpu_cores,
-                    directory: std::env::current_dir().unwrap(),
-                });
+                    directory: vm::default_root(),
+                    os_id: os.name.clone(),
+                    os_name: os.pretty_name.clone(),
+                    encrypt: false,
+                    passphrase: String::new(),
+                    passphrase_confirm: String::new(),
+                    remember_passphrase: false,
+                    installed_arches,
+                    quickemu_version: self.host.as_ref().and_then(|host| host.quickemu_version),
+                    allow_emulation: false,
+                    windows: os.name.starts_with("windows"),
+                    unattended: None,
+                    macos: (os.name == "macos").then(MacOSOptions::default),
+                    devices: DeviceOptions::recommended(&os.name),
+                    extra: Vec::new(),
+                    show_advanced: false,
+                    test_boot: false,
+                    collision: None,
+                    rename: false,
+                    reuse_downloads: true,
+                    directory_picker: None,
+                    error: None,
+                };
+                options.refresh();
+                self.options = Some(options);
                 self.page = Page::Options;
             }
             Message::SelectedRelease(release) => {
@@ -222,42 +1526,473 @@ impl Creation {
             }
             Message::SelectVMDir => {
                 return Command::perform(
-                    async move {
-                        let result = SelectedFiles::open_file()
-                            .title("Select VM Directory")
-                            .accept_label("Select")
-                            .modal(true)
-                            .multiple(false)
-                            .directory(true)
-                            .send()
-                            .await
-                            .unwrap()
-                            .response();
-
-                        result.ok().and_then(|directory| {
-                            directory
-                                .uris()
-                                .iter()
-                                .next()
-                                .and_then(|file| file.to_file_path().ok())
-                        })
-                    },
-                    |directory| {
-                        if let Some(directory) = directory {
-                            crate::app::Message::Creation(Message::SelectedDir(directory)).into()
-                        } else {
-                            crate::app::Message::Creation(Message::None).into()
-                        }
-                    },
+                    portal::pick("Select VM Directory", true, None),
+                    |result| crate::app::Message::Creation(Message::DirectoryPicked(result)).into(),
                 );
             }
+            Message::DirectoryPicked(result) => match result {
+                Ok(Some(directory)) => return self.update(Message::SelectedDir(directory)),
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("Falling back to the built-in directory picker: {e}");
+                    if let Some(options) = &mut self.options {
+                        options.directory_picker = Some(DirectoryPicker::new(
+                            &options.directory,
+                            format!("{}. Choose the VM directory here instead.", e.summary()),
+                        ));
+                    }
+                }
+            },
+            Message::PickerOpen(index) => {
+                if let Some(picker) = self
+                    .options
+                    .as_mut()
+                    .and_then(|o| o.directory_picker.as_mut())
+                {
+                    picker.open(index);
+                }
+            }
+            Message::PickerUp => {
+                if let Some(picker) = self
+                    .options
+                    .as_mut()
+                    .and_then(|o| o.directory_picker.as_mut())
+                {
+                    picker.up();
+                }
+            }
+            Message::PickerChoose => {
+                let picker = self
+                    .options
+                    .as_mut()
+                    .and_then(|o| o.directory_picker.take());
+                if let Some(picker) = picker {
+                    return self.update(Message::SelectedDir(picker.current().to_path_buf()));
+                }
+            }
+            Message::PickerCancel => {
+                if let Some(options) = &mut self.options {
+                    options.directory_picker = None;
+                }
+            }
             Message::SelectedDir(selected_directory) => {
                 if let Some(OptionSelection { directory, .. }) = &mut self.options {
                     *directory = selected_directory;
-                    println!(
-                        "Directory updated: {}. Exists: {}",
-                        directory.display(),
-                        directory.exists()
+                    tracing::debug!(
+                        directory = %directory.display(),
+                        exists = directory.exists(),
+                        "VM directory selected"
+                    );
+                }
+            }
+            Message::SetAllowEmulation(allow) => {
+                if let Some(options) = &mut self.options {
+                    options.allow_emulation = allow;
+                }
+            }
+            Message::SetEncrypt(encrypt) => {
+                let qemu_img = self.host.as_ref().map_or(true, |host| host.qemu_img);
+                if let Some(options) = &mut self.options {
+                    options.encrypt = encrypt && qemu_img;
+                }
+            }
+            Message::SetPassphrase(passphrase) => {
+                if let Some(options) = &mut self.options {
+                    options.passphrase = passphrase;
+                }
+            }
+            Message::SetPassphraseConfirm(passphrase) => {
+                if let Some(options) = &mut self.options {
+                    options.passphrase_confirm = passphrase;
+                }
+            }
+            Message::SetRememberPassphrase(remember) => {
+                if let Some(options) = &mut self.options {
+                    options.remember_passphrase = remember;
+                }
+            }
+            Message::SetUnattended(enabled) => {
+                if let Some(options) = &mut self.options {
+                    options.unattended = enabled.then(|| Unattended {
+                        username: String::from("quickemu"),
+                        password: String::new(),
+                        locale: options
+                            .edition
+                            .as_deref()
+                            .and_then(unattended::locale_for_language)
+                            .unwrap_or("en-US")
+                            .to_string(),
+                    });
+                }
+            }
+            Message::SetUnattendedUser(username) => {
+                if let Some(unattended) = self.options.as_mut().and_then(|o| o.unattended.as_mut())
+                {
+                    unattended.username = username;
+                }
+            }
+            Message::SetUnattendedPassword(password) => {
+                if let Some(unattended) = self.options.as_mut().and_then(|o| o.unattended.as_mut())
+                {
+                    unattended.password = password;
+                }
+            }
+            Message::SetOpenCoreRef(opencore_ref) => {
+                if let Some(macos) = self.options.as_mut().and_then(|o| o.macos.as_mut()) {
+                    macos.opencore_ref = opencore_ref;
+                }
+            }
+            Message::SetMacFullInstaller(full) => {
+                if let Some(macos) = self.options.as_mut().and_then(|o| o.macos.as_mut()) {
+                    macos.installer = if full {
+                        MacInstaller::Full(None)
+                    } else {
+                        MacInstaller::Recovery
+                    };
+                }
+            }
+            Message::SelectMacInstaller => {
+                return Command::perform(
+                    portal::pick("Select macOS installer image", false, None),
+                    |result| {
+                        crate::app::Message::Creation(Message::MacInstallerPicked(result)).into()
+                    },
+                );
+            }
+            Message::MacInstallerPicked(result) => match result {
+                Ok(Some(path)) => return self.update(Message::SelectedMacInstaller(path)),
+                Ok(None) => {}
+                Err(e) => self.show_error(e, FailedStep::Pick),
+            },
+            Message::SelectedMacInstaller(path) => {
+                if let Some(macos) = self.options.as_mut().and_then(|o| o.macos.as_mut()) {
+                    macos.installer = MacInstaller::Full(Some(path));
+                }
+            }
+            Message::SetMacResolution(resolution) => {
+                if let Some(macos) = self.options.as_mut().and_then(|o| o.macos.as_mut()) {
+                    macos.resolution = resolution;
+                }
+            }
+            Message::SetSoundCard(choice) => {
+                if let Some(options) = &mut self.options {
+                    options.devices.set_sound_card_choice(choice);
+                }
+            }
+            Message::SetMicrophone(microphone) => {
+                if let Some(options) = &mut self.options {
+                    options.devices.microphone = microphone;
+                }
+            }
+            Message::SetTablet(tablet) => {
+                if let Some(options) = &mut self.options {
+                    options.devices.tablet = tablet;
+                }
+            }
+            Message::SetTestBoot(test_boot) => {
+                if let Some(options) = &mut self.options {
+                    options.test_boot = test_boot;
+                }
+            }
+            Message::SetRenameVM(rename) => {
+                if let Some(options) = &mut self.options {
+                    options.rename = rename;
+                }
+            }
+            Message::SetReuseDownloads(reuse) => {
+                if let Some(options) = &mut self.options {
+                    options.reuse_downloads = reuse;
+                }
+            }
+            Message::ToggleAdvanced => {
+                if let Some(options) = &mut self.options {
+                    options.show_advanced = !options.show_advanced;
+                }
+            }
+            Message::SetExtraKey(index, key) => {
+                if let Some(extra) = self.options.as_mut().and_then(|o| o.extra.get_mut(index)) {
+                    extra.key = key;
+                }
+            }
+            Message::SetExtraValue(index, value) => {
+                if let Some(extra) = self.options.as_mut().and_then(|o| o.extra.get_mut(index)) {
+                    extra.value = value;
+                }
+            }
+            Message::RemoveExtra(index) => {
+                if let Some(options) = &mut self.options {
+                    if index < options.extra.len() {
+                        options.extra.remove(index);
+                    }
+                }
+            }
+            Message::AddExtra => {
+                if let Some(options) = &mut self.options {
+                    options.extra.push(ExtraKey::default());
+                    options.show_advanced = true;
+                }
+            }
+            Message::SetUnattendedLocale(locale) => {
+                if let Some(unattended) = self.options.as_mut().and_then(|o| o.unattended.as_mut())
+                {
+                    unattended.locale = locale;
+                }
+            }
+            Message::Review => {
+                if let Some(options) = &mut self.options {
+                    match options.job() {
+                        Ok(job) => {
+                            options.error = None;
+                            let preview_job = job.clone();
+                            self.page = Page::Review(ConfigReview {
+                                job,
+                                preview: None,
+                                raw: false,
+                                lines: Vec::new(),
+                            });
+                            return Command::perform(
+                                async move { preview_job.preview() },
+                                |preview| {
+                                    crate::app::Message::Creation(Message::Previewed(preview))
+                                        .into()
+                                },
+                            );
+                        }
+                        Err(e) => options.error = Some(e),
+                    }
+                }
+            }
+            Message::Previewed(preview) => {
+                if let Page::Review(review) = &mut self.page {
+                    if let Ok(preview) = &preview {
+                        review.lines = preview.contents.lines().map(str::to_string).collect();
+                    }
+                    review.preview = Some(preview);
+                }
+            }
+            Message::SetRawConfig(raw) => {
+                if let Page::Review(review) = &mut self.page {
+                    review.raw = raw;
+                }
+            }
+            Message::SetConfigLine(index, line) => {
+                if let Page::Review(review) = &mut self.page {
+                    if let Some(entry) = review.lines.get_mut(index) {
+                        *entry = line;
+                    }
+                }
+            }
+            Message::RemoveConfigLine(index) => {
+                if let Page::Review(review) = &mut self.page {
+                    if index < review.lines.len() {
+                        review.lines.remove(index);
+                    }
+                }
+            }
+            Message::AddConfigLine => {
+                if let Page::Review(review) = &mut self.page {
+                    review.lines.push(String::new());
+                }
+            }
+            Message::Create => {
+                if let Page::Review(review) = &self.page {
+                    let existing = match &review.preview {
+                        Some(Ok(preview)) => Some(review.job.directory.join(&preview.file_name)),
+                        _ => None,
+                    };
+                    if let Some(existing) = existing.filter(|path| path.exists()) {
+                        return Confirmation::new(
+                            "Replace the existing VM config?",
+                            format!(
+                                "{} already exists. Creating this VM overwrites it.",
+                                existing.display()
+                            ),
+                            "Replace",
+                            Message::Overwrite,
+                        )
+                        .destructive()
+                        .ask();
+                    }
+                    return self.handle(Message::Overwrite);
+                }
+            }
+            Message::Overwrite => {
+                if let Page::Review(review) = &self.page {
+                    let mut job = review.job.clone();
+                    job.config_override = review.config_override();
+                    if let Some(checkpoint) = self.resume_checkpoint.take() {
+                        job.checkpoint = Some(checkpoint);
+                    }
+                    self.download_progress = vec![Default::default(); job.downloads.len()];
+                    self.queued_download = None;
+                    self.mapped_firmware = None;
+                    self.page = Page::Downloading(job);
+                }
+            }
+            Message::DownloadProgress(index, progress) => {
+                if let Some(entry) = self.download_progress.get_mut(index) {
+                    *entry = progress;
+                }
+            }
+            Message::DownloadQueued(index, queued) => {
+                self.queued_download = queued.then_some(index);
+            }
+            Message::FirmwareMapped(kind) => self.mapped_firmware = Some(kind),
+            Message::StageStarted(stage) => {
+                if let Page::Downloading(job) = &mut self.page {
+                    job.running = Some(stage);
+                }
+            }
+            Message::StageCompleted(checkpoint) => {
+                if let Page::Downloading(job) = &mut self.page {
+                    job.checkpoint = Some(checkpoint);
+                }
+            }
+            Message::StageFailed(stage, e) => {
+                if !matches!(self.page, Page::Downloading(_)) {
+                    return Command::none();
+                }
+                let Page::Downloading(job) = std::mem::take(&mut self.page) else {
+                    unreachable!();
+                };
+                if stage == Stage::Download {
+                    bus::publish(BusEvent::DownloadFailed {
+                        name: job.name.clone(),
+                        error: e.to_string(),
+                    });
+                }
+                self.show_error(e, FailedStep::Create(Box::new(job)));
+            }
+            Message::Created(result) => match result {
+                Ok(config_path) => {
+                    // Copies of the last VM have no options of their own to remember.
+                    let recent = match (&self.page, &self.options) {
+                        (Page::Downloading(_), Some(options)) => Some(options.recent()),
+                        _ => None,
+                    };
+                    self.page = Page::Complete(config_path.clone());
+                    resume::clear(self.session);
+                    bus::publish(BusEvent::CreationComplete(config_path));
+                    if let Some(recent) = recent {
+                        return Command::perform(async move { recent }, |recent| {
+                            crate::app::Message::RecordRecent(recent).into()
+                        });
+                    }
+                }
+                Err(e) => self.show_error(e, FailedStep::Options),
+            },
+            Message::ToggleErrorDetails => self.error_details = !self.error_details,
+            Message::CopyErrorReport => {
+                if let Page::Error(error, _) = &self.page {
+                    let error = error.clone();
+                    return Command::perform(async move { error.report().await }, |report| {
+                        crate::app::Message::CopyToClipboard(report).into()
+                    });
+                }
+            }
+            Message::Retry => match std::mem::take(&mut self.page) {
+                Page::Error(_, FailedStep::LoadOSList) => {
+                    self.page = Page::Loading;
+                    self.catalog = Some(CatalogLoad::start());
+                }
+                // Stages up to the checkpoint are skipped, so this picks up where it failed.
+                Page::Error(_, FailedStep::Create(mut job)) => {
+                    job.running = None;
+                    self.page = Page::Downloading(*job);
+                }
+                Page::Error(_, FailedStep::Options) => {
+                    self.page = Page::Options;
+                    return self.update(Message::Review);
+                }
+                Page::Error(_, FailedStep::Pick) => {
+                    self.page = Page::Options;
+                    return self.update(Message::SelectMacInstaller);
+                }
+                page => self.page = page,
+            },
+            Message::ResumeSaved => {
+                let Some(saved) = self.saved.take() else {
+                    return Command::none();
+                };
+                let Some(index) = self
+                    .os_list
+                    .iter()
+                    .position(|os| os.pretty_name == saved.os)
+                else {
+                    resume::clear(self.session);
+                    return Command::none();
+                };
+                let command = self.update(Message::SelectedOS(index));
+                if let Some(options) = &mut self.options {
+                    options.restore(&saved);
+                }
+                self.resume_checkpoint = saved.checkpoint.clone();
+                self.persist();
+                return command;
+            }
+            Message::SelectedRecent(index) => {
+                let Some(recent) = self.recent.get(index).cloned() else {
+                    return Command::none();
+                };
+                let Some(os_index) = self.os_list.iter().position(|os| os.name == recent.os) else {
+                    return Command::none();
+                };
+                let command = self.update(Message::SelectedOS(os_index));
+                if let Some(options) = &mut self.options {
+                    options.select(recent.release, recent.edition, recent.arch.as_deref());
+                }
+                return command;
+            }
+            Message::SetSpec(spec) => {
+                self.spec = spec;
+                self.spec_error = None;
+            }
+            Message::CreateFromSpec => match VMSpec::parse(&self.spec) {
+                Ok(spec) => {
+                    let command = self.request_recipe(spec.recipe.clone());
+                    match &mut self.options {
+                        Some(options) if options.os_id == spec.recipe.os => {
+                            options.apply_spec(&spec);
+                            self.spec.clear();
+                            self.spec_error = None;
+                        }
+                        _ => {
+                            self.spec_error =
+                                Some(format!("quickget has no OS called {}", spec.recipe.os));
+                        }
+                    }
+                    return command;
+                }
+                Err(e) => self.spec_error = Some(e),
+            },
+            Message::CopySpec => {
+                if let Some(options) = &self.options {
+                    return cosmic::iced::clipboard::write(options.spec().to_text());
+                }
+            }
+            Message::DiscardSaved => {
+                self.saved = None;
+                resume::clear(self.session);
+            }
+            Message::Estimated(generation, estimate) => {
+                if generation == self.estimate_generation {
+                    self.estimate = Some(estimate);
+                }
+            }
+            Message::CheckCollision => {
+                if let Some(options) = &mut self.options {
+                    let downloads = options.downloads();
+                    options.check_collision(downloads.as_deref().unwrap_or_default());
+                }
+            }
+            Message::PreviewSizes(index, sizes) => {
+                self.preview_sizes.insert(index, Some(sizes));
+            }
+            Message::CreateLikeLast => {
+                if let Some(vm) = &self.last_created {
+                    return Command::perform(
+                        duplicate::duplicate(vm.config_path.clone()),
+                        |result| crate::app::Message::Creation(Message::Created(result)).into(),
                     );
                 }
             }
@@ -265,20 +2000,451 @@ impl Creation {
         };
         Command::none()
     }
-    pub fn view(&self) -> Element<crate::app::Message> {
-        match self.page {
-            Page::Loading => widget::text("loading")
+    /// Loads the catalog while on the loading page, then runs the creation stages in order,
+    /// skipping those the job's checkpoint covers.
+    ///
+    /// `session` keeps two sessions creating the same VM from sharing one pipeline.
+    pub fn subscription(&self, session: u32) -> Subscription<crate::app::Message> {
+        match (&self.page, &self.catalog) {
+            (Page::Loading, Some(load)) if !load.cancelled => {
+                return Subscription::batch([
+                    Self::catalog_subscription(load.id),
+                    cosmic::iced::time::every(CATALOG_TICK)
+                        .map(|_| crate::app::Message::Creation(Message::CatalogTick)),
+                ]);
+            }
+            _ => {}
+        }
+        let Page::Downloading(job) = &self.page else {
+            return Subscription::none();
+        };
+        let job = job.clone();
+        let id = ("create", session, job.directory.clone(), job.name.clone());
+        subscription::channel(id, 100, move |mut output| async move {
+            let mut job = job;
+            for stage in Stage::ALL {
+                if job.is_done(stage) || !job.includes(stage) {
+                    continue;
+                }
+                let _ = output.send(Message::StageStarted(stage).into()).await;
+                match job.run_stage(stage, &mut output).await {
+                    Ok(config_path) => {
+                        let checkpoint = Checkpoint { stage, config_path };
+                        job.checkpoint = Some(checkpoint.clone());
+                        let _ = output
+                            .send(Message::StageCompleted(checkpoint).into())
+                            .await;
+                    }
+                    Err(e) => {
+                        let _ = output.send(Message::StageFailed(stage, e).into()).await;
+                        std::future::pending::<()>().await;
+                    }
+                }
+            }
+            let result = job
+                .checkpoint
+                .and_then(|checkpoint| checkpoint.config_path)
+                .ok_or_else(|| {
+                    AppError::new(
+                        ErrorCategory::Quickget,
+                        "quickget did not write a VM config",
+                    )
+                });
+            let _ = output.send(Message::Created(result).into()).await;
+            std::future::pending().await
+        })
+    }
+    /// Fetch the catalog and save a copy for when it can't be fetched.
+    fn catalog_subscription(id: u64) -> Subscription<crate::app::Message> {
+        subscription::channel(("catalog", id), 10, move |mut output| async move {
+            let result = catalog::fetch().await;
+            if let Ok(os_list) = &result {
+                let _ = output
+                    .send(Message::CatalogStage(catalog::Stage::Saving).into())
+                    .await;
+                if let Err(e) = catalog::save(os_list).await {
+                    tracing::warn!("Could not save the OS catalog: {e}");
+                }
+            }
+            let _ = output.send(Message::OSList(result).into()).await;
+            std::future::pending().await
+        })
+    }
+    fn loading_view(&self) -> Element<crate::app::Message> {
+        let Some(load) = &self.catalog else {
+            return widget::text("Loading the OS catalog")
                 .apply(widget::container)
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .align_x(Horizontal::Center)
                 .align_y(Vertical::Center)
+                .into();
+        };
+        let cached_button = load.cached_at.map(|cached_at| {
+            let now = SystemTime::now();
+            let seconds = |time: SystemTime| {
+                time.duration_since(UNIX_EPOCH)
+                    .map_or(0, |since| since.as_secs())
+            };
+            let age = format_age(seconds(cached_at), seconds(now));
+            widget::button::standard(format!("Use catalog saved {age}"))
+                .on_press(Message::UseCachedCatalog.into())
+        });
+        let column = if load.cancelled {
+            widget::column()
+                .push(widget::text::title3("Loading the OS catalog was cancelled"))
+                .push(
+                    widget::row()
+                        .push(
+                            widget::button::suggested("Try again")
+                                .on_press(Message::RetryCatalog.into()),
+                        )
+                        .push_maybe(cached_button)
+                        .spacing(8),
+                )
+        } else {
+            let elapsed = load.started.elapsed();
+            let frame = (elapsed.as_millis() / CATALOG_TICK.as_millis()) as usize % SPINNER.len();
+            let seconds = elapsed.as_secs();
+            widget::column()
+                .push(widget::text::title3(format!(
+                    "{} {}",
+                    SPINNER[frame],
+                    load.stage.label()
+                )))
+                .push(widget::text::caption(format!(
+                    "{}:{:02} elapsed",
+                    seconds / 60,
+                    seconds % 60
+                )))
+                .push(
+                    widget::row()
+                        .push(
+                            widget::button::standard("Cancel")
+                                .on_press(Message::CancelCatalog.into()),
+                        )
+                        .push_maybe(cached_button)
+                        .spacing(8),
+                )
+        };
+        column
+            .spacing(12)
+            .align_items(Alignment::Center)
+            .apply(widget::container)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Center)
+            .into()
+    }
+    fn review_view(review: &ConfigReview) -> Element<crate::app::Message> {
+        let mut column = widget::column()
+            .push(widget::text::title3("Review VM config"))
+            .push(widget::text::caption(
+                "Encryption, unattended installs and macOS settings are added once the \
+                 downloads finish, so they aren't shown here.",
+            ))
+            .spacing(12);
+        let ready = match &review.preview {
+            None => {
+                column = column.push(widget::text("Rendering config…"));
+                false
+            }
+            Some(Err(e)) => {
+                column = column.push(widget::text(e.to_string()));
+                false
+            }
+            Some(Ok(preview)) => {
+                column = column
+                    .push(widget::text::heading(preview.file_name.clone()))
+                    .push(widget::toggler(
+                        String::from("Advanced: edit raw config"),
+                        review.raw,
+                        |raw| Message::SetRawConfig(raw).into(),
+                    ));
+                let contents: Element<_> = if review.raw {
+                    let mut lines = widget::column().spacing(4);
+                    for (index, line) in review.lines.iter().enumerate() {
+                        lines = lines.push(
+                            widget::row()
+                                .push(
+                                    widget::text_input("Config line", line)
+                                        .font(cosmic::font::mono())
+                                        .on_input(move |line| {
+                                            Message::SetConfigLine(index, line).into()
+                                        }),
+                                )
+                                .push(
+                                    widget::button::icon(icon::from_name("list-remove-symbolic"))
+                                        .on_press(Message::RemoveConfigLine(index).into())
+                                        .tooltip(format!("Remove line {}", index + 1)),
+                                )
+                                .spacing(8)
+                                .align_items(Alignment::Center),
+                        );
+                    }
+                    lines
+                        .push(
+                            widget::button::standard("Add line")
+                                .on_press(Message::AddConfigLine.into()),
+                        )
+                        .into()
+                } else {
+                    config_view(&preview.contents)
+                };
+                column = column.push(widget::scrollable(contents).height(Length::Fill));
+                true
+            }
+        };
+        let buttons = widget::row()
+            .push(widget::button::standard("Back").on_press(Message::Back.into()))
+            .push(
+                widget::button::suggested("Create")
+                    .on_press_maybe(ready.then_some(Message::Create.into())),
+            )
+            .spacing(8);
+        column.push(buttons).into()
+    }
+    fn macos_view(macos: &MacOSOptions) -> Element<crate::app::Message> {
+        let opencore_row = widget::row()
+            .push(widget::text("OpenCore release:  ").width(Length::Shrink))
+            .push(
+                widget::text_input(macos::DEFAULT_OPENCORE_REF, &macos.opencore_ref)
+                    .on_input(|opencore_ref| Message::SetOpenCoreRef(opencore_ref).into()),
+            )
+            .align_items(Alignment::Center);
+        let full_installer = matches!(macos.installer, MacInstaller::Full(_));
+        let installer_toggle = widget::toggler(
+            String::from("Use a full installer instead of the recovery image"),
+            full_installer,
+            |full| Message::SetMacFullInstaller(full).into(),
+        );
+        let resolution_row = widget::row()
+            .push(widget::text("Resolution:  ").width(Length::Shrink))
+            .push(widget::dropdown(
+                &macos::RESOLUTIONS,
+                Some(macos.resolution),
+                |resolution| Message::SetMacResolution(resolution).into(),
+            ))
+            .align_items(Alignment::Center);
+        let mut column = widget::column()
+            .push(opencore_row)
+            .push(installer_toggle)
+            .spacing(8);
+        if let MacInstaller::Full(path) = &macos.installer {
+            let label = path.as_ref().map_or_else(
+                || String::from("No installer image selected"),
+                |path| path.to_string_lossy().into_owned(),
+            );
+            column = column.push(
+                widget::row()
+                    .push(widget::text(label).width(Length::Fill))
+                    .push(
+                        widget::button::icon(icon::from_name("document-open-symbolic"))
+                            .on_press(Message::SelectMacInstaller.into())
+                            .tooltip("Select installer image")
+                            .width(Length::Shrink),
+                    )
+                    .align_items(Alignment::Center),
+            );
+        }
+        column.push(resolution_row).into()
+    }
+    /// What to do about a VM already using the new one's name, see [`collision::check`].
+    fn collision_view(options: &OptionSelection) -> Option<Element<crate::app::Message>> {
+        let collision = options.collision.as_ref()?;
+        if !collision.clobbers() {
+            return Some(
+                widget::text::caption(
+                    "Installer images already downloaded to this directory will be reused.",
+                )
+                .into(),
+            );
+        }
+        let warning = widget::row()
+            .push(icon::from_name("dialog-warning-symbolic").size(16).icon())
+            .push(widget::text(format!(
+                "{}. Creating this VM under the same name would overwrite it.",
+                collision.message()
+            )))
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let rename_toggle = widget::toggler(
+            format!("Name the new VM {}", collision.free_name),
+            options.rename,
+            |rename| Message::SetRenameVM(rename).into(),
+        );
+        let reuse_toggle = (options.rename && collision.downloads > 0).then(|| {
+            widget::toggler(
+                String::from("Reuse the installer images already downloaded"),
+                options.reuse_downloads,
+                |reuse| Message::SetReuseDownloads(reuse).into(),
+            )
+        });
+        let column = widget::column()
+            .push(warning)
+            .push(rename_toggle)
+            .push_maybe(reuse_toggle)
+            .push(
+                widget::button::standard("Choose another directory")
+                    .on_press(Message::SelectVMDir.into()),
+            )
+            .spacing(8);
+        Some(column.into())
+    }
+    fn favorite_button(&self, os: &str) -> Element<crate::app::Message> {
+        let (icon_name, tooltip) = if self.favorites.iter().any(|favorite| favorite == os) {
+            ("starred-symbolic", "Remove from favorites")
+        } else {
+            ("non-starred-symbolic", "Add to favorites")
+        };
+        widget::button::icon(icon::from_name(icon_name))
+            .on_press(crate::app::Message::ToggleFavoriteOS(os.to_string()))
+            .tooltip(tooltip)
+            .width(Length::Shrink)
+            .into()
+    }
+    /// Starred OSes and recently created combinations, above the full list.
+    fn shortcuts_view(&self) -> Option<Element<crate::app::Message>> {
+        let os_index = |name: &str| self.os_list.iter().position(|os| os.name == name);
+        let mut column = widget::column().spacing(8);
+        let mut any_favorite = false;
+
+        let mut favorites = widget::list_column();
+        for (index, os) in self
+            .favorites
+            .iter()
+            .filter_map(|name| os_index(name))
+            .map(|index| (index, &self.os_list[index]))
+        {
+            let row = widget::row()
+                .push(os_icon(&os.name, 24))
+                .push(
+                    widget::button::text(os.pretty_name.as_str())
+                        .on_press(Message::SelectedOS(index).into())
+                        .width(Length::Fill),
+                )
+                .push(self.favorite_button(&os.name))
+                .spacing(4)
+                .align_items(Alignment::Center);
+            favorites = favorites.add(row);
+            any_favorite = true;
+        }
+        if any_favorite {
+            column = column
+                .push(widget::text::heading("Favorites"))
+                .push(favorites);
+        }
+
+        let mut recent_list = widget::list_column();
+        let mut any_recent = false;
+        for (position, recent) in self.recent.iter().enumerate() {
+            let Some(index) = os_index(&recent.os) else {
+                continue;
+            };
+            let os = &self.os_list[index];
+            let label = recent.label(&os.pretty_name);
+            let row = widget::row()
+                .push(os_icon(&os.name, 24))
+                .push(
+                    widget::button::text(label)
+                        .on_press(Message::SelectedRecent(position).into())
+                        .width(Length::Fill),
+                )
+                .spacing(4)
+                .align_items(Alignment::Center);
+            recent_list = recent_list.add(row);
+            any_recent = true;
+        }
+        if any_recent {
+            column = column
+                .push(widget::text::heading("Recent"))
+                .push(recent_list);
+        }
+
+        (any_favorite || any_recent).then(|| column.push(widget::text::heading("All")).into())
+    }
+    fn spec_view(&self) -> Element<crate::app::Message> {
+        let input = widget::text_input("Paste a VM spec", &self.spec)
+            .on_input(|spec| Message::SetSpec(spec).into())
+            .on_submit(Message::CreateFromSpec.into());
+        let button = widget::button::standard("Create from spec").on_press_maybe(
+            (!self.spec.trim().is_empty()).then_some(Message::CreateFromSpec.into()),
+        );
+        widget::column()
+            .push(
+                widget::row()
+                    .push(input)
+                    .push(button)
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            )
+            .push_maybe(self.spec_error.clone().map(widget::text::caption))
+            .spacing(4)
+            .into()
+    }
+    fn create_like_last_button(&self) -> Option<Element<crate::app::Message>> {
+        let vm = self.last_created.as_ref()?;
+        let button = widget::button::standard("Create another like last one");
+        let button = if vm.is_encrypted() {
+            button.tooltip("Encrypted VMs can't be duplicated")
+        } else {
+            button
+                .on_press(Message::CreateLikeLast.into())
+                .tooltip(format!("Copy {}, reusing its downloaded images", vm.name))
+        };
+        Some(button.into())
+    }
+    fn preview_view(&self) -> Option<Element<crate::app::Message>> {
+        let (index, os) = self.filtered_os_list().nth(self.highlighted)?;
+        let releases = os
+            .releases
+            .iter()
+            .filter_map(|config| config.release.as_ref())
+            .unique()
+            .count();
+        let mut column = widget::column()
+            .push(os_icon(&os.name, 64))
+            .push(widget::text::title3(os.pretty_name.as_str()))
+            .push_maybe(os.description.clone().map(widget::text))
+            .push(widget::text::caption(format!("{releases} releases")))
+            .spacing(8);
+        match self.preview_sizes.get(&index) {
+            Some(Some(sizes)) if !sizes.is_empty() => {
+                column = column.push(widget::text::heading("Download sizes"));
+                for release in sizes {
+                    let size = release
+                        .size
+                        .map_or_else(|| String::from("unknown"), format_size);
+                    column =
+                        column.push(widget::text::caption(format!("{}: {size}", release.label)));
+                }
+            }
+            Some(Some(_)) => {}
+            _ => column = column.push(widget::text::caption("Looking up download sizes…")),
+        }
+        Some(
+            widget::container(column)
+                .padding(12)
+                .width(Length::FillPortion(2))
                 .into(),
+        )
+    }
+    /// `wide` is set when the window has room to show the OS preview beside the list.
+    pub fn view(&self, wide: bool) -> Element<crate::app::Message> {
+        match &self.page {
+            Page::Loading => self.loading_view(),
             Page::SelectOS => {
+                let search = widget::text_input::search_input("Search", &self.search)
+                    .id(SEARCH_ID.clone())
+                    .on_input(|search| Message::Search(search).into())
+                    .on_submit(Message::SelectHighlighted.into());
                 let mut list_column = widget::list_column().style(theme::Container::ContextDrawer);
-                let os_list = self.os_list.clone();
-                for os in os_list {
-                    let mut row = widget::row().align_items(Alignment::End);
+                for (position, (index, os)) in self.filtered_os_list().enumerate() {
+                    let mut row = widget::row()
+                        .push(os_icon(&os.name, 24))
+                        .spacing(4)
+                        .align_items(Alignment::Center);
                     if let Some(homepage) = os.homepage.clone() {
                         let homepage_button =
                             widget::button::icon(icon::from_name("go-home-symbolic"))
@@ -287,14 +2453,72 @@ impl Creation {
                                 .width(Length::Shrink);
                         row = row.push(homepage_button);
                     }
-                    let button = widget::button::text(os.pretty_name.clone())
-                        .on_press(Message::SelectedOS(os).into())
+                    let name = os.pretty_name.as_str();
+                    let button = if position == self.highlighted {
+                        widget::button::suggested(name)
+                    } else {
+                        widget::button::text(name)
+                    };
+                    let button = button
+                        .on_press(Message::SelectedOS(index).into())
                         .width(Length::Fill);
-                    row = row.push(button);
+                    row = row.push(button).push(self.favorite_button(&os.name));
 
                     list_column = list_column.add(row);
                 }
-                widget::scrollable(list_column).into()
+                let resume_banner = self.saved.as_ref().map(|saved| {
+                    let mut text = format!("Resume previous setup: {}", saved.label());
+                    if saved.downloaded > 0 {
+                        text.push_str(&format!(" ({} downloaded)", format_size(saved.downloaded)));
+                    }
+                    widget::row()
+                        .push(widget::text(text).width(Length::Fill))
+                        .push(
+                            widget::button::standard("Discard")
+                                .on_press(Message::DiscardSaved.into()),
+                        )
+                        .push(
+                            widget::button::suggested("Resume")
+                                .on_press(Message::ResumeSaved.into()),
+                        )
+                        .spacing(8)
+                        .align_items(Alignment::Center)
+                });
+                // Shortcuts would be confusing above a filtered list.
+                let shortcuts = self
+                    .search
+                    .is_empty()
+                    .then(|| self.shortcuts_view())
+                    .flatten();
+                let list = widget::column()
+                    .push_maybe(resume_banner)
+                    .push_maybe(self.create_like_last_button())
+                    .push(self.spec_view())
+                    .push(search)
+                    .push(
+                        widget::scrollable(
+                            widget::column()
+                                .push_maybe(shortcuts)
+                                .push(list_column)
+                                .spacing(12),
+                        )
+                        .id(OS_LIST_ID.clone())
+                        .on_scroll(|viewport| {
+                            Message::OSListScrolled(viewport.absolute_offset().y).into()
+                        }),
+                    )
+                    .spacing(8);
+                match (self.show_preview && wide)
+                    .then(|| self.preview_view())
+                    .flatten()
+                {
+                    Some(preview) => widget::row()
+                        .push(list.width(Length::FillPortion(3)))
+                        .push(preview)
+                        .spacing(12)
+                        .into(),
+                    None => list.into(),
+                }
             }
             Page::Options => {
                 let OptionSelection {
@@ -304,42 +2528,120 @@ impl Creation {
                     release_list,
                     edition_list,
                     arch_list,
+                    hidden_releases,
                     ram,
                     cpu_cores,
                     directory,
+                    encrypt,
+                    passphrase,
+                    passphrase_confirm,
+                    remember_passphrase,
+                    installed_arches,
+                    allow_emulation,
+                    windows,
+                    unattended,
+                    macos,
+                    devices,
+                    extra,
+                    show_advanced,
+                    test_boot,
+                    os_id,
+                    os_name,
+                    error,
                     ..
                 } = self.options.as_ref().unwrap();
 
                 let mut list = widget::list_column();
+                let back_button = widget::button::icon(icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back.into())
+                    .tooltip("Back to OS list (Esc)")
+                    .width(Length::Shrink);
+                let header = widget::row()
+                    .push(back_button)
+                    .push(os_icon(os_id, 32))
+                    .push(widget::text::title3(os_name))
+                    .spacing(8)
+                    .align_items(Alignment::Center);
+                list = list.add(header);
                 let mut row = widget::row();
+                let selected = release.clone().map(Release::new);
                 let release_dropdown =
-                    widget::combo_box(release_list, "Release", release.as_ref(), |release| {
-                        Message::SelectedRelease(release).into()
+                    widget::combo_box(release_list, "Release", selected.as_ref(), |release| {
+                        Message::SelectedRelease(release.name).into()
                     });
-                row = row.push(release_dropdown);
+                row = row.push(labelled("Release", release_dropdown));
 
                 if let Some(edition_list) = edition_list {
-                    let edition_dropdown =
-                        widget::combo_box(edition_list, "Edition", edition.as_ref(), |edition| {
-                            Message::SelectedEdition(edition).into()
-                        });
-                    row = row.push(edition_dropdown);
+                    let placeholder = if *windows { "Language" } else { "Edition" };
+                    let selected = edition.clone().map(Edition);
+                    let edition_dropdown = widget::combo_box(
+                        edition_list,
+                        placeholder,
+                        selected.as_ref(),
+                        |edition| Message::SelectedEdition(edition.0).into(),
+                    );
+                    row = row.push(labelled(placeholder, edition_dropdown));
                 }
 
                 let arch_dropdown =
                     widget::combo_box(arch_list, "Architecture", arch.as_ref(), |arch| {
                         Message::SelectedArch(arch).into()
                     });
-                row = row.push(arch_dropdown);
-                list = list.add(row);
+                row = row.push(labelled("Architecture", arch_dropdown));
+                list = list.add(row.spacing(8));
+                if *hidden_releases > 0 {
+                    list = list.add(widget::text::caption(format!(
+                        "{hidden_releases} pre-release or end-of-life releases hidden. Turn on \
+                         \"Show testing releases\" in Settings to list them."
+                    )));
+                }
+
+                if let Some(arch) = arch {
+                    let binary = host_probe::qemu_system_binary(arch);
+                    if !installed_arches.contains(arch) {
+                        list = list.add(widget::text(format!(
+                            "{binary} is not installed. Install QEMU's {} system emulator to \
+                             create this VM.",
+                            host_probe::arch_name(arch)
+                        )));
+                    } else if host_probe::needs_emulation(arch) {
+                        let warning = widget::row()
+                            .push(icon::from_name("dialog-warning-symbolic").size(16).icon())
+                            .push(widget::text(format!(
+                                "{} guests can't use hardware acceleration on this {} host and \
+                                 will run under much slower TCG emulation.",
+                                host_probe::arch_name(arch),
+                                host_probe::arch_name(&host_probe::native_arch())
+                            )))
+                            .spacing(8)
+                            .align_items(Alignment::Center);
+                        let allow_toggle = widget::toggler(
+                            String::from("Allow emulation"),
+                            *allow_emulation,
+                            |allow| Message::SetAllowEmulation(allow).into(),
+                        );
+                        list = list.add(warning).add(allow_toggle);
+                    } else if let Some(hint) = self
+                        .host
+                        .as_ref()
+                        .and_then(|host| host.acceleration_hint(arch))
+                    {
+                        list = list.add(widget::text::caption(hint));
+                    }
+                }
 
+                let requirements = self.options.as_ref().unwrap().requirements();
                 let total_cores = QuickgetInstance::get_total_cpu_cores() as f64;
-                let cpu_slider = widget::slider(1.0..=total_cores, *cpu_cores as f64, |x| {
+                let min_cores = (requirements.min_cpu_cores as f64).min(total_cores);
+                let cpu_slider = widget::slider(min_cores..=total_cores, *cpu_cores as f64, |x| {
                     Message::SetCPUCores(x as usize).into()
                 });
                 let cpu_text = widget::text("CPU Cores:  ").width(Length::Shrink);
+                // Spelled out, since the slider's own value is only a number to a screen reader.
+                let cores = if *cpu_cores == 1 { "core" } else { "cores" };
                 let selected_cpu_text =
-                    widget::text(format!("  {cpu_cores}")).width(Length::Shrink);
+                    widget::text(format!("  {cpu_cores} {cores} of {total_cores}"))
+                        .width(Length::Shrink);
                 let cpu_row = widget::row()
                     .push(cpu_text)
                     .push(cpu_slider)
@@ -347,17 +2649,37 @@ impl Creation {
                 list = list.add(cpu_row);
 
                 let ram_gb = QuickgetInstance::get_total_ram() as f64 / (1024 * 1024 * 1024) as f64;
+                let min_ram_gb = (requirements.min_ram as f64 / (1024 * 1024 * 1024) as f64)
+                    .max(0.25)
+                    .min(ram_gb);
                 let ram_slider =
-                    widget::slider(0.25..=ram_gb as f64, *ram, |x| Message::SetRAM(x).into())
+                    widget::slider(min_ram_gb..=ram_gb, *ram, |x| Message::SetRAM(x).into())
                         .step(0.01);
                 let ram_text = widget::text("RAM:  ").width(Length::Shrink);
-                let selected_ram_text =
-                    widget::text(format!("  {ram:.2} GiB")).width(Length::Shrink);
+                let selected_ram_text = widget::text(format!("  {ram:.2} GiB of {ram_gb:.2} GiB"))
+                    .width(Length::Shrink);
                 let ram_row = widget::row()
                     .push(ram_text)
                     .push(ram_slider)
                     .push(selected_ram_text);
                 list = list.add(ram_row);
+                if let Some(warning) =
+                    requirements.warning((*ram * (1024 * 1024 * 1024) as f64) as u64, *cpu_cores)
+                {
+                    let warning = widget::row()
+                        .push(icon::from_name("dialog-warning-symbolic").size(16).icon())
+                        .push(widget::text(warning))
+                        .spacing(8)
+                        .align_items(Alignment::Center);
+                    list = list.add(warning);
+                }
+                if let Some(overcommit) = self.commitments.check(
+                    None,
+                    (*ram * (1024 * 1024 * 1024) as f64) as u64,
+                    *cpu_cores,
+                ) {
+                    list = list.add(overcommit_view(&overcommit, crate::app::Message::ShowVM));
+                }
 
                 let vm_dir_text = widget::text("VM Directory:  ").width(Length::Shrink);
                 let vm_dir_input = widget::text_input("VM Directory", directory.to_string_lossy())
@@ -372,10 +2694,315 @@ impl Creation {
                     .push(vm_dir_input)
                     .push(vm_dir_open_button);
                 list = list.add(vm_dir_row);
+                if let Some(picker) = &self.options.as_ref().unwrap().directory_picker {
+                    list = list.add(picker.view(DirectoryPickerActions {
+                        open: |index| Message::PickerOpen(index).into(),
+                        up: Message::PickerUp.into(),
+                        choose: Message::PickerChoose.into(),
+                        cancel: Message::PickerCancel.into(),
+                    }));
+                }
+                if let Some(available) = self
+                    .host
+                    .as_ref()
+                    .and_then(|host| host.free_space_at(directory))
+                {
+                    list = list.add(widget::text::caption(format!(
+                        "{} free in this location",
+                        format_size(available)
+                    )));
+                }
+                if let Some(collision) = Self::collision_view(self.options.as_ref().unwrap()) {
+                    list = list.add(collision);
+                }
+
+                let encrypt_toggle = widget::toggler(
+                    String::from("Encrypt disk image (LUKS)"),
+                    *encrypt,
+                    |encrypt| Message::SetEncrypt(encrypt).into(),
+                );
+                if self.host.as_ref().map_or(true, |host| host.qemu_img) {
+                    list = list.add(encrypt_toggle);
+                } else {
+                    list = list.add(widget::text::caption(
+                        "Install qemu-img (part of QEMU's tools) to encrypt disk images",
+                    ));
+                }
+                if *encrypt {
+                    let passphrase_input = widget::text_input("Passphrase", passphrase)
+                        .password()
+                        .on_input(|passphrase| Message::SetPassphrase(passphrase).into());
+                    let confirm_input =
+                        widget::text_input("Confirm passphrase", passphrase_confirm)
+                            .password()
+                            .on_input(|passphrase| Message::SetPassphraseConfirm(passphrase).into())
+                            .on_submit(Message::Review.into());
+                    let remember_checkbox = widget::checkbox(
+                        "Remember passphrase in the keyring",
+                        *remember_passphrase,
+                    )
+                    .on_toggle(|remember| Message::SetRememberPassphrase(remember).into());
+                    list = list
+                        .add(passphrase_input)
+                        .add(confirm_input)
+                        .add(remember_checkbox);
+                }
+
+                if *windows {
+                    let unattended_toggle = widget::toggler(
+                        String::from("Unattended installation"),
+                        unattended.is_some(),
+                        |enabled| Message::SetUnattended(enabled).into(),
+                    );
+                    list = list.add(unattended_toggle);
+                }
+                if let Some(unattended) = unattended {
+                    let user_input = widget::text_input("User name", &unattended.username)
+                        .on_input(|username| Message::SetUnattendedUser(username).into());
+                    let password_input = widget::text_input("Password", &unattended.password)
+                        .password()
+                        .on_input(|password| Message::SetUnattendedPassword(password).into());
+                    let locale_input = widget::text_input("Locale, e.g. en-US", &unattended.locale)
+                        .on_input(|locale| Message::SetUnattendedLocale(locale).into());
+                    list = list
+                        .add(user_input)
+                        .add(password_input)
+                        .add(locale_input)
+                        .add(widget::text::caption(
+                            "Windows Setup reads these from an extra drive, which keeps the \
+                             password in plain text.",
+                        ));
+                }
+
+                if let Some(macos) = macos {
+                    list = list.add(Self::macos_view(macos));
+                }
+
+                let unsupported =
+                    |key: &str| self.host.as_ref().and_then(|host| host.unsupported(key));
+                let microphone_unsupported = unsupported("sound_duplex");
+                let tablet_unsupported = unsupported("mouse");
+                list = list.add(
+                    widget::row()
+                        .push(widget::text("Sound"))
+                        .push(widget::dropdown(
+                            &devices::SOUND_CARD_CHOICES,
+                            devices.sound_card_choice(),
+                            |choice| Message::SetSoundCard(choice).into(),
+                        ))
+                        .push_maybe(devices.has_microphone_option().then(|| {
+                            let mut checkbox = widget::checkbox("Microphone", devices.microphone);
+                            if microphone_unsupported.is_none() {
+                                checkbox = checkbox.on_toggle(|microphone| {
+                                    Message::SetMicrophone(microphone).into()
+                                });
+                            }
+                            gated(checkbox, microphone_unsupported)
+                        }))
+                        .push({
+                            let mut checkbox = widget::checkbox(
+                                "Tablet pointer (follows the host cursor)",
+                                devices.tablet,
+                            );
+                            if tablet_unsupported.is_none() {
+                                checkbox =
+                                    checkbox.on_toggle(|tablet| Message::SetTablet(tablet).into());
+                            }
+                            gated(checkbox, tablet_unsupported)
+                        })
+                        .spacing(8)
+                        .align_items(Alignment::Center),
+                );
+
+                list = list.add(
+                    widget::column()
+                        .push(widget::toggler(
+                            String::from("Test-boot the VM after creating it"),
+                            *test_boot,
+                            |test_boot| Message::SetTestBoot(test_boot).into(),
+                        ))
+                        .push(widget::text::caption(format!(
+                            "Starts it without a window for up to {} seconds to check that it \
+                             reaches the bootloader or installer.",
+                            test_boot::TIMEOUT.as_secs()
+                        )))
+                        .spacing(4),
+                );
+
+                list = list.add(extra_keys_view(
+                    extra,
+                    &CREATION_MANAGED,
+                    *show_advanced,
+                    ExtraKeyActions {
+                        toggle: Message::ToggleAdvanced.into(),
+                        set_key: |index, key| Message::SetExtraKey(index, key).into(),
+                        set_value: |index, value| Message::SetExtraValue(index, value).into(),
+                        remove: |index| Message::RemoveExtra(index).into(),
+                        add: Message::AddExtra.into(),
+                    },
+                ));
+
+                if let Some(error) = error {
+                    list = list.add(widget::text(error.clone()));
+                }
+                let create_button =
+                    widget::button::suggested("Review config").on_press(Message::Review.into());
+                let copy_spec_button = widget::button::standard("Copy VM spec")
+                    .on_press(Message::CopySpec.into())
+                    .tooltip("Copy the OS, release and resources to share, e.g. in chat");
+                let estimate = match &self.estimate {
+                    _ if self
+                        .options
+                        .as_ref()
+                        .and_then(OptionSelection::selected_config)
+                        .is_none() =>
+                    {
+                        None
+                    }
+                    None => Some(String::from("Estimating download size…")),
+                    Some(DownloadEstimate {
+                        remaining: Some(0), ..
+                    }) => Some(String::from("Everything is already downloaded")),
+                    Some(
+                        estimate @ DownloadEstimate {
+                            remaining: Some(remaining),
+                            ..
+                        },
+                    ) => Some(match estimate.seconds() {
+                        Some(seconds) => format!(
+                            "{} to download, {} at the current speed",
+                            format_size(*remaining),
+                            format_duration(seconds)
+                        ),
+                        None => format!("{} to download", format_size(*remaining)),
+                    }),
+                    Some(_) => Some(String::from("Download size unknown")),
+                };
+                list = list.add(
+                    widget::row()
+                        .push(create_button)
+                        .push(copy_spec_button)
+                        .push_maybe(estimate.map(widget::text::caption))
+                        .spacing(12)
+                        .align_items(Alignment::Center),
+                );
 
-                list.into()
+                widget::scrollable(list).into()
+            }
+            Page::Review(review) => Self::review_view(review),
+            Page::Downloading(job) => {
+                let mut stages = widget::list_column();
+                for stage in Stage::ALL.into_iter().filter(|stage| job.includes(*stage)) {
+                    let (icon_name, status) = if job.is_done(stage) {
+                        ("emblem-ok-symbolic", "Done")
+                    } else if job.running == Some(stage) {
+                        ("content-loading-symbolic", "In progress")
+                    } else {
+                        ("radio-symbolic", "Waiting")
+                    };
+                    let row = widget::row()
+                        .push(icon::from_name(icon_name).size(16).icon())
+                        .push(widget::text(stage.label()).width(Length::Fill))
+                        .push(widget::text::caption(status))
+                        .align_items(Alignment::Center)
+                        .spacing(8);
+                    stages = stages.add(row);
+                }
+                let mut list = widget::list_column();
+                for (index, (qg_download, progress)) in job
+                    .downloads
+                    .iter()
+                    .zip(&self.download_progress)
+                    .enumerate()
+                {
+                    let file_name = qg_download
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| qg_download.url.clone());
+                    let status = match progress.total {
+                        _ if progress.finished => String::from("Done"),
+                        _ if self.queued_download == Some(index) => {
+                            String::from("Waiting for downloads of other VMs to finish")
+                        }
+                        Some(total) => format!(
+                            "{} of {}",
+                            format_size(progress.downloaded),
+                            format_size(total)
+                        ),
+                        None => format_size(progress.downloaded),
+                    };
+                    let total = progress.total.unwrap_or(0).max(1) as f32;
+                    let done = if progress.finished {
+                        total
+                    } else {
+                        progress.downloaded as f32
+                    };
+                    let mut column = widget::column()
+                        .push(widget::text::heading(file_name))
+                        .push(widget::progress_bar(0.0..=total, done))
+                        .push(widget::text::caption(status))
+                        .spacing(4);
+                    for retry in &progress.retries {
+                        column = column.push(widget::text::caption(retry.label()));
+                    }
+                    list = list.add(column);
+                }
+                widget::column()
+                    .push(widget::text::title3(format!("Creating {}", job.name)))
+                    .push(stages)
+                    .push(widget::scrollable(list))
+                    .spacing(12)
+                    .into()
+            }
+            Page::Complete(config_path) => widget::column()
+                .push(widget::text::title3("VM created"))
+                .push(widget::text(config_path.to_string_lossy().into_owned()))
+                .push_maybe(
+                    self.host
+                        .as_ref()
+                        .filter(|host| !host.quickemu)
+                        .map(|_| widget::text::caption(host_probe::QUICKEMU_HINT)),
+                )
+                .push_maybe(self.mapped_firmware.map(|kind| {
+                    widget::text::caption(format!(
+                        "No system copy of {} was found, so this VM uses the copy QERSUI \
+                         downloaded. Its config is set to boot=\"legacy\" for quickemu; QERSUI \
+                         attaches the firmware when it starts the VM.",
+                        kind.label()
+                    ))
+                }))
+                .push(
+                    widget::row()
+                        .push(
+                            widget::button::standard("Create another")
+                                .on_press(Message::Back.into()),
+                        )
+                        .push(
+                            widget::button::standard("Open first-boot checklist")
+                                .on_press(crate::app::Message::OpenChecklist(config_path.clone())),
+                        )
+                        .push_maybe(self.create_like_last_button())
+                        .spacing(8),
+                )
+                .spacing(12)
+                .into(),
+            Page::Error(error, step) => {
+                let back = match step {
+                    FailedStep::LoadOSList => None,
+                    FailedStep::Create(_) | FailedStep::Options | FailedStep::Pick => {
+                        Some(Message::Back.into())
+                    }
+                };
+                let actions = ErrorActions {
+                    toggle_details: Message::ToggleErrorDetails.into(),
+                    copy_report: Message::CopyErrorReport.into(),
+                    retry: Some(Message::Retry.into()),
+                    back,
+                };
+                error_view(error, self.error_details, actions)
             }
-            _ => widget::text("NOT YET IMPLEMENTED").into(),
+            Page::Docker => widget::text("NOT YET IMPLEMENTED").into(),
         }
     }
 }
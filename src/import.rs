@@ -0,0 +1,442 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use ashpd::desktop::file_chooser::FileFilter;
+use cosmic::app::Command;
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{subscription, Alignment, Length, Subscription};
+use cosmic::iced_widget::combo_box::State;
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::core::bus::{self, BusEvent};
+use crate::core::disk;
+use crate::core::download;
+use crate::core::portal::pick;
+use crate::core::probe::{self, GuestGuess};
+use crate::core::vm::{VMConfig, VM};
+
+const GUEST_OS_LIST: [&str; 9] = [
+    "linux", "windows", "macos", "freebsd", "netbsd", "openbsd", "haiku", "solaris", "reactos",
+];
+
+#[derive(Clone, Debug)]
+pub struct Import {
+    source: Option<PathBuf>,
+    name: String,
+    guest_os_list: State<String>,
+    guest_os: Option<String>,
+    guess: Option<GuestGuess>,
+    ram: String,
+    cpu_cores: String,
+    directory: PathBuf,
+    job: Option<ConversionJob>,
+    status: Option<Result<String, String>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    None,
+    /// The file chooser portal didn't open.
+    PickFailed(String),
+    SelectConfig,
+    SelectedConfig(PathBuf),
+    SelectDisk,
+    SelectedDisk(PathBuf),
+    Probed(PathBuf, Option<GuestGuess>),
+    SetName(String),
+    SetRAM(String),
+    SetCPUCores(String),
+    SelectedGuestOS(String),
+    SelectDir,
+    SelectedDir(PathBuf),
+    StartImport,
+    Progress(f32),
+    Finished(Result<PathBuf, String>),
+}
+
+/// A running `qemu-img convert` of a foreign disk into a new VM directory.
+#[derive(Clone, Debug)]
+struct ConversionJob {
+    source: PathBuf,
+    name: String,
+    guest_os: String,
+    ram: String,
+    cpu_cores: String,
+    directory: PathBuf,
+    progress: f32,
+}
+
+impl ConversionJob {
+    fn config_path(&self) -> PathBuf {
+        self.directory.join(format!("{}.conf", self.name))
+    }
+    fn disk_path(&self) -> PathBuf {
+        self.directory.join(&self.name).join("disk.qcow2")
+    }
+    async fn run(
+        &self,
+        output: &mut cosmic::iced::futures::channel::mpsc::Sender<crate::app::Message>,
+    ) -> Result<PathBuf, String> {
+        let config_path = self.config_path();
+        if config_path.exists() {
+            return Err(format!("{} already exists", config_path.display()));
+        }
+        let disk_path = self.disk_path();
+        // A disk left by another VM of this name is its data, not something to convert over.
+        if disk_path.exists() {
+            return Err(format!("{} already exists", disk_path.display()));
+        }
+        if let Some(parent) = disk_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Could not create {}: {e}", parent.display()))?;
+        }
+        // Converted next to the disk and moved into place once complete, so a failed or
+        // interrupted conversion never leaves a partial disk under the real name.
+        let partial = download::partial_path(&disk_path);
+
+        let mut child = tokio::process::Command::new("qemu-img")
+            .args(["convert", "-p", "-O", "qcow2"])
+            .arg(&self.source)
+            .arg(&partial)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not run qemu-img: {e}"))?;
+
+        if let Some(stdout) = child.stdout.take() {
+            let mut reader = BufReader::new(stdout);
+            let mut line = Vec::new();
+            while reader.read_until(b'\r', &mut line).await.unwrap_or(0) > 0 {
+                if let Some(progress) = disk::parse_progress(&String::from_utf8_lossy(&line)) {
+                    let _ = output.send(Message::Progress(progress).into()).await;
+                }
+                line.clear();
+            }
+        }
+        let result = match child.wait_with_output().await {
+            Ok(result) => result,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                return Err(format!("qemu-img failed: {e}"));
+            }
+        };
+        if !result.status.success() {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(format!(
+                "qemu-img failed: {}",
+                String::from_utf8_lossy(&result.stderr).trim()
+            ));
+        }
+        tokio::fs::rename(&partial, &disk_path)
+            .await
+            .map_err(|e| format!("Could not write {}: {e}", disk_path.display()))?;
+
+        let mut config = VMConfig::default();
+        config.set("guest_os", &self.guest_os);
+        config.set("disk_img", format!("{}/disk.qcow2", self.name));
+        for (key, value) in [("ram", &self.ram), ("cpu_cores", &self.cpu_cores)] {
+            if !value.trim().is_empty() {
+                config.set(key, value.trim());
+            }
+        }
+        tokio::fs::write(&config_path, config.serialize())
+            .await
+            .map_err(|e| format!("Could not write {}: {e}", config_path.display()))?;
+        Ok(config_path)
+    }
+}
+
+impl Import {
+    pub fn new(directory: PathBuf) -> Self {
+        Self {
+            source: None,
+            name: String::new(),
+            guest_os_list: State::new(GUEST_OS_LIST.map(String::from).to_vec()),
+            guest_os: Some(String::from("linux")),
+            guess: None,
+            ram: String::new(),
+            cpu_cores: String::new(),
+            directory,
+            job: None,
+            status: None,
+        }
+    }
+    pub fn update(&mut self, message: Message) -> Command<crate::app::Message> {
+        match message {
+            Message::SelectConfig => {
+                let filter = FileFilter::new("quickemu configs").glob("*.conf");
+                return Command::perform(
+                    pick("Select quickemu config", false, Some(filter)),
+                    |file| {
+                        match file {
+                            Ok(Some(file)) => {
+                                crate::app::Message::Import(Message::SelectedConfig(file))
+                            }
+                            Ok(None) => crate::app::Message::Import(Message::None),
+                            Err(e) => {
+                                crate::app::Message::Import(Message::PickFailed(e.to_string()))
+                            }
+                        }
+                        .into()
+                    },
+                );
+            }
+            Message::SelectedConfig(config_path) => match VM::load(config_path.clone()) {
+                Ok(vm) if vm.config.get("guest_os").is_some() => {
+                    self.status = Some(Ok(format!("Added {} to the library", vm.name)));
+                    return register(config_path);
+                }
+                Ok(_) => {
+                    self.status = Some(Err(format!(
+                        "{} is not a quickemu config",
+                        config_path.display()
+                    )))
+                }
+                Err(e) => self.status = Some(Err(e.to_string())),
+            },
+            Message::SelectDisk => {
+                let filter = FileFilter::new("Disk images")
+                    .glob("*.qcow2")
+                    .glob("*.vdi")
+                    .glob("*.vmdk")
+                    .glob("*.img")
+                    .glob("*.raw");
+                return Command::perform(pick("Select disk image", false, Some(filter)), |file| {
+                    match file {
+                        Ok(Some(file)) => crate::app::Message::Import(Message::SelectedDisk(file)),
+                        Ok(None) => crate::app::Message::Import(Message::None),
+                        Err(e) => crate::app::Message::Import(Message::PickFailed(e.to_string())),
+                    }
+                    .into()
+                });
+            }
+            Message::SelectedDisk(source) => {
+                if self.name.is_empty() {
+                    if let Some(stem) = source.file_stem() {
+                        self.name = stem.to_string_lossy().into_owned();
+                    }
+                }
+                self.guess = None;
+                self.source = Some(source.clone());
+                return Command::perform(
+                    async move {
+                        let guess = probe::inspect(source.clone()).await;
+                        (source, guess)
+                    },
+                    |(source, guess)| {
+                        crate::app::Message::Import(Message::Probed(source, guess)).into()
+                    },
+                );
+            }
+            Message::Probed(source, guess) => {
+                // Ignore results for a disk that has since been replaced by another selection.
+                if self.source.as_ref() != Some(&source) {
+                    return Command::none();
+                }
+                if let Some(guess) = &guess {
+                    if GUEST_OS_LIST.contains(&guess.guest_os.as_str()) {
+                        self.guest_os = Some(guess.guest_os.clone());
+                    }
+                    let (ram, cpu_cores) = guess.recommended_resources();
+                    self.ram = ram.to_string();
+                    self.cpu_cores = cpu_cores.to_string();
+                    // Replace the name only if it is still the one derived from the file name.
+                    let default_name = source
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned());
+                    if guess.inspected && default_name.as_deref() == Some(self.name.as_str()) {
+                        self.name = guess.label().replace(' ', "-");
+                    }
+                }
+                self.guess = guess;
+            }
+            Message::SetRAM(ram) => self.ram = ram,
+            Message::SetCPUCores(cpu_cores) => self.cpu_cores = cpu_cores,
+            Message::SetName(name) => self.name = name,
+            Message::SelectedGuestOS(guest_os) => self.guest_os = Some(guest_os),
+            Message::SelectDir => {
+                return Command::perform(pick("Select VM Directory", true, None), |dir| {
+                    match dir {
+                        Ok(Some(dir)) => crate::app::Message::Import(Message::SelectedDir(dir)),
+                        Ok(None) => crate::app::Message::Import(Message::None),
+                        Err(e) => crate::app::Message::Import(Message::PickFailed(e.to_string())),
+                    }
+                    .into()
+                });
+            }
+            Message::SelectedDir(directory) => self.directory = directory,
+            Message::StartImport => {
+                if let (Some(source), Some(guest_os)) = (&self.source, &self.guest_os) {
+                    if self.job.is_none() && !self.name.trim().is_empty() {
+                        self.status = None;
+                        self.job = Some(ConversionJob {
+                            source: source.clone(),
+                            name: self.name.trim().to_string(),
+                            guest_os: guest_os.clone(),
+                            ram: self.ram.clone(),
+                            cpu_cores: self.cpu_cores.clone(),
+                            directory: self.directory.clone(),
+                            progress: 0.0,
+                        });
+                    }
+                }
+            }
+            Message::Progress(progress) => {
+                if let Some(job) = &mut self.job {
+                    job.progress = progress;
+                }
+            }
+            Message::Finished(result) => {
+                self.job = None;
+                match result {
+                    Ok(config_path) => {
+                        self.status = Some(Ok(format!("Imported {}", config_path.display())));
+                        self.source = None;
+                        self.guess = None;
+                        self.name.clear();
+                        return register(config_path);
+                    }
+                    Err(e) => self.status = Some(Err(e)),
+                }
+            }
+            Message::PickFailed(error) => self.status = Some(Err(error)),
+            Message::None => {}
+        }
+        Command::none()
+    }
+    pub fn is_busy(&self) -> bool {
+        self.job.is_some()
+    }
+    pub fn subscription(&self) -> Subscription<crate::app::Message> {
+        let Some(job) = self.job.clone() else {
+            return Subscription::none();
+        };
+        subscription::channel(job.disk_path(), 100, move |mut output| async move {
+            let result = job.run(&mut output).await;
+            let _ = output.send(Message::Finished(result).into()).await;
+            std::future::pending().await
+        })
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        let register_section = widget::column()
+            .push(widget::text::title3("Register an existing VM"))
+            .push(widget::text::caption(
+                "Add a quickemu config from anywhere on disk to the library without moving it.",
+            ))
+            .push(
+                widget::button::standard("Choose config…")
+                    .on_press_maybe(self.job.is_none().then_some(Message::SelectConfig.into())),
+            )
+            .spacing(8);
+
+        let source_text = self
+            .source
+            .as_ref()
+            .map_or(String::from("No disk image selected"), |source| {
+                source.to_string_lossy().into_owned()
+            });
+        let source_row = widget::row()
+            .push(widget::text(source_text).width(Length::Fill))
+            .push(widget::button::standard("Choose disk…").on_press(Message::SelectDisk.into()))
+            .spacing(8);
+        let name_input = widget::text_input("VM name", &self.name)
+            .on_input(|name| Message::SetName(name).into());
+        let guest_os_dropdown = widget::combo_box(
+            &self.guest_os_list,
+            "Guest OS",
+            self.guest_os.as_ref(),
+            |guest_os| Message::SelectedGuestOS(guest_os).into(),
+        );
+        let guess_row = self.guess.as_ref().map(|guess| {
+            let how = if guess.inspected {
+                "inspection"
+            } else {
+                "partition layout"
+            };
+            widget::row()
+                .push(icon::from_name(guess.icon_name()).size(24))
+                .push(widget::text(format!(
+                    "Detected {} from {how}",
+                    guess.label()
+                )))
+                .spacing(8)
+                .align_items(Alignment::Center)
+        });
+        let resources_row = widget::row()
+            .push(widget::text("RAM:  ").width(Length::Shrink))
+            .push(
+                widget::text_input("e.g. 4G", &self.ram)
+                    .on_input(|ram| Message::SetRAM(ram).into())
+                    .width(Length::Fixed(100.0)),
+            )
+            .push(widget::text("  CPU cores:  ").width(Length::Shrink))
+            .push(
+                widget::text_input("e.g. 2", &self.cpu_cores)
+                    .on_input(|cores| Message::SetCPUCores(cores).into())
+                    .width(Length::Fixed(100.0)),
+            );
+        let vm_dir_row = widget::row()
+            .push(widget::text("VM Directory:  ").width(Length::Shrink))
+            .push(
+                widget::text_input("VM Directory", self.directory.to_string_lossy())
+                    .on_input(|dir| Message::SelectedDir(PathBuf::from(dir)).into()),
+            )
+            .push(
+                widget::button::icon(icon::from_name("folder-open-symbolic"))
+                    .on_press(Message::SelectDir.into())
+                    .tooltip("Select VM Directory")
+                    .width(Length::Shrink),
+            );
+
+        let can_import = self.job.is_none()
+            && self.source.is_some()
+            && self.guest_os.is_some()
+            && !self.name.trim().is_empty();
+        let mut disk_section = widget::column()
+            .push(widget::text::title3("Import a disk image"))
+            .push(widget::text::caption(
+                "qcow2, VDI, VMDK and raw images are converted to qcow2 and a new config is generated.",
+            ))
+            .push(source_row)
+            .push_maybe(guess_row)
+            .push(name_input)
+            .push(guest_os_dropdown)
+            .push(resources_row)
+            .push(vm_dir_row)
+            .push(
+                widget::button::suggested("Import")
+                    .on_press_maybe(can_import.then_some(Message::StartImport.into())),
+            )
+            .spacing(8);
+        if let Some(job) = &self.job {
+            disk_section = disk_section
+                .push(widget::progress_bar(0.0..=100.0, job.progress))
+                .push(widget::text(format!("Converting… {:.0}%", job.progress)));
+        }
+
+        let mut column = widget::column()
+            .push(register_section)
+            .push(disk_section)
+            .spacing(24);
+        match &self.status {
+            Some(Ok(status)) => column = column.push(widget::text(status.clone())),
+            Some(Err(e)) => column = column.push(widget::text(format!("Import failed: {e}"))),
+            None => {}
+        }
+        widget::scrollable(column).into()
+    }
+}
+
+fn register(config_path: PathBuf) -> Command<crate::app::Message> {
+    bus::publish(BusEvent::VMAdded(config_path));
+    Command::none()
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(val: Message) -> Self {
+        crate::app::Message::Import(val)
+    }
+}
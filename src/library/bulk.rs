@@ -0,0 +1,401 @@
+use std::path::PathBuf;
+
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{subscription, Alignment, Length, Subscription};
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+
+use super::{Deletion, ItemKind, Message};
+use crate::core::archive::{self, ArchiveFormat, Cancellation, ExportProgress, ExportRequest};
+use crate::core::units::format_size;
+use crate::core::vm::VM;
+
+/// Something done to every ticked VM at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BulkAction {
+    Start,
+    Stop,
+    Tag,
+    Untag,
+    Export,
+    Delete,
+}
+
+impl BulkAction {
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Start => "Start",
+            Self::Stop => "Shut down",
+            Self::Tag => "Tag",
+            Self::Untag => "Untag",
+            Self::Export => "Export",
+            Self::Delete => "Delete",
+        }
+    }
+    /// What it does, for the confirmation; a deletion says how much it removes instead.
+    pub fn detail(&self, tag: &str) -> String {
+        match self {
+            Self::Start => {
+                String::from("Encrypted VMs ask for their passphrase if none is stored.")
+            }
+            Self::Stop => {
+                String::from("Each guest is asked to shut down, as with its power button.")
+            }
+            Self::Tag => format!("Adds the tag {tag}."),
+            Self::Untag => format!("Removes the tag {tag}."),
+            Self::Export => {
+                String::from("Each is archived as .tar.zst next to its config, one after another.")
+            }
+            Self::Delete => String::from("Their files will be permanently removed."),
+        }
+    }
+}
+
+/// "1 VM" or "3 VMs".
+pub fn vm_count(count: usize) -> String {
+    match count {
+        1 => String::from("1 VM"),
+        count => format!("{count} VMs"),
+    }
+}
+
+/// The body of the confirmation asked before a bulk action: what it does, to which VMs, and which
+/// ticked ones it leaves alone and why.
+pub fn summary(detail: &str, targets: &[&VM], skipped: &[(String, String)]) -> String {
+    let names = targets
+        .iter()
+        .map(|vm| vm.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut summary = format!("{detail}\n\n{names}");
+    if !skipped.is_empty() {
+        summary.push_str("\n\nSkipped:");
+        for (name, reason) in skipped {
+            summary.push_str(&format!("\n{name}: {reason}"));
+        }
+    }
+    summary
+}
+
+/// An export or deletion of several VMs, worked through one VM at a time.
+#[derive(Clone, Debug)]
+pub struct BulkRun {
+    action: BulkAction,
+    tasks: Vec<BulkTask>,
+    skipped: Vec<(String, String)>,
+    /// `None` until confirmed.
+    started: Option<Cancellation>,
+    /// The task being worked on.
+    current: Option<usize>,
+    stage: String,
+    done: u64,
+    total: u64,
+    results: Vec<Option<Result<Outcome, String>>>,
+    finished: bool,
+}
+
+#[derive(Clone, Debug)]
+enum BulkTask {
+    Export(ExportRequest),
+    Delete(Deletion),
+}
+
+#[derive(Clone, Debug)]
+pub enum Outcome {
+    Exported(PathBuf),
+    Deleted { reclaimed: u64 },
+}
+
+#[derive(Clone, Debug)]
+pub enum BulkMessage {
+    Started(usize),
+    Progress(ExportProgress),
+    Done(usize, Result<Outcome, String>),
+    Finished,
+    Cancel,
+    Close,
+}
+
+impl BulkTask {
+    fn vm(&self) -> &VM {
+        match self {
+            Self::Export(request) => &request.vm,
+            Self::Delete(deletion) => &deletion.vm,
+        }
+    }
+}
+
+impl Outcome {
+    fn label(&self) -> String {
+        match self {
+            Self::Exported(path) => format!("Exported to {}", path.display()),
+            Self::Deleted { reclaimed } => format!("Deleted, {} freed", format_size(*reclaimed)),
+        }
+    }
+}
+
+impl BulkRun {
+    /// Export each of `vms` as a compressed archive next to its config, as the export dialog
+    /// does by default.
+    pub fn export(vms: Vec<VM>, skipped: Vec<(String, String)>) -> Self {
+        let tasks = vms
+            .into_iter()
+            .map(|vm| {
+                let destination = vm.root().to_path_buf();
+                BulkTask::Export(ExportRequest {
+                    vm,
+                    format: ArchiveFormat::TarZst,
+                    level: 3,
+                    destination,
+                })
+            })
+            .collect();
+        Self::new(BulkAction::Export, tasks, skipped)
+    }
+    pub async fn delete(vms: Vec<VM>, skipped: Vec<(String, String)>) -> Self {
+        let mut tasks = Vec::new();
+        for vm in vms {
+            tasks.push(BulkTask::Delete(Deletion::plan(vm).await));
+        }
+        Self::new(BulkAction::Delete, tasks, skipped)
+    }
+    fn new(action: BulkAction, tasks: Vec<BulkTask>, skipped: Vec<(String, String)>) -> Self {
+        Self {
+            action,
+            results: vec![None; tasks.len()],
+            tasks,
+            skipped,
+            started: None,
+            current: None,
+            stage: String::new(),
+            done: 0,
+            total: 0,
+            finished: false,
+        }
+    }
+    pub fn action(&self) -> BulkAction {
+        self.action
+    }
+    pub fn is_running(&self) -> bool {
+        self.started.is_some() && !self.finished
+    }
+    pub fn skipped(&self) -> &[(String, String)] {
+        &self.skipped
+    }
+    pub fn vms(&self) -> impl Iterator<Item = &VM> {
+        self.tasks.iter().map(BulkTask::vm)
+    }
+    /// The VM of the task at `index`, e.g. to record a finished export in its timeline.
+    pub fn vm(&self, index: usize) -> Option<&VM> {
+        self.tasks.get(index).map(BulkTask::vm)
+    }
+    /// What the action does, for the confirmation.
+    pub fn detail(&self) -> String {
+        match self.action {
+            BulkAction::Delete => {
+                let (files, size) = self
+                    .tasks
+                    .iter()
+                    .filter_map(|task| match task {
+                        BulkTask::Delete(deletion) => Some(deletion),
+                        BulkTask::Export(_) => None,
+                    })
+                    .flat_map(Deletion::selected)
+                    .fold((0, 0), |(files, size), item| (files + 1, size + item.size));
+                format!(
+                    "Their configs, disks, installer images and EFI variables, {files} files \
+                     ({}), will be permanently removed. This can't be undone.",
+                    format_size(size)
+                )
+            }
+            action => action.detail(""),
+        }
+    }
+    pub fn start(&mut self) {
+        if self.started.is_none() {
+            self.started = Some(Cancellation::default());
+        }
+    }
+    pub fn update(&mut self, message: BulkMessage) {
+        match message {
+            BulkMessage::Started(index) => {
+                self.current = Some(index);
+                self.stage = String::from("Starting");
+                self.done = 0;
+                self.total = 0;
+            }
+            BulkMessage::Progress(ExportProgress::Stage(stage)) => self.stage = stage,
+            BulkMessage::Progress(ExportProgress::Bytes { done, total }) => {
+                self.done = done;
+                self.total = total;
+            }
+            BulkMessage::Done(index, result) => {
+                if let Some(slot) = self.results.get_mut(index) {
+                    *slot = Some(result);
+                }
+                self.current = None;
+            }
+            BulkMessage::Finished => self.finished = true,
+            BulkMessage::Cancel => {
+                if let Some(cancellation) = &self.started {
+                    cancellation.cancel();
+                }
+            }
+            BulkMessage::Close => {}
+        }
+    }
+    pub fn subscription(&self) -> Subscription<crate::app::Message> {
+        let Some(cancellation) = self.started.clone().filter(|_| !self.finished) else {
+            return Subscription::none();
+        };
+        let id = (
+            self.action,
+            self.vms()
+                .map(|vm| vm.config_path.clone())
+                .collect::<Vec<_>>(),
+        );
+        let tasks = self.tasks.clone();
+        subscription::channel(id, 100, move |mut output| async move {
+            for (index, task) in tasks.into_iter().enumerate() {
+                // Cancelling stops the export in progress, and deletions before the next VM.
+                if cancellation.is_cancelled() {
+                    break;
+                }
+                let _ = output
+                    .send(Message::Bulk(BulkMessage::Started(index)).into())
+                    .await;
+                let result = match task {
+                    BulkTask::Delete(deletion) => delete(deletion).await,
+                    BulkTask::Export(request) => {
+                        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+                        let cancellation = cancellation.clone();
+                        let export = tokio::task::spawn_blocking(move || {
+                            archive::export(&request, &cancellation, &tx)
+                        });
+                        while let Some(progress) = rx.recv().await {
+                            let msg = Message::Bulk(BulkMessage::Progress(progress));
+                            let _ = output.send(msg.into()).await;
+                        }
+                        export
+                            .await
+                            .unwrap_or_else(|e| Err(e.to_string()))
+                            .map(Outcome::Exported)
+                    }
+                };
+                let _ = output
+                    .send(Message::Bulk(BulkMessage::Done(index, result)).into())
+                    .await;
+            }
+            let _ = output
+                .send(Message::Bulk(BulkMessage::Finished).into())
+                .await;
+            std::future::pending().await
+        })
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        let count = self.tasks.len();
+        let completed = self.results.iter().flatten().count();
+        let failed = self
+            .results
+            .iter()
+            .flatten()
+            .filter(|result| result.is_err())
+            .count();
+        let verb = match self.action {
+            BulkAction::Delete => "Deleting",
+            _ => "Exporting",
+        };
+        // The VM in progress counts for the part of it that is done.
+        let current_part = match self.current {
+            Some(_) if self.total > 0 => self.done as f32 / self.total as f32,
+            _ => 0.0,
+        };
+        let mut status = format!("{completed} of {count} done");
+        if failed > 0 {
+            status.push_str(&format!(", {failed} failed"));
+        }
+        if self.finished && completed < count {
+            status.push_str(", the rest was cancelled");
+        }
+
+        let mut list = widget::list_column();
+        for (index, task) in self.tasks.iter().enumerate() {
+            let (icon_name, detail) = match &self.results[index] {
+                Some(Ok(outcome)) => ("emblem-ok-symbolic", outcome.label()),
+                Some(Err(e)) => ("dialog-error-symbolic", e.clone()),
+                None if self.current == Some(index) => (
+                    "content-loading-symbolic",
+                    match self.action {
+                        BulkAction::Export if self.total > 0 => format!(
+                            "{} ({} of {})",
+                            self.stage,
+                            format_size(self.done),
+                            format_size(self.total)
+                        ),
+                        BulkAction::Export => self.stage.clone(),
+                        _ => String::from("Removing files"),
+                    },
+                ),
+                None => ("content-loading-symbolic", String::from("Waiting")),
+            };
+            list = list.add(
+                widget::row()
+                    .push(icon::from_name(icon_name).size(16).icon())
+                    .push(widget::text(task.vm().name.clone()).width(Length::Fill))
+                    .push(widget::text::caption(detail))
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            );
+        }
+        for (name, reason) in &self.skipped {
+            list = list.add(
+                widget::row()
+                    .push(
+                        icon::from_name("action-unavailable-symbolic")
+                            .size(16)
+                            .icon(),
+                    )
+                    .push(widget::text(name.clone()).width(Length::Fill))
+                    .push(widget::text::caption(format!("Skipped: {reason}")))
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            );
+        }
+
+        let button = if self.is_running() {
+            widget::button::destructive("Cancel")
+                .on_press(Message::Bulk(BulkMessage::Cancel).into())
+        } else {
+            widget::button::standard("Close").on_press(Message::Bulk(BulkMessage::Close).into())
+        };
+        widget::column()
+            .push(widget::text::title3(format!("{verb} {}", vm_count(count))))
+            .push(widget::progress_bar(
+                0.0..=count.max(1) as f32,
+                completed as f32 + current_part,
+            ))
+            .push(widget::text::caption(status))
+            .push(widget::scrollable(list).height(Length::Fill))
+            .push(button)
+            .spacing(12)
+            .into()
+    }
+}
+
+/// Remove everything a VM's deletion planned, the config last so an interrupted deletion still
+/// shows up in the library.
+async fn delete(deletion: Deletion) -> Result<Outcome, String> {
+    let mut queue = deletion.selected().cloned().collect::<Vec<_>>();
+    queue.sort_by_key(|item| item.kind == ItemKind::Config);
+    let mut reclaimed = 0;
+    for item in queue {
+        tokio::fs::remove_file(&item.path)
+            .await
+            .map_err(|e| format!("Could not remove {}: {e}", item.path.display()))?;
+        reclaimed += item.size;
+    }
+    let _ = tokio::fs::remove_file(deletion.vm.metadata_path()).await;
+    let _ = tokio::fs::remove_file(deletion.vm.thumbnail_path()).await;
+    let _ = tokio::fs::remove_dir(deletion.vm.vm_dir()).await;
+    Ok(Outcome::Deleted { reclaimed })
+}
@@ -0,0 +1,271 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::app::Command;
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{subscription, Alignment, Subscription};
+use cosmic::widget;
+use cosmic::Element;
+
+use super::Message;
+use crate::core::disk::{self, DiskInfo, Maintenance};
+use crate::core::units::{format_size, parse_size};
+use crate::core::vm::VM;
+
+#[derive(Clone, Debug)]
+pub struct DiskPanel {
+    vm: VM,
+    /// Linked clones backed by this disk, which rewriting it in place would break.
+    clones: usize,
+    info: Option<Result<DiskInfo, String>>,
+    /// New virtual size as typed, e.g. `128G`.
+    size: String,
+    /// Index into [`disk::FORMATS`].
+    format: Option<usize>,
+    running: Option<RunningMaintenance>,
+    result: Option<Result<String, String>>,
+}
+
+#[derive(Clone, Debug)]
+struct RunningMaintenance {
+    action: Maintenance,
+    progress: f32,
+}
+
+#[derive(Clone, Debug)]
+pub enum DiskMessage {
+    Inspected(Result<DiskInfo, String>),
+    SetSize(String),
+    SetFormat(usize),
+    Start(Maintenance),
+    Progress(f32),
+    Finished(Result<(), String>),
+    Close,
+}
+
+impl DiskPanel {
+    pub fn new(vm: VM, clones: usize) -> (Self, Command<crate::app::Message>) {
+        let panel = Self {
+            vm,
+            clones,
+            info: None,
+            size: String::new(),
+            format: None,
+            running: None,
+            result: None,
+        };
+        let command = panel.inspect();
+        (panel, command)
+    }
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+    pub fn vm(&self) -> &VM {
+        &self.vm
+    }
+    /// The operation in progress, if any.
+    pub fn action(&self) -> Option<&Maintenance> {
+        self.running.as_ref().map(|running| &running.action)
+    }
+    fn inspect(&self) -> Command<crate::app::Message> {
+        let disk = self.vm.system_disk();
+        Command::perform(async move { disk::info(&disk).await }, |info| {
+            crate::app::Message::Library(Message::Disk(DiskMessage::Inspected(info))).into()
+        })
+    }
+    pub fn update(&mut self, message: DiskMessage) -> Command<crate::app::Message> {
+        match message {
+            DiskMessage::Inspected(info) => {
+                if let Ok(info) = &info {
+                    self.format = disk::FORMATS
+                        .iter()
+                        .position(|format| *format != info.format);
+                }
+                self.info = Some(info);
+            }
+            DiskMessage::SetSize(size) => self.size = size,
+            DiskMessage::SetFormat(format) => self.format = Some(format),
+            DiskMessage::Start(action) => {
+                let rewrites = matches!(action, Maintenance::Compact | Maintenance::Convert(_));
+                if self.running.is_none() && !(rewrites && self.clones > 0) {
+                    self.result = None;
+                    self.running = Some(RunningMaintenance {
+                        action,
+                        progress: 0.0,
+                    });
+                }
+            }
+            DiskMessage::Progress(progress) => {
+                if let Some(running) = &mut self.running {
+                    running.progress = progress;
+                }
+            }
+            DiskMessage::Finished(result) => {
+                if let Some(running) = self.running.take() {
+                    self.result = Some(result.map(|()| running.action.done_message()));
+                    if let (Maintenance::Convert(_), Ok(vm)) =
+                        (&running.action, VM::load(self.vm.config_path.clone()))
+                    {
+                        self.vm = vm;
+                    }
+                    self.size.clear();
+                    return self.inspect();
+                }
+            }
+            DiskMessage::Close => {}
+        }
+        Command::none()
+    }
+    pub fn subscription(&self) -> Subscription<crate::app::Message> {
+        let Some(running) = &self.running else {
+            return Subscription::none();
+        };
+        let vm = self.vm.clone();
+        let action = running.action.clone();
+        let id = (vm.config_path.clone(), action.clone());
+        subscription::channel(id, 100, move |mut output| async move {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let task = tokio::spawn(async move { disk::run(&vm, &action, &tx).await });
+            while let Some(progress) = rx.recv().await {
+                let msg = Message::Disk(DiskMessage::Progress(progress));
+                let _ = output.send(msg.into()).await;
+            }
+            let result = task.await.unwrap_or_else(|e| Err(e.to_string()));
+            let _ = output
+                .send(Message::Disk(DiskMessage::Finished(result)).into())
+                .await;
+            std::future::pending().await
+        })
+    }
+    /// `vm_running` disables the actions, which need exclusive access to the image.
+    pub fn view(&self, vm_running: bool) -> Element<crate::app::Message> {
+        let mut column = widget::column()
+            .push(widget::text::title3(format!("Disk of {}", self.vm.name)))
+            .spacing(12);
+
+        let info = match &self.info {
+            None => {
+                column = column.push(widget::text("Inspecting disk…"));
+                None
+            }
+            Some(Err(e)) => {
+                column = column.push(widget::text(e.clone()));
+                None
+            }
+            Some(Ok(info)) => Some(info),
+        };
+        if let Some(info) = info {
+            column = column
+                .push(widget::text::caption(info.path.display().to_string()))
+                .push(widget::text(format!(
+                    "{}: {} used of {} virtual",
+                    info.format,
+                    format_size(info.actual_size),
+                    format_size(info.virtual_size)
+                )));
+            if info.snapshots > 0 {
+                column = column.push(widget::text::caption(format!(
+                    "Has {} snapshots, which compacting or converting would discard; \
+                     delete them first",
+                    info.snapshots
+                )));
+            }
+        }
+        let idle = self.running.is_none() && !vm_running && !self.vm.is_encrypted();
+        let rewritable = info.filter(|info| idle && info.snapshots == 0 && self.clones == 0);
+        if self.clones > 0 {
+            column = column.push(widget::text::caption(
+                "Linked clones are based on this template's disk, so it can't be compacted or \
+                 converted",
+            ));
+        }
+
+        let compact_button = widget::button::standard("Compact").on_press_maybe(
+            rewritable
+                .filter(|info| info.format == "qcow2")
+                .map(|_| Message::Disk(DiskMessage::Start(Maintenance::Compact)).into()),
+        );
+        column = column.push(
+            widget::column()
+                .push(compact_button)
+                .push(widget::text::caption(
+                    "Frees the space of blocks the guest has deleted. Run fstrim, or \
+                     \"Optimize Drives\" on Windows, in the guest first.",
+                ))
+                .spacing(4),
+        );
+
+        let new_size = parse_size(&self.size);
+        let grow = info
+            .filter(|_| idle)
+            .zip(new_size)
+            .filter(|(info, size)| *size > info.virtual_size)
+            .map(|(_, size)| Message::Disk(DiskMessage::Start(Maintenance::Grow(size))).into());
+        column = column.push(
+            widget::column()
+                .push(
+                    widget::row()
+                        .push(
+                            widget::text_input("New size, e.g. 128G", &self.size)
+                                .on_input(|size| Message::Disk(DiskMessage::SetSize(size)).into()),
+                        )
+                        .push(widget::button::standard("Grow").on_press_maybe(grow))
+                        .spacing(8)
+                        .align_items(Alignment::Center),
+                )
+                .push(widget::text::caption(
+                    "The guest still has to grow its partition and filesystem into the new space.",
+                ))
+                .spacing(4),
+        );
+
+        let target = self.format.and_then(|format| disk::FORMATS.get(format));
+        let convert = rewritable
+            .zip(target)
+            .filter(|(info, format)| info.format != **format)
+            .map(|(_, format)| {
+                Message::Disk(DiskMessage::Start(Maintenance::Convert(format.to_string()))).into()
+            });
+        column = column.push(
+            widget::row()
+                .push(widget::text("Convert to"))
+                .push(widget::dropdown(&disk::FORMATS, self.format, |format| {
+                    Message::Disk(DiskMessage::SetFormat(format)).into()
+                }))
+                .push(widget::button::standard("Convert").on_press_maybe(convert))
+                .spacing(8)
+                .align_items(Alignment::Center),
+        );
+
+        if vm_running {
+            column = column.push(widget::text::caption(format!(
+                "Shut down {} to change its disk",
+                self.vm.name
+            )));
+        } else if self.vm.is_encrypted() {
+            column = column.push(widget::text::caption(
+                "Maintenance of encrypted disks isn't supported yet",
+            ));
+        }
+        if let Some(running) = &self.running {
+            column = column
+                .push(widget::progress_bar(0.0..=100.0, running.progress))
+                .push(widget::text(format!(
+                    "{} ({:.0}%)",
+                    running.action.label(),
+                    running.progress
+                )));
+        }
+        match &self.result {
+            Some(Ok(done)) => column = column.push(widget::text(done.clone())),
+            Some(Err(e)) => column = column.push(widget::text(format!("Failed: {e}"))),
+            None => {}
+        }
+
+        let close_button = widget::button::standard("Close").on_press_maybe(
+            self.running
+                .is_none()
+                .then_some(Message::Disk(DiskMessage::Close).into()),
+        );
+        widget::scrollable(column.push(close_button)).into()
+    }
+}
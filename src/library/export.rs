@@ -0,0 +1,232 @@
+use std::path::PathBuf;
+
+use cosmic::app::Command;
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{subscription, Length, Subscription};
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+
+use super::Message;
+use crate::core::archive::{self, ArchiveFormat, Cancellation, ExportProgress, ExportRequest};
+use crate::core::portal::pick;
+use crate::core::units::format_size;
+use crate::core::vm::VM;
+
+#[derive(Clone, Debug)]
+pub struct ExportDialog {
+    request: ExportRequest,
+    running: Option<RunningExport>,
+    result: Option<Result<PathBuf, String>>,
+}
+
+#[derive(Clone, Debug)]
+struct RunningExport {
+    cancellation: Cancellation,
+    stage: String,
+    done: u64,
+    total: u64,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExportMessage {
+    None,
+    /// The file chooser portal didn't open.
+    PickFailed(String),
+    SetFormat(ArchiveFormat),
+    SetLevel(i32),
+    SelectDir,
+    SelectedDir(PathBuf),
+    Start,
+    Cancel,
+    Progress(ExportProgress),
+    Finished(Result<PathBuf, String>),
+    Close,
+}
+
+impl ExportDialog {
+    pub fn new(vm: VM, destination: PathBuf) -> Self {
+        Self {
+            request: ExportRequest {
+                vm,
+                format: ArchiveFormat::TarZst,
+                level: 3,
+                destination,
+            },
+            running: None,
+            result: None,
+        }
+    }
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+    pub fn vm(&self) -> &VM {
+        &self.request.vm
+    }
+    pub fn update(&mut self, message: ExportMessage) -> Command<crate::app::Message> {
+        // The subscription is keyed on the request, so it must not change mid-export.
+        let editing = matches!(
+            message,
+            ExportMessage::SetFormat(_)
+                | ExportMessage::SetLevel(_)
+                | ExportMessage::SelectedDir(_)
+        );
+        if editing && self.is_running() {
+            return Command::none();
+        }
+        match message {
+            ExportMessage::SetFormat(format) => self.request.format = format,
+            ExportMessage::SetLevel(level) => self.request.level = level,
+            ExportMessage::SelectDir => {
+                return Command::perform(pick("Select Export Directory", true, None), |dir| {
+                    let msg = match dir {
+                        Ok(Some(dir)) => ExportMessage::SelectedDir(dir),
+                        Ok(None) => ExportMessage::None,
+                        Err(e) => ExportMessage::PickFailed(e.to_string()),
+                    };
+                    crate::app::Message::Library(Message::Export(msg)).into()
+                });
+            }
+            ExportMessage::SelectedDir(destination) => self.request.destination = destination,
+            ExportMessage::Start => {
+                if self.running.is_none() {
+                    self.result = None;
+                    self.running = Some(RunningExport {
+                        cancellation: Cancellation::default(),
+                        stage: String::from("Starting"),
+                        done: 0,
+                        total: 0,
+                    });
+                }
+            }
+            ExportMessage::Cancel => {
+                if let Some(running) = &self.running {
+                    running.cancellation.cancel();
+                }
+            }
+            ExportMessage::Progress(progress) => {
+                if let Some(running) = &mut self.running {
+                    match progress {
+                        ExportProgress::Stage(stage) => running.stage = stage,
+                        ExportProgress::Bytes { done, total } => {
+                            running.done = done;
+                            running.total = total;
+                        }
+                    }
+                }
+            }
+            ExportMessage::Finished(result) => {
+                self.running = None;
+                self.result = Some(result);
+            }
+            // Shown where the result of an export would be; the directory can still be typed in.
+            ExportMessage::PickFailed(error) => self.result = Some(Err(error)),
+            ExportMessage::Close | ExportMessage::None => {}
+        }
+        Command::none()
+    }
+    pub fn subscription(&self) -> Subscription<crate::app::Message> {
+        let Some(running) = &self.running else {
+            return Subscription::none();
+        };
+        let request = self.request.clone();
+        let cancellation = running.cancellation.clone();
+        subscription::channel(request.output_path(), 100, move |mut output| async move {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let task =
+                tokio::task::spawn_blocking(move || archive::export(&request, &cancellation, &tx));
+            while let Some(progress) = rx.recv().await {
+                let msg = Message::Export(ExportMessage::Progress(progress));
+                let _ = output.send(msg.into()).await;
+            }
+            let result = task.await.unwrap_or_else(|e| Err(e.to_string()));
+            let _ = output
+                .send(Message::Export(ExportMessage::Finished(result)).into())
+                .await;
+            std::future::pending().await
+        })
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        let request = &self.request;
+        let idle = self.running.is_none();
+
+        let mut format_row = widget::row().spacing(12);
+        for format in [ArchiveFormat::TarZst, ArchiveFormat::Ova] {
+            format_row = format_row.push(widget::radio(
+                format.to_string(),
+                format,
+                Some(request.format),
+                |format| Message::Export(ExportMessage::SetFormat(format)).into(),
+            ));
+        }
+
+        let mut column = widget::column()
+            .push(widget::text::title3(format!("Export {}", request.vm.name)))
+            .push(format_row)
+            .spacing(12);
+
+        if request.format == ArchiveFormat::TarZst {
+            let level_slider = widget::slider(1..=19, request.level, |level| {
+                Message::Export(ExportMessage::SetLevel(level)).into()
+            });
+            column = column.push(
+                widget::row()
+                    .push(widget::text("Compression level:  ").width(Length::Shrink))
+                    .push(level_slider)
+                    .push(widget::text(format!("  {}", request.level)).width(Length::Shrink)),
+            );
+        }
+
+        let dir_row = widget::row()
+            .push(widget::text("Destination:  ").width(Length::Shrink))
+            .push(
+                widget::text_input("Destination", request.destination.to_string_lossy()).on_input(
+                    |dir| Message::Export(ExportMessage::SelectedDir(PathBuf::from(dir))).into(),
+                ),
+            )
+            .push(
+                widget::button::icon(icon::from_name("folder-open-symbolic"))
+                    .on_press(Message::Export(ExportMessage::SelectDir).into())
+                    .tooltip("Select Export Directory")
+                    .width(Length::Shrink),
+            );
+        column = column.push(dir_row).push(widget::text::caption(format!(
+            "Writes {}",
+            request.output_path().display()
+        )));
+
+        if let Some(running) = &self.running {
+            let bar = widget::progress_bar(0.0..=running.total.max(1) as f32, running.done as f32);
+            column = column.push(bar).push(widget::text(format!(
+                "{} ({} of {})",
+                running.stage,
+                format_size(running.done),
+                format_size(running.total)
+            )));
+        }
+        match &self.result {
+            Some(Ok(path)) => {
+                column = column.push(widget::text(format!("Exported to {}", path.display())))
+            }
+            Some(Err(e)) => column = column.push(widget::text(format!("Export failed: {e}"))),
+            None => {}
+        }
+
+        let buttons = if idle {
+            widget::row()
+                .push(
+                    widget::button::standard("Close")
+                        .on_press(Message::Export(ExportMessage::Close).into()),
+                )
+                .push(
+                    widget::button::suggested("Export")
+                        .on_press(Message::Export(ExportMessage::Start).into()),
+                )
+        } else {
+            widget::row().push(
+                widget::button::destructive("Cancel")
+                    .on_press(Message::Export(ExportMessage::Cancel).into()),
+            )
+        };
+        widget::scrollable(column.push(buttons.spacing(8))).into()
+    }
+}
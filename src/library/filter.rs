@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::core::metadata::Metadata;
+use crate::core::vm::VM;
+
+/// Heading of the group collecting VMs without tags, listed after every tag.
+const UNTAGGED: &str = "Untagged";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    #[default]
+    None,
+    Tag,
+    Family,
+    State,
+}
+
+impl GroupBy {
+    pub const ALL: [Self; 4] = [Self::None, Self::Tag, Self::Family, Self::State];
+    /// Dropdown entries, in the order of [`GroupBy::ALL`].
+    pub const CHOICES: [&'static str; 4] = [
+        "No grouping",
+        "Group by tag",
+        "Group by OS",
+        "Group by state",
+    ];
+}
+
+/// Which VMs the library lists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    #[default]
+    All,
+    Running,
+    Stopped,
+    Tag(String),
+    Family(String),
+}
+
+impl Filter {
+    pub fn label(&self) -> String {
+        match self {
+            Self::All => String::from("All VMs"),
+            Self::Running => String::from("Running"),
+            Self::Stopped => String::from("Stopped"),
+            Self::Tag(tag) => format!("Tagged {tag}"),
+            Self::Family(family) => family.clone(),
+        }
+    }
+    pub fn matches(&self, vm: &VM, metadata: Option<&Metadata>, running: bool) -> bool {
+        match self {
+            Self::All => true,
+            Self::Running => running,
+            Self::Stopped => !running,
+            Self::Tag(tag) => metadata.is_some_and(|metadata| metadata.has_tag(tag)),
+            Self::Family(name) => family(vm) == *name,
+        }
+    }
+    /// Filters offered for `vms`: states, then the OS families and tags present among them.
+    pub fn available(vms: &[VM], metadata: &HashMap<PathBuf, Metadata>) -> Vec<Self> {
+        let mut families = vms.iter().map(family).collect::<Vec<_>>();
+        families.sort();
+        families.dedup();
+        let mut tags = vms
+            .iter()
+            .filter_map(|vm| metadata.get(&vm.config_path))
+            .flat_map(|metadata| metadata.tags.iter().cloned())
+            .collect::<Vec<_>>();
+        tags.sort_by_key(|tag| tag.to_lowercase());
+        tags.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        [Self::All, Self::Running, Self::Stopped]
+            .into_iter()
+            .chain(families.into_iter().map(Self::Family))
+            .chain(tags.into_iter().map(Self::Tag))
+            .collect()
+    }
+}
+
+/// The OS family of a VM's guest, from the quickemu `guest_os` value.
+pub fn family(vm: &VM) -> String {
+    match vm.config.get("guest_os").unwrap_or_default() {
+        "" => String::from("Unknown OS"),
+        "linux" | "linux_old" => String::from("Linux"),
+        "windows" | "windows-server" => String::from("Windows"),
+        "macos" => String::from("macOS"),
+        "freebsd" | "ghostbsd" | "netbsd" | "openbsd" => String::from("BSD"),
+        other => {
+            let mut chars = other.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+    }
+}
+
+/// Group headings a VM is listed under; tagged VMs appear once per tag.
+pub fn groups(group_by: GroupBy, vm: &VM, metadata: Option<&Metadata>, state: &str) -> Vec<String> {
+    match group_by {
+        GroupBy::None => vec![String::new()],
+        GroupBy::Tag => match metadata.map(|metadata| &metadata.tags) {
+            Some(tags) if !tags.is_empty() => tags.clone(),
+            _ => vec![String::from(UNTAGGED)],
+        },
+        GroupBy::Family => vec![family(vm)],
+        GroupBy::State => vec![state.to_string()],
+    }
+}
+
+/// Alphabetical, except that untagged VMs come last.
+pub fn compare_groups(a: &str, b: &str) -> std::cmp::Ordering {
+    (a == UNTAGGED)
+        .cmp(&(b == UNTAGGED))
+        .then_with(|| a.to_lowercase().cmp(&b.to_lowercase()))
+}
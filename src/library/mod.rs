@@ -0,0 +1,2640 @@
+mod bulk;
+mod disk;
+mod export;
+mod filter;
+mod passthrough;
+mod storage;
+mod verify;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use cosmic::app::Command;
+use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::keyboard::{self, Modifiers};
+use cosmic::iced::{event, Alignment, Length, Subscription};
+use cosmic::widget::{self, icon, tooltip};
+use cosmic::{theme, Apply, Element};
+use itertools::Itertools;
+use quickget_core::QuickgetInstance;
+
+use crate::config::DoubleClickAction;
+use crate::confirm::Confirmation;
+use crate::core::appearance::{self, AccentColor, Appearance};
+use crate::core::autostart;
+use crate::core::bus::{self, BusEvent};
+use crate::core::config_keys::{ExtraKey, EDIT_MANAGED};
+use crate::core::devices;
+use crate::core::edit::{self, VMEdit};
+use crate::core::encryption;
+use crate::core::error::{AppError, ErrorCategory};
+use crate::core::guest_network::{self, NetworkMode};
+use crate::core::host_probe::{self, HostCapabilities};
+use crate::core::installers;
+use crate::core::launcher;
+use crate::core::metadata::{self, ActivityKind, ChecklistItem, Metadata};
+use crate::core::overcommit::{Commitments, Overcommit};
+use crate::core::qmp::{self, RunState};
+use crate::core::release_watch::{self, ReleaseNotice};
+use crate::core::snapshot;
+use crate::core::spec::VMSpec;
+use crate::core::template;
+use crate::core::units::{format_age, format_size, parse_size};
+use crate::core::vm::{self, VM};
+use crate::core::watch;
+use crate::widgets::error_view::{error_view, ErrorActions};
+use crate::widgets::extra_keys::{extra_keys_view, ExtraKeyActions};
+use crate::widgets::gated::gated;
+use crate::widgets::overcommit::overcommit_view;
+use crate::widgets::status_badge::{status_badge, Status};
+use bulk::{vm_count, BulkAction, BulkMessage, BulkRun, Outcome};
+use disk::{DiskMessage, DiskPanel};
+use export::{ExportDialog, ExportMessage};
+use filter::{Filter, GroupBy};
+use passthrough::{PassthroughMessage, PassthroughPanel};
+use storage::{StorageManager, StorageMessage};
+use verify::{VerifyMessage, VerifyPanel};
+
+#[derive(Default, Clone, Debug)]
+pub struct Library {
+    roots: Vec<PathBuf>,
+    registered: Vec<PathBuf>,
+    vms: Vec<VM>,
+    running: Vec<PathBuf>,
+    /// Running VMs that QEMU reports as paused.
+    paused: Vec<PathBuf>,
+    errors: HashMap<PathBuf, String>,
+    metadata: HashMap<PathBuf, Metadata>,
+    thumbnails: HashMap<PathBuf, widget::image::Handle>,
+    selected: Option<PathBuf>,
+    /// VMs ticked for a bulk action, by config.
+    checked: Vec<PathBuf>,
+    /// Held down now, so Ctrl+click can tick rows.
+    modifiers: Modifiers,
+    /// Added to or removed from the ticked VMs.
+    bulk_tag: String,
+    /// An export or deletion of the ticked VMs waiting for confirmation.
+    pending_bulk: Option<BulkRun>,
+    last_click: Option<(PathBuf, Instant)>,
+    inline_edit: Option<InlineEdit>,
+    double_click_action: DoubleClickAction,
+    /// Startup probe results; `None` until the probe finishes.
+    host: Option<Arc<HostCapabilities>>,
+    error_details: bool,
+    group_by: GroupBy,
+    filter: Filter,
+    /// Filters offered for the current VMs, with their dropdown labels.
+    filters: Vec<Filter>,
+    filter_labels: Vec<String>,
+    /// Set once the autostart VMs were started after the first scan.
+    autostarted: bool,
+    /// Delete a VM's downloaded installer images when its first session ends.
+    delete_installers_after_boot: bool,
+    /// VM name asked for on the command line, launched once the library is scanned.
+    pending_launch: Option<String>,
+    /// Outdated or end-of-life releases, by VM config.
+    release_notices: HashMap<PathBuf, ReleaseNotice>,
+    /// Set once the releases were first checked after a scan.
+    releases_checked: bool,
+    page: Page,
+}
+
+/// How often running VMs are asked for their state over QMP.
+const STATUS_POLL: Duration = Duration::from_secs(5);
+
+/// How often running VMs' displays are captured for their thumbnails.
+const THUMBNAIL_INTERVAL: Duration = Duration::from_secs(3);
+const THUMBNAIL_WIDTH: u16 = 96;
+const THUMBNAIL_HEIGHT: u16 = 60;
+
+/// Two presses on the same row within this interval count as a double-click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Name, resources, networking and appearance being edited directly in a list row.
+#[derive(Clone, Debug)]
+struct InlineEdit {
+    config_path: PathBuf,
+    edit: VMEdit,
+    /// Saved to the VM's metadata rather than its config.
+    appearance: Appearance,
+    /// Comma-separated, as typed; saved to the metadata.
+    tags: String,
+    /// Host bridges, discovered when the edit began.
+    bridges: Vec<String>,
+    /// Missing prerequisites of the chosen bridge.
+    network_warning: Option<String>,
+    /// Whether the hand-written config keys are shown.
+    show_advanced: bool,
+    error: Option<String>,
+}
+
+impl InlineEdit {
+    fn set_network(&mut self, network: NetworkMode) {
+        self.network_warning = match &network {
+            NetworkMode::Bridged(bridge) if !bridge.is_empty() => {
+                guest_network::bridge_warning(bridge)
+            }
+            NetworkMode::Bridged(_) if self.bridges.is_empty() => {
+                Some(String::from("This host has no bridge interfaces"))
+            }
+            _ => None,
+        };
+        self.edit.network = network;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    Refresh,
+    Scanned(Vec<VM>),
+    Launch(usize),
+    PassphraseLookedUp(VM, Option<String>),
+    SetUnlockPassphrase(String),
+    SetRememberPassphrase(bool),
+    Unlock,
+    CancelUnlock,
+    Started(VM, Result<(), String>),
+    PollStatus,
+    CaptureThumbnails,
+    /// A thumbnail PNG, captured from a running VM or read back from the last capture.
+    ThumbnailCaptured(PathBuf, Result<Vec<u8>, String>),
+    StatusQueried(PathBuf, Result<RunState, String>),
+    Pause(usize),
+    Resume(usize),
+    PowerDown(usize),
+    /// Ask before stopping QEMU outright.
+    RequestForceOff(usize),
+    ForceOff(PathBuf),
+    /// Result of a QMP control command.
+    Controlled(VM, Result<(), String>),
+    /// Result of opening a viewer or SSH session for a VM.
+    Opened(PathBuf, Result<(), String>),
+    RowPressed(usize),
+    SetChecked(usize, bool),
+    /// Tick every VM the filter shows, or untick all.
+    CheckAll(bool),
+    ModifiersChanged(Modifiers),
+    SetBulkTag(String),
+    RequestBulk(BulkAction),
+    /// Start, stop or tag the ticked VMs, once confirmed.
+    ConfirmBulk(BulkAction),
+    /// An export or deletion of the ticked VMs, to be confirmed.
+    BulkPlanned(Box<BulkRun>),
+    StartBulk,
+    DiscardBulk,
+    Bulk(BulkMessage),
+    EditSelected,
+    SetEditName(String),
+    SetEditRAM(String),
+    SetEditCPUCores(String),
+    /// Index into [`appearance::COLOR_CHOICES`].
+    SetEditColor(usize),
+    SetEditGlyph(String),
+    SetEditTags(String),
+    /// Index into [`guest_network::MODE_CHOICES`].
+    SetEditNetwork(usize),
+    SetEditBridge(usize),
+    SetEditMac(String),
+    /// Index into [`devices::SOUND_CARD_CHOICES`].
+    SetEditSoundCard(usize),
+    SetEditMicrophone(bool),
+    SetEditTablet(bool),
+    ToggleEditAdvanced,
+    SetEditExtraKey(usize, String),
+    SetEditExtraValue(usize, String),
+    RemoveEditExtra(usize),
+    AddEditExtra,
+    CommitEdit,
+    CancelEdit,
+    EditApplied(Result<VM, String>),
+    RequestDelete(usize),
+    DeletionPlanned(Box<Deletion>),
+    SetKeepInstaller(bool),
+    /// Ask before removing the selected files.
+    DeleteSelected,
+    ConfirmDelete,
+    CancelDelete,
+    Removed(Result<(), AppError>),
+    ToggleErrorDetails,
+    CopyErrorReport,
+    RetryDelete,
+    RequestExport(usize),
+    Export(ExportMessage),
+    OpenDisk(usize),
+    Disk(DiskMessage),
+    OpenStorage,
+    Storage(StorageMessage),
+    OpenPassthrough,
+    Passthrough(PassthroughMessage),
+    InstallersCleaned(PathBuf, Result<u64, String>),
+    /// Re-check the installer images of the VM with this config, or of every VM.
+    VerifyImages(Option<PathBuf>),
+    Verify(VerifyMessage),
+    OpenChecklist(usize),
+    SetChecklistItem(ChecklistItem, bool),
+    SetNotes(String),
+    SaveNotes,
+    TakeSnapshot,
+    SnapshotTaken(PathBuf, Result<(), String>),
+    DismissChecklist,
+    CloseChecklist,
+    /// Index into [`GroupBy::ALL`].
+    SetGroupBy(usize),
+    /// Index into the filters offered for the current VMs.
+    SetFilter(usize),
+    OpenOverview(usize),
+    SetAutostart(bool),
+    SetTemplate(bool),
+    /// VM configs were added, removed, renamed or edited outside QERSUI.
+    ConfigsChanged,
+    /// Copy the recipe and resources of the VM shown in the overview as a VM spec.
+    CopySpec,
+    /// Create a linked clone of the template shown in the overview.
+    CloneTemplate,
+    Cloned(PathBuf, Result<PathBuf, AppError>),
+    CloseOverview,
+    MetadataSaved(PathBuf, Result<(), String>),
+    CheckReleases,
+    ReleasesChecked(Result<HashMap<PathBuf, ReleaseNotice>, AppError>),
+}
+
+#[derive(Clone, Debug, Default)]
+enum Page {
+    #[default]
+    Loading,
+    List,
+    Delete(Deletion),
+    Export(ExportDialog),
+    /// Size and maintenance of a VM's system disk.
+    Disk(DiskPanel),
+    /// Downloaded installer images across the library.
+    Storage(StorageManager),
+    /// Host checks and options for passing a GPU to a VM.
+    Passthrough(PassthroughPanel),
+    /// Installer images being checked against their published checksums.
+    Verify(VerifyPanel),
+    Unlock(UnlockPrompt),
+    /// The first-boot checklist of the VM with this config.
+    Checklist(PathBuf),
+    /// Details and activity timeline of the VM with this config.
+    Overview(PathBuf),
+    /// A failed deletion, with the VM so it can be retried.
+    Error(AppError, VM),
+    /// An export or deletion of several VMs.
+    Bulk(BulkRun),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ControlAction {
+    Pause,
+    Resume,
+    PowerDown,
+    ForceOff,
+}
+
+/// Asks for the disk passphrase of an encrypted VM that has none stored in the keyring.
+#[derive(Clone, Debug)]
+struct UnlockPrompt {
+    vm: VM,
+    passphrase: String,
+    remember: bool,
+}
+
+/// Everything that deleting a VM would remove, gathered before asking the user to confirm.
+#[derive(Clone, Debug)]
+pub struct Deletion {
+    vm: VM,
+    items: Vec<DeletionItem>,
+    keep_installer: bool,
+    progress: Option<DeletionProgress>,
+}
+
+#[derive(Clone, Debug)]
+struct DeletionItem {
+    path: PathBuf,
+    kind: ItemKind,
+    size: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ItemKind {
+    Config,
+    Disk,
+    Installer,
+    Firmware,
+}
+
+#[derive(Clone, Debug)]
+struct DeletionProgress {
+    queue: Vec<DeletionItem>,
+    removed: usize,
+    reclaimed: u64,
+    total: u64,
+}
+
+impl ItemKind {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Config => "Config file",
+            Self::Disk => "Disk image",
+            Self::Installer => "Installer image",
+            Self::Firmware => "EFI variables",
+        }
+    }
+}
+
+impl Deletion {
+    async fn plan(vm: VM) -> Self {
+        let candidates = std::iter::once((vm.config_path.clone(), ItemKind::Config))
+            .chain(vm.disk_images().into_iter().map(|p| (p, ItemKind::Disk)))
+            .chain(
+                vm.installer_images()
+                    .into_iter()
+                    .map(|p| (p, ItemKind::Installer)),
+            )
+            .chain(
+                vm.firmware_vars()
+                    .into_iter()
+                    .map(|p| (p, ItemKind::Firmware)),
+            )
+            .collect::<Vec<_>>();
+
+        let mut items = Vec::new();
+        for (path, kind) in candidates {
+            if let Ok(metadata) = tokio::fs::metadata(&path).await {
+                items.push(DeletionItem {
+                    path,
+                    kind,
+                    size: metadata.len(),
+                });
+            }
+        }
+        Self {
+            vm,
+            items,
+            keep_installer: false,
+            progress: None,
+        }
+    }
+    fn selected(&self) -> impl Iterator<Item = &DeletionItem> {
+        self.items
+            .iter()
+            .filter(|item| !(self.keep_installer && item.kind == ItemKind::Installer))
+    }
+}
+
+impl Library {
+    pub fn new(roots: Vec<PathBuf>, registered: Vec<PathBuf>) -> Self {
+        Self {
+            roots,
+            registered,
+            ..Default::default()
+        }
+    }
+    pub fn refresh(&self) -> Command<crate::app::Message> {
+        let roots = self.roots.clone();
+        let registered = self.registered.clone();
+        Command::perform(vm::discover(roots, registered), |vms| {
+            crate::app::Message::Library(Message::Scanned(vms)).into()
+        })
+    }
+    /// Track a config living outside the VM directories and rescan.
+    pub fn register(&mut self, config_path: PathBuf) -> Command<crate::app::Message> {
+        if !self.registered.contains(&config_path) {
+            self.registered.push(config_path);
+        }
+        self.refresh()
+    }
+    pub fn update(&mut self, message: Message) -> Command<crate::app::Message> {
+        match message {
+            Message::Refresh => {
+                if let Page::Error(..) = self.page {
+                    self.page = Page::List;
+                }
+                return self.refresh();
+            }
+            Message::Scanned(vms) => {
+                self.running = vms
+                    .iter()
+                    .filter(|vm| vm.running_pid().is_some())
+                    .map(|vm| vm.config_path.clone())
+                    .collect();
+                self.metadata = vms
+                    .iter()
+                    .map(|vm| (vm.config_path.clone(), vm.metadata()))
+                    .collect();
+                self.thumbnails
+                    .retain(|config_path, _| vms.iter().any(|vm| &vm.config_path == config_path));
+                self.checked
+                    .retain(|config_path| vms.iter().any(|vm| &vm.config_path == config_path));
+                self.vms = vms;
+                self.refresh_filters();
+                match &self.page {
+                    Page::Loading => self.page = Page::List,
+                    // Deleted or renamed outside QERSUI.
+                    Page::Overview(config_path) | Page::Checklist(config_path)
+                        if !self.vms.iter().any(|vm| &vm.config_path == config_path) =>
+                    {
+                        self.page = Page::List;
+                    }
+                    _ => {}
+                }
+                let mut commands = vec![self.poll_status(), self.load_thumbnails()];
+                if !self.autostarted {
+                    self.autostarted = true;
+                    commands.push(self.autostart());
+                }
+                if let Some(name) = self.pending_launch.take() {
+                    commands.push(self.launch_by_name(name));
+                }
+                if !self.releases_checked {
+                    commands.push(self.check_releases());
+                }
+                return Command::batch(commands);
+            }
+            Message::Launch(index) => {
+                if let Some(vm) = self.vms.get(index).cloned() {
+                    if self.running.contains(&vm.config_path) {
+                        return Command::none();
+                    }
+                    if let Some(blocker) = self.launch_blocker(&vm) {
+                        self.errors.insert(vm.config_path, blocker);
+                        return Command::none();
+                    }
+                    if vm.is_encrypted() {
+                        return Command::perform(
+                            async move {
+                                let passphrase = encryption::lookup_passphrase(&vm).await;
+                                (vm, passphrase)
+                            },
+                            |(vm, passphrase)| {
+                                crate::app::Message::Library(Message::PassphraseLookedUp(
+                                    vm, passphrase,
+                                ))
+                                .into()
+                            },
+                        );
+                    }
+                    return self.launch(vm, None);
+                }
+            }
+            Message::PassphraseLookedUp(vm, passphrase) => match passphrase {
+                Some(passphrase) => return self.launch(vm, Some(passphrase)),
+                None => {
+                    self.page = Page::Unlock(UnlockPrompt {
+                        vm,
+                        passphrase: String::new(),
+                        remember: false,
+                    })
+                }
+            },
+            Message::SetUnlockPassphrase(passphrase) => {
+                if let Page::Unlock(prompt) = &mut self.page {
+                    prompt.passphrase = passphrase;
+                }
+            }
+            Message::SetRememberPassphrase(remember) => {
+                if let Page::Unlock(prompt) = &mut self.page {
+                    prompt.remember = remember;
+                }
+            }
+            Message::Unlock => {
+                let Page::Unlock(prompt) = &self.page else {
+                    return Command::none();
+                };
+                if prompt.passphrase.is_empty() {
+                    return Command::none();
+                }
+                let UnlockPrompt {
+                    vm,
+                    passphrase,
+                    remember,
+                } = prompt.clone();
+                self.page = Page::List;
+                if remember {
+                    let store_vm = vm.clone();
+                    let store_passphrase = passphrase.clone();
+                    let store = Command::perform(
+                        async move { encryption::store_passphrase(&store_vm, &store_passphrase).await },
+                        |result| {
+                            if let Err(e) = result {
+                                tracing::warn!("Failed to store disk passphrase: {e}");
+                            }
+                            crate::app::Message::Library(Message::Refresh).into()
+                        },
+                    );
+                    return Command::batch([self.launch(vm, Some(passphrase)), store]);
+                }
+                return self.launch(vm, Some(passphrase));
+            }
+            Message::CancelUnlock => {
+                if let Page::Unlock(_) = self.page {
+                    self.page = Page::List;
+                }
+            }
+            Message::Started(vm, result) => match result {
+                Ok(()) => {
+                    bus::publish(BusEvent::VMStarted(vm.clone()));
+                    return bus::background(async move {
+                        launcher::wait_for_exit(&vm).await;
+                        bus::publish(BusEvent::VMStopped(vm));
+                    });
+                }
+                Err(e) => {
+                    self.running.retain(|path| path != &vm.config_path);
+                    self.errors.insert(vm.config_path, e);
+                }
+            },
+            Message::RowPressed(index) => {
+                let Some(vm) = self.vms.get(index) else {
+                    return Command::none();
+                };
+                if self.modifiers.control() {
+                    let config_path = vm.config_path.clone();
+                    let checked = !self.checked.contains(&config_path);
+                    self.set_checked(config_path, checked);
+                    return Command::none();
+                }
+                let now = Instant::now();
+                let double_click = self.last_click.as_ref().is_some_and(|(path, time)| {
+                    path == &vm.config_path && now.duration_since(*time) < DOUBLE_CLICK
+                });
+                self.selected = Some(vm.config_path.clone());
+                self.last_click = (!double_click).then(|| (vm.config_path.clone(), now));
+                if double_click {
+                    return self.double_click(index);
+                }
+            }
+            Message::SetChecked(index, checked) => {
+                if let Some(vm) = self.vms.get(index) {
+                    self.set_checked(vm.config_path.clone(), checked);
+                }
+            }
+            Message::CheckAll(checked) => {
+                self.checked = if checked {
+                    self.vms
+                        .iter()
+                        .filter(|vm| {
+                            self.filter.matches(
+                                vm,
+                                self.metadata.get(&vm.config_path),
+                                self.running.contains(&vm.config_path),
+                            )
+                        })
+                        .map(|vm| vm.config_path.clone())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+            }
+            Message::ModifiersChanged(modifiers) => self.modifiers = modifiers,
+            Message::SetBulkTag(tag) => self.bulk_tag = tag,
+            Message::RequestBulk(action) => {
+                let (targets, skipped) = self.bulk_targets(action);
+                if targets.is_empty() {
+                    return Command::none();
+                }
+                match action {
+                    BulkAction::Export => {
+                        let run = BulkRun::export(targets, skipped);
+                        return self.update(Message::BulkPlanned(Box::new(run)));
+                    }
+                    BulkAction::Delete => {
+                        return Command::perform(BulkRun::delete(targets, skipped), |run| {
+                            crate::app::Message::Library(Message::BulkPlanned(Box::new(run))).into()
+                        });
+                    }
+                    _ => {
+                        let detail = action.detail(self.bulk_tag.trim());
+                        let targets = targets.iter().collect::<Vec<_>>();
+                        return Confirmation::new(
+                            format!("{} {}?", action.verb(), vm_count(targets.len())),
+                            bulk::summary(&detail, &targets, &skipped),
+                            action.verb(),
+                            Message::ConfirmBulk(action),
+                        )
+                        .ask();
+                    }
+                }
+            }
+            Message::ConfirmBulk(action) => return self.run_bulk(action),
+            Message::BulkPlanned(run) => {
+                let action = run.action();
+                let targets = run.vms().collect::<Vec<_>>();
+                let mut confirmation = Confirmation::new(
+                    format!("{} {}?", action.verb(), vm_count(targets.len())),
+                    bulk::summary(&run.detail(), &targets, run.skipped()),
+                    action.verb(),
+                    Message::StartBulk,
+                )
+                .on_cancel(Message::DiscardBulk);
+                if action == BulkAction::Delete {
+                    confirmation = confirmation.destructive();
+                }
+                self.pending_bulk = Some(*run);
+                return confirmation.ask();
+            }
+            Message::StartBulk => {
+                if let Some(mut run) = self.pending_bulk.take() {
+                    run.start();
+                    self.page = Page::Bulk(run);
+                }
+            }
+            Message::DiscardBulk => self.pending_bulk = None,
+            Message::Bulk(BulkMessage::Close) => {
+                if let Page::Bulk(run) = &self.page {
+                    if !run.is_running() {
+                        self.page = Page::List;
+                    }
+                }
+            }
+            Message::Bulk(msg) => {
+                let Page::Bulk(run) = &mut self.page else {
+                    return Command::none();
+                };
+                let exported = match &msg {
+                    BulkMessage::Done(index, Ok(Outcome::Exported(path))) => run
+                        .vm(*index)
+                        .map(|vm| (vm.config_path.clone(), path.display().to_string())),
+                    _ => None,
+                };
+                if let BulkMessage::Done(index, Ok(Outcome::Deleted { .. })) = &msg {
+                    if let Some(vm) = run.vm(*index) {
+                        self.checked
+                            .retain(|config_path| config_path != &vm.config_path);
+                    }
+                }
+                let deleted =
+                    matches!(msg, BulkMessage::Finished) && run.action() == BulkAction::Delete;
+                run.update(msg);
+                if let Some((config_path, path)) = exported {
+                    return self.update_metadata(config_path, |metadata| {
+                        metadata.record(ActivityKind::BackedUp, Some(path))
+                    });
+                }
+                if deleted {
+                    return self.refresh();
+                }
+            }
+            Message::PollStatus => return self.poll_status(),
+            Message::CaptureThumbnails => {
+                return Command::batch(
+                    self.vms
+                        .iter()
+                        .filter(|vm| self.running.contains(&vm.config_path))
+                        .cloned()
+                        .map(|vm| {
+                            Command::perform(
+                                async move {
+                                    let result = qmp::capture_thumbnail(&vm).await;
+                                    (vm.config_path, result)
+                                },
+                                |(config_path, result)| {
+                                    crate::app::Message::Library(Message::ThumbnailCaptured(
+                                        config_path,
+                                        result,
+                                    ))
+                                    .into()
+                                },
+                            )
+                        }),
+                );
+            }
+            // A missed frame isn't worth reporting, the previous one stays up.
+            Message::ThumbnailCaptured(config_path, result) => {
+                if let Ok(bytes) = result {
+                    self.thumbnails
+                        .insert(config_path, widget::image::Handle::from_memory(bytes));
+                }
+            }
+            Message::StatusQueried(config_path, result) => {
+                self.paused.retain(|path| path != &config_path);
+                // VMs started outside QERSUI have no QMP socket and keep the PID-based state.
+                if let Ok(RunState::Paused) = result {
+                    self.paused.push(config_path);
+                }
+            }
+            Message::Pause(index) => return self.control(index, ControlAction::Pause),
+            Message::Resume(index) => return self.control(index, ControlAction::Resume),
+            Message::PowerDown(index) => return self.control(index, ControlAction::PowerDown),
+            Message::RequestForceOff(index) => {
+                if let Some(vm) = self.vms.get(index) {
+                    return Confirmation::new(
+                        format!("Force off {}?", vm.name),
+                        "The VM stops at once, as if its power cord were pulled. Anything unsaved \
+                         in it is lost.",
+                        "Force off",
+                        Message::ForceOff(vm.config_path.clone()),
+                    )
+                    .destructive()
+                    .ask();
+                }
+            }
+            Message::ForceOff(config_path) => {
+                // Looked up again, as the list may have changed while the dialog was open.
+                let index = self.vms.iter().position(|vm| vm.config_path == config_path);
+                if let Some(index) = index {
+                    return self.control(index, ControlAction::ForceOff);
+                }
+            }
+            Message::Controlled(vm, result) => match result {
+                Ok(()) => {
+                    self.errors.remove(&vm.config_path);
+                    return Self::query_status(vm);
+                }
+                Err(e) => {
+                    self.errors.insert(vm.config_path, e);
+                }
+            },
+            Message::Opened(config_path, result) => match result {
+                Ok(()) => {
+                    self.errors.remove(&config_path);
+                }
+                Err(e) => {
+                    self.errors.insert(config_path, e);
+                }
+            },
+            Message::OpenChecklist(index) => {
+                if let Some(vm) = self.vms.get(index) {
+                    self.page = Page::Checklist(vm.config_path.clone());
+                }
+            }
+            Message::SetChecklistItem(item, done) => {
+                if let Page::Checklist(config_path) = &self.page {
+                    let config_path = config_path.clone();
+                    return self.update_checklist(config_path, item, done);
+                }
+            }
+            Message::SetNotes(notes) => {
+                if let Page::Checklist(config_path) = &self.page {
+                    self.metadata.entry(config_path.clone()).or_default().notes = notes;
+                }
+            }
+            Message::SaveNotes => {
+                if let Page::Checklist(config_path) = &self.page {
+                    let config_path = config_path.clone();
+                    let noted = self
+                        .metadata
+                        .get(&config_path)
+                        .is_some_and(|metadata| !metadata.notes.trim().is_empty());
+                    return self.update_checklist(
+                        config_path,
+                        ChecklistItem::CredentialsNote,
+                        noted,
+                    );
+                }
+            }
+            Message::TakeSnapshot => {
+                let Page::Checklist(config_path) = &self.page else {
+                    return Command::none();
+                };
+                let Some(vm) = self
+                    .vms
+                    .iter()
+                    .find(|vm| &vm.config_path == config_path)
+                    .cloned()
+                else {
+                    return Command::none();
+                };
+                return Command::perform(
+                    async move {
+                        let result = snapshot::create(&vm, "fresh install").await;
+                        (vm.config_path, result)
+                    },
+                    |(config_path, result)| {
+                        crate::app::Message::Library(Message::SnapshotTaken(config_path, result))
+                            .into()
+                    },
+                );
+            }
+            Message::SnapshotTaken(config_path, result) => match result {
+                Ok(()) => {
+                    self.errors.remove(&config_path);
+                    return self.update_metadata(config_path, |metadata| {
+                        if let Some(checklist) = &mut metadata.first_boot {
+                            checklist.set(ChecklistItem::FreshSnapshot, true);
+                        }
+                        metadata.record(
+                            ActivityKind::Snapshotted,
+                            Some(String::from("fresh install")),
+                        );
+                    });
+                }
+                Err(e) => {
+                    self.errors.insert(config_path, e);
+                }
+            },
+            Message::DismissChecklist => {
+                if let Page::Checklist(config_path) = &self.page {
+                    let config_path = config_path.clone();
+                    self.page = Page::List;
+                    return self
+                        .update_metadata(config_path, |metadata| metadata.first_boot = None);
+                }
+            }
+            Message::CloseChecklist => self.page = Page::List,
+            Message::OpenOverview(index) => {
+                if let Some(vm) = self.vms.get(index) {
+                    self.page = Page::Overview(vm.config_path.clone());
+                }
+            }
+            Message::SetAutostart(autostart) => {
+                if let Page::Overview(config_path) = &self.page {
+                    let config_path = config_path.clone();
+                    return self
+                        .update_metadata(config_path, |metadata| metadata.autostart = autostart);
+                }
+            }
+            Message::SetTemplate(template) => {
+                if let Page::Overview(config_path) = &self.page {
+                    let config_path = config_path.clone();
+                    return self
+                        .update_metadata(config_path, |metadata| metadata.template = template);
+                }
+            }
+            Message::ConfigsChanged => return self.refresh(),
+            Message::CopySpec => {
+                if let Page::Overview(config_path) = &self.page {
+                    let vm = self.vms.iter().find(|vm| &vm.config_path == config_path);
+                    let recipe = self
+                        .metadata
+                        .get(config_path)
+                        .and_then(|metadata| metadata.recipe.clone());
+                    if let (Some(vm), Some(recipe)) = (vm, recipe) {
+                        let spec = VMSpec {
+                            recipe,
+                            ram: vm.ram().map(str::to_string),
+                            cpu_cores: vm.cpu_cores().and_then(|cores| cores.trim().parse().ok()),
+                        };
+                        return cosmic::iced::clipboard::write(spec.to_text());
+                    }
+                }
+            }
+            Message::CloneTemplate => {
+                if let Page::Overview(config_path) = &self.page {
+                    let config_path = config_path.clone();
+                    return Command::perform(
+                        async move {
+                            let result = template::clone(config_path.clone()).await;
+                            (config_path, result)
+                        },
+                        |(config_path, result)| {
+                            crate::app::Message::Library(Message::Cloned(config_path, result))
+                                .into()
+                        },
+                    );
+                }
+            }
+            Message::Cloned(template, result) => match result {
+                Ok(config_path) => {
+                    self.page = Page::Overview(config_path);
+                    return self.refresh();
+                }
+                Err(e) => {
+                    tracing::error!("Could not clone {}: {e}", template.display());
+                    self.errors.insert(template, e.to_string());
+                }
+            },
+            Message::CloseOverview => self.page = Page::List,
+            Message::SetGroupBy(choice) => {
+                if let Some(group_by) = GroupBy::ALL.get(choice) {
+                    self.group_by = *group_by;
+                }
+            }
+            Message::SetFilter(choice) => {
+                if let Some(filter) = self.filters.get(choice) {
+                    self.filter = filter.clone();
+                }
+            }
+            Message::MetadataSaved(config_path, result) => {
+                if let Err(e) = result {
+                    self.errors.insert(config_path, e);
+                }
+            }
+            Message::CheckReleases => return self.check_releases(),
+            Message::ReleasesChecked(result) => match result {
+                Ok(notices) => {
+                    let mut commands = Vec::new();
+                    for (config_path, notice) in &notices {
+                        let message = notice.message();
+                        let metadata = self.metadata.get(config_path);
+                        // Notify once per notice, not on every check.
+                        if metadata.and_then(|m| m.release_notice.as_ref()) == Some(&message) {
+                            continue;
+                        }
+                        let Some(vm) = self.vms.iter().find(|vm| vm.config_path == *config_path)
+                        else {
+                            continue;
+                        };
+                        let title = metadata
+                            .map_or_else(|| vm.name.clone(), |m| m.appearance.title(&vm.name));
+                        bus::publish(BusEvent::ReleaseNotice {
+                            title,
+                            message: message.clone(),
+                        });
+                        commands.push(self.update_metadata(config_path.clone(), |metadata| {
+                            metadata.release_notice = Some(message)
+                        }));
+                    }
+                    self.release_notices = notices;
+                    return Command::batch(commands);
+                }
+                Err(e) => tracing::warn!("Could not check VM releases: {e}"),
+            },
+            Message::EditSelected => self.begin_edit(),
+            Message::SetEditName(name) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.edit.name = name;
+                }
+            }
+            Message::SetEditRAM(ram) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.edit.ram = ram;
+                }
+            }
+            Message::SetEditCPUCores(cpu_cores) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.edit.cpu_cores = cpu_cores;
+                }
+            }
+            Message::SetEditColor(choice) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.appearance.color = AccentColor::from_choice(choice);
+                }
+            }
+            Message::SetEditNetwork(choice) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    let bridge = match &inline_edit.edit.network {
+                        NetworkMode::Bridged(bridge) => Some(bridge.clone()),
+                        _ => inline_edit.bridges.first().cloned(),
+                    };
+                    inline_edit.set_network(NetworkMode::from_choice(choice, bridge.as_deref()));
+                }
+            }
+            Message::SetEditBridge(index) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    if let Some(bridge) = inline_edit.bridges.get(index).cloned() {
+                        inline_edit.set_network(NetworkMode::Bridged(bridge));
+                    }
+                }
+            }
+            Message::SetEditMac(mac) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.edit.mac = mac;
+                }
+            }
+            Message::SetEditSoundCard(choice) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.edit.devices.set_sound_card_choice(choice);
+                }
+            }
+            Message::SetEditMicrophone(microphone) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.edit.devices.microphone = microphone;
+                }
+            }
+            Message::SetEditTablet(tablet) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.edit.devices.tablet = tablet;
+                }
+            }
+            Message::ToggleEditAdvanced => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.show_advanced = !inline_edit.show_advanced;
+                }
+            }
+            Message::SetEditExtraKey(index, key) => {
+                if let Some(extra) = self
+                    .inline_edit
+                    .as_mut()
+                    .and_then(|inline_edit| inline_edit.edit.extra.get_mut(index))
+                {
+                    extra.key = key;
+                }
+            }
+            Message::SetEditExtraValue(index, value) => {
+                if let Some(extra) = self
+                    .inline_edit
+                    .as_mut()
+                    .and_then(|inline_edit| inline_edit.edit.extra.get_mut(index))
+                {
+                    extra.value = value;
+                }
+            }
+            Message::RemoveEditExtra(index) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    if index < inline_edit.edit.extra.len() {
+                        inline_edit.edit.extra.remove(index);
+                    }
+                }
+            }
+            Message::AddEditExtra => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.edit.extra.push(ExtraKey::default());
+                    inline_edit.show_advanced = true;
+                }
+            }
+            Message::SetEditTags(tags) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    inline_edit.tags = tags;
+                }
+            }
+            Message::SetEditGlyph(glyph) => {
+                if let Some(inline_edit) = &mut self.inline_edit {
+                    // A glyph is a single emoji or symbol, not a second name.
+                    inline_edit.appearance.glyph = glyph.chars().take(4).collect();
+                }
+            }
+            Message::CommitEdit => {
+                let Some(inline_edit) = &mut self.inline_edit else {
+                    return Command::none();
+                };
+                let Some(vm) = self
+                    .vms
+                    .iter()
+                    .find(|vm| vm.config_path == inline_edit.config_path)
+                    .cloned()
+                else {
+                    self.inline_edit = None;
+                    return Command::none();
+                };
+                if let Err(e) = inline_edit.edit.validate(&vm) {
+                    inline_edit.error = Some(e);
+                    return Command::none();
+                }
+                let edit = inline_edit.edit.clone();
+                let quickemu = self.host.as_ref().and_then(|host| host.quickemu_version);
+                let metadata = self.metadata.entry(vm.config_path.clone()).or_default();
+                metadata.appearance = inline_edit.appearance.clone();
+                metadata.tags = metadata::parse_tags(&inline_edit.tags);
+                let metadata = metadata.clone();
+                // The metadata is saved first, so a rename moves it along with the VM directory.
+                return Command::perform(
+                    async move {
+                        vm.save_metadata(&metadata).await?;
+                        let from = vm.config_path.clone();
+                        let vm = edit::apply(vm, edit, quickemu).await?;
+                        if vm.config_path != from {
+                            let to = vm.config_path.clone();
+                            bus::publish(BusEvent::VMRenamed { from, to });
+                        }
+                        let mut metadata = vm.metadata();
+                        metadata.record(ActivityKind::ConfigEdited, None);
+                        vm.save_metadata(&metadata).await?;
+                        Ok(vm)
+                    },
+                    |result| crate::app::Message::Library(Message::EditApplied(result)).into(),
+                );
+            }
+            Message::CancelEdit => self.inline_edit = None,
+            Message::EditApplied(result) => match result {
+                Ok(vm) => {
+                    // A renamed config from outside the VM directories is only found under its
+                    // new path.
+                    if let Some(inline_edit) = self.inline_edit.take() {
+                        let from = inline_edit.config_path;
+                        if let Some(registered) =
+                            self.registered.iter_mut().find(|path| **path == from)
+                        {
+                            *registered = vm.config_path.clone();
+                        }
+                    }
+                    self.selected = Some(vm.config_path);
+                    return self.refresh();
+                }
+                Err(e) => {
+                    if let Some(inline_edit) = &mut self.inline_edit {
+                        inline_edit.error = Some(e);
+                    }
+                }
+            },
+            Message::RequestExport(index) => {
+                if let Some(vm) = self.vms.get(index).cloned() {
+                    let destination = vm.root().to_path_buf();
+                    self.page = Page::Export(ExportDialog::new(vm, destination));
+                }
+            }
+            Message::Export(ExportMessage::Close) => {
+                if let Page::Export(dialog) = &self.page {
+                    if !dialog.is_running() {
+                        self.page = Page::List;
+                    }
+                }
+            }
+            Message::Export(msg) => {
+                if let Page::Export(dialog) = &mut self.page {
+                    let backup = match &msg {
+                        ExportMessage::Finished(Ok(path)) => {
+                            Some((dialog.vm().config_path.clone(), path.display().to_string()))
+                        }
+                        _ => None,
+                    };
+                    let command = dialog.update(msg);
+                    if let Some((config_path, path)) = backup {
+                        let recorded = self.update_metadata(config_path, |metadata| {
+                            metadata.record(ActivityKind::BackedUp, Some(path))
+                        });
+                        return Command::batch([command, recorded]);
+                    }
+                    return command;
+                }
+            }
+            Message::OpenDisk(index) => {
+                if let Some(vm) = self.vms.get(index).cloned() {
+                    let clones = self.clone_count(&vm.config_path);
+                    let (panel, command) = DiskPanel::new(vm, clones);
+                    self.page = Page::Disk(panel);
+                    return command;
+                }
+            }
+            Message::Disk(DiskMessage::Close) => {
+                if let Page::Disk(panel) = &self.page {
+                    if !panel.is_running() {
+                        self.page = Page::List;
+                        // A conversion points the config at the new image.
+                        return self.refresh();
+                    }
+                }
+            }
+            Message::Disk(msg) => {
+                if let Page::Disk(panel) = &mut self.page {
+                    let maintained = match (&msg, panel.action()) {
+                        (DiskMessage::Finished(Ok(())), Some(action)) => {
+                            Some((panel.vm().config_path.clone(), action.done_message()))
+                        }
+                        _ => None,
+                    };
+                    let command = panel.update(msg);
+                    if let Some((config_path, detail)) = maintained {
+                        let recorded = self.update_metadata(config_path, |metadata| {
+                            metadata.record(ActivityKind::DiskMaintained, Some(detail))
+                        });
+                        return Command::batch([command, recorded]);
+                    }
+                    return command;
+                }
+            }
+            Message::OpenStorage => {
+                let (manager, command) = StorageManager::new(self.vms.clone());
+                self.page = Page::Storage(manager);
+                return command;
+            }
+            Message::Storage(StorageMessage::Close) => {
+                if let Page::Storage(manager) = &self.page {
+                    if !manager.is_deleting() {
+                        self.page = Page::List;
+                        return self.refresh();
+                    }
+                }
+            }
+            Message::Storage(msg) => {
+                if let Page::Storage(manager) = &mut self.page {
+                    return manager.update(msg);
+                }
+            }
+            Message::OpenPassthrough => {
+                let (panel, command) = PassthroughPanel::new(self.vms.clone());
+                self.page = Page::Passthrough(panel);
+                return command;
+            }
+            Message::Passthrough(PassthroughMessage::Close) => {
+                if let Page::Passthrough(panel) = &self.page {
+                    if !panel.is_applying() {
+                        self.page = Page::List;
+                        return self.refresh();
+                    }
+                }
+            }
+            Message::Passthrough(msg) => {
+                if let Page::Passthrough(panel) = &mut self.page {
+                    return panel.update(msg);
+                }
+            }
+            Message::VerifyImages(config_path) => {
+                let vms = self
+                    .vms
+                    .iter()
+                    .filter(|vm| {
+                        config_path
+                            .as_ref()
+                            .map_or(true, |path| vm.config_path == *path)
+                    })
+                    .cloned()
+                    .collect();
+                let (panel, command) = VerifyPanel::new(vms);
+                self.page = Page::Verify(panel);
+                return command;
+            }
+            Message::Verify(VerifyMessage::Close) => {
+                if let Page::Verify(panel) = &self.page {
+                    if !panel.is_repairing() {
+                        self.page = Page::List;
+                    }
+                }
+            }
+            Message::Verify(msg) => {
+                if let Page::Verify(panel) = &mut self.page {
+                    return panel.update(msg);
+                }
+            }
+            Message::InstallersCleaned(config_path, result) => match result {
+                Ok(0) => {}
+                Ok(_) => return self.refresh(),
+                Err(e) => {
+                    self.errors.insert(config_path, e);
+                }
+            },
+            Message::RequestDelete(index) => {
+                if let Some(vm) = self.vms.get(index).cloned() {
+                    if let Some(blocker) = self.delete_blocker(&vm) {
+                        self.errors.insert(vm.config_path, blocker);
+                        return Command::none();
+                    }
+                    return Command::perform(Deletion::plan(vm), |deletion| {
+                        crate::app::Message::Library(Message::DeletionPlanned(Box::new(deletion)))
+                            .into()
+                    });
+                }
+            }
+            Message::DeletionPlanned(deletion) => self.page = Page::Delete(*deletion),
+            Message::SetKeepInstaller(keep) => {
+                if let Page::Delete(deletion) = &mut self.page {
+                    if deletion.progress.is_none() {
+                        deletion.keep_installer = keep;
+                    }
+                }
+            }
+            Message::CancelDelete => {
+                if let Page::Delete(Deletion { progress: None, .. }) = self.page {
+                    self.page = Page::List;
+                }
+            }
+            Message::DeleteSelected => {
+                if let Page::Delete(deletion @ Deletion { progress: None, .. }) = &self.page {
+                    let files = deletion.selected().count();
+                    let size = deletion.selected().map(|item| item.size).sum();
+                    let files = if files == 1 {
+                        String::from("1 file")
+                    } else {
+                        format!("{files} files")
+                    };
+                    return Confirmation::new(
+                        format!("Delete {}?", deletion.vm.name),
+                        format!(
+                            "{files} ({}) will be permanently removed. This can't be undone.",
+                            format_size(size)
+                        ),
+                        "Delete",
+                        Message::ConfirmDelete,
+                    )
+                    .destructive()
+                    .ask();
+                }
+            }
+            Message::ConfirmDelete => {
+                if let Page::Delete(deletion @ Deletion { progress: None, .. }) = &mut self.page {
+                    let mut queue = deletion.selected().cloned().collect::<Vec<_>>();
+                    // Remove the config last so an interrupted deletion still shows up in the library.
+                    queue.sort_by_key(|item| item.kind == ItemKind::Config);
+                    let total = queue.iter().map(|item| item.size).sum();
+                    deletion.progress = Some(DeletionProgress {
+                        queue,
+                        removed: 0,
+                        reclaimed: 0,
+                        total,
+                    });
+                    return self.remove_next();
+                }
+            }
+            Message::Removed(result) => {
+                let Page::Delete(deletion) = &mut self.page else {
+                    return Command::none();
+                };
+                match result {
+                    Ok(()) => {
+                        if let Some(progress) = &mut deletion.progress {
+                            let item = progress.queue.remove(0);
+                            progress.removed += 1;
+                            progress.reclaimed += item.size;
+                        }
+                        return self.remove_next();
+                    }
+                    Err(e) => {
+                        self.error_details = false;
+                        self.page = Page::Error(e, deletion.vm.clone());
+                        return self.refresh();
+                    }
+                }
+            }
+            Message::ToggleErrorDetails => self.error_details = !self.error_details,
+            Message::CopyErrorReport => {
+                if let Page::Error(error, _) = &self.page {
+                    let error = error.clone();
+                    return Command::perform(async move { error.report().await }, |report| {
+                        crate::app::Message::CopyToClipboard(report).into()
+                    });
+                }
+            }
+            Message::RetryDelete => {
+                if let Page::Error(_, vm) = &self.page {
+                    // Plan again, as some files may already be gone.
+                    return Command::perform(Deletion::plan(vm.clone()), |deletion| {
+                        crate::app::Message::Library(Message::DeletionPlanned(Box::new(deletion)))
+                            .into()
+                    });
+                }
+            }
+        };
+        Command::none()
+    }
+    fn launch(&mut self, vm: VM, passphrase: Option<String>) -> Command<crate::app::Message> {
+        self.errors.remove(&vm.config_path);
+        self.running.push(vm.config_path.clone());
+        Command::perform(
+            async move {
+                let result = launcher::start(&vm, passphrase).await;
+                (vm, result)
+            },
+            |(vm, result)| crate::app::Message::Library(Message::Started(vm, result)).into(),
+        )
+    }
+    /// Launch the VM called `name`, as soon as the library has been scanned.
+    pub fn launch_by_name(&mut self, name: String) -> Command<crate::app::Message> {
+        if let Page::Loading = self.page {
+            self.pending_launch = Some(name);
+            return Command::none();
+        }
+        match self.vms.iter().position(|vm| vm.name == name) {
+            Some(index) => self.update(Message::Launch(index)),
+            None => {
+                tracing::warn!("There is no VM called {name}");
+                Command::none()
+            }
+        }
+    }
+    /// Start the VMs marked for autostart that can run on this host.
+    fn autostart(&mut self) -> Command<crate::app::Message> {
+        let vms = autostart::pending(&self.vms)
+            .filter(|vm| self.launch_blocker(vm).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+        Command::batch(vms.into_iter().map(|vm| {
+            self.running.push(vm.config_path.clone());
+            Command::perform(autostart::start(vm, false), |(vm, result)| {
+                crate::app::Message::Library(Message::Started(vm, result)).into()
+            })
+        }))
+    }
+    pub fn set_host(&mut self, host: Arc<HostCapabilities>) {
+        self.host = Some(host);
+    }
+    /// The VMs taking host resources now or at the next start: running ones and autostart ones.
+    pub fn commitments(&self) -> Commitments {
+        Commitments::new(self.vms.iter().filter(|vm| {
+            self.running.contains(&vm.config_path)
+                || self
+                    .metadata
+                    .get(&vm.config_path)
+                    .is_some_and(|metadata| metadata.autostart)
+        }))
+    }
+    /// Why `vm` can't be launched on this host, as shown on its greyed-out launch button.
+    fn launch_blocker(&self, vm: &VM) -> Option<String> {
+        if self.clone_count(&vm.config_path) > 0 {
+            // Writing to a disk that backs others corrupts them.
+            return Some(String::from(
+                "Linked clones are based on this template's disk, starting it would break them",
+            ));
+        }
+        let host = self.host.as_ref()?;
+        let arch = host_probe::parse_arch(vm.config.get("arch").unwrap_or("x86_64"))?;
+        host.launch_blocker(&arch, vm.config.get("tpm") == Some("on"))
+    }
+    fn delete_blocker(&self, vm: &VM) -> Option<String> {
+        match self.clone_count(&vm.config_path) {
+            0 => None,
+            1 => Some(String::from(
+                "A linked clone is based on this template's disk, delete it first",
+            )),
+            count => Some(format!(
+                "{count} linked clones are based on this template's disk, delete them first"
+            )),
+        }
+    }
+    fn set_checked(&mut self, config_path: PathBuf, checked: bool) {
+        self.checked.retain(|path| path != &config_path);
+        if checked {
+            self.checked.push(config_path);
+        }
+    }
+    /// The ticked VMs `action` applies to, and the other ticked ones with why they're left out.
+    fn bulk_targets(&self, action: BulkAction) -> (Vec<VM>, Vec<(String, String)>) {
+        let tag = self.bulk_tag.trim();
+        let mut targets = Vec::new();
+        let mut skipped = Vec::new();
+        for vm in self
+            .vms
+            .iter()
+            .filter(|vm| self.checked.contains(&vm.config_path))
+        {
+            let running = self.running.contains(&vm.config_path);
+            let tagged = self
+                .metadata
+                .get(&vm.config_path)
+                .is_some_and(|metadata| metadata.has_tag(tag));
+            let reason = match action {
+                BulkAction::Start if running => Some(String::from("Already running")),
+                BulkAction::Start => self.launch_blocker(vm),
+                BulkAction::Stop if !running => Some(String::from("Not running")),
+                BulkAction::Tag | BulkAction::Untag if tag.is_empty() => {
+                    Some(String::from("No tag entered"))
+                }
+                BulkAction::Tag if tagged => Some(format!("Already tagged {tag}")),
+                BulkAction::Untag if !tagged => Some(format!("Not tagged {tag}")),
+                BulkAction::Export | BulkAction::Delete if running => Some(String::from("Running")),
+                BulkAction::Delete => self.delete_blocker(vm),
+                _ => None,
+            };
+            match reason {
+                Some(reason) => skipped.push((vm.name.clone(), reason)),
+                None => targets.push(vm.clone()),
+            }
+        }
+        (targets, skipped)
+    }
+    /// Start, stop or tag the ticked VMs, looked up again as the list may have changed while
+    /// the confirmation was open.
+    fn run_bulk(&mut self, action: BulkAction) -> Command<crate::app::Message> {
+        let (targets, _) = self.bulk_targets(action);
+        let indices = targets
+            .iter()
+            .filter_map(|target| {
+                self.vms
+                    .iter()
+                    .position(|vm| vm.config_path == target.config_path)
+            })
+            .collect::<Vec<_>>();
+        let tag = self.bulk_tag.trim().to_string();
+        let commands: Vec<Command<crate::app::Message>> = match action {
+            BulkAction::Start => indices
+                .into_iter()
+                .map(|index| self.update(Message::Launch(index)))
+                .collect(),
+            BulkAction::Stop => indices
+                .into_iter()
+                .map(|index| self.control(index, ControlAction::PowerDown))
+                .collect(),
+            BulkAction::Tag => targets
+                .into_iter()
+                .map(|vm| {
+                    let tag = tag.clone();
+                    self.update_metadata(vm.config_path, move |metadata| metadata.tags.push(tag))
+                })
+                .collect(),
+            BulkAction::Untag => targets
+                .into_iter()
+                .map(|vm| {
+                    self.update_metadata(vm.config_path, |metadata| {
+                        metadata.tags.retain(|t| !t.eq_ignore_ascii_case(&tag))
+                    })
+                })
+                .collect(),
+            BulkAction::Export | BulkAction::Delete => Vec::new(),
+        };
+        self.refresh_filters();
+        Command::batch(commands)
+    }
+    fn clone_count(&self, template: &Path) -> usize {
+        template::clones_of(template, &self.metadata).count()
+    }
+    /// Read the last thumbnails saved for VMs that don't show one yet.
+    fn load_thumbnails(&self) -> Command<crate::app::Message> {
+        Command::batch(
+            self.vms
+                .iter()
+                .filter(|vm| !self.thumbnails.contains_key(&vm.config_path))
+                .map(|vm| {
+                    let config_path = vm.config_path.clone();
+                    let path = vm.thumbnail_path();
+                    Command::perform(
+                        async move {
+                            let result = tokio::fs::read(&path).await.map_err(|e| e.to_string());
+                            (config_path, result)
+                        },
+                        |(config_path, result)| {
+                            crate::app::Message::Library(Message::ThumbnailCaptured(
+                                config_path,
+                                result,
+                            ))
+                            .into()
+                        },
+                    )
+                }),
+        )
+    }
+    fn poll_status(&self) -> Command<crate::app::Message> {
+        Command::batch(
+            self.vms
+                .iter()
+                .filter(|vm| self.running.contains(&vm.config_path))
+                .cloned()
+                .map(Self::query_status),
+        )
+    }
+    fn query_status(vm: VM) -> Command<crate::app::Message> {
+        Command::perform(
+            async move {
+                let result = qmp::status(&vm).await;
+                (vm.config_path, result)
+            },
+            |(config_path, result)| {
+                crate::app::Message::Library(Message::StatusQueried(config_path, result)).into()
+            },
+        )
+    }
+    fn control(&self, index: usize, action: ControlAction) -> Command<crate::app::Message> {
+        let Some(vm) = self.vms.get(index).cloned() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                let result = match action {
+                    ControlAction::Pause => qmp::pause(&vm).await,
+                    ControlAction::Resume => qmp::resume(&vm).await,
+                    ControlAction::PowerDown => qmp::powerdown(&vm).await,
+                    ControlAction::ForceOff => qmp::quit(&vm).await,
+                };
+                (vm, result)
+            },
+            |(vm, result)| crate::app::Message::Library(Message::Controlled(vm, result)).into(),
+        )
+    }
+    pub fn open_overview(&mut self, config_path: PathBuf) {
+        self.page = Page::Overview(config_path);
+    }
+    pub fn open_checklist(&mut self, config_path: PathBuf) {
+        self.page = Page::Checklist(config_path);
+    }
+    fn update_checklist(
+        &mut self,
+        config_path: PathBuf,
+        item: ChecklistItem,
+        done: bool,
+    ) -> Command<crate::app::Message> {
+        self.update_metadata(config_path, |metadata| {
+            if let Some(checklist) = &mut metadata.first_boot {
+                checklist.set(item, done);
+            }
+        })
+    }
+    /// Compare every VM's release against the latest quickget catalog.
+    fn check_releases(&mut self) -> Command<crate::app::Message> {
+        self.releases_checked = true;
+        Command::perform(release_watch::check_all(self.vms.clone()), |result| {
+            crate::app::Message::Library(Message::ReleasesChecked(result)).into()
+        })
+    }
+    /// Change a VM's metadata in memory and write it to its sidecar file.
+    fn update_metadata(
+        &mut self,
+        config_path: PathBuf,
+        change: impl FnOnce(&mut Metadata),
+    ) -> Command<crate::app::Message> {
+        let Some(vm) = self
+            .vms
+            .iter()
+            .find(|vm| vm.config_path == config_path)
+            .cloned()
+        else {
+            return Command::none();
+        };
+        let metadata = self.metadata.entry(config_path).or_default();
+        change(metadata);
+        let metadata = metadata.clone();
+        Command::perform(
+            async move {
+                let result = vm.save_metadata(&metadata).await;
+                (vm.config_path, result)
+            },
+            |(config_path, result)| {
+                crate::app::Message::Library(Message::MetadataSaved(config_path, result)).into()
+            },
+        )
+    }
+    /// Offer filters for the tags and OS families now present, keeping the current one if it is.
+    fn refresh_filters(&mut self) {
+        self.filters = Filter::available(&self.vms, &self.metadata);
+        self.filter_labels = self.filters.iter().map(Filter::label).collect();
+        if !self.filters.contains(&self.filter) {
+            self.filter = Filter::All;
+        }
+    }
+    pub fn set_double_click_action(&mut self, action: DoubleClickAction) {
+        self.double_click_action = action;
+    }
+    pub fn set_delete_installers_after_boot(&mut self, delete: bool) {
+        self.delete_installers_after_boot = delete;
+    }
+    fn double_click(&mut self, index: usize) -> Command<crate::app::Message> {
+        let Some(vm) = self.vms.get(index).cloned() else {
+            return Command::none();
+        };
+        let config_path = vm.config_path.clone();
+        match self.double_click_action {
+            DoubleClickAction::Edit => self.begin_edit(),
+            DoubleClickAction::Launch => return self.update(Message::Launch(index)),
+            DoubleClickAction::OpenViewer
+                if self
+                    .host
+                    .as_ref()
+                    .is_some_and(|host| host.spice_clients.is_empty()) =>
+            {
+                self.errors.insert(
+                    config_path,
+                    String::from("Install remote-viewer or spicy to open the display"),
+                );
+            }
+            DoubleClickAction::OpenViewer => {
+                return Command::perform(
+                    async move { launcher::open_viewer(&vm).await },
+                    move |result| {
+                        crate::app::Message::Library(Message::Opened(config_path, result)).into()
+                    },
+                )
+            }
+            DoubleClickAction::ConnectSSH => {
+                return Command::perform(
+                    async move { launcher::connect_ssh(&vm).await },
+                    move |result| {
+                        crate::app::Message::Library(Message::Opened(config_path, result)).into()
+                    },
+                )
+            }
+        }
+        Command::none()
+    }
+    /// Start editing the selected row. Running VMs can't be renamed or resized.
+    fn begin_edit(&mut self) {
+        let Some(selected) = &self.selected else {
+            return;
+        };
+        if self.running.contains(selected) {
+            return;
+        }
+        if let Some(vm) = self.vms.iter().find(|vm| &vm.config_path == selected) {
+            let metadata = self.metadata.get(&vm.config_path);
+            let edit = VMEdit::from_vm(vm);
+            let network = edit.network.clone();
+            let mut inline_edit = InlineEdit {
+                config_path: vm.config_path.clone(),
+                edit,
+                appearance: metadata
+                    .map(|metadata| metadata.appearance.clone())
+                    .unwrap_or_default(),
+                tags: metadata
+                    .map(|metadata| metadata.tags.join(", "))
+                    .unwrap_or_default(),
+                bridges: guest_network::bridges(),
+                network_warning: None,
+                show_advanced: false,
+                error: None,
+            };
+            inline_edit.set_network(network);
+            self.inline_edit = Some(inline_edit);
+        }
+    }
+    pub fn on_event(&mut self, event: &BusEvent) -> Command<crate::app::Message> {
+        match event {
+            BusEvent::VMStopped(vm) => {
+                self.running.retain(|path| path != &vm.config_path);
+                self.paused.retain(|path| path != &vm.config_path);
+                let first_session = self.metadata.get(&vm.config_path).is_some_and(|metadata| {
+                    metadata
+                        .activity
+                        .iter()
+                        .filter(|activity| activity.kind == ActivityKind::Started)
+                        .count()
+                        == 1
+                });
+                let cleanup = if self.delete_installers_after_boot && first_session {
+                    let (vm, vms) = (vm.clone(), self.vms.clone());
+                    Command::perform(
+                        async move {
+                            let result = installers::delete_for(&vm, vms).await;
+                            (vm.config_path, result)
+                        },
+                        |(config_path, result)| {
+                            crate::app::Message::Library(Message::InstallersCleaned(
+                                config_path,
+                                result,
+                            ))
+                            .into()
+                        },
+                    )
+                } else {
+                    Command::none()
+                };
+                let recorded = self.update_metadata(vm.config_path.clone(), |metadata| {
+                    metadata.record(ActivityKind::Stopped, None)
+                });
+                return Command::batch([cleanup, recorded]);
+            }
+            BusEvent::VMAdded(config_path) | BusEvent::CreationComplete(config_path) => {
+                return self.register(config_path.clone())
+            }
+            BusEvent::VMStarted(vm) => {
+                return self.update_metadata(vm.config_path.clone(), |metadata| {
+                    if let Some(checklist) = metadata
+                        .first_boot
+                        .as_mut()
+                        .filter(|checklist| !checklist.is_done(ChecklistItem::BootOnce))
+                    {
+                        checklist.set(ChecklistItem::BootOnce, true);
+                    }
+                    metadata.record(ActivityKind::Started, None);
+                });
+            }
+            // Registrations were updated along with the edit.
+            BusEvent::VMRenamed { .. }
+            | BusEvent::DownloadFailed { .. }
+            | BusEvent::ReleaseNotice { .. } => {}
+        }
+        Command::none()
+    }
+    /// Whether an export, disk operation or deletion of VMs or installer images is in progress.
+    pub fn is_busy(&self) -> bool {
+        match &self.page {
+            Page::Export(dialog) => dialog.is_running(),
+            Page::Disk(panel) => panel.is_running(),
+            Page::Storage(manager) => manager.is_deleting(),
+            Page::Passthrough(panel) => panel.is_applying(),
+            Page::Verify(panel) => panel.is_repairing(),
+            Page::Delete(deletion) => deletion.progress.is_some(),
+            Page::Bulk(run) => run.is_running(),
+            _ => false,
+        }
+    }
+    pub fn subscription(&self) -> Subscription<crate::app::Message> {
+        let page = match &self.page {
+            Page::Export(dialog) => dialog.subscription(),
+            Page::Disk(panel) => panel.subscription(),
+            Page::Verify(panel) => panel.subscription(),
+            Page::Bulk(run) => run.subscription(),
+            _ => Subscription::none(),
+        };
+        let modifiers = event::listen_with(|event, _status| match event {
+            event::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => Some(
+                crate::app::Message::Library(Message::ModifiersChanged(modifiers)),
+            ),
+            _ => None,
+        });
+        let releases = cosmic::iced::time::every(release_watch::CHECK_INTERVAL)
+            .map(|_| crate::app::Message::Library(Message::CheckReleases));
+        let watch = watch::subscription(
+            self.watched_directories(),
+            crate::app::Message::Library(Message::ConfigsChanged),
+        );
+        if self.running.is_empty() {
+            return Subscription::batch([page, releases, watch, modifiers]);
+        }
+        let poll = cosmic::iced::time::every(STATUS_POLL)
+            .map(|_| crate::app::Message::Library(Message::PollStatus));
+        let thumbnails = cosmic::iced::time::every(THUMBNAIL_INTERVAL)
+            .map(|_| crate::app::Message::Library(Message::CaptureThumbnails));
+        Subscription::batch([page, releases, watch, modifiers, poll, thumbnails])
+    }
+    /// The VM directories, and those of configs registered from elsewhere.
+    fn watched_directories(&self) -> Vec<PathBuf> {
+        let registered = self
+            .registered
+            .iter()
+            .filter_map(|config_path| config_path.parent())
+            .map(Path::to_path_buf);
+        self.roots
+            .iter()
+            .cloned()
+            .chain(registered)
+            .unique()
+            .collect()
+    }
+    fn remove_next(&mut self) -> Command<crate::app::Message> {
+        let Page::Delete(deletion) = &self.page else {
+            return Command::none();
+        };
+        let Some(progress) = &deletion.progress else {
+            return Command::none();
+        };
+        match progress.queue.first() {
+            Some(item) => {
+                let path = item.path.clone();
+                Command::perform(
+                    async move {
+                        tokio::fs::remove_file(&path).await.map_err(|e| {
+                            AppError::from_error(
+                                ErrorCategory::Disk,
+                                format!("Could not remove {}", path.display()),
+                                &e,
+                            )
+                        })
+                    },
+                    |result| crate::app::Message::Library(Message::Removed(result)).into(),
+                )
+            }
+            None => {
+                let _ = std::fs::remove_file(deletion.vm.metadata_path());
+                let _ = std::fs::remove_file(deletion.vm.thumbnail_path());
+                // Only clean up the VM directory if nothing else (e.g. a kept ISO) remains in it.
+                let _ = std::fs::remove_dir(deletion.vm.vm_dir());
+                self.page = Page::List;
+                self.refresh()
+            }
+        }
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        match &self.page {
+            Page::Loading => widget::text("loading")
+                .apply(widget::container)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center)
+                .into(),
+            Page::List => {
+                let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+                for (index, vm) in self.vms.iter().enumerate() {
+                    let metadata = self.metadata.get(&vm.config_path);
+                    let running = self.running.contains(&vm.config_path);
+                    if !self.filter.matches(vm, metadata, running) {
+                        continue;
+                    }
+                    let state = match running {
+                        _ if self.paused.contains(&vm.config_path) => "Paused",
+                        true => "Running",
+                        false => "Stopped",
+                    };
+                    for group in filter::groups(self.group_by, vm, metadata, state) {
+                        match groups.iter_mut().find(|(title, _)| *title == group) {
+                            Some((_, indices)) => indices.push(index),
+                            None => groups.push((group, vec![index])),
+                        }
+                    }
+                }
+                groups.sort_by(|(a, _), (b, _)| filter::compare_groups(a, b));
+                let mut list = widget::column().spacing(12);
+                if groups.is_empty() && !self.vms.is_empty() {
+                    list = list.push(widget::text::caption("No VMs match this filter"));
+                }
+                for (title, indices) in groups {
+                    let mut list_column =
+                        widget::list_column().style(theme::Container::ContextDrawer);
+                    for index in indices {
+                        list_column = list_column.add(self.vm_row(index, &self.vms[index]));
+                    }
+                    list = list
+                        .push_maybe((!title.is_empty()).then(|| widget::text::heading(title)))
+                        .push(list_column);
+                }
+                let filter_bar = widget::row()
+                    .push(widget::dropdown(
+                        &GroupBy::CHOICES,
+                        GroupBy::ALL
+                            .iter()
+                            .position(|group_by| *group_by == self.group_by),
+                        |choice| Message::SetGroupBy(choice).into(),
+                    ))
+                    .push(widget::dropdown(
+                        &self.filter_labels,
+                        self.filters
+                            .iter()
+                            .position(|filter| *filter == self.filter),
+                        |choice| Message::SetFilter(choice).into(),
+                    ))
+                    .spacing(8)
+                    .align_items(Alignment::Center);
+                let footer = widget::row()
+                    .push(widget::button::standard("Refresh").on_press(Message::Refresh.into()))
+                    .push(
+                        widget::button::standard("Installer images")
+                            .on_press(Message::OpenStorage.into()),
+                    )
+                    .push(
+                        widget::button::standard("Verify images")
+                            .on_press(Message::VerifyImages(None).into()),
+                    )
+                    .push(
+                        widget::button::standard("GPU passthrough")
+                            .on_press(Message::OpenPassthrough.into()),
+                    )
+                    .spacing(8);
+                let quickemu_banner = self.host.as_ref().filter(|host| !host.quickemu).map(|_| {
+                    widget::row()
+                        .push(icon::from_name("dialog-warning-symbolic").size(16).icon())
+                        .push(
+                            widget::text(format!(
+                                "{}. VMs can still be created, listed and edited.",
+                                host_probe::QUICKEMU_HINT
+                            ))
+                            .width(Length::Fill),
+                        )
+                        .push(widget::button::link(String::from("Get quickemu")).on_press(
+                            crate::app::Message::LaunchUrl(host_probe::QUICKEMU_URL.to_string()),
+                        ))
+                        .spacing(8)
+                        .align_items(Alignment::Center)
+                });
+                widget::column()
+                    .push_maybe(quickemu_banner)
+                    .push(filter_bar)
+                    .push_maybe((!self.checked.is_empty()).then(|| self.bulk_bar()))
+                    .push(widget::scrollable(list).height(Length::Fill))
+                    .push(footer)
+                    .into()
+            }
+            Page::Delete(deletion) => self.deletion_view(deletion),
+            Page::Export(dialog) => dialog.view(),
+            Page::Disk(panel) => panel.view(self.running.contains(&panel.vm().config_path)),
+            Page::Storage(manager) => manager.view(),
+            Page::Passthrough(panel) => panel.view(),
+            Page::Verify(panel) => panel.view(),
+            Page::Unlock(prompt) => Self::unlock_view(prompt),
+            Page::Checklist(config_path) => self.checklist_view(config_path),
+            Page::Overview(config_path) => self.overview_view(config_path),
+            Page::Bulk(run) => run.view(),
+            Page::Error(error, _) => {
+                let actions = ErrorActions {
+                    toggle_details: Message::ToggleErrorDetails.into(),
+                    copy_report: Message::CopyErrorReport.into(),
+                    retry: Some(Message::RetryDelete.into()),
+                    back: Some(Message::Refresh.into()),
+                };
+                error_view(error, self.error_details, actions)
+            }
+        }
+    }
+    fn vm_row<'a>(&'a self, index: usize, vm: &'a VM) -> Element<'a, crate::app::Message> {
+        let appearance = self
+            .metadata
+            .get(&vm.config_path)
+            .map(|metadata| &metadata.appearance);
+        let details = match &self.inline_edit {
+            Some(inline_edit) if inline_edit.config_path == vm.config_path => {
+                Self::inline_edit_view(
+                    inline_edit,
+                    self.edit_overcommit(inline_edit),
+                    self.host.as_deref(),
+                )
+            }
+            _ => {
+                let title = appearance
+                    .map_or_else(|| vm.name.clone(), |appearance| appearance.title(&vm.name));
+                let mut details = widget::column().push(widget::text::heading(title));
+                if let (Some(ram), Some(cpu_cores)) = (vm.ram(), vm.cpu_cores()) {
+                    details = details.push(widget::text::caption(format!(
+                        "{ram} RAM, {cpu_cores} CPU cores"
+                    )));
+                }
+                widget::mouse_area(details.width(Length::Fill))
+                    .on_press(Message::RowPressed(index).into())
+                    .into()
+            }
+        };
+        let running = self.running.contains(&vm.config_path);
+        let paused = self.paused.contains(&vm.config_path);
+        let badge = match self.errors.get(&vm.config_path) {
+            _ if paused => status_badge(Status::Paused, None),
+            _ if running => status_badge(Status::Running, None),
+            Some(error) => status_badge(Status::Error, Some(error.clone())),
+            None => status_badge(Status::Stopped, None),
+        };
+        let release_notice = self.release_notices.get(&vm.config_path).map(|notice| {
+            let release_badge = widget::row()
+                .push(icon::from_name("dialog-warning-symbolic").size(16).icon())
+                .push(widget::text::caption(notice.label()))
+                .spacing(4)
+                .align_items(Alignment::Center);
+            let upgrade_button = notice.upgrade.clone().map(|upgrade| {
+                widget::button::icon(icon::from_name("software-update-available-symbolic"))
+                    .on_press(crate::app::Message::CreateUpgraded(upgrade))
+                    .tooltip("Create upgraded VM")
+                    .width(Length::Shrink)
+            });
+            widget::row()
+                .push(widget::tooltip(
+                    release_badge,
+                    widget::text(notice.message()),
+                    tooltip::Position::Top,
+                ))
+                .push_maybe(upgrade_button)
+                .spacing(4)
+                .align_items(Alignment::Center)
+        });
+        let blocker = self.launch_blocker(vm);
+        let launch_button = widget::button::icon(icon::from_name("media-playback-start-symbolic"))
+            .on_press_maybe(
+                (!running && blocker.is_none()).then_some(Message::Launch(index).into()),
+            )
+            .tooltip(blocker.unwrap_or_else(|| format!("Launch {}", vm.name)))
+            .width(Length::Shrink);
+        let delete_blocker = self.delete_blocker(vm);
+        let delete_button = widget::button::icon(icon::from_name("user-trash-symbolic"))
+            .on_press_maybe(
+                (!running && delete_blocker.is_none())
+                    .then_some(Message::RequestDelete(index).into()),
+            )
+            .tooltip(delete_blocker.unwrap_or_else(|| format!("Delete {}", vm.name)))
+            .width(Length::Shrink);
+        let export_button = widget::button::icon(icon::from_name("document-export-symbolic"))
+            .on_press_maybe((!running).then_some(Message::RequestExport(index).into()))
+            .tooltip(format!("Export {}", vm.name))
+            .width(Length::Shrink);
+        let disk_button = widget::button::icon(icon::from_name("drive-harddisk-symbolic"))
+            .on_press(Message::OpenDisk(index).into())
+            .tooltip(format!("Disk of {}", vm.name))
+            .width(Length::Shrink);
+        let overview_button = widget::button::icon(icon::from_name("document-properties-symbolic"))
+            .on_press(Message::OpenOverview(index).into())
+            .tooltip(format!("Overview of {}", vm.name))
+            .width(Length::Shrink);
+        let checklist_button = self
+            .metadata
+            .get(&vm.config_path)
+            .and_then(|metadata| metadata.first_boot.as_ref())
+            .filter(|checklist| !checklist.is_complete())
+            .map(|checklist| {
+                widget::button::icon(icon::from_name("checkbox-checked-symbolic"))
+                    .on_press(Message::OpenChecklist(index).into())
+                    .tooltip(format!(
+                        "First-boot checklist ({}/{})",
+                        checklist.done.len(),
+                        ChecklistItem::ALL.len()
+                    ))
+                    .width(Length::Shrink)
+            });
+        let thumbnail: Element<_> = match self.thumbnails.get(&vm.config_path) {
+            Some(handle) => widget::image(handle.clone())
+                .width(Length::Fixed(THUMBNAIL_WIDTH.into()))
+                .height(Length::Fixed(THUMBNAIL_HEIGHT.into()))
+                .into(),
+            None => icon::from_name("computer-symbolic")
+                .size(32)
+                .icon()
+                .apply(widget::container)
+                .width(Length::Fixed(THUMBNAIL_WIDTH.into()))
+                .height(Length::Fixed(THUMBNAIL_HEIGHT.into()))
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center)
+                .into(),
+        };
+        let checkbox = widget::checkbox("", self.checked.contains(&vm.config_path))
+            .on_toggle(move |checked| Message::SetChecked(index, checked).into());
+        widget::row()
+            .push(checkbox)
+            .push_maybe(
+                appearance
+                    .and_then(|appearance| appearance.color)
+                    .map(Self::color_stripe),
+            )
+            .push(thumbnail)
+            .push(details)
+            .push(badge)
+            .push_maybe(release_notice)
+            .push_maybe(checklist_button)
+            .push_maybe(running.then(|| Self::control_buttons(index, &vm.name, paused)))
+            .push(launch_button)
+            .push(overview_button)
+            .push(disk_button)
+            .push(export_button)
+            .push(delete_button)
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .into()
+    }
+    /// Actions on the ticked VMs, shown above the list while any are ticked.
+    fn bulk_bar(&self) -> Element<crate::app::Message> {
+        let button = |action: BulkAction, label: &'static str| {
+            let button = if action == BulkAction::Delete {
+                widget::button::destructive(label)
+            } else {
+                widget::button::standard(label)
+            };
+            let (targets, _) = self.bulk_targets(action);
+            button.on_press_maybe(
+                (!targets.is_empty()).then_some(Message::RequestBulk(action).into()),
+            )
+        };
+        let tag_input = widget::text_input("Tag", &self.bulk_tag)
+            .on_input(|tag| Message::SetBulkTag(tag).into())
+            .on_submit(Message::RequestBulk(BulkAction::Tag).into())
+            .width(Length::Fixed(140.0));
+        widget::row()
+            .push(
+                widget::text(format!("{} selected", vm_count(self.checked.len())))
+                    .width(Length::Fill),
+            )
+            .push(button(BulkAction::Start, "Start"))
+            .push(button(BulkAction::Stop, "Shut down"))
+            .push(button(BulkAction::Export, "Export"))
+            .push(tag_input)
+            .push(button(BulkAction::Tag, "Add tag"))
+            .push(button(BulkAction::Untag, "Remove tag"))
+            .push(button(BulkAction::Delete, "Delete"))
+            .push(widget::button::text("Select all").on_press(Message::CheckAll(true).into()))
+            .push(widget::button::text("Clear").on_press(Message::CheckAll(false).into()))
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .into()
+    }
+    fn control_buttons<'a>(
+        index: usize,
+        name: &str,
+        paused: bool,
+    ) -> Element<'a, crate::app::Message> {
+        let pause_button = if paused {
+            widget::button::icon(icon::from_name("media-playback-start-symbolic"))
+                .on_press(Message::Resume(index).into())
+                .tooltip(format!("Resume {name}"))
+        } else {
+            widget::button::icon(icon::from_name("media-playback-pause-symbolic"))
+                .on_press(Message::Pause(index).into())
+                .tooltip(format!("Pause {name}"))
+        };
+        let power_button = widget::button::icon(icon::from_name("system-shutdown-symbolic"))
+            .on_press(Message::PowerDown(index).into())
+            .tooltip(format!("Shut down {name}"));
+        let force_off_button = widget::button::icon(icon::from_name("process-stop-symbolic"))
+            .on_press(Message::RequestForceOff(index).into())
+            .tooltip(format!("Force off {name}"));
+        widget::row()
+            .push(pause_button.width(Length::Shrink))
+            .push(power_button.width(Length::Shrink))
+            .push(force_off_button.width(Length::Shrink))
+            .into()
+    }
+    fn checklist_view<'a>(&'a self, config_path: &Path) -> Element<'a, crate::app::Message> {
+        let vm = self.vms.iter().find(|vm| vm.config_path == config_path);
+        let metadata = self.metadata.get(config_path);
+        let (Some(vm), Some(checklist)) = (vm, metadata.and_then(|m| m.first_boot.as_ref())) else {
+            return widget::text("loading")
+                .apply(widget::container)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center)
+                .into();
+        };
+        let running = self.running.iter().any(|path| path == config_path);
+        let mut list = widget::list_column();
+        for item in ChecklistItem::ALL {
+            let checkbox = widget::checkbox(item.label(), checklist.is_done(item))
+                .on_toggle(move |done| Message::SetChecklistItem(item, done).into());
+            let mut column = widget::column()
+                .push(checkbox)
+                .push(widget::text::caption(item.hint()))
+                .spacing(4);
+            match item {
+                ChecklistItem::FreshSnapshot => {
+                    column =
+                        column
+                            .push(widget::button::standard("Take snapshot").on_press_maybe(
+                                (!running).then_some(Message::TakeSnapshot.into()),
+                            ));
+                }
+                ChecklistItem::CredentialsNote => {
+                    let notes = metadata.map_or("", |metadata| metadata.notes.as_str());
+                    column = column.push(
+                        widget::row()
+                            .push(
+                                widget::text_input("Notes", notes)
+                                    .on_input(|notes| Message::SetNotes(notes).into())
+                                    .on_submit(Message::SaveNotes.into()),
+                            )
+                            .push(
+                                widget::button::standard("Save")
+                                    .on_press(Message::SaveNotes.into()),
+                            )
+                            .spacing(8),
+                    );
+                }
+                ChecklistItem::BootOnce | ChecklistItem::InstallGuestTools => {}
+            }
+            list = list.add(column);
+        }
+        let buttons = widget::row()
+            .push(
+                widget::button::standard("Dismiss checklist")
+                    .on_press(Message::DismissChecklist.into()),
+            )
+            .push(widget::button::suggested("Done").on_press(Message::CloseChecklist.into()))
+            .spacing(8);
+        widget::column()
+            .push(widget::text::title3(format!(
+                "First-boot checklist for {}",
+                vm.name
+            )))
+            .push_maybe(
+                self.errors
+                    .get(config_path)
+                    .map(|error| widget::text::caption(error.clone())),
+            )
+            .push(widget::scrollable(list))
+            .push(buttons)
+            .spacing(12)
+            .into()
+    }
+    fn overview_view<'a>(&'a self, config_path: &Path) -> Element<'a, crate::app::Message> {
+        let Some(vm) = self.vms.iter().find(|vm| vm.config_path == config_path) else {
+            return widget::text("loading")
+                .apply(widget::container)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center)
+                .into();
+        };
+        let metadata = self.metadata.get(config_path);
+        let title = metadata.map_or_else(
+            || vm.name.clone(),
+            |metadata| metadata.appearance.title(&vm.name),
+        );
+        let cloned_from = metadata
+            .and_then(|metadata| metadata.cloned_from.as_ref())
+            .map(|template| {
+                let name = template
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                widget::text::caption(format!("Linked clone of {name}"))
+            });
+        let mut details = widget::column().spacing(4);
+        if let (Some(ram), Some(cpu_cores)) = (vm.ram(), vm.cpu_cores()) {
+            details = details.push(widget::text(format!("{ram} RAM, {cpu_cores} CPU cores")));
+        }
+        details = details
+            .push(widget::text::caption(vm.vm_dir().display().to_string()))
+            .push_maybe(cloned_from);
+        let autostart = widget::toggler(
+            String::from("Start when QERSUI opens"),
+            metadata.is_some_and(|metadata| metadata.autostart),
+            |autostart| Message::SetAutostart(autostart).into(),
+        );
+        let is_template = metadata.is_some_and(|metadata| metadata.template);
+        let template_toggle = widget::toggler(
+            String::from("Use as a template for linked clones"),
+            is_template,
+            |template| Message::SetTemplate(template).into(),
+        );
+        let clones = self.clone_count(config_path);
+        let template_actions = is_template.then(|| {
+            let caption = match clones {
+                0 => String::from(
+                    "Clones share this VM's disk and only store their own changes. It can't be \
+                     started while any exist.",
+                ),
+                1 => String::from("1 linked clone is based on this template."),
+                count => format!("{count} linked clones are based on this template."),
+            };
+            let running = self.running.contains(&vm.config_path);
+            widget::column()
+                .push(widget::text::caption(caption))
+                .push(
+                    widget::button::standard("New VM from template")
+                        .on_press_maybe((!running).then_some(Message::CloneTemplate.into())),
+                )
+                .spacing(8)
+        });
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let activity = metadata.map_or(&[][..], |metadata| metadata.activity.as_slice());
+        let timeline: Element<_> = if activity.is_empty() {
+            widget::text::caption("No activity recorded yet").into()
+        } else {
+            let mut list = widget::list_column();
+            // Newest first.
+            for entry in activity.iter().rev() {
+                let mut text = widget::column().push(widget::text(entry.kind.label()));
+                if let Some(detail) = &entry.detail {
+                    text = text.push(widget::text::caption(detail.clone()));
+                }
+                list = list.add(
+                    widget::row()
+                        .push(icon::from_name(entry.kind.icon_name()).size(16).icon())
+                        .push(text.width(Length::Fill))
+                        .push(widget::text::caption(format_age(entry.at, now)))
+                        .spacing(8)
+                        .align_items(Alignment::Center),
+                );
+            }
+            widget::scrollable(list).height(Length::Fill).into()
+        };
+        widget::column()
+            .push(widget::text::title3(title))
+            .push(details)
+            .push(autostart)
+            .push(template_toggle)
+            .push_maybe(template_actions)
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard("Verify installer images")
+                            .on_press(Message::VerifyImages(Some(vm.config_path.clone())).into()),
+                    )
+                    .push_maybe(
+                        metadata
+                            .is_some_and(|metadata| metadata.recipe.is_some())
+                            .then(|| {
+                                widget::button::standard("Copy VM spec")
+                                    .on_press(Message::CopySpec.into())
+                            }),
+                    )
+                    .spacing(8),
+            )
+            .push(widget::text::heading("Activity"))
+            .push(timeline)
+            .push(widget::button::suggested("Done").on_press(Message::CloseOverview.into()))
+            .spacing(12)
+            .into()
+    }
+    fn unlock_view(prompt: &UnlockPrompt) -> Element<crate::app::Message> {
+        let passphrase_input = widget::text_input("Passphrase", &prompt.passphrase)
+            .password()
+            .on_input(|passphrase| Message::SetUnlockPassphrase(passphrase).into())
+            .on_submit(Message::Unlock.into());
+        let remember_checkbox =
+            widget::checkbox("Remember passphrase in the keyring", prompt.remember)
+                .on_toggle(|remember| Message::SetRememberPassphrase(remember).into());
+        let buttons =
+            widget::row()
+                .push(widget::button::standard("Cancel").on_press(Message::CancelUnlock.into()))
+                .push(widget::button::suggested("Unlock").on_press_maybe(
+                    (!prompt.passphrase.is_empty()).then_some(Message::Unlock.into()),
+                ))
+                .spacing(8);
+        widget::column()
+            .push(widget::text::title3(format!("Unlock {}", prompt.vm.name)))
+            .push(widget::text(
+                "The disk image is encrypted. Enter its passphrase to start the VM.",
+            ))
+            .push(passphrase_input)
+            .push(remember_checkbox)
+            .push(buttons)
+            .spacing(12)
+            .into()
+    }
+    /// A bar in the VM's accent color at the start of its row.
+    fn color_stripe<'a>(color: AccentColor) -> Element<'a, crate::app::Message> {
+        let (r, g, b) = color.rgb();
+        widget::Space::new(Length::Fixed(4.0), Length::Fixed(THUMBNAIL_HEIGHT.into()))
+            .apply(widget::container)
+            .style(theme::Container::custom(move |_| {
+                cosmic::iced_style::container::Appearance {
+                    background: Some(cosmic::iced::Color::from_rgb8(r, g, b).into()),
+                    ..Default::default()
+                }
+            }))
+            .into()
+    }
+    /// Whether the edited RAM and CPU cores would over-commit the host, counted with the other
+    /// running and autostart VMs.
+    fn edit_overcommit(&self, inline_edit: &InlineEdit) -> Option<Overcommit> {
+        let edit = &inline_edit.edit;
+        let ram = match edit.ram.trim() {
+            "" => QuickgetInstance::get_recommended_ram(),
+            ram => parse_size(ram)?,
+        };
+        let cpu_cores = match edit.cpu_cores.trim() {
+            "" => QuickgetInstance::get_recommended_cpu_cores(),
+            cores => cores.parse().ok()?,
+        };
+        self.commitments()
+            .check(Some(&inline_edit.config_path), ram, cpu_cores)
+    }
+    fn inline_edit_view<'a>(
+        inline_edit: &'a InlineEdit,
+        overcommit: Option<Overcommit>,
+        host: Option<&HostCapabilities>,
+    ) -> Element<'a, crate::app::Message> {
+        let InlineEdit {
+            edit,
+            appearance,
+            error,
+            ..
+        } = inline_edit;
+        let inputs = widget::row()
+            .push(
+                widget::text_input("Name", &edit.name)
+                    .on_input(|name| Message::SetEditName(name).into())
+                    .on_submit(Message::CommitEdit.into()),
+            )
+            .push(
+                widget::text_input("RAM", &edit.ram)
+                    .on_input(|ram| Message::SetEditRAM(ram).into())
+                    .on_submit(Message::CommitEdit.into())
+                    .width(Length::Fixed(80.0)),
+            )
+            .push(
+                widget::text_input("CPU cores", &edit.cpu_cores)
+                    .on_input(|cores| Message::SetEditCPUCores(cores).into())
+                    .on_submit(Message::CommitEdit.into())
+                    .width(Length::Fixed(80.0)),
+            )
+            .push(
+                widget::text_input("Glyph", &appearance.glyph)
+                    .on_input(|glyph| Message::SetEditGlyph(glyph).into())
+                    .on_submit(Message::CommitEdit.into())
+                    .width(Length::Fixed(56.0)),
+            )
+            .push(widget::dropdown(
+                &appearance::COLOR_CHOICES,
+                Some(AccentColor::choice(appearance.color)),
+                |choice| Message::SetEditColor(choice).into(),
+            ))
+            .push(
+                widget::button::icon(icon::from_name("object-select-symbolic"))
+                    .on_press(Message::CommitEdit.into())
+                    .tooltip("Save changes"),
+            )
+            .push(
+                widget::button::icon(icon::from_name("window-close-symbolic"))
+                    .on_press(Message::CancelEdit.into())
+                    .tooltip("Discard changes"),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let tags_input = widget::text_input("Tags, separated by commas", &inline_edit.tags)
+            .on_input(|tags| Message::SetEditTags(tags).into())
+            .on_submit(Message::CommitEdit.into());
+        let bridge_dropdown = match &edit.network {
+            NetworkMode::Bridged(bridge) => Some(widget::dropdown(
+                &inline_edit.bridges,
+                inline_edit.bridges.iter().position(|b| b == bridge),
+                |index| Message::SetEditBridge(index).into(),
+            )),
+            _ => None,
+        };
+        let unsupported = |key: &str| host.and_then(|host| host.unsupported(key));
+        let mac_unsupported = unsupported("macaddr");
+        let microphone_unsupported = unsupported("sound_duplex");
+        let tablet_unsupported = unsupported("mouse");
+        let network_row = widget::row()
+            .push(widget::text("Network"))
+            .push(widget::dropdown(
+                &guest_network::MODE_CHOICES,
+                Some(edit.network.choice()),
+                |choice| Message::SetEditNetwork(choice).into(),
+            ))
+            .push_maybe(bridge_dropdown)
+            .push({
+                let mut mac_input = widget::text_input("MAC address (automatic)", &edit.mac)
+                    .width(Length::Fixed(180.0));
+                if mac_unsupported.is_none() {
+                    mac_input = mac_input
+                        .on_input(|mac| Message::SetEditMac(mac).into())
+                        .on_submit(Message::CommitEdit.into());
+                }
+                gated(mac_input, mac_unsupported)
+            })
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let devices_row = widget::row()
+            .push(widget::text("Sound"))
+            .push(widget::dropdown(
+                &devices::SOUND_CARD_CHOICES,
+                edit.devices.sound_card_choice(),
+                |choice| Message::SetEditSoundCard(choice).into(),
+            ))
+            .push_maybe(edit.devices.has_microphone_option().then(|| {
+                let mut checkbox = widget::checkbox("Microphone", edit.devices.microphone);
+                if microphone_unsupported.is_none() {
+                    checkbox = checkbox
+                        .on_toggle(|microphone| Message::SetEditMicrophone(microphone).into());
+                }
+                gated(checkbox, microphone_unsupported)
+            }))
+            .push({
+                let mut checkbox = widget::checkbox("Tablet pointer", edit.devices.tablet);
+                if tablet_unsupported.is_none() {
+                    checkbox = checkbox.on_toggle(|tablet| Message::SetEditTablet(tablet).into());
+                }
+                gated(checkbox, tablet_unsupported)
+            })
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let mut column = widget::column()
+            .push(inputs)
+            .push_maybe(
+                overcommit
+                    .map(|overcommit| overcommit_view(&overcommit, crate::app::Message::ShowVM)),
+            )
+            .push(network_row)
+            .push_maybe(
+                inline_edit
+                    .network_warning
+                    .as_ref()
+                    .map(|warning| widget::text::caption(warning.clone())),
+            )
+            .push(devices_row)
+            .push(tags_input)
+            .push(extra_keys_view(
+                &edit.extra,
+                &EDIT_MANAGED,
+                inline_edit.show_advanced,
+                ExtraKeyActions {
+                    toggle: Message::ToggleEditAdvanced.into(),
+                    set_key: |index, key| Message::SetEditExtraKey(index, key).into(),
+                    set_value: |index, value| Message::SetEditExtraValue(index, value).into(),
+                    remove: |index| Message::RemoveEditExtra(index).into(),
+                    add: Message::AddEditExtra.into(),
+                },
+            ))
+            .spacing(4)
+            .width(Length::Fill);
+        if let Some(error) = error {
+            column = column.push(widget::text::caption(error.clone()));
+        }
+        column.into()
+    }
+    fn deletion_view<'a>(&'a self, deletion: &'a Deletion) -> Element<'a, crate::app::Message> {
+        let mut list = widget::list_column();
+        for item in &deletion.items {
+            let kept = deletion.keep_installer && item.kind == ItemKind::Installer;
+            let kind = if kept {
+                format!("{} (kept)", item.kind.label())
+            } else {
+                item.kind.label().to_string()
+            };
+            let row = widget::row()
+                .push(widget::text(kind).width(Length::Fixed(160.0)))
+                .push(widget::text(item.path.to_string_lossy().into_owned()).width(Length::Fill))
+                .push(widget::text(format_size(item.size)).width(Length::Shrink));
+            list = list.add(row);
+        }
+
+        let reclaimed = deletion.selected().map(|item| item.size).sum::<u64>();
+        let mut column = widget::column()
+            .push(widget::text::title3(format!(
+                "Delete {}?",
+                deletion.vm.name
+            )))
+            .push(widget::text(
+                "The following files will be permanently removed:",
+            ))
+            .push(list)
+            .push(widget::text(format!(
+                "Total reclaimed: {}",
+                format_size(reclaimed)
+            )))
+            .spacing(12);
+
+        match &deletion.progress {
+            None => {
+                if deletion
+                    .items
+                    .iter()
+                    .any(|item| item.kind == ItemKind::Installer)
+                {
+                    let keep_toggle = widget::toggler(
+                        String::from("Keep downloaded installer image"),
+                        deletion.keep_installer,
+                        |keep| Message::SetKeepInstaller(keep).into(),
+                    );
+                    column = column.push(keep_toggle);
+                }
+                let buttons = widget::row()
+                    .push(widget::button::standard("Cancel").on_press(Message::CancelDelete.into()))
+                    .push(
+                        widget::button::destructive("Delete")
+                            .on_press(Message::DeleteSelected.into()),
+                    )
+                    .spacing(8);
+                column = column.push(buttons);
+            }
+            Some(progress) => {
+                let status = match progress.queue.first() {
+                    Some(item) => format!("Removing {}", item.path.to_string_lossy()),
+                    None => String::from("Finishing"),
+                };
+                let bar = widget::progress_bar(
+                    0.0..=progress.total.max(1) as f32,
+                    progress.reclaimed as f32,
+                );
+                column = column.push(bar).push(widget::text(format!(
+                    "{status} ({} of {} files, {} reclaimed)",
+                    progress.removed,
+                    progress.removed + progress.queue.len(),
+                    format_size(progress.reclaimed)
+                )));
+            }
+        }
+        widget::scrollable(column).into()
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(val: Message) -> Self {
+        crate::app::Message::Library(val)
+    }
+}
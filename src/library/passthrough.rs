@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::app::Command;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+
+use super::Message;
+use crate::core::vfio::{self, HostReport};
+use crate::core::vm::VM;
+use crate::widgets::config_view::config_view;
+
+/// Checks the host for passing a GPU to a VM with VFIO, without changing anything on it.
+#[derive(Clone, Debug)]
+pub struct PassthroughPanel {
+    vms: Vec<VM>,
+    vm_names: Vec<String>,
+    /// `None` while scanning.
+    report: Option<HostReport>,
+    gpu_labels: Vec<String>,
+    selected_gpu: usize,
+    /// Index into `vms` of the VM to add the GPU to.
+    target: Option<usize>,
+    applying: bool,
+    result: Option<Result<String, String>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum PassthroughMessage {
+    Scanned(HostReport),
+    Rescan,
+    SelectGpu(usize),
+    SetTarget(usize),
+    CopyOptions,
+    Apply,
+    Applied(Result<VM, String>),
+    Close,
+}
+
+impl PassthroughPanel {
+    pub fn new(vms: Vec<VM>) -> (Self, Command<crate::app::Message>) {
+        let panel = Self {
+            vm_names: vms.iter().map(|vm| vm.name.clone()).collect(),
+            vms,
+            report: None,
+            gpu_labels: Vec::new(),
+            selected_gpu: 0,
+            target: None,
+            applying: false,
+            result: None,
+        };
+        (panel, Self::scan())
+    }
+    pub fn is_applying(&self) -> bool {
+        self.applying
+    }
+    fn scan() -> Command<crate::app::Message> {
+        Command::perform(vfio::scan(), |report| {
+            crate::app::Message::Library(Message::Passthrough(PassthroughMessage::Scanned(report)))
+                .into()
+        })
+    }
+    fn gpu(&self) -> Option<&vfio::Gpu> {
+        self.report.as_ref()?.gpus.get(self.selected_gpu)
+    }
+    pub fn update(&mut self, message: PassthroughMessage) -> Command<crate::app::Message> {
+        match message {
+            PassthroughMessage::Scanned(report) => {
+                self.gpu_labels = report
+                    .gpus
+                    .iter()
+                    .map(|gpu| format!("{} ({})", gpu.device.name, gpu.device.address))
+                    .collect();
+                if self.selected_gpu >= report.gpus.len() {
+                    self.selected_gpu = 0;
+                }
+                self.report = Some(report);
+            }
+            PassthroughMessage::Rescan => {
+                self.report = None;
+                return Self::scan();
+            }
+            PassthroughMessage::SelectGpu(index) => {
+                self.selected_gpu = index;
+                self.result = None;
+            }
+            PassthroughMessage::SetTarget(index) => self.target = Some(index),
+            PassthroughMessage::CopyOptions => {
+                if let Some(gpu) = self.gpu() {
+                    return cosmic::iced::clipboard::write(vfio::config_snippet(gpu));
+                }
+            }
+            PassthroughMessage::Apply => {
+                let (Some(gpu), Some(vm)) = (
+                    self.gpu().cloned(),
+                    self.target.and_then(|index| self.vms.get(index)).cloned(),
+                ) else {
+                    return Command::none();
+                };
+                self.applying = true;
+                self.result = None;
+                return Command::perform(vfio::apply(vm, gpu), |result| {
+                    crate::app::Message::Library(Message::Passthrough(PassthroughMessage::Applied(
+                        result,
+                    )))
+                    .into()
+                });
+            }
+            PassthroughMessage::Applied(result) => {
+                self.applying = false;
+                self.result = Some(result.map(|vm| {
+                    let message = format!("Added to {}", vm.name);
+                    if let Some(existing) = self
+                        .vms
+                        .iter_mut()
+                        .find(|existing| existing.config_path == vm.config_path)
+                    {
+                        *existing = vm;
+                    }
+                    message
+                }));
+            }
+            PassthroughMessage::Close => {}
+        }
+        Command::none()
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        let mut column = widget::column()
+            .push(widget::text::title3("GPU passthrough"))
+            .push(widget::text::caption(
+                "Checks whether a GPU can be handed to a VM with VFIO. Nothing on the host is \
+                 changed; the remaining steps are listed for you to take.",
+            ))
+            .spacing(12);
+        let buttons = widget::row()
+            .push(
+                widget::button::standard("Rescan").on_press_maybe(
+                    self.report
+                        .is_some()
+                        .then_some(Message::Passthrough(PassthroughMessage::Rescan).into()),
+                ),
+            )
+            .push(widget::button::standard("Close").on_press_maybe(
+                (!self.applying).then_some(Message::Passthrough(PassthroughMessage::Close).into()),
+            ))
+            .spacing(8);
+
+        let Some(report) = &self.report else {
+            return column.push(widget::text("Scanning…")).push(buttons).into();
+        };
+        let Some(gpu) = self.gpu() else {
+            return column
+                .push(widget::text("No GPUs found on this host"))
+                .push(buttons)
+                .into();
+        };
+        if self.gpu_labels.len() > 1 {
+            column = column.push(widget::dropdown(
+                &self.gpu_labels,
+                Some(self.selected_gpu),
+                |index| Message::Passthrough(PassthroughMessage::SelectGpu(index)).into(),
+            ));
+        } else {
+            column = column.push(widget::text::heading(self.gpu_labels[0].clone()));
+        }
+
+        let requirements = vfio::requirements(report, gpu);
+        let ready = requirements.iter().all(|requirement| requirement.met);
+        let mut checklist = widget::list_column();
+        for requirement in requirements {
+            let icon_name = if requirement.met {
+                "emblem-ok-symbolic"
+            } else {
+                "dialog-warning-symbolic"
+            };
+            checklist = checklist.add(
+                widget::row()
+                    .push(icon::from_name(icon_name).size(16).icon())
+                    .push(
+                        widget::column()
+                            .push(widget::text(requirement.label))
+                            .push(widget::text::caption(requirement.details)),
+                    )
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            );
+        }
+
+        let mut devices = widget::list_column();
+        for device in gpu.passed_devices() {
+            let driver = device.driver.as_deref().unwrap_or("no driver");
+            devices = devices.add(
+                widget::column()
+                    .push(widget::text(device.name.clone()))
+                    .push(widget::text::caption(format!(
+                        "{}, [{}], {driver}",
+                        device.address,
+                        device.ids()
+                    ))),
+            );
+        }
+        let group = gpu.group.map_or_else(
+            || String::from("No IOMMU group"),
+            |group| format!("IOMMU group {group}"),
+        );
+
+        let apply_row = widget::row()
+            .push(widget::dropdown(&self.vm_names, self.target, |index| {
+                Message::Passthrough(PassthroughMessage::SetTarget(index)).into()
+            }))
+            .push(
+                widget::button::standard("Add to VM").on_press_maybe(
+                    (self.target.is_some() && !self.applying)
+                        .then_some(Message::Passthrough(PassthroughMessage::Apply).into()),
+                ),
+            )
+            .push(
+                widget::button::standard("Copy")
+                    .on_press(Message::Passthrough(PassthroughMessage::CopyOptions).into()),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center);
+
+        column = column
+            .push(widget::text::heading("Host"))
+            .push(checklist)
+            .push(widget::text::heading(format!("Passed devices, {group}")))
+            .push(devices)
+            .push(widget::text::heading("quickemu config"))
+            .push(config_view(&vfio::config_snippet(gpu)))
+            .push_maybe((!ready).then(|| {
+                widget::text::caption(
+                    "The VM won't start with these options until the steps above are done.",
+                )
+            }))
+            .push(apply_row);
+        match &self.result {
+            Some(Ok(message)) => column = column.push(widget::text(message.clone())),
+            Some(Err(e)) => column = column.push(widget::text(format!("Could not add: {e}"))),
+            None => {}
+        }
+        widget::scrollable(column.push(buttons).width(Length::Fill)).into()
+    }
+}
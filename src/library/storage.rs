@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cosmic::app::Command;
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+
+use super::Message;
+use crate::core::installers::{self, Installer};
+use crate::core::units::{format_age, format_size};
+use crate::core::vm::VM;
+
+/// Downloaded installer images across the library, for reclaiming their space.
+#[derive(Clone, Debug)]
+pub struct StorageManager {
+    vms: Vec<VM>,
+    /// `None` while scanning.
+    installers: Option<Vec<Installer>>,
+    selected: Vec<PathBuf>,
+    deleting: bool,
+    result: Option<Result<String, String>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum StorageMessage {
+    Scanned(Vec<Installer>),
+    SetSelected(PathBuf, bool),
+    SelectAll(bool),
+    DeleteSelected,
+    Deleted(Result<u64, String>),
+    Close,
+}
+
+impl StorageManager {
+    pub fn new(vms: Vec<VM>) -> (Self, Command<crate::app::Message>) {
+        let manager = Self {
+            vms,
+            installers: None,
+            selected: Vec::new(),
+            deleting: false,
+            result: None,
+        };
+        let command = manager.scan();
+        (manager, command)
+    }
+    pub fn is_deleting(&self) -> bool {
+        self.deleting
+    }
+    fn scan(&self) -> Command<crate::app::Message> {
+        Command::perform(installers::scan(self.vms.clone()), |installers| {
+            crate::app::Message::Library(Message::Storage(StorageMessage::Scanned(installers)))
+                .into()
+        })
+    }
+    pub fn update(&mut self, message: StorageMessage) -> Command<crate::app::Message> {
+        match message {
+            StorageMessage::Scanned(installers) => {
+                self.selected
+                    .retain(|path| installers.iter().any(|installer| installer.path == *path));
+                self.installers = Some(installers);
+            }
+            StorageMessage::SetSelected(path, selected) => {
+                self.selected.retain(|selected| *selected != path);
+                if selected {
+                    self.selected.push(path);
+                }
+            }
+            StorageMessage::SelectAll(selected) => {
+                self.selected = match (&self.installers, selected) {
+                    (Some(installers), true) => installers
+                        .iter()
+                        .map(|installer| installer.path.clone())
+                        .collect(),
+                    _ => Vec::new(),
+                };
+            }
+            StorageMessage::DeleteSelected => {
+                if self.deleting || self.selected.is_empty() {
+                    return Command::none();
+                }
+                let doomed = self
+                    .installers
+                    .iter()
+                    .flatten()
+                    .filter(|installer| self.selected.contains(&installer.path))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                self.deleting = true;
+                self.result = None;
+                return Command::perform(
+                    async move {
+                        let mut reclaimed = 0;
+                        for installer in &doomed {
+                            reclaimed += installers::delete(installer).await?;
+                        }
+                        Ok::<_, String>(reclaimed)
+                    },
+                    |result| {
+                        crate::app::Message::Library(Message::Storage(StorageMessage::Deleted(
+                            result,
+                        )))
+                        .into()
+                    },
+                );
+            }
+            StorageMessage::Deleted(result) => {
+                self.deleting = false;
+                self.selected.clear();
+                self.result =
+                    Some(result.map(|reclaimed| format!("Freed {}", format_size(reclaimed))));
+                return self.scan();
+            }
+            StorageMessage::Close => {}
+        }
+        Command::none()
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        let mut column = widget::column()
+            .push(widget::text::title3("Installer images"))
+            .push(widget::text::caption(
+                "ISOs downloaded into VM directories. Deleting one also removes it from the VMs \
+                 that attach it, so keep those still being installed.",
+            ))
+            .spacing(12);
+
+        let Some(installers) = &self.installers else {
+            return column.push(widget::text("Scanning…")).into();
+        };
+        if installers.is_empty() {
+            column = column.push(widget::text("No downloaded installer images"));
+        } else {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            let mut list = widget::list_column();
+            for installer in installers {
+                let name = installer
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let path = installer.path.clone();
+                let checkbox = widget::checkbox(name, self.selected.contains(&installer.path))
+                    .on_toggle(move |selected| {
+                        Message::Storage(StorageMessage::SetSelected(path.clone(), selected)).into()
+                    });
+                let vms = installer
+                    .vms
+                    .iter()
+                    .map(|vm| vm.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let last_used = installer.last_used.map_or_else(
+                    || String::from("never started"),
+                    |at| format!("last used {}", format_age(at, now)),
+                );
+                list = list.add(
+                    widget::column()
+                        .push(checkbox)
+                        .push(widget::text::caption(format!(
+                            "{}, {vms}, {last_used}",
+                            format_size(installer.size)
+                        )))
+                        .spacing(4),
+                );
+            }
+            let all_selected = self.selected.len() == installers.len();
+            let selected_size = installers
+                .iter()
+                .filter(|installer| self.selected.contains(&installer.path))
+                .map(|installer| installer.size)
+                .sum::<u64>();
+            column = column
+                .push(
+                    widget::checkbox("Select all", all_selected).on_toggle(|selected| {
+                        Message::Storage(StorageMessage::SelectAll(selected)).into()
+                    }),
+                )
+                .push(widget::scrollable(list).height(Length::Fill));
+
+            let delete_button = widget::button::destructive(format!(
+                "Delete selected ({})",
+                format_size(selected_size)
+            ))
+            .on_press_maybe(
+                (!self.deleting && !self.selected.is_empty())
+                    .then_some(Message::Storage(StorageMessage::DeleteSelected).into()),
+            );
+            column = column.push(delete_button);
+        }
+        match &self.result {
+            Some(Ok(freed)) => column = column.push(widget::text(freed.clone())),
+            Some(Err(e)) => column = column.push(widget::text(format!("Deletion failed: {e}"))),
+            None => {}
+        }
+        column
+            .push(widget::button::standard("Close").on_press_maybe(
+                (!self.deleting).then_some(Message::Storage(StorageMessage::Close).into()),
+            ))
+            .into()
+    }
+}
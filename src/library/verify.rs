@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::app::Command;
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{subscription, Alignment, Length, Subscription};
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+
+use super::Message;
+use crate::core::download::DownloadProgress;
+use crate::core::reverify::{self, ImageCheck, ImageStatus};
+use crate::core::units::format_size;
+use crate::core::vm::VM;
+
+/// Re-checks the installer images of one or more VMs against their published checksums.
+#[derive(Clone, Debug)]
+pub struct VerifyPanel {
+    vms: Vec<VM>,
+    /// Per VM, in the order of `vms`; `None` until checked. VMs are checked one at a time so
+    /// their images aren't all read from disk at once.
+    results: Vec<Option<Vec<ImageCheck>>>,
+    repairing: Option<Repair>,
+}
+
+#[derive(Clone, Debug)]
+struct Repair {
+    vm: usize,
+    image: usize,
+    progress: DownloadProgress,
+}
+
+#[derive(Clone, Debug)]
+pub enum VerifyMessage {
+    Checked(usize, Vec<ImageCheck>),
+    /// Download image `.1` of VM `.0` again.
+    Repair(usize, usize),
+    RepairProgress(DownloadProgress),
+    Repaired(ImageStatus),
+    Close,
+}
+
+impl VerifyPanel {
+    pub fn new(vms: Vec<VM>) -> (Self, Command<crate::app::Message>) {
+        let panel = Self {
+            results: vec![None; vms.len()],
+            vms,
+            repairing: None,
+        };
+        let command = panel.check(0);
+        (panel, command)
+    }
+    pub fn is_repairing(&self) -> bool {
+        self.repairing.is_some()
+    }
+    fn check(&self, index: usize) -> Command<crate::app::Message> {
+        let Some(vm) = self.vms.get(index).cloned() else {
+            return Command::none();
+        };
+        Command::perform(async move { reverify::check(&vm).await }, move |checks| {
+            crate::app::Message::Library(Message::Verify(VerifyMessage::Checked(index, checks)))
+                .into()
+        })
+    }
+    pub fn update(&mut self, message: VerifyMessage) -> Command<crate::app::Message> {
+        match message {
+            VerifyMessage::Checked(index, checks) => {
+                if let Some(result) = self.results.get_mut(index) {
+                    *result = Some(checks);
+                    return self.check(index + 1);
+                }
+            }
+            VerifyMessage::Repair(vm, image) => {
+                if self.repairing.is_none() {
+                    self.repairing = Some(Repair {
+                        vm,
+                        image,
+                        progress: DownloadProgress::default(),
+                    });
+                }
+            }
+            VerifyMessage::RepairProgress(progress) => {
+                if let Some(repair) = &mut self.repairing {
+                    repair.progress = progress;
+                }
+            }
+            VerifyMessage::Repaired(status) => {
+                if let Some(repair) = self.repairing.take() {
+                    if let Some(check) = self.image_mut(repair.vm, repair.image) {
+                        check.status = status;
+                    }
+                }
+            }
+            VerifyMessage::Close => {}
+        }
+        Command::none()
+    }
+    fn image(&self, vm: usize, image: usize) -> Option<&ImageCheck> {
+        self.results.get(vm)?.as_ref()?.get(image)
+    }
+    fn image_mut(&mut self, vm: usize, image: usize) -> Option<&mut ImageCheck> {
+        self.results.get_mut(vm)?.as_mut()?.get_mut(image)
+    }
+    pub fn subscription(&self) -> Subscription<crate::app::Message> {
+        let Some(check) = self
+            .repairing
+            .as_ref()
+            .and_then(|repair| self.image(repair.vm, repair.image))
+            .cloned()
+        else {
+            return Subscription::none();
+        };
+        let id = (check.path.clone(), check.source.url.clone());
+        subscription::channel(id, 100, move |mut output| async move {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let task = tokio::spawn(async move {
+                reverify::repair(&check, |progress| {
+                    let _ = tx.send(progress);
+                })
+                .await
+            });
+            while let Some(progress) = rx.recv().await {
+                let msg = Message::Verify(VerifyMessage::RepairProgress(progress));
+                let _ = output.send(msg.into()).await;
+            }
+            let status = match task.await {
+                Ok(Ok(status)) => status,
+                Ok(Err(e)) => ImageStatus::Failed(e.to_string()),
+                Err(e) => ImageStatus::Failed(e.to_string()),
+            };
+            let _ = output
+                .send(Message::Verify(VerifyMessage::Repaired(status)).into())
+                .await;
+            std::future::pending().await
+        })
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        let checks = self.results.iter().flatten().flatten();
+        let problems = checks
+            .clone()
+            .filter(|check| check.status.repairable())
+            .count();
+        let verified = checks
+            .filter(|check| check.status == ImageStatus::Verified)
+            .count();
+        let summary = if self.results.iter().any(Option::is_none) {
+            String::from("Checking…")
+        } else {
+            format!("{verified} verified, {problems} damaged or missing")
+        };
+        let mut column = widget::column()
+            .push(widget::text::title3("Verify installer images"))
+            .push(widget::text(summary))
+            .spacing(12);
+
+        let mut list = widget::list_column();
+        for (vm_index, (vm, result)) in self.vms.iter().zip(&self.results).enumerate() {
+            let mut details = widget::column()
+                .push(widget::text::heading(vm.name.clone()))
+                .spacing(4);
+            match result {
+                None => details = details.push(widget::text::caption("Waiting…")),
+                Some(checks) if checks.is_empty() => {
+                    details = details.push(widget::text::caption(
+                        "No record of its downloads, and its release isn't in the OS catalog",
+                    ))
+                }
+                Some(checks) => {
+                    for (image_index, check) in checks.iter().enumerate() {
+                        details = details.push(self.image_row(vm_index, image_index, check));
+                    }
+                }
+            }
+            list = list.add(details);
+        }
+        column = column.push(widget::scrollable(list).height(Length::Fill));
+
+        let close_button = widget::button::standard("Close").on_press_maybe(
+            self.repairing
+                .is_none()
+                .then_some(Message::Verify(VerifyMessage::Close).into()),
+        );
+        column.push(close_button).into()
+    }
+    fn image_row<'a>(
+        &'a self,
+        vm: usize,
+        image: usize,
+        check: &'a ImageCheck,
+    ) -> Element<'a, crate::app::Message> {
+        let icon_name = match check.status {
+            ImageStatus::Verified => "emblem-ok-symbolic",
+            ImageStatus::Unchecked => "dialog-question-symbolic",
+            _ => "dialog-warning-symbolic",
+        };
+        let repairing = self
+            .repairing
+            .as_ref()
+            .filter(|repair| repair.vm == vm && repair.image == image);
+        let status: Element<_> = match repairing {
+            Some(repair) => {
+                let progress = &repair.progress;
+                let total = progress.total.unwrap_or(0);
+                widget::column()
+                    .push(
+                        widget::progress_bar(0.0..=total.max(1) as f32, progress.downloaded as f32)
+                            .width(Length::Fixed(240.0)),
+                    )
+                    .push(widget::text::caption(format!(
+                        "Downloading again, {} of {}",
+                        format_size(progress.downloaded),
+                        format_size(total)
+                    )))
+                    .into()
+            }
+            None => widget::text::caption(check.status.label()).into(),
+        };
+        let repair_button = check.status.repairable().then(|| {
+            widget::button::standard("Download again").on_press_maybe(
+                self.repairing
+                    .is_none()
+                    .then_some(Message::Verify(VerifyMessage::Repair(vm, image)).into()),
+            )
+        });
+        widget::row()
+            .push(icon::from_name(icon_name).size(16).icon())
+            .push(
+                widget::column()
+                    .push(widget::text(check.source.file_name.clone()))
+                    .push(status)
+                    .width(Length::Fill),
+            )
+            .push_maybe(repair_button)
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .into()
+    }
+}
@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::time::{Duration, Instant};
+
+use cosmic::app::Command;
+use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::{event, mouse, Alignment, Length, Subscription};
+use cosmic::widget;
+use cosmic::{Apply, Element};
+
+use crate::core::lock::{self, AppLock, LockMethod};
+
+/// Covers the UI while the app lock is active and tracks inactivity for auto-lock.
+#[derive(Clone, Debug)]
+pub struct LockScreen {
+    locked: bool,
+    last_activity: Instant,
+    input: String,
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    Activity,
+    Tick,
+    LockNow,
+    SetInput(String),
+    Unlock,
+    Unlocked(Result<(), String>),
+}
+
+impl LockScreen {
+    pub fn new(app_lock: &AppLock) -> Self {
+        Self {
+            locked: app_lock.is_enabled(),
+            last_activity: Instant::now(),
+            input: String::new(),
+            error: None,
+        }
+    }
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+    fn lock(&mut self) {
+        self.locked = true;
+        self.input.clear();
+        self.error = None;
+    }
+    fn unlock(&mut self) {
+        self.locked = false;
+        self.error = None;
+        self.touch();
+    }
+    pub fn update(&mut self, message: Message, app_lock: &AppLock) -> Command<crate::app::Message> {
+        match message {
+            Message::Activity => self.touch(),
+            Message::Tick => {
+                let idle = app_lock
+                    .auto_lock()
+                    .is_some_and(|timeout| self.last_activity.elapsed() >= timeout);
+                if idle && !self.locked {
+                    self.lock();
+                }
+            }
+            Message::LockNow => {
+                if app_lock.is_enabled() {
+                    self.lock();
+                }
+            }
+            Message::SetInput(input) => self.input = input,
+            Message::Unlock => match app_lock.method {
+                LockMethod::Disabled => self.unlock(),
+                LockMethod::Passphrase => {
+                    if app_lock.verify(&self.input) {
+                        self.unlock();
+                    } else {
+                        self.error = Some(String::from("Incorrect passphrase"));
+                    }
+                    self.input.clear();
+                }
+                LockMethod::System => {
+                    return Command::perform(lock::authenticate_system(), |result| {
+                        crate::app::Message::Lock(Message::Unlocked(result)).into()
+                    });
+                }
+            },
+            Message::Unlocked(result) => match result {
+                Ok(()) => self.unlock(),
+                Err(e) => self.error = Some(e),
+            },
+        }
+        Command::none()
+    }
+    pub fn subscription(&self, app_lock: &AppLock) -> Subscription<crate::app::Message> {
+        let activity = event::listen_with(|event, _status| match event {
+            event::Event::Mouse(mouse::Event::ButtonPressed(_))
+            | event::Event::Mouse(mouse::Event::WheelScrolled { .. }) => {
+                Some(crate::app::Message::Lock(Message::Activity))
+            }
+            _ => None,
+        });
+        let tick = match app_lock.auto_lock() {
+            Some(_) if !self.locked => cosmic::iced::time::every(Duration::from_secs(15))
+                .map(|_| crate::app::Message::Lock(Message::Tick)),
+            _ => Subscription::none(),
+        };
+        Subscription::batch([activity, tick])
+    }
+    pub fn view(&self, app_lock: &AppLock) -> Element<crate::app::Message> {
+        let mut column = widget::column()
+            .push(widget::icon::from_name("system-lock-screen-symbolic").size(64))
+            .push(widget::text::title3("QERSUI is locked"))
+            .align_items(Alignment::Center)
+            .spacing(12)
+            .max_width(360);
+        if app_lock.method == LockMethod::Passphrase {
+            column = column.push(
+                widget::text_input("Passphrase", &self.input)
+                    .password()
+                    .on_input(|input| Message::SetInput(input).into())
+                    .on_submit(Message::Unlock.into()),
+            );
+        }
+        column = column.push(widget::button::suggested("Unlock").on_press(Message::Unlock.into()));
+        if let Some(error) = &self.error {
+            column = column.push(widget::text::caption(error.clone()));
+        }
+        column
+            .apply(widget::container)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Center)
+            .into()
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(val: Message) -> Self {
+        crate::app::Message::Lock(val)
+    }
+}
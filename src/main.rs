@@ -1,10 +1,20 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use app::YourApp;
+use app::{Flags, YourApp};
+use cosmic::cosmic_config::{self, CosmicConfigEntry};
+use cosmic::Application;
 /// The `app` module is used by convention to indicate the main component of our application.
 mod app;
+mod config;
+mod confirm;
 mod core;
 mod creation;
+mod import;
+mod library;
+mod lock_screen;
+mod onboarding;
+mod settings;
+mod widgets;
 
 /// The `cosmic::app::run()` function is the starting point of your application.
 /// It takes two arguments:
@@ -12,6 +22,82 @@ mod creation;
 /// - `()` is the flags that your app needs to use before it starts.
 ///  If your app does not need any flags, you can pass in `()`.
 fn main() -> cosmic::iced::Result {
-    let settings = cosmic::app::Settings::default();
-    cosmic::app::run::<YourApp>(settings, ())
+    let config = load_config();
+    // First, so problems setting up the network are logged.
+    core::logging::init(config.log_level);
+    // The environment is only safe to change while this is the only thread.
+    core::network::init(&config.network);
+    core::localization::init();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("doctor") => std::process::exit(doctor()),
+        Some(core::autostart::SUBCOMMAND) => std::process::exit(autostart()),
+        _ => {}
+    }
+    let activation = match core::activation::parse(&args) {
+        Ok(activation) => activation,
+        Err(e) if e.is_empty() => {
+            print!("{}", core::activation::USAGE);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprint!("{e}\n\n{}", core::activation::USAGE);
+            std::process::exit(2);
+        }
+    };
+    // Closing is handled by the app so it can warn about, or keep running, active operations.
+    let mut settings = cosmic::app::Settings::default().exit_on_close(false);
+    if let Some((width, height)) = config.session.size {
+        settings = settings.size(cosmic::iced::Size::new(width as f32, height as f32));
+    }
+    // A second invocation hands its activation to the running instance over D-Bus and exits.
+    cosmic::app::run_single_instance::<YourApp>(settings, Flags { activation })
+}
+
+fn load_config() -> config::Config {
+    cosmic_config::Config::new(YourApp::APP_ID, config::Config::VERSION)
+        .ok()
+        .map(|handler| {
+            config::Config::get_entry(&handler).unwrap_or_else(|(_errors, config)| config)
+        })
+        .unwrap_or_default()
+}
+
+fn runtime() -> Option<tokio::runtime::Runtime> {
+    tokio::runtime::Runtime::new()
+        .map_err(|e| tracing::error!("Could not start the async runtime: {e}"))
+        .ok()
+}
+
+/// `qersui doctor`: print the self-test report, exiting with 1 if any check failed.
+fn doctor() -> i32 {
+    let config = load_config();
+    let registered = config.registered_vms;
+    let Some(runtime) = runtime() else {
+        return 1;
+    };
+    let checks = runtime.block_on(core::doctor::run(core::doctor::vm_roots(&registered)));
+    print!("{}", core::doctor::report(&checks));
+    i32::from(!checks.iter().all(core::doctor::Check::passed))
+}
+
+/// `qersui autostart`: start the autostart VMs headless, exiting with 1 if any failed to start.
+fn autostart() -> i32 {
+    let config = load_config();
+    let Some(runtime) = runtime() else {
+        return 1;
+    };
+    let roots = core::doctor::vm_roots(&config.registered_vms);
+    let failed = runtime.block_on(async {
+        let vms = core::vm::discover(roots, config.registered_vms).await;
+        let mut failed = false;
+        for vm in core::autostart::pending(&vms) {
+            if let (vm, Err(e)) = core::autostart::start(vm.clone(), true).await {
+                tracing::error!("Could not start {}: {e}", vm.name);
+                failed = true;
+            }
+        }
+        failed
+    });
+    i32::from(failed)
 }
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::Arc;
+
+use cosmic::app::Command;
+use cosmic::iced::alignment::Horizontal;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{self, icon};
+use cosmic::{Apply, Element};
+
+use crate::core::dependencies::{self, Distro, Finding};
+use crate::core::host_probe::{self, HostCapabilities};
+
+/// Shown on first launch: what the host is missing for running VMs, and how to get it.
+#[derive(Clone, Debug)]
+pub struct Onboarding {
+    distro: Option<Distro>,
+    /// `None` while the host is being probed.
+    findings: Option<Vec<Finding>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    Recheck,
+    CopyCommand(String),
+    /// Open Settings, where the self-test checks everything again in more depth.
+    OpenSettings,
+    Finish,
+}
+
+impl Onboarding {
+    pub fn new() -> Self {
+        Self {
+            distro: Distro::detect(),
+            findings: None,
+        }
+    }
+    pub fn set_host(&mut self, host: &HostCapabilities) {
+        self.findings = Some(dependencies::check(host, self.distro));
+    }
+    /// [`Message::OpenSettings`] and [`Message::Finish`] are handled by the app.
+    pub fn update(&mut self, message: Message) -> Command<crate::app::Message> {
+        match message {
+            Message::Recheck => {
+                self.findings = None;
+                return Command::perform(host_probe::probe(), |host| {
+                    crate::app::Message::HostProbed(Arc::new(host)).into()
+                });
+            }
+            Message::CopyCommand(command) => return cosmic::iced::clipboard::write(command),
+            Message::OpenSettings | Message::Finish => {}
+        }
+        Command::none()
+    }
+    pub fn view(&self) -> Element<crate::app::Message> {
+        let mut column = widget::column()
+            .push(widget::text::title1("Welcome to QERSUI"))
+            .push(widget::text(
+                "QERSUI creates and runs virtual machines with quickemu and QEMU. Here is what \
+                 this computer has of what they need.",
+            ))
+            .spacing(16)
+            .max_width(640);
+
+        let Some(findings) = &self.findings else {
+            return column
+                .push(widget::text("Checking this computer…"))
+                .apply(Self::centered);
+        };
+        let mut list = widget::list_column();
+        for finding in findings {
+            let icon_name = if finding.installed {
+                "emblem-ok-symbolic"
+            } else {
+                "dialog-warning-symbolic"
+            };
+            let mut details = widget::column()
+                .push(widget::text::heading(finding.dependency.label()))
+                .push(widget::text::caption(finding.details.clone()))
+                .spacing(4)
+                .width(Length::Fill);
+            if let Some(command) = &finding.command {
+                details = details.push(
+                    widget::row()
+                        .push(widget::text::monotext(command.clone()).width(Length::Fill))
+                        .push(
+                            widget::button::icon(icon::from_name("edit-copy-symbolic"))
+                                .on_press(Message::CopyCommand(command.clone()).into())
+                                .tooltip(format!(
+                                    "Copy the command for {}",
+                                    finding.dependency.label()
+                                ))
+                                .width(Length::Shrink),
+                        )
+                        .spacing(8)
+                        .align_items(Alignment::Center),
+                );
+            }
+            list = list.add(
+                widget::row()
+                    .push(icon::from_name(icon_name).size(24).icon())
+                    .push(details)
+                    .spacing(12)
+                    .align_items(Alignment::Center),
+            );
+        }
+        let missing = findings.iter().any(|finding| !finding.installed);
+        let summary = match (missing, self.distro) {
+            (false, _) => String::from("Everything is in place."),
+            (true, Some(distro)) => format!(
+                "Commands are for {}. Run them in a terminal, then check again.",
+                distro.label()
+            ),
+            (true, None) => String::from(
+                "Install the missing pieces with your distribution's package manager, then \
+                 check again.",
+            ),
+        };
+        let buttons = widget::row()
+            .push(widget::button::standard("Check again").on_press(Message::Recheck.into()))
+            .push(widget::button::standard("Open self-test").on_press(Message::OpenSettings.into()))
+            .push(widget::horizontal_space(Length::Fill))
+            .push(widget::button::suggested("Get started").on_press(Message::Finish.into()))
+            .spacing(8);
+        column = column
+            .push(list)
+            .push(widget::text::caption(summary))
+            .push(buttons);
+        widget::scrollable(column.apply(Self::centered)).into()
+    }
+    fn centered<'a>(
+        content: impl Into<Element<'a, crate::app::Message>>,
+    ) -> Element<'a, crate::app::Message> {
+        widget::container(content)
+            .width(Length::Fill)
+            .align_x(Horizontal::Center)
+            .padding(24)
+            .into()
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(message: Message) -> Self {
+        Self::Onboarding(message)
+    }
+}
@@ -0,0 +1,776 @@
+use std::path::PathBuf;
+
+use cosmic::app::Command;
+use cosmic::cosmic_config;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{self, icon};
+use cosmic::{Apply, Element};
+
+use crate::config::{Config, DoubleClickAction};
+use crate::core::autostart::{self, LoginAutostart};
+use crate::core::doctor::{self, Check};
+use crate::core::firmware::{self, FirmwareKind, FirmwareStatus};
+use crate::core::hooks::{EventKind, Hook, HookTarget};
+use crate::core::lock::LockMethod;
+use crate::core::logging::{self, LogLevel};
+use crate::core::network::{self, ProxyMode};
+use crate::core::portal;
+
+#[derive(Default, Clone, Debug)]
+pub struct Settings {
+    hook_target: String,
+    hook_events: Vec<EventKind>,
+    hook_errors: Vec<String>,
+    lock_passphrase: String,
+    lock_error: Option<String>,
+    /// Auto-lock minutes while the slider is dragged, saved once it is released.
+    auto_lock_minutes: Option<u32>,
+    doctor_running: bool,
+    doctor_checks: Vec<Check>,
+    firmware: Vec<FirmwareStatus>,
+    /// Firmware currently being downloaded or removed.
+    firmware_busy: Option<FirmwareKind>,
+    firmware_error: Option<String>,
+    /// Proxy fields being typed, saved once submitted.
+    proxy_url: Option<String>,
+    no_proxy: Option<String>,
+    testing_connection: bool,
+    connection_result: Option<Result<String, String>>,
+    login_autostart_error: Option<String>,
+    /// The end of the application log while its page is open, or why it couldn't be read.
+    log: Option<Result<String, String>>,
+    viewing_log: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    SetHookTarget(String),
+    ToggleHookEvent(EventKind, bool),
+    AddHook,
+    RemoveHook(usize),
+    HookErrors(Vec<String>),
+    SetLockMethod(LockMethod),
+    SetLockPassphrase(String),
+    SaveLockPassphrase,
+    SetAutoLock(u32),
+    SaveAutoLock,
+    SetDoubleClickAction(DoubleClickAction),
+    SetShowOSPreview(bool),
+    SetShowTestingReleases(bool),
+    SetDeleteInstallersAfterBoot(bool),
+    SetLoginAutostart(LoginAutostart),
+    LoginAutostartInstalled(Result<(), String>),
+    RunDoctor,
+    DoctorFinished(Vec<Check>),
+    CopyDoctorReport,
+    FetchFirmware(FirmwareKind),
+    RemoveFirmware(FirmwareKind),
+    FirmwareChanged(Result<(), String>),
+    SetProxyMode(ProxyMode),
+    SetProxyUrl(String),
+    SetNoProxy(String),
+    SaveProxy,
+    /// Index into [`network::CONNECTION_CHOICES`].
+    SetConnections(usize),
+    /// Index into [`network::RETRY_CHOICES`].
+    SetRetries(usize),
+    /// Index into [`network::BACKOFF_CHOICES`].
+    SetBackoff(usize),
+    /// Index into [`network::FAILOVER_CHOICES`].
+    SetFailover(usize),
+    SetResumeDownloads(bool),
+    SelectCABundle,
+    SetCABundle(Option<PathBuf>),
+    TestConnection,
+    ConnectionTested(Result<String, String>),
+    SetLogLevel(LogLevel),
+    /// Open the log page, or reload it when already open.
+    ViewLog,
+    LogLoaded(Result<String, String>),
+    CopyLog,
+    CloseLog,
+}
+
+impl Settings {
+    /// Look for system and managed firmware again.
+    pub fn refresh_firmware(&mut self) {
+        self.firmware = firmware::detect();
+    }
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &mut Config,
+        config_handler: Option<&cosmic_config::Config>,
+    ) -> Command<crate::app::Message> {
+        match message {
+            Message::SetHookTarget(target) => self.hook_target = target,
+            Message::ToggleHookEvent(event, enabled) => {
+                self.hook_events.retain(|e| e != &event);
+                if enabled {
+                    self.hook_events.push(event);
+                }
+            }
+            Message::AddHook => {
+                if self.hook_target.trim().is_empty() || self.hook_events.is_empty() {
+                    return Command::none();
+                }
+                config.hooks.push(Hook {
+                    target: HookTarget::parse(&self.hook_target),
+                    events: std::mem::take(&mut self.hook_events),
+                });
+                self.hook_target.clear();
+                config.save(config_handler);
+            }
+            Message::RemoveHook(index) => {
+                if index < config.hooks.len() {
+                    config.hooks.remove(index);
+                    config.save(config_handler);
+                }
+            }
+            Message::HookErrors(errors) => self.hook_errors = errors,
+            Message::SetLockMethod(method) => {
+                if method == LockMethod::Passphrase && config.app_lock.passphrase_hash.is_empty() {
+                    self.lock_error = Some(String::from("Set a passphrase first"));
+                    return Command::none();
+                }
+                self.lock_error = None;
+                config.app_lock.method = method;
+                config.save(config_handler);
+            }
+            Message::SetLockPassphrase(passphrase) => self.lock_passphrase = passphrase,
+            Message::SaveLockPassphrase => {
+                if self.lock_passphrase.is_empty() {
+                    return Command::none();
+                }
+                match config.app_lock.set_passphrase(&self.lock_passphrase) {
+                    Ok(()) => {
+                        self.lock_error = None;
+                        self.lock_passphrase.clear();
+                        config.app_lock.method = LockMethod::Passphrase;
+                        config.save(config_handler);
+                    }
+                    Err(e) => self.lock_error = Some(e),
+                }
+            }
+            Message::SetAutoLock(minutes) => self.auto_lock_minutes = Some(minutes),
+            Message::SaveAutoLock => {
+                if let Some(minutes) = self.auto_lock_minutes.take() {
+                    config.app_lock.auto_lock_minutes = minutes;
+                    config.save(config_handler);
+                }
+            }
+            Message::SetDoubleClickAction(action) => {
+                config.double_click_action = action;
+                config.save(config_handler);
+            }
+            Message::SetShowOSPreview(show) => {
+                config.show_os_preview = show;
+                config.save(config_handler);
+            }
+            Message::SetLoginAutostart(kind) => {
+                config.login_autostart = kind;
+                config.save(config_handler);
+                self.login_autostart_error = None;
+                return Command::perform(autostart::install(kind), |result| {
+                    crate::app::Message::Settings(Message::LoginAutostartInstalled(result)).into()
+                });
+            }
+            Message::LoginAutostartInstalled(result) => {
+                self.login_autostart_error = result.err();
+            }
+            Message::SetShowTestingReleases(show) => {
+                config.show_testing_releases = show;
+                config.save(config_handler);
+            }
+            Message::SetDeleteInstallersAfterBoot(delete) => {
+                config.delete_installers_after_boot = delete;
+                config.save(config_handler);
+            }
+            Message::RunDoctor => {
+                self.doctor_running = true;
+                let roots = doctor::vm_roots(&config.registered_vms);
+                return Command::perform(doctor::run(roots), |checks| {
+                    crate::app::Message::Settings(Message::DoctorFinished(checks)).into()
+                });
+            }
+            Message::DoctorFinished(checks) => {
+                self.doctor_running = false;
+                self.doctor_checks = checks;
+            }
+            Message::CopyDoctorReport => {
+                return cosmic::iced::clipboard::write(doctor::report(&self.doctor_checks));
+            }
+            Message::FetchFirmware(kind) => {
+                self.firmware_busy = Some(kind);
+                self.firmware_error = None;
+                return Command::perform(
+                    async move { firmware::fetch(kind).await.map(drop) },
+                    |result| crate::app::Message::Settings(Message::FirmwareChanged(result)).into(),
+                );
+            }
+            Message::RemoveFirmware(kind) => {
+                self.firmware_busy = Some(kind);
+                self.firmware_error = None;
+                return Command::perform(firmware::remove(kind), |result| {
+                    crate::app::Message::Settings(Message::FirmwareChanged(result)).into()
+                });
+            }
+            Message::SetProxyMode(mode) => {
+                config.network.proxy_mode = mode;
+                config.save(config_handler);
+            }
+            Message::SetProxyUrl(url) => self.proxy_url = Some(url),
+            Message::SetNoProxy(no_proxy) => self.no_proxy = Some(no_proxy),
+            Message::SaveProxy => {
+                if self.proxy_url.is_none() && self.no_proxy.is_none() {
+                    return Command::none();
+                }
+                if let Some(url) = self.proxy_url.take() {
+                    config.network.proxy_url = url;
+                }
+                if let Some(no_proxy) = self.no_proxy.take() {
+                    config.network.no_proxy = no_proxy;
+                }
+                config.save(config_handler);
+            }
+            Message::SetConnections(choice) => {
+                config.network.connections_per_download = network::CONNECTION_CHOICES[choice];
+                config.save(config_handler);
+            }
+            Message::SetRetries(choice) => {
+                config.network.retry.attempts = network::RETRY_CHOICES[choice];
+                config.save(config_handler);
+            }
+            Message::SetBackoff(choice) => {
+                config.network.retry.backoff_secs = network::BACKOFF_CHOICES[choice];
+                config.save(config_handler);
+            }
+            Message::SetFailover(choice) => {
+                config.network.retry.failover_after = network::FAILOVER_CHOICES[choice];
+                config.save(config_handler);
+            }
+            Message::SetResumeDownloads(resume) => {
+                config.network.retry.resume = resume;
+                config.save(config_handler);
+            }
+            Message::SelectCABundle => {
+                return Command::perform(portal::pick("Select CA bundle", false, None), |path| {
+                    match path {
+                        Ok(Some(path)) => {
+                            crate::app::Message::Settings(Message::SetCABundle(Some(path))).into()
+                        }
+                        Ok(None) => cosmic::app::Message::None,
+                        Err(e) => {
+                            tracing::warn!("Could not pick a CA bundle: {e}");
+                            cosmic::app::Message::None
+                        }
+                    }
+                });
+            }
+            Message::SetCABundle(path) => {
+                config.network.ca_bundle = path;
+                config.save(config_handler);
+            }
+            Message::TestConnection => {
+                self.testing_connection = true;
+                self.connection_result = None;
+                return Command::perform(
+                    network::test_connection(self.network_draft(config)),
+                    |result| {
+                        crate::app::Message::Settings(Message::ConnectionTested(result)).into()
+                    },
+                );
+            }
+            Message::ConnectionTested(result) => {
+                self.testing_connection = false;
+                self.connection_result = Some(result);
+            }
+            Message::FirmwareChanged(result) => {
+                self.firmware_busy = None;
+                self.firmware_error = result.err();
+                self.refresh_firmware();
+            }
+            Message::SetLogLevel(level) => {
+                config.log_level = level;
+                config.save(config_handler);
+                logging::set_level(level);
+            }
+            Message::ViewLog => {
+                self.viewing_log = true;
+                return Command::perform(logging::tail(), |log| {
+                    crate::app::Message::Settings(Message::LogLoaded(log)).into()
+                });
+            }
+            Message::LogLoaded(log) => self.log = Some(log),
+            Message::CopyLog => {
+                if let Some(Ok(log)) = &self.log {
+                    return cosmic::iced::clipboard::write(log.clone());
+                }
+            }
+            Message::CloseLog => {
+                self.viewing_log = false;
+                self.log = None;
+            }
+        }
+        Command::none()
+    }
+    /// The network settings with the proxy fields as currently typed.
+    fn network_draft(&self, config: &Config) -> network::NetworkSettings {
+        let mut settings = config.network.clone();
+        if let Some(url) = &self.proxy_url {
+            settings.proxy_url = url.clone();
+        }
+        if let Some(no_proxy) = &self.no_proxy {
+            settings.no_proxy = no_proxy.clone();
+        }
+        settings
+    }
+    fn log_view(&self) -> Element<crate::app::Message> {
+        let buttons =
+            widget::row()
+                .push(
+                    widget::button::standard("Back")
+                        .leading_icon(icon::from_name("go-previous-symbolic"))
+                        .on_press(Message::CloseLog.into()),
+                )
+                .push(widget::horizontal_space(Length::Fill))
+                .push(widget::button::standard("Reload").on_press(Message::ViewLog.into()))
+                .push(widget::button::standard("Copy").on_press_maybe(
+                    matches!(self.log, Some(Ok(_))).then_some(Message::CopyLog.into()),
+                ))
+                .spacing(8)
+                .align_items(Alignment::Center);
+        let path = logging::log_path().map_or_else(
+            || String::from("No state directory to keep a log in"),
+            |path| path.to_string_lossy().into_owned(),
+        );
+        let contents: Element<_> = match &self.log {
+            None => widget::text("Loading…").into(),
+            Some(Ok(log)) if log.is_empty() => widget::text("Nothing has been logged yet").into(),
+            Some(Ok(log)) => widget::text(log.clone())
+                .font(cosmic::font::mono())
+                .apply(widget::scrollable)
+                .height(Length::Fill)
+                .into(),
+            Some(Err(e)) => widget::text(e.clone()).into(),
+        };
+        widget::column()
+            .push(buttons)
+            .push(widget::text::title3("Application log"))
+            .push(widget::text::caption(path))
+            .push(contents)
+            .spacing(12)
+            .into()
+    }
+    pub fn view<'a>(&'a self, config: &'a Config) -> Element<'a, crate::app::Message> {
+        if self.viewing_log {
+            return self.log_view();
+        }
+        let mut hook_list = widget::list_column();
+        for (index, hook) in config.hooks.iter().enumerate() {
+            let events = hook
+                .events
+                .iter()
+                .map(EventKind::label)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let details = widget::column()
+                .push(widget::text(hook.target.to_string()))
+                .push(widget::text::caption(events))
+                .width(Length::Fill);
+            let remove_button = widget::button::icon(icon::from_name("edit-delete-symbolic"))
+                .on_press(Message::RemoveHook(index).into())
+                .tooltip("Remove hook")
+                .width(Length::Shrink);
+            hook_list = hook_list.add(
+                widget::row()
+                    .push(details)
+                    .push(remove_button)
+                    .align_items(Alignment::Center),
+            );
+        }
+
+        let target_input = widget::text_input("Script path or webhook URL", &self.hook_target)
+            .on_input(|target| Message::SetHookTarget(target).into());
+        let mut event_row = widget::row().spacing(12);
+        for event in EventKind::ALL {
+            let checkbox = widget::checkbox(event.label(), self.hook_events.contains(&event))
+                .on_toggle(move |enabled| Message::ToggleHookEvent(event, enabled).into());
+            event_row = event_row.push(checkbox);
+        }
+        let add_button = widget::button::standard("Add hook").on_press(Message::AddHook.into());
+
+        let mut action_row = widget::row().spacing(12);
+        for action in DoubleClickAction::ALL {
+            action_row = action_row.push(widget::radio(
+                action.label(),
+                action,
+                Some(config.double_click_action),
+                |action| Message::SetDoubleClickAction(action).into(),
+            ));
+        }
+
+        let mut login_autostart_row = widget::row().spacing(12);
+        for kind in LoginAutostart::ALL {
+            login_autostart_row = login_autostart_row.push(widget::radio(
+                kind.label(),
+                kind,
+                Some(config.login_autostart),
+                |kind| Message::SetLoginAutostart(kind).into(),
+            ));
+        }
+
+        let mut column = widget::column()
+            .push(widget::text::title3("Interface"))
+            .push(widget::text::caption("Double-clicking a VM:"))
+            .push(action_row)
+            .push(widget::toggler(
+                String::from("Preview the highlighted OS beside the list on wide windows"),
+                config.show_os_preview,
+                |show| Message::SetShowOSPreview(show).into(),
+            ))
+            .push(widget::toggler(
+                String::from("Show testing releases"),
+                config.show_testing_releases,
+                |show| Message::SetShowTestingReleases(show).into(),
+            ))
+            .push(widget::text::caption("Start VMs marked for autostart:"))
+            .push(login_autostart_row)
+            .push_maybe(
+                self.login_autostart_error
+                    .as_ref()
+                    .map(|error| widget::text::caption(error.clone())),
+            )
+            .push(widget::text::title3("Storage"))
+            .push(widget::toggler(
+                String::from("Delete downloaded installer images after a VM's first boot"),
+                config.delete_installers_after_boot,
+                |delete| Message::SetDeleteInstallersAfterBoot(delete).into(),
+            ))
+            .push(widget::text::caption(
+                "They are removed once the VM shuts down for the first time, and kept while other \
+                 VMs still use them. Installer images can also be deleted by hand from the library.",
+            ))
+            .push(widget::text::title3("Lifecycle hooks"))
+            .push(widget::text::caption(
+                "Scripts receive the event as JSON on stdin; webhooks receive it as a POST body.",
+            ))
+            .push(hook_list)
+            .push(target_input)
+            .push(event_row)
+            .push(add_button)
+            .spacing(12);
+        for error in &self.hook_errors {
+            column = column.push(widget::text::caption(format!("Hook failed: {error}")));
+        }
+
+        let app_lock = &config.app_lock;
+        let mut method_row = widget::row().spacing(12);
+        for method in LockMethod::ALL {
+            method_row = method_row.push(widget::radio(
+                method.label(),
+                method,
+                Some(app_lock.method),
+                |method| Message::SetLockMethod(method).into(),
+            ));
+        }
+        let passphrase_row = widget::row()
+            .push(
+                widget::text_input("New passphrase", &self.lock_passphrase)
+                    .password()
+                    .on_input(|passphrase| Message::SetLockPassphrase(passphrase).into())
+                    .on_submit(Message::SaveLockPassphrase.into()),
+            )
+            .push(
+                widget::button::standard("Set passphrase")
+                    .on_press(Message::SaveLockPassphrase.into()),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let auto_lock_minutes = self.auto_lock_minutes.unwrap_or(app_lock.auto_lock_minutes);
+        let auto_lock = match auto_lock_minutes {
+            0 => String::from("  Never"),
+            minutes => format!("  {minutes} min"),
+        };
+        let auto_lock_row = widget::row()
+            .push(widget::text("Lock after inactivity:  ").width(Length::Shrink))
+            .push(
+                widget::slider(0..=60, auto_lock_minutes, |minutes| {
+                    Message::SetAutoLock(minutes).into()
+                })
+                .on_release(Message::SaveAutoLock.into()),
+            )
+            .push(widget::text(auto_lock).width(Length::Shrink));
+        column = column
+            .push(widget::text::title3("App lock"))
+            .push(widget::text::caption(
+                "Require authentication to open QERSUI, e.g. on a shared computer.",
+            ))
+            .push(method_row)
+            .push(passphrase_row)
+            .push(auto_lock_row);
+        if let Some(error) = &self.lock_error {
+            column = column.push(widget::text::caption(error.clone()));
+        }
+
+        let doctor_row = widget::row()
+            .push(
+                widget::button::standard("Run self-test")
+                    .on_press_maybe((!self.doctor_running).then_some(Message::RunDoctor.into())),
+            )
+            .push_maybe((!self.doctor_checks.is_empty()).then(|| {
+                widget::button::standard("Copy report").on_press(Message::CopyDoctorReport.into())
+            }))
+            .spacing(8);
+        let mut doctor_list = widget::list_column();
+        for check in &self.doctor_checks {
+            let (icon_name, details) = match &check.result {
+                Ok(details) => ("emblem-ok-symbolic", details),
+                Err(details) => ("dialog-error-symbolic", details),
+            };
+            doctor_list = doctor_list.add(
+                widget::row()
+                    .push(icon::from_name(icon_name).size(16).icon())
+                    .push(
+                        widget::column()
+                            .push(widget::text(check.name))
+                            .push(widget::text::caption(details.clone())),
+                    )
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            );
+        }
+        let network_settings = &config.network;
+        let mut proxy_row = widget::row().spacing(12);
+        for mode in ProxyMode::ALL {
+            proxy_row = proxy_row.push(widget::radio(
+                mode.label(),
+                mode,
+                Some(network_settings.proxy_mode),
+                |mode| Message::SetProxyMode(mode).into(),
+            ));
+        }
+        let proxy_details: Element<_> = match network_settings.proxy_mode {
+            ProxyMode::Auto => widget::text::caption(match network::detected_proxy() {
+                Some(proxy) => format!("Using {proxy}"),
+                None => String::from("No proxy is set in the environment"),
+            })
+            .into(),
+            ProxyMode::Direct => widget::text::caption("Connecting directly").into(),
+            ProxyMode::Manual => widget::row()
+                .push(
+                    widget::text_input(
+                        "http://proxy.example.com:3128",
+                        self.proxy_url
+                            .as_deref()
+                            .unwrap_or(&network_settings.proxy_url),
+                    )
+                    .on_input(|url| Message::SetProxyUrl(url).into())
+                    .on_submit(Message::SaveProxy.into()),
+                )
+                .push(
+                    widget::text_input(
+                        "Hosts to reach directly",
+                        self.no_proxy
+                            .as_deref()
+                            .unwrap_or(&network_settings.no_proxy),
+                    )
+                    .on_input(|no_proxy| Message::SetNoProxy(no_proxy).into())
+                    .on_submit(Message::SaveProxy.into()),
+                )
+                .push(
+                    widget::button::standard("Apply").on_press_maybe(
+                        (self.proxy_url.is_some() || self.no_proxy.is_some())
+                            .then_some(Message::SaveProxy.into()),
+                    ),
+                )
+                .spacing(8)
+                .align_items(Alignment::Center)
+                .into(),
+        };
+        let ca_label = network_settings.ca_bundle.as_ref().map_or_else(
+            || String::from("Built-in and system certificate authorities only"),
+            |path| path.to_string_lossy().into_owned(),
+        );
+        let ca_row = widget::row()
+            .push(widget::text(ca_label).width(Length::Fill))
+            .push(
+                widget::button::standard("Choose CA bundle")
+                    .on_press(Message::SelectCABundle.into()),
+            )
+            .push_maybe(network_settings.ca_bundle.as_ref().map(|_| {
+                widget::button::standard("Clear").on_press(Message::SetCABundle(None).into())
+            }))
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let connections = network_settings.connections_per_download.max(1);
+        let connections_row = widget::row()
+            .push(widget::text("Connections per download").width(Length::Fill))
+            .push(widget::dropdown(
+                &["1", "2", "4", "8"],
+                network::CONNECTION_CHOICES
+                    .iter()
+                    .position(|choice| *choice >= connections),
+                |choice| Message::SetConnections(choice).into(),
+            ))
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let retries = network_settings.retry.attempts;
+        let retries_row = widget::row()
+            .push(widget::text("Retry failed downloads").width(Length::Fill))
+            .push(widget::dropdown(
+                &["Never", "3 times", "5 times", "10 times"],
+                network::RETRY_CHOICES
+                    .iter()
+                    .position(|choice| *choice >= retries),
+                |choice| Message::SetRetries(choice).into(),
+            ))
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let backoff = network_settings.retry.backoff_secs;
+        let backoff_row = widget::row()
+            .push(widget::text("Wait before the first retry").width(Length::Fill))
+            .push(widget::dropdown(
+                &["1 second", "2 seconds", "5 seconds", "10 seconds"],
+                network::BACKOFF_CHOICES
+                    .iter()
+                    .position(|choice| *choice >= backoff),
+                |choice| Message::SetBackoff(choice).into(),
+            ))
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let failover = network_settings.retry.failover_after;
+        let failover_row = widget::row()
+            .push(widget::text("Switch to a mirror after").width(Length::Fill))
+            .push(widget::dropdown(
+                &["1 failure", "2 failures", "3 failures"],
+                network::FAILOVER_CHOICES
+                    .iter()
+                    .position(|choice| *choice >= failover),
+                |choice| Message::SetFailover(choice).into(),
+            ))
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let resume_toggle = widget::toggler(
+            String::from("Resume interrupted downloads"),
+            network_settings.retry.resume,
+            |resume| Message::SetResumeDownloads(resume).into(),
+        );
+        let test_row = widget::row()
+            .push(widget::button::standard("Test connection").on_press_maybe(
+                (!self.testing_connection).then_some(Message::TestConnection.into()),
+            ))
+            .push_maybe(self.connection_result.as_ref().map(|result| {
+                widget::text::caption(match result {
+                    Ok(message) | Err(message) => message.clone(),
+                })
+            }))
+            .spacing(8)
+            .align_items(Alignment::Center);
+        column = column
+            .push(widget::text::title3("Network"))
+            .push(widget::text::caption(
+                "Used for the OS catalog, downloads and webhooks.",
+            ))
+            .push(proxy_row)
+            .push(proxy_details)
+            .push(ca_row)
+            .push(widget::text::caption(
+                "A CA bundle is trusted alongside the system's. The OS catalog picks up proxy and \
+                 CA changes after restarting QERSUI.",
+            ))
+            .push(connections_row)
+            .push(widget::text::caption(
+                "Large images are fetched in parallel ranges from servers that support it.",
+            ))
+            .push(retries_row)
+            .push(backoff_row)
+            .push(failover_row)
+            .push(resume_toggle)
+            .push(widget::text::caption(
+                "Each retry waits twice as long as the last; well-known hosts fall back to a mirror.",
+            ))
+            .push(test_row);
+
+        let mut firmware_list = widget::list_column();
+        for status in &self.firmware {
+            let kind = status.kind;
+            let details = match (&status.system, &status.managed) {
+                (Some(files), _) => files.code.to_string_lossy().into_owned(),
+                (None, Some(_)) => String::from("Downloaded by QERSUI"),
+                (None, None) if kind.fetchable() => String::from("Not installed"),
+                (None, None) => String::from("Not installed; install your distribution's package"),
+            };
+            let busy = self.firmware_busy.is_some();
+            let action = match &status.managed {
+                Some(_) => Some(
+                    widget::button::standard("Remove")
+                        .on_press_maybe((!busy).then_some(Message::RemoveFirmware(kind).into())),
+                ),
+                None if kind.fetchable() && status.system.is_none() => Some(
+                    widget::button::standard("Download")
+                        .on_press_maybe((!busy).then_some(Message::FetchFirmware(kind).into())),
+                ),
+                None => None,
+            };
+            firmware_list = firmware_list.add(
+                widget::row()
+                    .push(
+                        widget::column()
+                            .push(widget::text(kind.label()))
+                            .push(widget::text::caption(details))
+                            .width(Length::Fill),
+                    )
+                    .push_maybe(action)
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+            );
+        }
+        column = column
+            .push(widget::text::title3("Firmware"))
+            .push(widget::text::caption(
+                "UEFI firmware for each guest architecture. VMs created while a distribution copy \
+                 is missing use a downloaded one instead.",
+            ))
+            .push(firmware_list);
+        if let Some(error) = &self.firmware_error {
+            column = column.push(widget::text::caption(error.clone()));
+        }
+
+        let mut log_level_row = widget::row().spacing(12);
+        for level in LogLevel::ALL {
+            log_level_row = log_level_row.push(widget::radio(
+                level.label(),
+                level,
+                Some(config.log_level),
+                |level| Message::SetLogLevel(level).into(),
+            ));
+        }
+        column = column
+            .push(widget::text::title3("Logging"))
+            .push(widget::text::caption("Least severe events to record:"))
+            .push(log_level_row)
+            .push(
+                widget::button::standard("View application log").on_press(Message::ViewLog.into()),
+            );
+
+        column = column
+            .push(widget::text::title3("Self-test"))
+            .push(widget::text::caption(
+                "Checks portals, KVM, QEMU tools, VM directories and the OS index. Also \
+                 available as `qersui doctor`.",
+            ))
+            .push(doctor_row);
+        if !self.doctor_checks.is_empty() {
+            column = column.push(doctor_list);
+        }
+
+        widget::scrollable(column).into()
+    }
+}
+
+impl From<Message> for crate::app::Message {
+    fn from(val: Message) -> Self {
+        crate::app::Message::Settings(val)
+    }
+}
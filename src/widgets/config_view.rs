@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::{Color, Length};
+use cosmic::widget::{self, container};
+use cosmic::{theme, Element};
+
+/// Which part of a config line some text is, for coloring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Span {
+    Comment,
+    Key,
+    Value,
+}
+
+fn colored<'a, Message: 'static>(text: String, span: Span) -> Element<'a, Message> {
+    widget::container(widget::text(text).font(cosmic::font::mono()))
+        .style(theme::Container::custom(move |theme| {
+            let cosmic = theme.cosmic();
+            let color: Color = match span {
+                Span::Comment => cosmic.palette.neutral_6.into(),
+                Span::Key => cosmic.accent_color().into(),
+                Span::Value => cosmic.on_bg_color().into(),
+            };
+            container::Appearance {
+                text_color: Some(color),
+                ..Default::default()
+            }
+        }))
+        .into()
+}
+
+/// A read-only quickemu config with comments dimmed and keys in the accent color.
+pub fn config_view<'a, Message: 'static>(contents: &str) -> Element<'a, Message> {
+    let mut column = widget::column().width(Length::Fill);
+    for line in contents.lines() {
+        let row: Element<_> = match line.split_once('=') {
+            Some((key, value)) if !line.trim_start().starts_with('#') => widget::row()
+                .push(colored(key.to_string(), Span::Key))
+                .push(colored(format!("={value}"), Span::Value))
+                .into(),
+            _ => colored(line.to_string(), Span::Comment),
+        };
+        column = column.push(row);
+    }
+    column.into()
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+
+/// A directory browser for when the file chooser portal isn't available.
+#[derive(Clone, Debug)]
+pub struct DirectoryPicker {
+    current: PathBuf,
+    /// Names of the visible subdirectories of `current`, sorted.
+    subdirectories: Vec<String>,
+    /// Why the portal's chooser isn't used, shown above the listing.
+    reason: String,
+}
+
+/// Messages the picker can send back to the page that owns it.
+pub struct DirectoryPickerActions<Message> {
+    /// Index into the listed subdirectories.
+    pub open: fn(usize) -> Message,
+    pub up: Message,
+    pub choose: Message,
+    pub cancel: Message,
+}
+
+impl DirectoryPicker {
+    pub fn new(start: &Path, reason: impl Into<String>) -> Self {
+        // The start may be a directory that was typed in and doesn't exist yet.
+        let start = start
+            .ancestors()
+            .find(|ancestor| ancestor.is_dir())
+            .map_or_else(|| PathBuf::from("/"), Path::to_path_buf);
+        let mut picker = Self {
+            current: PathBuf::new(),
+            subdirectories: Vec::new(),
+            reason: reason.into(),
+        };
+        picker.go_to(start);
+        picker
+    }
+    pub fn current(&self) -> &Path {
+        &self.current
+    }
+    pub fn open(&mut self, index: usize) {
+        if let Some(name) = self.subdirectories.get(index) {
+            self.go_to(self.current.join(name));
+        }
+    }
+    pub fn up(&mut self) {
+        if let Some(parent) = self.current.parent() {
+            self.go_to(parent.to_path_buf());
+        }
+    }
+    fn go_to(&mut self, directory: PathBuf) {
+        let mut subdirectories = std::fs::read_dir(&directory)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| !name.starts_with('.'))
+            .collect::<Vec<_>>();
+        subdirectories.sort_by_key(|name| name.to_lowercase());
+        self.subdirectories = subdirectories;
+        self.current = directory;
+    }
+    pub fn view<Message: Clone + 'static>(
+        &self,
+        actions: DirectoryPickerActions<Message>,
+    ) -> Element<Message> {
+        let up_button = widget::button::icon(icon::from_name("go-up-symbolic"))
+            .on_press_maybe(self.current.parent().map(|_| actions.up))
+            .tooltip("Parent directory")
+            .width(Length::Shrink);
+        let location = widget::row()
+            .push(up_button)
+            .push(widget::text::monotext(self.current.display().to_string()))
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let mut list = widget::list_column();
+        for (index, name) in self.subdirectories.iter().enumerate() {
+            list = list.add(
+                widget::button::text(name.clone())
+                    .leading_icon(icon::from_name("folder-symbolic"))
+                    .on_press((actions.open)(index))
+                    .width(Length::Fill),
+            );
+        }
+        let listing: Element<_> = if self.subdirectories.is_empty() {
+            widget::text::caption("No subdirectories").into()
+        } else {
+            widget::scrollable(list).height(Length::Fixed(240.0)).into()
+        };
+        let buttons = widget::row()
+            .push(widget::button::standard("Cancel").on_press(actions.cancel))
+            .push(widget::button::suggested("Choose this directory").on_press(actions.choose))
+            .spacing(8);
+        widget::column()
+            .push(widget::text::caption(self.reason.clone()))
+            .push(location)
+            .push(listing)
+            .push(buttons)
+            .spacing(8)
+            .into()
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+
+use crate::core::error::AppError;
+
+/// Messages the error view can send back to the page that owns it.
+pub struct ErrorActions<Message> {
+    pub toggle_details: Message,
+    pub copy_report: Message,
+    /// Re-run the step that failed, if it can be retried.
+    pub retry: Option<Message>,
+    pub back: Option<Message>,
+}
+
+/// A categorized error with expandable details and retry/report actions.
+pub fn error_view<'a, Message: Clone + 'static>(
+    error: &'a AppError,
+    expanded: bool,
+    actions: ErrorActions<Message>,
+) -> Element<'a, Message> {
+    let header = widget::row()
+        .push(icon::from_name(error.category.icon_name()).size(32).icon())
+        .push(widget::text::title3(error.category.label()))
+        .spacing(12)
+        .align_items(Alignment::Center);
+
+    let details_label = if expanded {
+        "Hide details"
+    } else {
+        "Show details"
+    };
+    let mut column = widget::column()
+        .push(header)
+        .push(widget::text(error.summary().to_string()))
+        .push(widget::text::caption(error.category.hint()))
+        .push(widget::button::text(details_label).on_press(actions.toggle_details))
+        .spacing(12)
+        .max_width(640);
+    if expanded {
+        let mut chain = widget::list_column();
+        for (depth, cause) in error.chain.iter().enumerate() {
+            let prefix = if depth == 0 { "" } else { "caused by: " };
+            chain = chain.add(widget::text::monotext(format!("{prefix}{cause}")));
+        }
+        column = column.push(chain);
+    }
+
+    let mut buttons = widget::row().spacing(8);
+    if let Some(back) = actions.back {
+        buttons = buttons.push(widget::button::standard("Back").on_press(back));
+    }
+    buttons = buttons.push(widget::button::standard("Copy report").on_press(actions.copy_report));
+    if let Some(retry) = actions.retry {
+        buttons = buttons.push(widget::button::suggested("Retry").on_press(retry));
+    }
+    widget::scrollable(column.push(buttons))
+        .width(Length::Fill)
+        .into()
+}
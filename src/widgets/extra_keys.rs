@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+
+use crate::core::config_keys::{self, ExtraKey, Issue};
+
+/// Messages the advanced section can send back to the page that owns it.
+pub struct ExtraKeyActions<Message> {
+    pub toggle: Message,
+    pub set_key: fn(usize, String) -> Message,
+    pub set_value: fn(usize, String) -> Message,
+    pub remove: fn(usize) -> Message,
+    pub add: Message,
+}
+
+/// A collapsible list of hand-written config keys, each with its validation issue.
+pub fn extra_keys_view<'a, Message: Clone + 'static>(
+    extras: &'a [ExtraKey],
+    managed: &[(&str, &str)],
+    expanded: bool,
+    actions: ExtraKeyActions<Message>,
+) -> Element<'a, Message> {
+    let expander_icon = if expanded {
+        "go-down-symbolic"
+    } else {
+        "go-next-symbolic"
+    };
+    let label = match extras.len() {
+        0 => String::from("Advanced"),
+        count => format!("Advanced ({count})"),
+    };
+    let expander = widget::button::text(label)
+        .leading_icon(icon::from_name(expander_icon))
+        .on_press(actions.toggle);
+    let mut column = widget::column().push(expander).spacing(4);
+    if !expanded {
+        return column.into();
+    }
+
+    column = column.push(widget::text::caption(
+        "Extra quickemu config keys, written after the settings above.",
+    ));
+    let issues = config_keys::check(extras, managed);
+    for (index, (extra, issue)) in extras.iter().zip(issues).enumerate() {
+        let (set_key, set_value) = (actions.set_key, actions.set_value);
+        let row = widget::row()
+            .push(
+                widget::text_input("Key", &extra.key)
+                    .on_input(move |key| set_key(index, key))
+                    .width(Length::Fixed(160.0)),
+            )
+            .push(
+                widget::text_input("Value", &extra.value)
+                    .on_input(move |value| set_value(index, value)),
+            )
+            .push(
+                widget::button::icon(icon::from_name("list-remove-symbolic"))
+                    .on_press((actions.remove)(index))
+                    .tooltip("Remove key"),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center);
+        let issue = issue.map(|issue| {
+            let (icon_name, message) = match issue {
+                Issue::Warning(message) => ("dialog-warning-symbolic", message),
+                Issue::Error(message) => ("dialog-error-symbolic", message),
+            };
+            widget::row()
+                .push(icon::from_name(icon_name).size(16).icon())
+                .push(widget::text::caption(message))
+                .spacing(4)
+                .align_items(Alignment::Center)
+        });
+        column = column.push(row).push_maybe(issue);
+    }
+    column
+        .push(widget::button::standard("Add key").on_press(actions.add))
+        .into()
+}
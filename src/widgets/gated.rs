@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::widget::{self, tooltip};
+use cosmic::Element;
+
+/// `option`, which the caller left disabled when `reason` is set, with `reason` as its tooltip.
+pub fn gated<'a, Message: 'static>(
+    option: impl Into<Element<'a, Message>>,
+    reason: Option<String>,
+) -> Element<'a, Message> {
+    match reason {
+        Some(reason) => {
+            widget::tooltip(option, widget::text(reason), tooltip::Position::Top).into()
+        }
+        None => option.into(),
+    }
+}
@@ -0,0 +1,10 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+pub mod config_view;
+pub mod directory_picker;
+pub mod error_view;
+pub mod extra_keys;
+pub mod gated;
+pub mod os_icon;
+pub mod overcommit;
+pub mod status_badge;
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use cosmic::widget::icon::{self, IconFallback};
+use once_cell::sync::Lazy;
+
+/// Resolved handles by OS name, so each logo is looked up once, the first time it's shown.
+static HANDLES: Lazy<Mutex<HashMap<String, icon::Handle>>> = Lazy::new(Default::default);
+
+fn resolve(name: &str) -> icon::Handle {
+    // Flavours such as `ubuntu-server` fall back to their family's logo.
+    let family = name.split_once('-').map(|(family, _)| family);
+    let mut fallbacks = family
+        .map(|family| vec![Cow::Owned(format!("distributor-logo-{family}"))])
+        .unwrap_or_default();
+    fallbacks.push(Cow::Borrowed("computer-symbolic"));
+    icon::from_name(format!("distributor-logo-{name}"))
+        .fallback(Some(IconFallback::Names(fallbacks)))
+        .handle()
+}
+
+/// The icon theme's logo of the quickget OS `name`, or a generic computer.
+pub fn os_icon(name: &str, size: u16) -> icon::Icon {
+    // The map only ever gains finished entries, so one left by a panicking thread is still whole.
+    let handle = HANDLES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(name.to_string())
+        .or_insert_with(|| resolve(name))
+        .clone();
+    icon::icon(handle).size(size)
+}
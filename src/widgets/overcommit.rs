@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use cosmic::iced::Alignment;
+use cosmic::widget::{self, icon};
+use cosmic::Element;
+
+use crate::core::overcommit::Overcommit;
+use crate::core::units::format_size;
+
+/// A warning that a VM's allocation over-commits the host, linking to the VMs counted with it.
+pub fn overcommit_view<'a, Message: Clone + 'static>(
+    overcommit: &Overcommit,
+    show: impl Fn(PathBuf) -> Message,
+) -> Element<'a, Message> {
+    let warning = widget::row()
+        .push(icon::from_name("dialog-warning-symbolic").size(16).icon())
+        .push(widget::text(format!(
+            "{}. The host may slow down or start killing VMs.",
+            overcommit.message()
+        )))
+        .spacing(8)
+        .align_items(Alignment::Center);
+    let mut vms = widget::row().spacing(8).align_items(Alignment::Center);
+    for commitment in &overcommit.vms {
+        vms = vms.push(
+            widget::button::link(format!(
+                "{} ({}, {} CPU cores)",
+                commitment.name,
+                format_size(commitment.ram),
+                commitment.cpu_cores
+            ))
+            .on_press(show(commitment.config_path.clone())),
+        );
+    }
+    widget::column().push(warning).push(vms).spacing(4).into()
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::{Alignment, Background, Border, Color};
+use cosmic::widget::{self, container, icon, tooltip};
+use cosmic::{theme, Element};
+
+/// The lifecycle state of a VM or task, rendered the same way everywhere it appears.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Running,
+    Paused,
+    Stopped,
+    Suspended,
+    Error,
+    Downloading,
+}
+
+impl Status {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Running => "Running",
+            Self::Paused => "Paused",
+            Self::Stopped => "Stopped",
+            Self::Suspended => "Suspended",
+            Self::Error => "Error",
+            Self::Downloading => "Downloading",
+        }
+    }
+    fn description(&self) -> &'static str {
+        match self {
+            Self::Running => "The VM is running",
+            Self::Paused => "Execution is paused; the VM keeps its memory",
+            Self::Stopped => "The VM is powered off",
+            Self::Suspended => "The VM state has been saved to disk",
+            Self::Error => "The last operation failed",
+            Self::Downloading => "Installer images are being downloaded",
+        }
+    }
+    fn icon_name(&self) -> &'static str {
+        match self {
+            Self::Running => "media-playback-start-symbolic",
+            Self::Paused => "media-playback-pause-symbolic",
+            Self::Stopped => "media-playback-stop-symbolic",
+            Self::Suspended => "media-record-symbolic",
+            Self::Error => "dialog-error-symbolic",
+            Self::Downloading => "folder-download-symbolic",
+        }
+    }
+    fn color(&self, theme: &cosmic::Theme) -> Color {
+        let cosmic = theme.cosmic();
+        match self {
+            Self::Running => cosmic.success_color().into(),
+            Self::Paused | Self::Suspended => cosmic.warning_color().into(),
+            Self::Stopped => cosmic.palette.neutral_6.into(),
+            Self::Error => cosmic.destructive_color().into(),
+            Self::Downloading => cosmic.accent_color().into(),
+        }
+    }
+}
+
+/// A small colored pill with an icon and label, explaining the state in a tooltip.
+pub fn status_badge<'a, Message: 'static>(
+    status: Status,
+    detail: Option<String>,
+) -> Element<'a, Message> {
+    let content = widget::row()
+        .push(icon::from_name(status.icon_name()).size(12))
+        .push(widget::text::caption(status.label()))
+        .spacing(4)
+        .align_items(Alignment::Center);
+
+    let badge = widget::container(content)
+        .padding([2, 8])
+        .style(theme::Container::custom(move |theme| {
+            let color = status.color(theme);
+            container::Appearance {
+                text_color: Some(color),
+                icon_color: Some(color),
+                background: Some(Background::Color(Color { a: 0.15, ..color })),
+                border: Border {
+                    color,
+                    width: 1.0,
+                    radius: 12.0.into(),
+                },
+                ..Default::default()
+            }
+        }));
+
+    let description = match detail {
+        Some(detail) => format!("{}: {detail}", status.description()),
+        None => status.description().to_string(),
+    };
+    widget::tooltip(badge, widget::text(description), tooltip::Position::Top).into()
+}